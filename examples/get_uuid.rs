@@ -1,3 +1,4 @@
+#[cfg(windows)]
 fn main() {
     let adapters = ipconfig2::get_adapters().unwrap();
 
@@ -6,3 +7,6 @@ fn main() {
         println!("{:?}: {}", adapter.ip_addresses, uuid);
     }
 }
+
+#[cfg(not(windows))]
+fn main() {}
@@ -1,32 +1,416 @@
 #![allow(clippy::cast_ptr_alignment)]
 
 use std;
-use std::convert::TryFrom;
-use std::ffi::CStr;
+use std::borrow::Cow;
+use std::ffi::OsString;
+use std::fmt;
 use std::net::IpAddr;
+use std::str::FromStr;
 
+use crate::address::UnicastAddress;
 use crate::error::*;
-use crate::utils::guid_to_bytes;
-use socket2;
-use widestring::WideCString;
+use crate::network::IpNetwork;
+use crate::scope::ScopedIpAddr;
+#[cfg(windows)]
 use windows_sys::Win32::Foundation::ERROR_BUFFER_OVERFLOW;
+#[cfg(windows)]
 use windows_sys::Win32::Foundation::ERROR_SUCCESS;
 
+#[cfg(windows)]
 use windows_sys::Win32::NetworkManagement::IpHelper;
-use windows_sys::Win32::Networking::WinSock;
 // use windows_sys::Win32::System::Com::StringFromGUID2;
 
 /// Represent an operational status of the adapter
 /// See IP_ADAPTER_ADDRESSES docs for more details
-#[derive(Debug, Clone, Copy, PartialEq)]
+///
+/// Values outside the documented range are kept in `OperStatus::Unknown`
+/// instead of being rejected, some drivers do report them.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum OperStatus {
-    IfOperStatusUp = 1,
-    IfOperStatusDown = 2,
-    IfOperStatusTesting = 3,
-    IfOperStatusUnknown = 4,
-    IfOperStatusDormant = 5,
-    IfOperStatusNotPresent = 6,
-    IfOperStatusLowerLayerDown = 7,
+    IfOperStatusUp,
+    IfOperStatusDown,
+    IfOperStatusTesting,
+    #[default]
+    IfOperStatusUnknown,
+    IfOperStatusDormant,
+    IfOperStatusNotPresent,
+    IfOperStatusLowerLayerDown,
+    /// A raw value that is not defined by `IF_OPER_STATUS`.
+    Unknown(u32),
+}
+
+impl From<u32> for OperStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => OperStatus::IfOperStatusUp,
+            2 => OperStatus::IfOperStatusDown,
+            3 => OperStatus::IfOperStatusTesting,
+            4 => OperStatus::IfOperStatusUnknown,
+            5 => OperStatus::IfOperStatusDormant,
+            6 => OperStatus::IfOperStatusNotPresent,
+            7 => OperStatus::IfOperStatusLowerLayerDown,
+            v => OperStatus::Unknown(v),
+        }
+    }
+}
+
+impl From<OperStatus> for u32 {
+    fn from(value: OperStatus) -> Self {
+        match value {
+            OperStatus::IfOperStatusUp => 1,
+            OperStatus::IfOperStatusDown => 2,
+            OperStatus::IfOperStatusTesting => 3,
+            OperStatus::IfOperStatusUnknown => 4,
+            OperStatus::IfOperStatusDormant => 5,
+            OperStatus::IfOperStatusNotPresent => 6,
+            OperStatus::IfOperStatusLowerLayerDown => 7,
+            OperStatus::Unknown(v) => v,
+        }
+    }
+}
+
+impl OperStatus {
+    /// Parse the content of `/sys/class/net/<iface>/operstate` on Linux.
+    pub fn from_linux_operstate(operstate: &str) -> Option<Self> {
+        let status = match operstate.trim() {
+            "up" => OperStatus::IfOperStatusUp,
+            "down" => OperStatus::IfOperStatusDown,
+            "testing" => OperStatus::IfOperStatusTesting,
+            "unknown" => OperStatus::IfOperStatusUnknown,
+            "dormant" => OperStatus::IfOperStatusDormant,
+            "notpresent" => OperStatus::IfOperStatusNotPresent,
+            "lowerlayerdown" => OperStatus::IfOperStatusLowerLayerDown,
+            _ => return None,
+        };
+        Some(status)
+    }
+
+    /// The Linux operstate string for this status, `None` for raw values.
+    pub fn to_linux_operstate(&self) -> Option<&'static str> {
+        let operstate = match self {
+            OperStatus::IfOperStatusUp => "up",
+            OperStatus::IfOperStatusDown => "down",
+            OperStatus::IfOperStatusTesting => "testing",
+            OperStatus::IfOperStatusUnknown => "unknown",
+            OperStatus::IfOperStatusDormant => "dormant",
+            OperStatus::IfOperStatusNotPresent => "notpresent",
+            OperStatus::IfOperStatusLowerLayerDown => "lowerlayerdown",
+            OperStatus::Unknown(_) => return None,
+        };
+        Some(operstate)
+    }
+}
+
+/// Formats the status with its RFC 2863 name, or the raw value for `Unknown`.
+impl fmt::Display for OperStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            OperStatus::IfOperStatusUp => f.write_str("up"),
+            OperStatus::IfOperStatusDown => f.write_str("down"),
+            OperStatus::IfOperStatusTesting => f.write_str("testing"),
+            OperStatus::IfOperStatusUnknown => f.write_str("unknown"),
+            OperStatus::IfOperStatusDormant => f.write_str("dormant"),
+            OperStatus::IfOperStatusNotPresent => f.write_str("notPresent"),
+            OperStatus::IfOperStatusLowerLayerDown => f.write_str("lowerLayerDown"),
+            OperStatus::Unknown(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+/// Accepts the names produced by `Display` (case insensitive) or a raw value.
+impl FromStr for OperStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(v) = s.parse::<u32>() {
+            return Ok(v.into());
+        }
+        OperStatus::from_linux_operstate(&s.to_ascii_lowercase())
+            .ok_or_else(|| Error::parse("OperStatus", s))
+    }
+}
+
+/// Represent the administrative status of the adapter
+/// See NET_IF_ADMIN_STATUS docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum AdminStatus {
+    Up,
+    Down,
+    Testing,
+    /// A raw value that is not defined by `NET_IF_ADMIN_STATUS`.
+    Unknown(u32),
+}
+
+impl From<u32> for AdminStatus {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => AdminStatus::Up,
+            2 => AdminStatus::Down,
+            3 => AdminStatus::Testing,
+            v => AdminStatus::Unknown(v),
+        }
+    }
+}
+
+impl From<AdminStatus> for u32 {
+    fn from(value: AdminStatus) -> Self {
+        match value {
+            AdminStatus::Up => 1,
+            AdminStatus::Down => 2,
+            AdminStatus::Testing => 3,
+            AdminStatus::Unknown(v) => v,
+        }
+    }
+}
+
+impl Default for AdminStatus {
+    fn default() -> Self {
+        AdminStatus::Unknown(0)
+    }
+}
+
+impl AdminStatus {
+    const IFF_UP: u32 = 0x1;
+
+    /// Derive the administrative status from the Linux interface flags
+    /// (`/sys/class/net/<iface>/flags` or `ifi_flags`), Linux has no testing state.
+    pub fn from_linux_flags(flags: u32) -> Self {
+        if flags & Self::IFF_UP != 0 {
+            AdminStatus::Up
+        } else {
+            AdminStatus::Down
+        }
+    }
+}
+
+impl fmt::Display for AdminStatus {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            AdminStatus::Up => f.write_str("up"),
+            AdminStatus::Down => f.write_str("down"),
+            AdminStatus::Testing => f.write_str("testing"),
+            AdminStatus::Unknown(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl FromStr for AdminStatus {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(v) = s.parse::<u32>() {
+            return Ok(v.into());
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "up" => Ok(AdminStatus::Up),
+            "down" => Ok(AdminStatus::Down),
+            "testing" => Ok(AdminStatus::Testing),
+            _ => Err(Error::parse("AdminStatus", s)),
+        }
+    }
+}
+
+/// Represent the media connect state of the adapter
+/// See NET_IF_MEDIA_CONNECT_STATE docs for more details
+///
+/// `MediaConnectStateUnknown` is represented by `Unknown(0)`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum MediaConnectState {
+    Connected,
+    Disconnected,
+    Unknown(u32),
+}
+
+impl From<u32> for MediaConnectState {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => MediaConnectState::Connected,
+            2 => MediaConnectState::Disconnected,
+            v => MediaConnectState::Unknown(v),
+        }
+    }
+}
+
+impl From<MediaConnectState> for u32 {
+    fn from(value: MediaConnectState) -> Self {
+        match value {
+            MediaConnectState::Connected => 1,
+            MediaConnectState::Disconnected => 2,
+            MediaConnectState::Unknown(v) => v,
+        }
+    }
+}
+
+impl Default for MediaConnectState {
+    fn default() -> Self {
+        MediaConnectState::Unknown(0)
+    }
+}
+
+impl MediaConnectState {
+    /// Parse the content of `/sys/class/net/<iface>/carrier` on Linux.
+    /// Reading that file fails with `EINVAL` while the interface is down,
+    /// which callers should map to `Unknown(0)`.
+    pub fn from_linux_carrier(carrier: &str) -> Self {
+        match carrier.trim() {
+            "1" => MediaConnectState::Connected,
+            "0" => MediaConnectState::Disconnected,
+            _ => MediaConnectState::Unknown(0),
+        }
+    }
+}
+
+impl fmt::Display for MediaConnectState {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            MediaConnectState::Connected => f.write_str("connected"),
+            MediaConnectState::Disconnected => f.write_str("disconnected"),
+            MediaConnectState::Unknown(0) => f.write_str("unknown"),
+            MediaConnectState::Unknown(v) => write!(f, "{}", v),
+        }
+    }
+}
+
+impl FromStr for MediaConnectState {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(v) = s.parse::<u32>() {
+            return Ok(v.into());
+        }
+        match s.to_ascii_lowercase().as_str() {
+            "connected" => Ok(MediaConnectState::Connected),
+            "disconnected" => Ok(MediaConnectState::Disconnected),
+            "unknown" => Ok(MediaConnectState::Unknown(0)),
+            _ => Err(Error::parse("MediaConnectState", s)),
+        }
+    }
+}
+
+/// Represent the `Flags` of the adapter
+/// See IP_ADAPTER_ADDRESSES docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct AdapterFlags(pub u32);
+
+impl AdapterFlags {
+    pub const DDNS_ENABLED: u32 = 0x0001;
+    pub const REGISTER_ADAPTER_SUFFIX: u32 = 0x0002;
+    pub const DHCP_ENABLED: u32 = 0x0004;
+    pub const RECEIVE_ONLY: u32 = 0x0008;
+    pub const NO_MULTICAST: u32 = 0x0010;
+    pub const IPV6_OTHER_STATEFUL_CONFIG: u32 = 0x0020;
+    pub const NETBIOS_OVER_TCPIP_ENABLED: u32 = 0x0040;
+    pub const IPV4_ENABLED: u32 = 0x0080;
+    pub const IPV6_ENABLED: u32 = 0x0100;
+    pub const IPV6_MANAGE_ADDRESS_CONFIG: u32 = 0x0200;
+
+    pub fn contains(&self, flag: u32) -> bool {
+        self.0 & flag == flag
+    }
+
+    /// Dynamic DNS registration is enabled.
+    pub fn ddns_enabled(&self) -> bool {
+        self.contains(Self::DDNS_ENABLED)
+    }
+
+    /// The IPv4 configuration is obtained through DHCP.
+    pub fn dhcp_enabled(&self) -> bool {
+        self.contains(Self::DHCP_ENABLED)
+    }
+
+    pub fn netbios_over_tcpip_enabled(&self) -> bool {
+        self.contains(Self::NETBIOS_OVER_TCPIP_ENABLED)
+    }
+
+    pub fn ipv4_enabled(&self) -> bool {
+        self.contains(Self::IPV4_ENABLED)
+    }
+
+    pub fn ipv6_enabled(&self) -> bool {
+        self.contains(Self::IPV6_ENABLED)
+    }
+}
+
+/// Represent the encapsulation method of a tunnel adapter
+/// See TUNNEL_TYPE docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum TunnelType {
+    #[default]
+    None,
+    Other,
+    Direct,
+    SixToFour,
+    Isatap,
+    Teredo,
+    IpHttps,
+    /// A raw value that is not defined by `TUNNEL_TYPE`.
+    Unknown(u32),
+}
+
+impl From<u32> for TunnelType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => TunnelType::None,
+            1 => TunnelType::Other,
+            2 => TunnelType::Direct,
+            11 => TunnelType::SixToFour,
+            13 => TunnelType::Isatap,
+            14 => TunnelType::Teredo,
+            15 => TunnelType::IpHttps,
+            v => TunnelType::Unknown(v),
+        }
+    }
+}
+
+impl From<TunnelType> for u32 {
+    fn from(value: TunnelType) -> Self {
+        match value {
+            TunnelType::None => 0,
+            TunnelType::Other => 1,
+            TunnelType::Direct => 2,
+            TunnelType::SixToFour => 11,
+            TunnelType::Isatap => 13,
+            TunnelType::Teredo => 14,
+            TunnelType::IpHttps => 15,
+            TunnelType::Unknown(v) => v,
+        }
+    }
+}
+
+/// Represent the connection type of the adapter
+/// See NET_IF_CONNECTION_TYPE docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum ConnectionType {
+    #[default]
+    Dedicated,
+    Passive,
+    Demand,
+    /// A raw value that is not defined by `NET_IF_CONNECTION_TYPE`.
+    Unknown(u32),
+}
+
+impl From<u32> for ConnectionType {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => ConnectionType::Dedicated,
+            2 => ConnectionType::Passive,
+            3 => ConnectionType::Demand,
+            v => ConnectionType::Unknown(v),
+        }
+    }
+}
+
+impl From<ConnectionType> for u32 {
+    fn from(value: ConnectionType) -> Self {
+        match value {
+            ConnectionType::Dedicated => 1,
+            ConnectionType::Passive => 2,
+            ConnectionType::Demand => 3,
+            ConnectionType::Unknown(v) => v,
+        }
+    }
 }
 
 /// Represent an interface type
@@ -37,8 +421,10 @@ pub enum OperStatus {
 /// `IfType::Unsupported` is used. `IfType::Other`
 /// is different from `IfType::Unsupported`, as the former
 /// one is defined by the IANA itself.
-#[derive(Debug, Clone, Copy, PartialEq)]
+#[allow(clippy::manual_non_exhaustive)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
 pub enum IfType {
+    #[default]
     Other = 1,
     EthernetCsmacd = 6,
     Iso88025Tokenring = 9,
@@ -56,31 +442,156 @@ pub enum IfType {
     __Nonexhaustive,
 }
 
+impl From<u32> for IfType {
+    fn from(value: u32) -> Self {
+        match value {
+            1 => IfType::Other,
+            6 => IfType::EthernetCsmacd,
+            9 => IfType::Iso88025Tokenring,
+            23 => IfType::Ppp,
+            24 => IfType::SoftwareLoopback,
+            37 => IfType::Atm,
+            71 => IfType::Ieee80211,
+            131 => IfType::Tunnel,
+            144 => IfType::Ieee1394,
+            _ => IfType::Unsupported,
+        }
+    }
+}
+
+/// `IfType::Unsupported` has no IANA value and converts to 0.
+impl From<IfType> for u32 {
+    fn from(value: IfType) -> Self {
+        match value {
+            IfType::Unsupported | IfType::__Nonexhaustive => 0,
+            v => v as u32,
+        }
+    }
+}
+
 /// Represent an adapter.
-#[derive(Debug)]
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(default))]
 pub struct Adapter {
-    pub adapter_name: String,
+    /// The interface GUID in braces on Windows, the kernel name on Linux.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::os_string"))]
+    pub adapter_name: OsString,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
     pub network_guid: [u8; 16],
     pub luid: u64,
     pub ipv4_if_index: u32,
-    pub ip_addresses: Vec<IpAddr>,
-    pub prefixes: Vec<(IpAddr, u32)>,
-    pub gateways: Vec<IpAddr>,
-    pub dns_servers: Vec<IpAddr>,
-    pub description: String,
-    pub friendly_name: String,
+    /// The unicast addresses, link-local ones keep their zone index.
+    pub ip_addresses: Vec<ScopedIpAddr>,
+    /// The unicast addresses with their on-link prefix length, origin, DAD
+    /// state and lifetimes, in the same order as `ip_addresses`.
+    pub unicast_addresses: Vec<UnicastAddress>,
+    pub prefixes: Vec<IpNetwork>,
+    pub anycast_addresses: Vec<IpAddr>,
+    pub multicast_addresses: Vec<IpAddr>,
+    pub gateways: Vec<ScopedIpAddr>,
+    pub dns_servers: Vec<ScopedIpAddr>,
+    pub wins_servers: Vec<IpAddr>,
+    /// The DNS suffix associated with the adapter.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::os_string"))]
+    pub dns_suffix: OsString,
+    /// The additional DNS suffixes, see `FirstDnsSuffix`.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::os_string_list"))]
+    pub dns_suffixes: Vec<OsString>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::os_string"))]
+    pub description: OsString,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::os_string"))]
+    pub friendly_name: OsString,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::optional_mac"))]
     pub physical_address: Option<Vec<u8>>,
+    pub flags: AdapterFlags,
+    pub mtu: u32,
+    pub dhcpv4_server: Option<IpAddr>,
+    pub dhcpv6_server: Option<IpAddr>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::optional_mac"))]
+    pub dhcpv6_client_duid: Option<Vec<u8>>,
+    pub dhcpv6_iaid: u32,
     pub receive_link_speed: u64,
     pub transmit_link_speed: u64,
     pub oper_status: OperStatus,
+    pub admin_status: AdminStatus,
+    pub media_connect_state: MediaConnectState,
     pub if_type: IfType,
     pub ipv6_if_index: u32,
     pub ipv4_metric: u32,
     pub ipv6_metric: u32,
+    pub tunnel_type: TunnelType,
+    pub connection_type: ConnectionType,
+    /// The zone indices of each scope level, indexed by `SCOPE_LEVEL`.
+    pub zone_indices: [u32; 16],
+}
+
+impl Adapter {
+    /// `adapter_name`, with U+FFFD in place of what isn't valid Unicode.
+    pub fn adapter_name_lossy(&self) -> Cow<'_, str> {
+        self.adapter_name.to_string_lossy()
+    }
+
+    pub fn description_lossy(&self) -> Cow<'_, str> {
+        self.description.to_string_lossy()
+    }
+
+    pub fn friendly_name_lossy(&self) -> Cow<'_, str> {
+        self.friendly_name.to_string_lossy()
+    }
+
+    pub fn dns_suffix_lossy(&self) -> Cow<'_, str> {
+        self.dns_suffix.to_string_lossy()
+    }
+
+    /// The unicast addresses that may be used as the source of new
+    /// connections, tentative and deprecated addresses are skipped.
+    pub fn source_addresses(&self) -> impl Iterator<Item = &UnicastAddress> {
+        self.unicast_addresses
+            .iter()
+            .filter(|a| a.is_usable_as_source())
+    }
+}
+
+/// An adapter that couldn't be decoded.
+#[derive(Debug)]
+pub struct AdapterError {
+    pub adapter_name: OsString,
+    pub luid: u64,
+    pub error: Error,
+}
+
+/// The adapters of an enumeration, and the errors of those that couldn't be
+/// decoded.
+#[derive(Debug, Default)]
+pub struct AdapterList {
+    pub adapters: Vec<Adapter>,
+    pub errors: Vec<AdapterError>,
 }
 
 /// Get all the network adapters on this machine.
+///
+/// The adapters that can't be decoded are skipped, use
+/// [`get_adapters_partial`] to get their errors.
+///
+/// Use [`AdapterQuery`](crate::query::AdapterQuery) to only retrieve some of
+/// the adapters or some of their fields.
+#[cfg(windows)]
 pub fn get_adapters() -> Result<Vec<Adapter>> {
+    crate::query::AdapterQuery::new().get()
+}
+
+/// Get all the network adapters on this machine, and the errors of the
+/// adapters that couldn't be decoded.
+#[cfg(windows)]
+pub fn get_adapters_partial() -> Result<AdapterList> {
+    crate::query::AdapterQuery::new().get_partial()
+}
+
+/// Call `GetAdaptersAddresses` with an address family and `GAA_FLAG_*` flags,
+/// and return the buffer it filled.
+#[cfg(windows)]
+pub(crate) fn get_adapters_buffer(family: u32, flags: u32) -> Result<Vec<u8>> {
     unsafe {
         // Preallocate 16K per Microsoft recommendation, see Remarks section
         // https://docs.microsoft.com/en-us/windows/desktop/api/iphlpapi/nf-iphlpapi-getadaptersaddresses
@@ -92,8 +603,8 @@ pub fn get_adapters() -> Result<Vec<Adapter>> {
             adapters_addresses_buffer.resize(buf_len as usize, 0);
 
             result = IpHelper::GetAdaptersAddresses(
-                IpHelper::AF_UNSPEC,
-                0x0080 | 0x0010, //GAA_FLAG_INCLUDE_GATEWAYS | GAA_FLAG_INCLUDE_PREFIX,
+                family,
+                flags,
                 std::ptr::null_mut(),
                 adapters_addresses_buffer.as_mut_ptr() as *mut _,
                 &mut buf_len as *mut _,
@@ -101,180 +612,53 @@ pub fn get_adapters() -> Result<Vec<Adapter>> {
         }
 
         if result != ERROR_SUCCESS {
-            return Err(Error {
-                kind: ErrorKind::Os(result),
-            });
+            return Err(Error::os(result).with_operation("GetAdaptersAddresses"));
         }
-
-        let mut adapters = vec![];
-        let mut adapter_addresses_ptr =
-            adapters_addresses_buffer.as_mut_ptr() as *const IpHelper::IP_ADAPTER_ADDRESSES_LH;
-
-        while !adapter_addresses_ptr.is_null() {
-            adapters.push(get_adapter(adapter_addresses_ptr)?);
-            adapter_addresses_ptr = adapter_addresses_ptr.read_unaligned().Next;
-        }
-
-        Ok(adapters)
-    }
-}
-
-// ref: https://learn.microsoft.com/en-us/windows/win32/api/iptypes/ns-iptypes-ip_adapter_addresses_lh
-unsafe fn get_adapter(
-    adapter_addresses_ptr: *const IpHelper::IP_ADAPTER_ADDRESSES_LH,
-) -> Result<Adapter> {
-    let adapter_addresses = adapter_addresses_ptr.read_unaligned();
-    let guid = guid_to_bytes(&adapter_addresses.NetworkGuid);
-    let luid = adapter_addresses.Luid.Value;
-    let ipv4_if_index = adapter_addresses.Anonymous1.Anonymous.IfIndex;
-    let adapter_name = CStr::from_ptr(adapter_addresses.AdapterName as _)
-        .to_str()?
-        .to_owned();
-    let dns_servers = get_dns_servers(adapter_addresses.FirstDnsServerAddress)?;
-    let gateways = get_gateways(adapter_addresses.FirstGatewayAddress)?;
-    let prefixes = get_prefixes(adapter_addresses.FirstPrefix)?;
-    let unicast_addresses = get_unicast_addresses(adapter_addresses.FirstUnicastAddress)?;
-    let receive_link_speed: u64 = adapter_addresses.ReceiveLinkSpeed;
-    let transmit_link_speed: u64 = adapter_addresses.TransmitLinkSpeed;
-    let ipv4_metric = adapter_addresses.Ipv4Metric;
-    let ipv6_metric = adapter_addresses.Ipv6Metric;
-    let oper_status = match adapter_addresses.OperStatus {
-        1 => OperStatus::IfOperStatusUp,
-        2 => OperStatus::IfOperStatusDown,
-        3 => OperStatus::IfOperStatusTesting,
-        4 => OperStatus::IfOperStatusUnknown,
-        5 => OperStatus::IfOperStatusDormant,
-        6 => OperStatus::IfOperStatusNotPresent,
-        7 => OperStatus::IfOperStatusLowerLayerDown,
-        v => {
-            panic!("unexpected OperStatus value: {}", v);
-        }
-    };
-    let if_type = match adapter_addresses.IfType {
-        1 => IfType::Other,
-        6 => IfType::EthernetCsmacd,
-        9 => IfType::Iso88025Tokenring,
-        23 => IfType::Ppp,
-        24 => IfType::SoftwareLoopback,
-        37 => IfType::Atm,
-        71 => IfType::Ieee80211,
-        131 => IfType::Tunnel,
-        144 => IfType::Ieee1394,
-        _ => IfType::Unsupported,
-    };
-    let ipv6_if_index = adapter_addresses.Ipv6IfIndex;
-
-    let description = WideCString::from_ptr_str(adapter_addresses.Description).to_string()?;
-    let friendly_name = WideCString::from_ptr_str(adapter_addresses.FriendlyName).to_string()?;
-    let physical_address = if adapter_addresses.PhysicalAddressLength == 0 {
-        None
-    } else {
-        Some(
-            adapter_addresses.PhysicalAddress[..adapter_addresses.PhysicalAddressLength as usize]
-                .to_vec(),
-        )
-    };
-    Ok(Adapter {
-        adapter_name,
-        network_guid: guid,
-        luid,
-        ipv4_if_index,
-        ip_addresses: unicast_addresses,
-        prefixes,
-        gateways,
-        dns_servers,
-        description,
-        friendly_name,
-        physical_address,
-        receive_link_speed,
-        transmit_link_speed,
-        oper_status,
-        if_type,
-        ipv6_if_index,
-        ipv4_metric,
-        ipv6_metric,
-    })
-}
-
-unsafe fn socket_address_to_ipaddr(socket_address: &WinSock::SOCKET_ADDRESS) -> IpAddr {
-    let (_, sockaddr) = socket2::SockAddr::try_init(|storage, length| {
-        let sockaddr_length = usize::try_from(socket_address.iSockaddrLength).unwrap();
-        assert!(sockaddr_length <= std::mem::size_of_val(&storage.read_unaligned()));
-        let dst: *mut u8 = storage.cast();
-        let src: *const u8 = socket_address.lpSockaddr.cast();
-        dst.copy_from_nonoverlapping(src, sockaddr_length);
-        std::ptr::write_unaligned(length, socket_address.iSockaddrLength);
-        Ok(())
-    })
-    .unwrap();
-
-    sockaddr.as_socket().map(|s| s.ip()).unwrap()
-}
-
-unsafe fn get_dns_servers(
-    mut dns_server_ptr: *const IpHelper::IP_ADAPTER_DNS_SERVER_ADDRESS_XP,
-) -> Result<Vec<IpAddr>> {
-    let mut dns_servers = vec![];
-
-    while !dns_server_ptr.is_null() {
-        let dns_server = dns_server_ptr.read_unaligned();
-        let ipaddr = socket_address_to_ipaddr(&dns_server.Address);
-        dns_servers.push(ipaddr);
-
-        dns_server_ptr = dns_server.Next;
-    }
-
-    Ok(dns_servers)
-}
-
-unsafe fn get_gateways(
-    mut gateway_ptr: *const IpHelper::IP_ADAPTER_GATEWAY_ADDRESS_LH,
-) -> Result<Vec<IpAddr>> {
-    let mut gateways = vec![];
-
-    while !gateway_ptr.is_null() {
-        let gateway = gateway_ptr.read_unaligned();
-        let ipaddr = socket_address_to_ipaddr(&gateway.Address);
-        gateways.push(ipaddr);
-
-        gateway_ptr = gateway.Next;
-    }
-
-    Ok(gateways)
-}
-
-unsafe fn get_unicast_addresses(
-    mut unicast_addresses_ptr: *const IpHelper::IP_ADAPTER_UNICAST_ADDRESS_LH,
-) -> Result<Vec<IpAddr>> {
-    let mut unicast_addresses = vec![];
-
-    while !unicast_addresses_ptr.is_null() {
-        let unicast_address = unicast_addresses_ptr.read_unaligned();
-        let ipaddr = socket_address_to_ipaddr(&unicast_address.Address);
-        unicast_addresses.push(ipaddr);
-
-        unicast_addresses_ptr = unicast_address.Next;
+        Ok(adapters_addresses_buffer)
     }
-
-    Ok(unicast_addresses)
 }
 
-unsafe fn get_prefixes(
-    mut prefixes_ptr: *const IpHelper::IP_ADAPTER_PREFIX_XP,
-) -> Result<Vec<(IpAddr, u32)>> {
-    let mut prefixes = vec![];
-
-    while !prefixes_ptr.is_null() {
-        let prefix = prefixes_ptr.read_unaligned();
-        let ipaddr = socket_address_to_ipaddr(&prefix.Address);
-        prefixes.push((ipaddr, prefix.PrefixLength));
-
-        prefixes_ptr = prefix.Next;
+/// Call `GetAdaptersAddresses` with an address family and `GAA_FLAG_*` flags,
+/// and decode the adapters, only the ones `lookup` may match if there is one.
+#[cfg(windows)]
+pub(crate) fn get_adapters_addresses(
+    family: u32,
+    flags: u32,
+    lookup: Option<&crate::query::AdapterLookup>,
+) -> Result<AdapterList> {
+    let buffer = get_adapters_buffer(family, flags)?;
+    let mut list = crate::decode::decode_adapters(&buffer, buffer.as_ptr() as u64, lookup)?;
+
+    for adapter in &mut list.adapters {
+        let luid = IpHelper::NET_LUID_LH {
+            Value: adapter.luid,
+        };
+        let (admin_status, media_connect_state) = unsafe { get_if_states(luid) };
+        adapter.admin_status = admin_status;
+        adapter.media_connect_state = media_connect_state;
     }
+    Ok(list)
+}
 
-    Ok(prefixes)
+/// The administrative and media state are not part of `IP_ADAPTER_ADDRESSES`,
+/// so they are read from the `MIB_IF_ROW2` of the interface. Both fall back to
+/// unknown if the row can't be retrieved.
+#[cfg(windows)]
+unsafe fn get_if_states(luid: IpHelper::NET_LUID_LH) -> (AdminStatus, MediaConnectState) {
+    let mut row: IpHelper::MIB_IF_ROW2 = std::mem::zeroed();
+    row.InterfaceLuid = luid;
+    let code = IpHelper::GetIfEntry2(&mut row as *mut _);
+    if code != 0 {
+        tracing::warn!("GetIfEntry2 failed for luid {}: {}", luid.Value, code);
+        return (AdminStatus::Unknown(0), MediaConnectState::Unknown(0));
+    }
+    (
+        AdminStatus::from(row.AdminStatus as u32),
+        MediaConnectState::from(row.MediaConnectState as u32),
+    )
 }
 
+#[cfg(windows)]
 #[test]
 fn test_convert() {
     let adapters = get_adapters().unwrap();
@@ -300,10 +684,151 @@ fn test_convert() {
     }
 }
 
+#[cfg(windows)]
 #[test]
 fn test_get_dns() {
     let adapters = get_adapters().unwrap();
     for a in adapters {
-        println!("{}: {:?}", a.friendly_name, a.dns_servers);
+        println!("{}: {:?}", a.friendly_name_lossy(), a.dns_servers);
+    }
+}
+
+#[test]
+fn test_oper_status_conversion() {
+    let cases = [
+        (1, OperStatus::IfOperStatusUp, "up", Some("up")),
+        (2, OperStatus::IfOperStatusDown, "down", Some("down")),
+        (
+            3,
+            OperStatus::IfOperStatusTesting,
+            "testing",
+            Some("testing"),
+        ),
+        (
+            4,
+            OperStatus::IfOperStatusUnknown,
+            "unknown",
+            Some("unknown"),
+        ),
+        (
+            5,
+            OperStatus::IfOperStatusDormant,
+            "dormant",
+            Some("dormant"),
+        ),
+        (
+            6,
+            OperStatus::IfOperStatusNotPresent,
+            "notPresent",
+            Some("notpresent"),
+        ),
+        (
+            7,
+            OperStatus::IfOperStatusLowerLayerDown,
+            "lowerLayerDown",
+            Some("lowerlayerdown"),
+        ),
+        (0, OperStatus::Unknown(0), "0", None),
+        (8, OperStatus::Unknown(8), "8", None),
+        (u32::MAX, OperStatus::Unknown(u32::MAX), "4294967295", None),
+    ];
+    for (raw, status, display, operstate) in cases {
+        assert_eq!(OperStatus::from(raw), status);
+        assert_eq!(u32::from(status), raw);
+        assert_eq!(status.to_string(), display);
+        assert_eq!(display.parse::<OperStatus>().unwrap(), status);
+        assert_eq!(
+            display.to_uppercase().parse::<OperStatus>().unwrap(),
+            status
+        );
+        assert_eq!(status.to_linux_operstate(), operstate);
+        if let Some(operstate) = operstate {
+            assert_eq!(OperStatus::from_linux_operstate(operstate), Some(status));
+            let line = format!("{}\n", operstate);
+            assert_eq!(OperStatus::from_linux_operstate(&line), Some(status));
+        }
+    }
+    assert!("sideways".parse::<OperStatus>().is_err());
+    assert_eq!(OperStatus::from_linux_operstate("sideways"), None);
+}
+
+#[test]
+fn test_admin_status_conversion() {
+    let cases = [
+        (1, AdminStatus::Up, "up"),
+        (2, AdminStatus::Down, "down"),
+        (3, AdminStatus::Testing, "testing"),
+        (0, AdminStatus::Unknown(0), "0"),
+        (42, AdminStatus::Unknown(42), "42"),
+    ];
+    for (raw, status, display) in cases {
+        assert_eq!(AdminStatus::from(raw), status);
+        assert_eq!(u32::from(status), raw);
+        assert_eq!(status.to_string(), display);
+        assert_eq!(display.parse::<AdminStatus>().unwrap(), status);
+    }
+    let flags = [
+        (0x1003, AdminStatus::Up),
+        (0x1002, AdminStatus::Down),
+        (0x0, AdminStatus::Down),
+    ];
+    for (flags, status) in flags {
+        assert_eq!(AdminStatus::from_linux_flags(flags), status);
+    }
+    assert!("enabled".parse::<AdminStatus>().is_err());
+}
+
+#[test]
+fn test_media_connect_state_conversion() {
+    let cases = [
+        (0, MediaConnectState::Unknown(0), "unknown"),
+        (1, MediaConnectState::Connected, "connected"),
+        (2, MediaConnectState::Disconnected, "disconnected"),
+        (9, MediaConnectState::Unknown(9), "9"),
+    ];
+    for (raw, state, display) in cases {
+        assert_eq!(MediaConnectState::from(raw), state);
+        assert_eq!(u32::from(state), raw);
+        assert_eq!(state.to_string(), display);
+        assert_eq!(display.parse::<MediaConnectState>().unwrap(), state);
+    }
+    let carriers = [
+        ("1\n", MediaConnectState::Connected),
+        ("0\n", MediaConnectState::Disconnected),
+        ("", MediaConnectState::Unknown(0)),
+    ];
+    for (carrier, state) in carriers {
+        assert_eq!(MediaConnectState::from_linux_carrier(carrier), state);
+    }
+    assert!("plugged".parse::<MediaConnectState>().is_err());
+}
+
+#[test]
+fn test_adapter_fields_conversion() {
+    let flags = AdapterFlags(
+        AdapterFlags::DDNS_ENABLED | AdapterFlags::DHCP_ENABLED | AdapterFlags::IPV4_ENABLED,
+    );
+    assert!(flags.ddns_enabled());
+    assert!(flags.dhcp_enabled());
+    assert!(flags.ipv4_enabled());
+    assert!(!flags.ipv6_enabled());
+    assert!(!flags.netbios_over_tcpip_enabled());
+
+    let tunnel_types = [
+        (0, TunnelType::None),
+        (11, TunnelType::SixToFour),
+        (14, TunnelType::Teredo),
+        (12, TunnelType::Unknown(12)),
+    ];
+    for (raw, tunnel_type) in tunnel_types {
+        assert_eq!(TunnelType::from(raw), tunnel_type);
+    }
+    let connection_types = [
+        (1, ConnectionType::Dedicated),
+        (3, ConnectionType::Demand),
+        (4, ConnectionType::Unknown(4)),
+    ];
+    for (raw, connection_type) in connection_types {
+        assert_eq!(ConnectionType::from(raw), connection_type);
     }
 }
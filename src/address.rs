@@ -0,0 +1,456 @@
+//! Per-address details of the unicast addresses of an adapter.
+//! See IP_ADAPTER_UNICAST_ADDRESS_LH docs for more details
+
+use std::net::IpAddr;
+use std::time::Duration;
+
+use crate::network::IpNetwork;
+
+/// Represent where the prefix of an address comes from
+/// See NL_PREFIX_ORIGIN docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum PrefixOrigin {
+    Other,
+    Manual,
+    WellKnown,
+    Dhcp,
+    RouterAdvertisement,
+    Unchanged,
+    /// A raw value that is not defined by `NL_PREFIX_ORIGIN`.
+    Unknown(u32),
+}
+
+impl From<u32> for PrefixOrigin {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => PrefixOrigin::Other,
+            1 => PrefixOrigin::Manual,
+            2 => PrefixOrigin::WellKnown,
+            3 => PrefixOrigin::Dhcp,
+            4 => PrefixOrigin::RouterAdvertisement,
+            16 => PrefixOrigin::Unchanged,
+            v => PrefixOrigin::Unknown(v),
+        }
+    }
+}
+
+impl From<PrefixOrigin> for u32 {
+    fn from(value: PrefixOrigin) -> Self {
+        match value {
+            PrefixOrigin::Other => 0,
+            PrefixOrigin::Manual => 1,
+            PrefixOrigin::WellKnown => 2,
+            PrefixOrigin::Dhcp => 3,
+            PrefixOrigin::RouterAdvertisement => 4,
+            PrefixOrigin::Unchanged => 16,
+            PrefixOrigin::Unknown(v) => v,
+        }
+    }
+}
+
+/// Represent where the interface identifier of an address comes from
+/// See NL_SUFFIX_ORIGIN docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum SuffixOrigin {
+    Other,
+    Manual,
+    WellKnown,
+    Dhcp,
+    LinkLayerAddress,
+    /// A randomly generated suffix, used by temporary (privacy) addresses.
+    Random,
+    Unchanged,
+    /// A raw value that is not defined by `NL_SUFFIX_ORIGIN`.
+    Unknown(u32),
+}
+
+impl From<u32> for SuffixOrigin {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => SuffixOrigin::Other,
+            1 => SuffixOrigin::Manual,
+            2 => SuffixOrigin::WellKnown,
+            3 => SuffixOrigin::Dhcp,
+            4 => SuffixOrigin::LinkLayerAddress,
+            5 => SuffixOrigin::Random,
+            16 => SuffixOrigin::Unchanged,
+            v => SuffixOrigin::Unknown(v),
+        }
+    }
+}
+
+impl From<SuffixOrigin> for u32 {
+    fn from(value: SuffixOrigin) -> Self {
+        match value {
+            SuffixOrigin::Other => 0,
+            SuffixOrigin::Manual => 1,
+            SuffixOrigin::WellKnown => 2,
+            SuffixOrigin::Dhcp => 3,
+            SuffixOrigin::LinkLayerAddress => 4,
+            SuffixOrigin::Random => 5,
+            SuffixOrigin::Unchanged => 16,
+            SuffixOrigin::Unknown(v) => v,
+        }
+    }
+}
+
+/// Represent the duplicate address detection state of an address
+/// See NL_DAD_STATE docs for more details
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum DadState {
+    Invalid,
+    Tentative,
+    Duplicate,
+    Deprecated,
+    Preferred,
+    /// A raw value that is not defined by `NL_DAD_STATE`.
+    Unknown(u32),
+}
+
+impl From<u32> for DadState {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => DadState::Invalid,
+            1 => DadState::Tentative,
+            2 => DadState::Duplicate,
+            3 => DadState::Deprecated,
+            4 => DadState::Preferred,
+            v => DadState::Unknown(v),
+        }
+    }
+}
+
+impl From<DadState> for u32 {
+    fn from(value: DadState) -> Self {
+        match value {
+            DadState::Invalid => 0,
+            DadState::Tentative => 1,
+            DadState::Duplicate => 2,
+            DadState::Deprecated => 3,
+            DadState::Preferred => 4,
+            DadState::Unknown(v) => v,
+        }
+    }
+}
+
+/// `struct ifa_cacheinfo` from the `IFA_CACHEINFO` attribute on Linux.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub struct IfaCacheinfo {
+    pub ifa_prefered: u32,
+    pub ifa_valid: u32,
+    pub cstamp: u32,
+    pub tstamp: u32,
+}
+
+/// The `IFA_F_*` flags of `ifa_flags` and `IFA_FLAGS` on Linux.
+pub mod ifa_flags {
+    pub const IFA_F_TEMPORARY: u32 = 0x01;
+    pub const IFA_F_NODAD: u32 = 0x02;
+    pub const IFA_F_OPTIMISTIC: u32 = 0x04;
+    pub const IFA_F_DADFAILED: u32 = 0x08;
+    pub const IFA_F_HOMEADDRESS: u32 = 0x10;
+    pub const IFA_F_DEPRECATED: u32 = 0x20;
+    pub const IFA_F_TENTATIVE: u32 = 0x40;
+    pub const IFA_F_PERMANENT: u32 = 0x80;
+    pub const IFA_F_MANAGETEMPADDR: u32 = 0x100;
+    pub const IFA_F_NOPREFIXROUTE: u32 = 0x200;
+    pub const IFA_F_MCAUTOJOIN: u32 = 0x400;
+    pub const IFA_F_STABLE_PRIVACY: u32 = 0x800;
+}
+
+/// Represent a unicast address of an adapter.
+///
+/// A lifetime of `None` means the address never expires.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct UnicastAddress {
+    /// The address with its on-link prefix length.
+    pub address: IpNetwork,
+    pub prefix_origin: PrefixOrigin,
+    pub suffix_origin: SuffixOrigin,
+    pub dad_state: DadState,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::schema::optional_lifetime")
+    )]
+    pub valid_lifetime: Option<Duration>,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::schema::optional_lifetime")
+    )]
+    pub preferred_lifetime: Option<Duration>,
+    /// The DHCP lease lifetime, only meaningful for DHCP addresses.
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::schema::optional_lifetime")
+    )]
+    pub lease_lifetime: Option<Duration>,
+}
+
+/// Both Windows and Linux use `0xffffffff` as an infinite lifetime.
+pub(crate) fn lifetime(secs: u32) -> Option<Duration> {
+    if secs == u32::MAX {
+        None
+    } else {
+        Some(Duration::from_secs(secs.into()))
+    }
+}
+
+impl UnicastAddress {
+    pub fn ip(&self) -> IpAddr {
+        self.address.addr()
+    }
+
+    pub fn prefix_len(&self) -> u8 {
+        self.address.prefix()
+    }
+
+    /// Whether this is a temporary (privacy) address, see RFC 8981.
+    pub fn is_temporary(&self) -> bool {
+        self.suffix_origin == SuffixOrigin::Random
+            && self.prefix_origin == PrefixOrigin::RouterAdvertisement
+    }
+
+    pub fn is_tentative(&self) -> bool {
+        self.dad_state == DadState::Tentative
+    }
+
+    pub fn is_deprecated(&self) -> bool {
+        self.dad_state == DadState::Deprecated
+    }
+
+    /// Whether the address may be picked as the source of new connections,
+    /// tentative, duplicate, deprecated and invalid addresses are not.
+    pub fn is_usable_as_source(&self) -> bool {
+        self.dad_state == DadState::Preferred
+    }
+
+    /// Build the address from the `ifa_flags`/`IFA_FLAGS` and `IFA_CACHEINFO`
+    /// of an `RTM_NEWADDR` message on Linux.
+    ///
+    /// Linux doesn't record the origins, they are inferred from the flags:
+    /// permanent addresses are manual (or well-known for loopback and
+    /// link-local), the others come from router advertisements for IPv6
+    /// and from DHCP for IPv4.
+    pub fn from_linux(address: IpNetwork, flags: u32, cacheinfo: Option<IfaCacheinfo>) -> Self {
+        use ifa_flags::*;
+
+        let ip = address.addr();
+        let well_known = ip.is_loopback() || is_link_local(ip);
+        let prefix_origin = if well_known {
+            PrefixOrigin::WellKnown
+        } else if flags & IFA_F_PERMANENT != 0 {
+            PrefixOrigin::Manual
+        } else if ip.is_ipv6() {
+            PrefixOrigin::RouterAdvertisement
+        } else {
+            PrefixOrigin::Dhcp
+        };
+        // RFC 7217 stable privacy identifiers are opaque but not temporary
+        let suffix_origin = if flags & IFA_F_TEMPORARY != 0 {
+            SuffixOrigin::Random
+        } else if flags & IFA_F_STABLE_PRIVACY != 0 {
+            SuffixOrigin::Other
+        } else {
+            match prefix_origin {
+                PrefixOrigin::WellKnown if ip.is_loopback() => SuffixOrigin::WellKnown,
+                PrefixOrigin::WellKnown | PrefixOrigin::RouterAdvertisement => {
+                    SuffixOrigin::LinkLayerAddress
+                }
+                PrefixOrigin::Dhcp => SuffixOrigin::Dhcp,
+                _ => SuffixOrigin::Manual,
+            }
+        };
+        let dad_state = if flags & IFA_F_DADFAILED != 0 {
+            DadState::Duplicate
+        } else if flags & IFA_F_TENTATIVE != 0 && flags & IFA_F_OPTIMISTIC == 0 {
+            DadState::Tentative
+        } else if flags & IFA_F_DEPRECATED != 0 {
+            DadState::Deprecated
+        } else {
+            DadState::Preferred
+        };
+        let cacheinfo = cacheinfo.unwrap_or(IfaCacheinfo {
+            ifa_prefered: u32::MAX,
+            ifa_valid: u32::MAX,
+            ..Default::default()
+        });
+        let lease_lifetime = if prefix_origin == PrefixOrigin::Dhcp {
+            lifetime(cacheinfo.ifa_valid)
+        } else {
+            None
+        };
+        UnicastAddress {
+            address,
+            prefix_origin,
+            suffix_origin,
+            dad_state,
+            valid_lifetime: lifetime(cacheinfo.ifa_valid),
+            preferred_lifetime: lifetime(cacheinfo.ifa_prefered),
+            lease_lifetime,
+        }
+    }
+}
+
+impl From<IpNetwork> for UnicastAddress {
+    /// A manually configured, preferred address that never expires.
+    fn from(address: IpNetwork) -> Self {
+        UnicastAddress {
+            address,
+            prefix_origin: PrefixOrigin::Manual,
+            suffix_origin: SuffixOrigin::Manual,
+            dad_state: DadState::Preferred,
+            valid_lifetime: None,
+            preferred_lifetime: None,
+            lease_lifetime: None,
+        }
+    }
+}
+
+fn is_link_local(ip: IpAddr) -> bool {
+    match ip {
+        IpAddr::V4(ip) => ip.is_link_local(),
+        IpAddr::V6(ip) => ip.segments()[0] & 0xffc0 == 0xfe80,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::ifa_flags::*;
+    use super::*;
+
+    #[test]
+    fn test_raw_conversion() {
+        assert_eq!(PrefixOrigin::from(4), PrefixOrigin::RouterAdvertisement);
+        assert_eq!(PrefixOrigin::from(16), PrefixOrigin::Unchanged);
+        assert_eq!(PrefixOrigin::from(7), PrefixOrigin::Unknown(7));
+        assert_eq!(SuffixOrigin::from(5), SuffixOrigin::Random);
+        assert_eq!(SuffixOrigin::from(6), SuffixOrigin::Unknown(6));
+        assert_eq!(DadState::from(1), DadState::Tentative);
+        assert_eq!(DadState::from(5), DadState::Unknown(5));
+        assert_eq!(lifetime(u32::MAX), None);
+        assert_eq!(lifetime(3600), Some(Duration::from_secs(3600)));
+    }
+
+    #[test]
+    fn test_from_linux() {
+        let cacheinfo = IfaCacheinfo {
+            ifa_prefered: 1800,
+            ifa_valid: 3600,
+            ..Default::default()
+        };
+        // (address, flags, prefix origin, suffix origin, dad state, usable as source)
+        let cases = [
+            (
+                "127.0.0.1/8",
+                IFA_F_PERMANENT,
+                PrefixOrigin::WellKnown,
+                SuffixOrigin::WellKnown,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "192.168.1.10/24",
+                IFA_F_PERMANENT,
+                PrefixOrigin::Manual,
+                SuffixOrigin::Manual,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "192.168.1.11/24",
+                0,
+                PrefixOrigin::Dhcp,
+                SuffixOrigin::Dhcp,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "fe80::1/64",
+                IFA_F_PERMANENT,
+                PrefixOrigin::WellKnown,
+                SuffixOrigin::LinkLayerAddress,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "fe80::2/64",
+                IFA_F_PERMANENT | IFA_F_TENTATIVE,
+                PrefixOrigin::WellKnown,
+                SuffixOrigin::LinkLayerAddress,
+                DadState::Tentative,
+                false,
+            ),
+            (
+                "2001:db8::1/64",
+                IFA_F_MANAGETEMPADDR,
+                PrefixOrigin::RouterAdvertisement,
+                SuffixOrigin::LinkLayerAddress,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "2001:db8::2/64",
+                IFA_F_TEMPORARY,
+                PrefixOrigin::RouterAdvertisement,
+                SuffixOrigin::Random,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "2001:db8::6/64",
+                IFA_F_STABLE_PRIVACY,
+                PrefixOrigin::RouterAdvertisement,
+                SuffixOrigin::Other,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "2001:db8::3/64",
+                IFA_F_TEMPORARY | IFA_F_DEPRECATED,
+                PrefixOrigin::RouterAdvertisement,
+                SuffixOrigin::Random,
+                DadState::Deprecated,
+                false,
+            ),
+            (
+                "2001:db8::4/64",
+                IFA_F_TENTATIVE | IFA_F_OPTIMISTIC,
+                PrefixOrigin::RouterAdvertisement,
+                SuffixOrigin::LinkLayerAddress,
+                DadState::Preferred,
+                true,
+            ),
+            (
+                "2001:db8::5/64",
+                IFA_F_PERMANENT | IFA_F_TENTATIVE | IFA_F_DADFAILED,
+                PrefixOrigin::Manual,
+                SuffixOrigin::Manual,
+                DadState::Duplicate,
+                false,
+            ),
+        ];
+        for (address, flags, prefix_origin, suffix_origin, dad_state, usable) in cases {
+            let addr = UnicastAddress::from_linux(address.parse().unwrap(), flags, Some(cacheinfo));
+            assert_eq!(addr.prefix_origin, prefix_origin, "{}", address);
+            assert_eq!(addr.suffix_origin, suffix_origin, "{}", address);
+            assert_eq!(addr.dad_state, dad_state, "{}", address);
+            assert_eq!(addr.is_usable_as_source(), usable, "{}", address);
+            assert_eq!(
+                addr.is_temporary(),
+                flags & IFA_F_TEMPORARY != 0,
+                "{}",
+                address
+            );
+            assert_eq!(addr.valid_lifetime, Some(Duration::from_secs(3600)));
+            assert_eq!(addr.preferred_lifetime, Some(Duration::from_secs(1800)));
+        }
+
+        let addr = UnicastAddress::from_linux("10.0.0.2/8".parse().unwrap(), 0, Some(cacheinfo));
+        assert_eq!(addr.lease_lifetime, Some(Duration::from_secs(3600)));
+        let addr = UnicastAddress::from_linux("10.0.0.2/8".parse().unwrap(), IFA_F_PERMANENT, None);
+        assert_eq!(addr.valid_lifetime, None);
+        assert_eq!(addr.lease_lifetime, None);
+    }
+}
@@ -0,0 +1,177 @@
+//! Pluggable access to the OS.
+//!
+//! Code that enumerates the adapters, reads or writes their DNS settings, or
+//! looks up interface indexes can take a [`Backend`] instead of calling the
+//! free functions, which use [`OsBackend`] on Windows. [`FakeBackend`] is an
+//! in-memory backend that runs the same code on any OS.
+
+pub mod fake;
+
+pub use fake::FakeBackend;
+
+use std::net::IpAddr;
+
+use crate::adapter::AdapterList;
+use crate::decode::AdapterBuffer;
+use crate::error::*;
+use crate::interface::InterfaceId;
+use crate::query::{AdapterQuery, AddressFamily};
+
+/// The operations that depend on the OS.
+pub trait Backend {
+    /// Enumerate the adapters with the address family and the `GAA_FLAG_*`
+    /// flags of `query`. The lookup of the query is applied by the caller,
+    /// see [`AdapterQuery::get_from`].
+    fn adapters(&self, query: &AdapterQuery) -> Result<AdapterList>;
+
+    /// The raw `GetAdaptersAddresses` buffer of `query`, see
+    /// [`AdapterQuery::get_buffer`].
+    ///
+    /// The default implementation encodes the adapters of
+    /// [`Backend::adapters`].
+    fn adapter_buffer(&self, query: &AdapterQuery) -> Result<AdapterBuffer> {
+        Ok(AdapterBuffer::encode(&self.adapters(query)?.adapters))
+    }
+
+    /// The IPv4 or IPv6 DNS settings of an interface.
+    fn dns_settings(&self, interface: &InterfaceId, is_ipv6: bool) -> Result<DnsSettings>;
+
+    /// Replace the IPv4 or IPv6 DNS settings of an interface.
+    fn set_dns_settings(
+        &self,
+        interface: &InterfaceId,
+        is_ipv6: bool,
+        settings: &DnsSettings,
+    ) -> Result<()>;
+
+    /// The IPv4 or IPv6 interface index of an interface, `None` if no
+    /// adapter of the family matches.
+    ///
+    /// The default implementation enumerates the adapters of the family.
+    fn interface_index(&self, interface: &InterfaceId, is_ipv6: bool) -> Result<Option<u32>> {
+        let family = if is_ipv6 {
+            AddressFamily::Ipv6
+        } else {
+            AddressFamily::Ipv4
+        };
+        let adapters = AdapterQuery::new()
+            .family(family)
+            .interface(interface)
+            .dns_servers(false)
+            .multicast(false)
+            .anycast(false)
+            .gateways(false)
+            .prefixes(false)
+            .get_from(self)?;
+        let if_index = adapters
+            .iter()
+            .map(|adapter| {
+                if is_ipv6 {
+                    adapter.ipv6_if_index
+                } else {
+                    adapter.ipv4_if_index
+                }
+            })
+            .find(|&if_index| if_index != 0);
+        Ok(if_index)
+    }
+}
+
+/// The DNS settings of an interface for one address family, the
+/// `NameServer` and `SearchList` of `DNS_INTERFACE_SETTINGS`.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DnsSettings {
+    pub name_servers: Vec<IpAddr>,
+    pub search_list: Vec<String>,
+}
+
+impl DnsSettings {
+    /// Parse the `NameServer` and `SearchList` strings, lists separated by
+    /// commas or spaces.
+    pub fn from_lists(name_servers: &str, search_list: &str) -> Result<Self> {
+        fn split(list: &str) -> impl Iterator<Item = &str> {
+            list.split([',', ' ']).filter(|item| !item.is_empty())
+        }
+        Ok(DnsSettings {
+            name_servers: split(name_servers)
+                .map(|server| {
+                    server
+                        .parse()
+                        .map_err(|_| Error::parse("name server", server))
+                })
+                .collect::<Result<_>>()?,
+            search_list: split(search_list).map(str::to_owned).collect(),
+        })
+    }
+
+    /// The `NameServer` and `SearchList` strings, separated by commas.
+    pub fn to_lists(&self) -> (String, String) {
+        let name_servers: Vec<_> = self.name_servers.iter().map(IpAddr::to_string).collect();
+        (name_servers.join(","), self.search_list.join(","))
+    }
+}
+
+/// The backend of the free functions, calling the Windows APIs.
+#[cfg(windows)]
+#[derive(Debug, Clone, Copy, Default)]
+pub struct OsBackend;
+
+#[cfg(windows)]
+impl Backend for OsBackend {
+    fn adapters(&self, query: &AdapterQuery) -> Result<AdapterList> {
+        crate::adapter::get_adapters_addresses(
+            query.address_family().to_windows(),
+            query.flags(),
+            query.lookup(),
+        )
+    }
+
+    fn adapter_buffer(&self, query: &AdapterQuery) -> Result<AdapterBuffer> {
+        query.get_buffer()
+    }
+
+    fn dns_settings(&self, interface: &InterfaceId, is_ipv6: bool) -> Result<DnsSettings> {
+        crate::dns::read_dns_settings(interface, is_ipv6)
+    }
+
+    fn set_dns_settings(
+        &self,
+        interface: &InterfaceId,
+        is_ipv6: bool,
+        settings: &DnsSettings,
+    ) -> Result<()> {
+        crate::dns::write_dns_settings(interface, is_ipv6, settings)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_dns_lists() {
+        let settings =
+            DnsSettings::from_lists("1.1.1.1,2606:4700:4700::1111 9.9.9.9", "corp.example.com")
+                .unwrap();
+        assert_eq!(
+            settings.name_servers,
+            ["1.1.1.1", "2606:4700:4700::1111", "9.9.9.9"].map(|s| s.parse::<IpAddr>().unwrap())
+        );
+        assert_eq!(settings.search_list, ["corp.example.com"]);
+        assert_eq!(
+            settings.to_lists(),
+            (
+                "1.1.1.1,2606:4700:4700::1111,9.9.9.9".to_owned(),
+                "corp.example.com".to_owned()
+            )
+        );
+
+        assert_eq!(
+            DnsSettings::from_lists("", "").unwrap(),
+            DnsSettings::default()
+        );
+        let err = DnsSettings::from_lists("1.1.1.1,dns.google", "").unwrap_err();
+        assert_eq!(err.to_string(), "invalid name server: \"dns.google\"");
+    }
+}
@@ -0,0 +1,535 @@
+//! An in-memory [`Backend`] for tests.
+//!
+//! The adapters and DNS settings are programmed by the test, the writes are
+//! applied to them and recorded as [`Mutation`]s, and any operation can be
+//! made to fail with an OS error code.
+
+use std::collections::HashMap;
+use std::ffi::OsString;
+use std::net::IpAddr;
+use std::sync::{Mutex, MutexGuard};
+
+use super::{Backend, DnsSettings};
+use crate::adapter::{
+    Adapter, AdapterFlags, AdapterList, AdminStatus, IfType, MediaConnectState, OperStatus,
+};
+use crate::address::UnicastAddress;
+use crate::error::*;
+use crate::interface::InterfaceId;
+use crate::network::IpNetwork;
+use crate::query::{AdapterLookup, AdapterQuery};
+use crate::scope::ScopedIpAddr;
+use crate::snapshot::AdapterKey;
+
+/// The operations of a [`Backend`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Operation {
+    Adapters,
+    DnsSettings,
+    SetDnsSettings,
+}
+
+impl Operation {
+    /// The Windows function of the operation, used in the errors.
+    pub fn function(self) -> &'static str {
+        match self {
+            Operation::Adapters => "GetAdaptersAddresses",
+            Operation::DnsSettings => "GetInterfaceDnsSettings",
+            Operation::SetDnsSettings => "SetInterfaceDnsSettings",
+        }
+    }
+}
+
+/// A write made through a [`FakeBackend`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum Mutation {
+    SetDnsSettings {
+        /// The interface as the caller identified it.
+        interface: InterfaceId,
+        adapter: AdapterKey,
+        is_ipv6: bool,
+        settings: DnsSettings,
+    },
+}
+
+/// An in-memory backend.
+///
+/// The DNS settings of an adapter default to its DNS servers of the family
+/// and its DNS suffixes. Setting them also replaces the DNS servers of the
+/// family and the DNS suffixes of the adapter, as Windows does.
+#[derive(Debug, Default)]
+pub struct FakeBackend {
+    state: Mutex<State>,
+}
+
+#[derive(Debug, Default)]
+struct State {
+    adapters: Vec<Adapter>,
+    dns_settings: HashMap<(AdapterKey, bool), DnsSettings>,
+    failures: HashMap<Operation, u32>,
+    mutations: Vec<Mutation>,
+}
+
+impl State {
+    fn check(&self, operation: Operation) -> Result<()> {
+        match self.failures.get(&operation) {
+            Some(&code) => Err(Error::os(code).with_operation(operation.function())),
+            None => Ok(()),
+        }
+    }
+
+    fn find(&mut self, interface: &InterfaceId) -> Result<&mut Adapter> {
+        let lookup = AdapterLookup::from(interface);
+        self.adapters
+            .iter_mut()
+            .find(|adapter| lookup.matches(adapter))
+            .ok_or_else(|| Error::interface_not_found(interface))
+    }
+}
+
+impl FakeBackend {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub fn with_adapter(self, adapter: Adapter) -> Self {
+        self.add_adapter(adapter);
+        self
+    }
+
+    fn state(&self) -> MutexGuard<'_, State> {
+        // a panicking test doesn't make the state inconsistent
+        self.state.lock().unwrap_or_else(|err| err.into_inner())
+    }
+
+    /// Add an adapter, or replace the adapter with the same key.
+    pub fn add_adapter(&self, adapter: Adapter) {
+        let mut state = self.state();
+        let key = adapter.key();
+        match state.adapters.iter_mut().find(|a| a.key() == key) {
+            Some(existing) => *existing = adapter,
+            None => state.adapters.push(adapter),
+        }
+    }
+
+    /// Remove an adapter and its DNS settings.
+    pub fn remove_adapter(&self, interface: impl Into<InterfaceId>) -> Result<Adapter> {
+        let mut state = self.state();
+        let key = state.find(&interface.into())?.key();
+        state.dns_settings.retain(|(k, _), _| *k != key);
+        let position = state.adapters.iter().position(|a| a.key() == key).unwrap();
+        Ok(state.adapters.remove(position))
+    }
+
+    /// Change an adapter in place, e.g. to bring it down.
+    pub fn update_adapter(
+        &self,
+        interface: impl Into<InterfaceId>,
+        update: impl FnOnce(&mut Adapter),
+    ) -> Result<()> {
+        update(self.state().find(&interface.into())?);
+        Ok(())
+    }
+
+    /// A copy of the adapters.
+    pub fn adapter_list(&self) -> Vec<Adapter> {
+        self.state().adapters.clone()
+    }
+
+    /// Make every call of `operation` fail with the OS error `code`, until
+    /// [`FakeBackend::recover`].
+    pub fn fail(&self, operation: Operation, code: u32) {
+        self.state().failures.insert(operation, code);
+    }
+
+    pub fn recover(&self, operation: Operation) {
+        self.state().failures.remove(&operation);
+    }
+
+    /// The writes made so far, in order.
+    pub fn mutations(&self) -> Vec<Mutation> {
+        self.state().mutations.clone()
+    }
+
+    /// The writes made so far, forgetting them.
+    pub fn take_mutations(&self) -> Vec<Mutation> {
+        std::mem::take(&mut self.state().mutations)
+    }
+}
+
+impl Backend for FakeBackend {
+    /// The adapters as `GetAdaptersAddresses` returns them for the family
+    /// and flags of the query, see [`AdapterQuery::apply`].
+    fn adapters(&self, query: &AdapterQuery) -> Result<AdapterList> {
+        let state = self.state();
+        state.check(Operation::Adapters)?;
+        Ok(AdapterList {
+            adapters: state
+                .adapters
+                .iter()
+                .filter_map(|a| query.apply(a))
+                .collect(),
+            errors: vec![],
+        })
+    }
+
+    fn dns_settings(&self, interface: &InterfaceId, is_ipv6: bool) -> Result<DnsSettings> {
+        let mut state = self.state();
+        state.check(Operation::DnsSettings)?;
+        let adapter = state.find(interface)?;
+        let key = adapter.key();
+        let default = DnsSettings {
+            name_servers: adapter
+                .dns_servers
+                .iter()
+                .map(|server| server.ip())
+                .filter(|ip| ip.is_ipv6() == is_ipv6)
+                .collect(),
+            search_list: adapter
+                .dns_suffixes
+                .iter()
+                .map(|suffix| suffix.to_string_lossy().into_owned())
+                .collect(),
+        };
+        Ok(state
+            .dns_settings
+            .get(&(key, is_ipv6))
+            .cloned()
+            .unwrap_or(default))
+    }
+
+    fn set_dns_settings(
+        &self,
+        interface: &InterfaceId,
+        is_ipv6: bool,
+        settings: &DnsSettings,
+    ) -> Result<()> {
+        let mut state = self.state();
+        state.check(Operation::SetDnsSettings)?;
+        let adapter = state.find(interface)?;
+        adapter
+            .dns_servers
+            .retain(|server| server.ip().is_ipv6() != is_ipv6);
+        adapter.dns_servers.extend(
+            settings
+                .name_servers
+                .iter()
+                .map(|&ip| ScopedIpAddr::from(ip)),
+        );
+        adapter.dns_suffixes = settings.search_list.iter().map(OsString::from).collect();
+        let key = adapter.key();
+
+        state
+            .dns_settings
+            .insert((key.clone(), is_ipv6), settings.clone());
+        state.mutations.push(Mutation::SetDnsSettings {
+            interface: interface.clone(),
+            adapter: key,
+            is_ipv6,
+            settings: settings.clone(),
+        });
+        Ok(())
+    }
+}
+
+/// A builder of [`Adapter`]s for tests.
+///
+/// The adapter is an Ethernet adapter, up and connected, with IPv4 and IPv6
+/// enabled and an MTU of 1500.
+#[derive(Debug, Clone)]
+pub struct AdapterBuilder {
+    adapter: Adapter,
+}
+
+impl Adapter {
+    /// Build an adapter named `name`, the GUID on Windows or the kernel name
+    /// on Linux.
+    pub fn builder(name: impl Into<OsString>) -> AdapterBuilder {
+        AdapterBuilder {
+            adapter: Adapter {
+                adapter_name: name.into(),
+                oper_status: OperStatus::IfOperStatusUp,
+                admin_status: AdminStatus::Up,
+                media_connect_state: MediaConnectState::Connected,
+                if_type: IfType::EthernetCsmacd,
+                flags: AdapterFlags(AdapterFlags::IPV4_ENABLED | AdapterFlags::IPV6_ENABLED),
+                mtu: 1500,
+                ..Default::default()
+            },
+        }
+    }
+}
+
+impl AdapterBuilder {
+    pub fn luid(mut self, luid: u64) -> Self {
+        self.adapter.luid = luid;
+        self
+    }
+
+    /// The IPv4 and IPv6 interface index.
+    pub fn index(mut self, index: u32) -> Self {
+        self.adapter.ipv4_if_index = index;
+        self.adapter.ipv6_if_index = index;
+        self
+    }
+
+    pub fn friendly_name(mut self, friendly_name: impl Into<OsString>) -> Self {
+        self.adapter.friendly_name = friendly_name.into();
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<OsString>) -> Self {
+        self.adapter.description = description.into();
+        self
+    }
+
+    pub fn if_type(mut self, if_type: IfType) -> Self {
+        self.adapter.if_type = if_type;
+        self
+    }
+
+    pub fn oper_status(mut self, oper_status: OperStatus) -> Self {
+        self.adapter.oper_status = oper_status;
+        self
+    }
+
+    pub fn physical_address(mut self, address: &[u8]) -> Self {
+        self.adapter.physical_address = Some(address.to_vec());
+        self
+    }
+
+    pub fn mtu(mut self, mtu: u32) -> Self {
+        self.adapter.mtu = mtu;
+        self
+    }
+
+    /// The receive and transmit link speed, in bits per second.
+    pub fn link_speed(mut self, speed: u64) -> Self {
+        self.adapter.receive_link_speed = speed;
+        self.adapter.transmit_link_speed = speed;
+        self
+    }
+
+    /// The IPv4 and IPv6 interface metric.
+    pub fn metric(mut self, metric: u32) -> Self {
+        self.adapter.ipv4_metric = metric;
+        self.adapter.ipv6_metric = metric;
+        self
+    }
+
+    /// Add a unicast address with its on-link prefix.
+    pub fn address(mut self, address: IpNetwork) -> Self {
+        self.adapter.ip_addresses.push(address.addr().into());
+        self.adapter
+            .unicast_addresses
+            .push(UnicastAddress::from(address));
+        let prefix = address.trunc();
+        if !self.adapter.prefixes.contains(&prefix) {
+            self.adapter.prefixes.push(prefix);
+        }
+        self
+    }
+
+    pub fn gateway(mut self, gateway: IpAddr) -> Self {
+        self.adapter.gateways.push(gateway.into());
+        self
+    }
+
+    pub fn dns_server(mut self, server: IpAddr) -> Self {
+        self.adapter.dns_servers.push(server.into());
+        self
+    }
+
+    /// Add a DNS suffix, the first one is the connection-specific suffix.
+    pub fn dns_suffix(mut self, suffix: impl Into<OsString>) -> Self {
+        let suffix = suffix.into();
+        if self.adapter.dns_suffix.is_empty() {
+            self.adapter.dns_suffix = suffix.clone();
+        }
+        self.adapter.dns_suffixes.push(suffix);
+        self
+    }
+
+    pub fn build(self) -> Adapter {
+        self.adapter
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::luid::Luid;
+    use crate::query::AddressFamily;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn backend() -> FakeBackend {
+        FakeBackend::new()
+            .with_adapter(
+                Adapter::builder("{5B1E2E5A-9C53-4A23-8F4B-8E42C2C1B2D1}")
+                    .luid(0x6000001000000)
+                    .index(7)
+                    .friendly_name("Ethernet")
+                    .address("192.168.1.20/24".parse().unwrap())
+                    .address("fe80::1/64".parse().unwrap())
+                    .gateway(ip("192.168.1.1"))
+                    .dns_server(ip("192.168.1.1"))
+                    .dns_server(ip("fe80::53"))
+                    .dns_suffix("corp.example.com")
+                    .link_speed(1_000_000_000)
+                    .build(),
+            )
+            .with_adapter(
+                Adapter::builder("wintun")
+                    .luid(0x10000002000000)
+                    .index(12)
+                    .friendly_name("VPN")
+                    .if_type(IfType::Tunnel)
+                    .address("10.8.0.2/32".parse().unwrap())
+                    .build(),
+            )
+    }
+
+    #[test]
+    fn test_adapter_builder() {
+        let adapter = Adapter::builder("eth0")
+            .address("192.168.1.20/24".parse().unwrap())
+            .address("192.168.1.21/24".parse().unwrap())
+            .dns_suffix("a.example")
+            .dns_suffix("b.example")
+            .build();
+        assert_eq!(adapter.adapter_name, "eth0");
+        assert_eq!(adapter.oper_status, OperStatus::IfOperStatusUp);
+        assert!(adapter.flags.ipv4_enabled());
+        assert_eq!(adapter.ip_addresses.len(), 2);
+        assert_eq!(adapter.prefixes, ["192.168.1.0/24".parse().unwrap()]);
+        assert_eq!(adapter.dns_suffix, "a.example");
+        assert_eq!(adapter.dns_suffixes, ["a.example", "b.example"]);
+    }
+
+    #[test]
+    fn test_fake_queries() {
+        let backend = backend();
+        let names = |query: AdapterQuery| -> Vec<String> {
+            query
+                .get_from(&backend)
+                .unwrap()
+                .iter()
+                .map(|a| a.friendly_name_lossy().into_owned())
+                .collect()
+        };
+        assert_eq!(names(AdapterQuery::new()), ["Ethernet", "VPN"]);
+        assert_eq!(names(AdapterQuery::new().index(12)), ["VPN"]);
+        let v6 = AdapterQuery::new()
+            .family(AddressFamily::Ipv6)
+            .gateways(false)
+            .get_from(&backend)
+            .unwrap();
+        assert_eq!(v6[0].ip_addresses, [ip("fe80::1").into()]);
+        assert!(v6[0].gateways.is_empty());
+        assert_eq!(v6[1].ip_addresses, []);
+        backend
+            .update_adapter("wintun", |a| {
+                a.flags = AdapterFlags(AdapterFlags::IPV4_ENABLED)
+            })
+            .unwrap();
+        assert_eq!(
+            names(AdapterQuery::new().family(AddressFamily::Ipv6)),
+            ["Ethernet"]
+        );
+        assert_eq!(
+            names(AdapterQuery::new().interface(InterfaceId::Alias("Ethernet".into()))),
+            ["Ethernet"]
+        );
+
+        let dyn_backend: &dyn Backend = &backend;
+        assert_eq!(
+            dyn_backend
+                .interface_index(&InterfaceId::Name("wintun".into()), false)
+                .unwrap(),
+            Some(12)
+        );
+        assert_eq!(
+            backend
+                .interface_index(&InterfaceId::Luid(1), true)
+                .unwrap(),
+            None
+        );
+
+        backend
+            .update_adapter("wintun", |a| a.ipv6_if_index = 0)
+            .unwrap();
+        assert_eq!(
+            backend.interface_index(&"wintun".into(), true).unwrap(),
+            None
+        );
+        backend
+            .remove_adapter(Luid::from(0x10000002000000))
+            .unwrap();
+        assert_eq!(backend.adapter_list().len(), 1);
+        assert!(backend
+            .remove_adapter("wintun")
+            .unwrap_err()
+            .is_interface_not_found());
+    }
+
+    #[test]
+    fn test_fake_dns() {
+        let backend = backend();
+        let ethernet = InterfaceId::Luid(0x6000001000000);
+        assert_eq!(
+            backend.dns_settings(&ethernet, false).unwrap(),
+            DnsSettings {
+                name_servers: vec![ip("192.168.1.1")],
+                search_list: vec!["corp.example.com".into()],
+            }
+        );
+        assert_eq!(
+            backend.dns_settings(&ethernet, true).unwrap().name_servers,
+            [ip("fe80::53")]
+        );
+
+        let settings = DnsSettings {
+            name_servers: vec![ip("1.1.1.1"), ip("1.0.0.1")],
+            search_list: vec!["vpn.example.com".into()],
+        };
+        let vpn = InterfaceId::Alias("VPN".into());
+        backend.set_dns_settings(&vpn, false, &settings).unwrap();
+        assert_eq!(backend.dns_settings(&vpn, false).unwrap(), settings);
+        let adapters = AdapterQuery::new().index(12).get_from(&backend).unwrap();
+        assert_eq!(
+            adapters[0].dns_servers,
+            [ip("1.1.1.1").into(), ip("1.0.0.1").into()]
+        );
+        assert_eq!(adapters[0].dns_suffixes, ["vpn.example.com"]);
+        assert_eq!(
+            backend.take_mutations(),
+            [Mutation::SetDnsSettings {
+                interface: vpn.clone(),
+                adapter: AdapterKey::Luid(0x10000002000000),
+                is_ipv6: false,
+                settings: settings.clone(),
+            }]
+        );
+        assert!(backend.mutations().is_empty());
+
+        // ERROR_ACCESS_DENIED
+        backend.fail(Operation::SetDnsSettings, 5);
+        let err = backend
+            .set_dns_settings(&vpn, false, &settings)
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AccessDenied));
+        assert_eq!(err.operation(), Some("SetInterfaceDnsSettings"));
+        assert!(backend.mutations().is_empty());
+        backend.recover(Operation::SetDnsSettings);
+        assert!(backend.set_dns_settings(&vpn, false, &settings).is_ok());
+
+        let unknown = InterfaceId::Index(99);
+        assert!(backend
+            .dns_settings(&unknown, false)
+            .unwrap_err()
+            .is_interface_not_found());
+    }
+}
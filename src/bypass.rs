@@ -0,0 +1,211 @@
+//! Find the traffic that a routing table sends around a TUN interface.
+//!
+//! A VPN usually routes `0.0.0.0/1` and `128.0.0.0/1` through its TUN, but
+//! any more specific route wins over them: the LAN, the route to the VPN
+//! server, or the routes of another VPN client. [`find_bypasses`] walks the
+//! main table and reports every prefix whose longest-prefix match is a
+//! route of another interface.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::adapter::Adapter;
+use crate::network::IpNetwork;
+use crate::prefix_set::PrefixSet;
+use crate::route::{Route, RouteTable, MAIN_TABLE};
+use crate::snapshot::AdapterKey;
+
+/// Prefixes routed around the TUN by the same route.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Bypass {
+    /// The destinations matched by `route` and by no more specific route.
+    pub prefixes: Vec<IpNetwork>,
+    pub route: Route,
+    /// The adapter of the route interface, if known.
+    pub adapter: Option<AdapterKey>,
+    /// The friendly name of the adapter, empty if unknown.
+    pub friendly_name: String,
+}
+
+/// Loopback, link-local, multicast and broadcast destinations, never sent
+/// through a TUN.
+fn local_destinations() -> PrefixSet {
+    let net = |addr: IpAddr, prefix| IpNetwork::new(addr, prefix).unwrap();
+    [
+        net(Ipv4Addr::new(127, 0, 0, 0).into(), 8),
+        net(Ipv4Addr::new(169, 254, 0, 0).into(), 16),
+        net(Ipv4Addr::new(224, 0, 0, 0).into(), 4),
+        net(Ipv4Addr::BROADCAST.into(), 32),
+        net(Ipv6Addr::LOCALHOST.into(), 128),
+        net(Ipv6Addr::new(0xfe80, 0, 0, 0, 0, 0, 0, 0).into(), 10),
+        net(Ipv6Addr::new(0xff00, 0, 0, 0, 0, 0, 0, 0).into(), 8),
+    ]
+    .into_iter()
+    .collect()
+}
+
+/// The routes of the main table that carry traffic outside of the interface
+/// `tun_index`, sorted by destination.
+///
+/// Each route is reported with the part of its destination that no more
+/// specific route covers, local destinations excluded. Destinations without
+/// any route are not reported, their traffic goes nowhere.
+pub fn find_bypasses(table: &RouteTable, adapters: &[Adapter], tun_index: u32) -> Vec<Bypass> {
+    let mut destinations: Vec<IpNetwork> = table
+        .routes()
+        .iter()
+        .filter(|route| route.table == MAIN_TABLE)
+        .map(|route| route.destination.trunc())
+        .collect();
+    destinations.sort();
+    destinations.dedup();
+
+    let local = local_destinations();
+    let mut bypasses = vec![];
+    for destination in &destinations {
+        let mut region: PrefixSet = std::iter::once(*destination).collect();
+        for other in &destinations {
+            if other.prefix() > destination.prefix() && other.is_subnet_of(destination) {
+                region.remove(*other);
+            }
+        }
+        let region = region.difference(&local);
+        let prefixes = region.prefixes();
+        let route = match prefixes.first() {
+            Some(first) => match table.lookup(first.network()) {
+                Some(route) => route,
+                None => continue,
+            },
+            None => continue,
+        };
+        if route.if_index == tun_index {
+            continue;
+        }
+
+        let adapter = adapters.iter().find(|adapter| {
+            let if_index = match route.destination {
+                IpNetwork::V4(_) => adapter.ipv4_if_index,
+                IpNetwork::V6(_) => adapter.ipv6_if_index,
+            };
+            if_index == route.if_index
+        });
+        bypasses.push(Bypass {
+            prefixes,
+            route: route.clone(),
+            adapter: adapter.map(Adapter::key),
+            friendly_name: adapter
+                .map(|adapter| adapter.friendly_name_lossy().into_owned())
+                .unwrap_or_default(),
+        });
+    }
+    bypasses
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn route(destination: &str, if_index: u32, metric: u32) -> Route {
+        Route::new(destination.parse().unwrap(), if_index, metric)
+    }
+
+    fn nets(list: &[&str]) -> Vec<IpNetwork> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_find_bypasses() {
+        let adapters = [
+            Adapter {
+                adapter_name: "eth0".into(),
+                friendly_name: "Ethernet".into(),
+                ipv4_if_index: 2,
+                ipv6_if_index: 2,
+                ..Default::default()
+            },
+            Adapter {
+                adapter_name: "tailscale0".into(),
+                ipv4_if_index: 12,
+                ..Default::default()
+            },
+        ];
+        let routes = [
+            route("0.0.0.0/0", 2, 25).via("192.168.1.1".parse::<IpAddr>().unwrap()),
+            route("192.168.1.0/24", 2, 0),
+            route("192.168.1.10/32", 2, 0),
+            route("224.0.0.0/4", 2, 0),
+            route("127.0.0.0/8", 1, 0),
+            route("203.0.113.7/32", 2, 0),
+            route("0.0.0.0/1", 9, 0),
+            route("128.0.0.0/1", 9, 0),
+            route("100.64.0.0/10", 12, 0),
+            // loses against the TUN on metric
+            route("10.0.0.0/8", 2, 50),
+            route("10.0.0.0/8", 9, 5),
+            route("10.1.0.0/16", 9, 0).in_table(100),
+            route("::/0", 2, 0),
+            route("fe80::/64", 2, 0),
+        ];
+        let table = RouteTable::with_adapters(routes, &adapters);
+        let bypasses = find_bypasses(&table, &adapters, 9);
+
+        let summary: Vec<_> = bypasses[..4]
+            .iter()
+            .map(|b| {
+                (
+                    b.prefixes.clone(),
+                    b.route.destination,
+                    b.friendly_name.as_str(),
+                )
+            })
+            .collect();
+        assert_eq!(
+            summary,
+            [
+                (nets(&["100.64.0.0/10"]), nets(&["100.64.0.0/10"])[0], ""),
+                (
+                    nets(&[
+                        "192.168.1.0/29",
+                        "192.168.1.8/31",
+                        "192.168.1.11/32",
+                        "192.168.1.12/30",
+                        "192.168.1.16/28",
+                        "192.168.1.32/27",
+                        "192.168.1.64/26",
+                        "192.168.1.128/25",
+                    ]),
+                    nets(&["192.168.1.0/24"])[0],
+                    "Ethernet",
+                ),
+                (
+                    nets(&["192.168.1.10/32"]),
+                    nets(&["192.168.1.10/32"])[0],
+                    "Ethernet"
+                ),
+                (
+                    nets(&["203.0.113.7/32"]),
+                    nets(&["203.0.113.7/32"])[0],
+                    "Ethernet"
+                ),
+            ]
+        );
+        assert_eq!(bypasses.len(), 5);
+        assert_eq!(bypasses[4].route.destination, nets(&["::/0"])[0]);
+        let ipv6 = |addr: &str| {
+            let addr: IpAddr = addr.parse().unwrap();
+            bypasses[4].prefixes.iter().any(|p| p.contains(addr))
+        };
+        assert!(ipv6("2001:db8::1"));
+        assert!(ipv6("::2"));
+        assert!(!ipv6("::1"));
+        assert!(!ipv6("fe80::1"));
+        assert!(!ipv6("ff02::1"));
+        assert_eq!(
+            bypasses[0].adapter,
+            Some(AdapterKey::Name("tailscale0".into()))
+        );
+        assert!(bypasses[3].route.next_hop.is_none());
+
+        assert!(find_bypasses(&RouteTable::new(), &adapters, 9).is_empty());
+    }
+}
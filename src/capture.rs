@@ -0,0 +1,545 @@
+//! Record and replay the network state of a machine, enabled with the
+//! `capture` feature.
+//!
+//! A [`Capture`] holds what the read APIs return on one machine: the raw
+//! `GetAdaptersAddresses` buffer, the DNS settings of the adapters, the
+//! computer-level DNS settings and the WFP sublayers. It is stored as
+//! versioned JSON (see [`crate::schema`]), and [`ReplayBackend`] serves it
+//! back so that a bug report from a customer machine can be reproduced on any
+//! OS.
+
+use std::path::Path;
+
+use serde::{Deserialize, Serialize};
+
+use crate::adapter::{AdapterList, AdminStatus, MediaConnectState};
+use crate::backend::{Backend, DnsSettings};
+use crate::decode::AdapterBuffer;
+use crate::error::*;
+use crate::fwpm::SubLayer;
+use crate::interface::InterfaceId;
+use crate::query::{AdapterLookup, AdapterQuery};
+use crate::schema::{Versioned, SCHEMA_VERSION};
+use crate::snapshot::AdapterKey;
+use crate::Adapter;
+
+/// The network state of a machine at one point in time.
+///
+/// The parts that couldn't be read are listed in `errors` rather than
+/// failing the whole capture.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct Capture {
+    /// The buffer of all the interfaces, kept raw so that replaying it goes
+    /// through the same decoder, bugs included.
+    pub adapters: AdapterBuffer,
+    /// The states of the adapters, which are not part of the buffer.
+    pub if_states: Vec<CapturedIfState>,
+    pub dns_settings: Vec<CapturedDnsSettings>,
+    /// `None` if the computer settings weren't captured.
+    pub computer: Option<ComputerSettings>,
+    pub sub_layers: Vec<SubLayer>,
+    pub errors: Vec<CaptureError>,
+}
+
+/// The administrative and media states of one adapter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedIfState {
+    pub adapter: AdapterKey,
+    pub admin_status: AdminStatus,
+    pub media_connect_state: MediaConnectState,
+}
+
+/// The IPv4 or IPv6 DNS settings of one adapter.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CapturedDnsSettings {
+    pub adapter: AdapterKey,
+    pub is_ipv6: bool,
+    pub settings: DnsSettings,
+}
+
+/// The DNS settings of the computer, see [`crate::computer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct ComputerSettings {
+    pub search_list: Vec<String>,
+    pub domain: Option<String>,
+    pub round_robin: bool,
+}
+
+/// A part of the state that couldn't be captured.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct CaptureError {
+    /// What was being read, e.g. `"IPv6 DNS settings of Ethernet"`.
+    pub what: String,
+    pub error: String,
+}
+
+impl CaptureError {
+    fn new(what: impl Into<String>, error: &Error) -> Self {
+        CaptureError {
+            what: what.into(),
+            error: error.to_string(),
+        }
+    }
+}
+
+impl Capture {
+    /// Capture the adapters and their DNS settings through `backend`.
+    ///
+    /// The buffer is kept even if it can't be decoded.
+    pub fn from_backend(backend: &(impl Backend + ?Sized)) -> Self {
+        let mut capture = Capture::default();
+        let query = AdapterQuery::new().wins_servers(true).all_interfaces(true);
+        match backend.adapter_buffer(&query) {
+            Ok(buffer) => capture.adapters = buffer,
+            Err(err) => {
+                capture.errors.push(CaptureError::new("adapters", &err));
+                return capture;
+            }
+        }
+        match backend.adapters(&query) {
+            Ok(list) => {
+                capture.if_states = list
+                    .adapters
+                    .iter()
+                    .map(|adapter| CapturedIfState {
+                        adapter: adapter.key(),
+                        admin_status: adapter.admin_status,
+                        media_connect_state: adapter.media_connect_state,
+                    })
+                    .collect()
+            }
+            Err(err) => capture
+                .errors
+                .push(CaptureError::new("interface states", &err)),
+        }
+        let list = match capture.adapters.decode() {
+            Ok(list) => list,
+            Err(err) => {
+                capture.errors.push(CaptureError::new("adapters", &err));
+                return capture;
+            }
+        };
+        for error in &list.errors {
+            let what = format!("adapter {}", error.adapter_name.to_string_lossy());
+            capture.errors.push(CaptureError::new(what, &error.error));
+        }
+        for adapter in &list.adapters {
+            for is_ipv6 in [false, true] {
+                match backend.dns_settings(&InterfaceId::from(adapter), is_ipv6) {
+                    Ok(settings) => capture.dns_settings.push(CapturedDnsSettings {
+                        adapter: adapter.key(),
+                        is_ipv6,
+                        settings,
+                    }),
+                    Err(err) => {
+                        let what = format!(
+                            "{} DNS settings of {}",
+                            if is_ipv6 { "IPv6" } else { "IPv4" },
+                            adapter.adapter_name.to_string_lossy()
+                        );
+                        capture.errors.push(CaptureError::new(what, &err));
+                    }
+                }
+            }
+        }
+        capture
+    }
+
+    /// Serialize as pretty printed JSON, tagged with the schema version.
+    pub fn to_json(&self) -> Result<String> {
+        serde_json::to_string_pretty(&Versioned::new(self))
+            .map_err(|err| Error::parse("capture", &err.to_string()))
+    }
+
+    /// Parse a capture written by [`Capture::to_json`].
+    ///
+    /// The version is checked before the payload, whose shape may have
+    /// changed in a newer version.
+    pub fn from_json(json: &str) -> Result<Self> {
+        #[derive(Deserialize)]
+        struct Header {
+            schema_version: u32,
+        }
+
+        let parse_error = |err: serde_json::Error| Error::parse("capture", &err.to_string());
+        let header: Header = serde_json::from_str(json).map_err(parse_error)?;
+        if header.schema_version > SCHEMA_VERSION {
+            return Err(Error::out_of_range(
+                "capture schema version",
+                header.schema_version.into(),
+            ));
+        }
+        let versioned: Versioned<Capture> = serde_json::from_str(json).map_err(parse_error)?;
+        Ok(versioned.data)
+    }
+
+    /// The states of an adapter.
+    pub fn if_state_of(&self, key: &AdapterKey) -> Option<&CapturedIfState> {
+        self.if_states.iter().find(|state| state.adapter == *key)
+    }
+
+    /// The DNS settings of an adapter.
+    pub fn dns_settings_of(&self, key: &AdapterKey, is_ipv6: bool) -> Option<&DnsSettings> {
+        self.dns_settings
+            .iter()
+            .find(|captured| captured.adapter == *key && captured.is_ipv6 == is_ipv6)
+            .map(|captured| &captured.settings)
+    }
+}
+
+/// Capture the network state of this machine.
+#[cfg(windows)]
+pub fn capture() -> Capture {
+    let mut capture = Capture::from_backend(&crate::backend::OsBackend);
+    #[cfg(feature = "computer")]
+    match computer_settings() {
+        Ok(settings) => capture.computer = Some(settings),
+        Err(err) => capture
+            .errors
+            .push(CaptureError::new("computer settings", &err)),
+    }
+    match crate::fwpm::get_sub_layers() {
+        Ok(sub_layers) => capture.sub_layers = sub_layers,
+        Err(err) => capture.errors.push(CaptureError::new("sub layers", &err)),
+    }
+    capture
+}
+
+#[cfg(all(windows, feature = "computer"))]
+fn computer_settings() -> Result<ComputerSettings> {
+    Ok(ComputerSettings {
+        search_list: crate::computer::get_search_list()?,
+        domain: crate::computer::get_domain()?,
+        round_robin: crate::computer::is_round_robin_enabled()?,
+    })
+}
+
+/// A read-only backend serving a [`Capture`].
+///
+/// Writes fail with [`ErrorKind::AccessDenied`], the capture is never
+/// modified.
+#[derive(Debug, Clone, Default)]
+pub struct ReplayBackend {
+    capture: Capture,
+}
+
+impl From<Capture> for ReplayBackend {
+    fn from(capture: Capture) -> Self {
+        ReplayBackend { capture }
+    }
+}
+
+impl ReplayBackend {
+    /// Replay a capture file written by [`Capture::to_json`].
+    pub fn open(path: impl AsRef<Path>) -> Result<Self> {
+        let json = std::fs::read_to_string(path)?;
+        Ok(Capture::from_json(&json)?.into())
+    }
+
+    pub fn capture(&self) -> &Capture {
+        &self.capture
+    }
+
+    /// The computer settings, [`ErrorKind::NotFound`] if they weren't
+    /// captured.
+    pub fn computer_settings(&self) -> Result<&ComputerSettings> {
+        self.capture
+            .computer
+            .as_ref()
+            .ok_or_else(|| ErrorKind::NotFound.into())
+    }
+
+    pub fn sub_layers(&self) -> &[SubLayer] {
+        &self.capture.sub_layers
+    }
+
+    /// Decode the captured buffer, with the captured states.
+    fn decode(&self) -> Result<AdapterList> {
+        let mut list = self.capture.adapters.decode()?;
+        for adapter in &mut list.adapters {
+            if let Some(state) = self.capture.if_state_of(&adapter.key()) {
+                adapter.admin_status = state.admin_status;
+                adapter.media_connect_state = state.media_connect_state;
+            }
+        }
+        Ok(list)
+    }
+
+    fn find(&self, interface: &InterfaceId) -> Result<Adapter> {
+        let lookup = AdapterLookup::from(interface);
+        self.decode()?
+            .adapters
+            .into_iter()
+            .find(|adapter| lookup.matches(adapter))
+            .ok_or_else(|| Error::interface_not_found(interface))
+    }
+}
+
+impl Backend for ReplayBackend {
+    /// The captured adapters with the family and flags of the query, and the
+    /// adapters the buffer failed to decode.
+    fn adapters(&self, query: &AdapterQuery) -> Result<AdapterList> {
+        let list = self.decode()?;
+        Ok(AdapterList {
+            adapters: list
+                .adapters
+                .iter()
+                .filter_map(|adapter| query.apply(adapter))
+                .collect(),
+            errors: list.errors,
+        })
+    }
+
+    /// [`ErrorKind::NotFound`] if the settings of the adapter weren't
+    /// captured.
+    fn dns_settings(&self, interface: &InterfaceId, is_ipv6: bool) -> Result<DnsSettings> {
+        let key = self.find(interface)?.key();
+        self.capture
+            .dns_settings_of(&key, is_ipv6)
+            .cloned()
+            .ok_or_else(|| {
+                Error::from(ErrorKind::NotFound)
+                    .with_operation("GetInterfaceDnsSettings")
+                    .with_interface(interface)
+            })
+    }
+
+    fn set_dns_settings(
+        &self,
+        interface: &InterfaceId,
+        _is_ipv6: bool,
+        _settings: &DnsSettings,
+    ) -> Result<()> {
+        Err(Error::from(ErrorKind::AccessDenied)
+            .with_operation("SetInterfaceDnsSettings")
+            .with_interface(interface))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::backend::fake::Operation;
+    use crate::backend::FakeBackend;
+    use crate::decode::offsets;
+    use crate::fwpm::DisplayData;
+    use crate::query::AddressFamily;
+    use crate::scope::ScopedIpAddr;
+
+    fn fake() -> FakeBackend {
+        FakeBackend::new()
+            .with_adapter(
+                Adapter::builder("{C38D57D1-05A7-4C33-904F-7FBCEEE60E82}")
+                    .luid(0x0006_0000_0100_0000)
+                    .index(12)
+                    .friendly_name("Ethernet")
+                    .address("192.168.1.20/24".parse().unwrap())
+                    .gateway("192.168.1.1".parse().unwrap())
+                    .dns_server("192.168.1.1".parse().unwrap())
+                    .dns_suffix("corp.example.com")
+                    .build(),
+            )
+            .with_adapter(
+                Adapter::builder("{0D7F1A3B-2E4C-4B9A-8D6E-1F2A3B4C5D6E}")
+                    .luid(0x0083_0000_0000_0000)
+                    .index(31)
+                    .friendly_name("VPN")
+                    .address("10.8.0.2/32".parse().unwrap())
+                    .dns_server("fd00::53".parse().unwrap())
+                    .build(),
+            )
+    }
+
+    #[test]
+    fn test_capture_round_trip() {
+        let mut capture = Capture::from_backend(&fake());
+        assert_eq!(capture.adapters.decode().unwrap().adapters.len(), 2);
+        assert_eq!(capture.if_states.len(), 2);
+        assert_eq!(capture.dns_settings.len(), 4);
+        assert!(capture.errors.is_empty());
+        capture.computer = Some(ComputerSettings {
+            search_list: vec!["corp.example.com".to_owned()],
+            domain: Some("corp.example.com".to_owned()),
+            round_robin: true,
+        });
+        capture.sub_layers.push(SubLayer {
+            sub_layer_key: [0x11; 16],
+            display_data: DisplayData::new("VPN".to_owned(), None),
+            flags: 0,
+            weight: 0x100,
+        });
+
+        let json = capture.to_json().unwrap();
+        let value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        assert_eq!(value["schema_version"], 1);
+        assert_eq!(
+            value["data"]["dns_settings"][0],
+            serde_json::json!({
+                "adapter": { "guid": "c38d57d1-05a7-4c33-904f-7fbceee60e82" },
+                "is_ipv6": false,
+                "settings": {
+                    "name_servers": ["192.168.1.1"],
+                    "search_list": ["corp.example.com"],
+                },
+            })
+        );
+        assert_eq!(Capture::from_json(&json).unwrap(), capture);
+    }
+
+    #[test]
+    fn test_capture_errors() {
+        let backend = fake();
+        backend.fail(Operation::DnsSettings, 5);
+        let capture = Capture::from_backend(&backend);
+        assert_eq!(capture.if_states.len(), 2);
+        assert!(capture.dns_settings.is_empty());
+        assert_eq!(capture.errors.len(), 4);
+        assert_eq!(
+            capture.errors[1].what,
+            "IPv6 DNS settings of {C38D57D1-05A7-4C33-904F-7FBCEEE60E82}"
+        );
+
+        backend.fail(Operation::Adapters, 5);
+        let capture = Capture::from_backend(&backend);
+        assert_eq!(capture.adapters, AdapterBuffer::default());
+        assert_eq!(capture.errors.len(), 1);
+        assert_eq!(capture.errors[0].what, "adapters");
+    }
+
+    #[test]
+    fn test_replay() {
+        let json = Capture::from_backend(&fake()).to_json().unwrap();
+        let replay = ReplayBackend::from(Capture::from_json(&json).unwrap());
+
+        let adapter = AdapterQuery::new()
+            .name("VPN")
+            .find_from(&replay)
+            .unwrap()
+            .unwrap();
+        assert_eq!(adapter.ipv4_if_index, 31);
+        assert_eq!(
+            replay
+                .interface_index(&InterfaceId::Alias("Ethernet".to_owned()), false)
+                .unwrap(),
+            Some(12)
+        );
+        assert_eq!(
+            replay
+                .dns_settings(&InterfaceId::Luid(0x0083_0000_0000_0000), true)
+                .unwrap()
+                .name_servers,
+            ["fd00::53".parse::<std::net::IpAddr>().unwrap()]
+        );
+        assert!(replay
+            .dns_settings(&InterfaceId::Index(99), false)
+            .unwrap_err()
+            .is_interface_not_found());
+
+        let err = replay
+            .set_dns_settings(&InterfaceId::Index(12), false, &DnsSettings::default())
+            .unwrap_err();
+        assert!(matches!(err.kind(), ErrorKind::AccessDenied));
+        assert_eq!(err.operation(), Some("SetInterfaceDnsSettings"));
+        assert!(matches!(
+            replay.computer_settings().unwrap_err().kind(),
+            ErrorKind::NotFound
+        ));
+
+        // the states are restored and the query is applied
+        let adapters = AdapterQuery::new().get_from(&replay).unwrap();
+        assert_eq!(adapters.len(), 2);
+        assert!(adapters
+            .iter()
+            .all(|adapter| adapter.admin_status == AdminStatus::Up));
+        let adapters = AdapterQuery::new()
+            .family(AddressFamily::Ipv6)
+            .get_from(&replay)
+            .unwrap();
+        assert!(adapters
+            .iter()
+            .all(|adapter| adapter.ip_addresses.is_empty()));
+        assert_eq!(
+            adapters[1].dns_servers,
+            ["fd00::53".parse::<ScopedIpAddr>().unwrap()]
+        );
+    }
+
+    #[test]
+    fn test_replay_decode_errors() {
+        let mut capture = Capture::from_backend(&fake());
+        let mut bytes = capture.adapters.bytes().to_vec();
+        // the description of the first adapter points before the buffer
+        let description = offsets::adapter::DESCRIPTION;
+        bytes[description..description + offsets::POINTER_SIZE]
+            .copy_from_slice(&1u64.to_le_bytes()[..offsets::POINTER_SIZE]);
+        capture.adapters = AdapterBuffer::new(bytes, capture.adapters.base());
+        let replay = ReplayBackend::from(capture);
+
+        let list = AdapterQuery::new().get_partial_from(&replay).unwrap();
+        assert_eq!(list.adapters.len(), 1);
+        assert_eq!(list.errors.len(), 1);
+        assert_eq!(
+            list.errors[0].adapter_name,
+            "{C38D57D1-05A7-4C33-904F-7FBCEEE60E82}"
+        );
+
+        let capture = Capture {
+            adapters: AdapterBuffer::new(vec![0; 4], 0x1000),
+            ..Capture::default()
+        };
+        assert!(ReplayBackend::from(capture)
+            .adapters(&AdapterQuery::new())
+            .is_err());
+    }
+
+    #[test]
+    fn test_replay_pointer_width() {
+        let json = Capture::from_backend(&fake()).to_json().unwrap();
+        let mut value: serde_json::Value = serde_json::from_str(&json).unwrap();
+        let adapters = &mut value["data"]["adapters"];
+        assert_eq!(adapters["pointer_width"], usize::BITS);
+
+        // written before the pointer width was recorded
+        adapters.as_object_mut().unwrap().remove("pointer_width");
+        let capture = Capture::from_json(&value.to_string()).unwrap();
+        assert_eq!(capture.adapters.pointer_width(), usize::BITS);
+
+        // captured by a build of the other width
+        let other = if usize::BITS == 64 { 32 } else { 64 };
+        value["data"]["adapters"]["pointer_width"] = other.into();
+        let replay = ReplayBackend::from(Capture::from_json(&value.to_string()).unwrap());
+        let err = replay.adapters(&AdapterQuery::new()).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::PointerWidth { buffer, target }
+                if *buffer == other && *target == usize::BITS
+        ));
+    }
+
+    #[test]
+    fn test_unsupported_version() {
+        // the payload of a newer version isn't parsed
+        let err =
+            Capture::from_json(r#"{"schema_version": 2, "data": {"adapters": []}}"#).unwrap_err();
+        assert!(matches!(
+            err.kind(),
+            ErrorKind::OutOfRange {
+                what: "capture schema version",
+                value: 2
+            }
+        ));
+        assert_eq!(
+            Capture::from_json(r#"{"schema_version": 1, "data": {}}"#).unwrap(),
+            Capture::default()
+        );
+        assert!(matches!(
+            Capture::from_json("{").unwrap_err().kind(),
+            ErrorKind::Parse {
+                what: "capture",
+                ..
+            }
+        ));
+    }
+}
@@ -0,0 +1,1659 @@
+//! A bounds-checked decoder of the `IP_ADAPTER_ADDRESSES_LH` lists returned
+//! by `GetAdaptersAddresses`.
+//!
+//! The records of the buffer point to each other, and to their strings and
+//! socket addresses, with absolute addresses. An [`AdapterBuffer`] keeps the
+//! bytes with the address they were returned at, so that a buffer captured
+//! on a machine, or built by hand, can be decoded on any OS. Every pointer is
+//! checked against the buffer and every list against cycles: a malformed
+//! buffer is an error, never a panic or a read outside of the buffer.
+//!
+//! The buffer is decoded with the layout of the target, 64 or 32 bits: a
+//! buffer captured by a 32 bits build is only decoded by another one, which
+//! is why an [`AdapterBuffer`] records the pointer width it was laid out
+//! with.
+
+use std::collections::HashSet;
+use std::ffi::OsString;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::adapter::{
+    Adapter, AdapterError, AdapterFlags, AdapterList, ConnectionType, IfType, OperStatus,
+    TunnelType,
+};
+use crate::address::{lifetime, DadState, PrefixOrigin, SuffixOrigin, UnicastAddress};
+use crate::error::*;
+use crate::network::IpNetwork;
+use crate::query::AdapterLookup;
+use crate::scope::ScopedIpAddr;
+use crate::utils::{
+    os_string_from_ansi, os_string_from_wide, os_string_to_ansi, os_string_to_wide,
+};
+
+/// The address families of Windows, `AF_INET6` is not the Linux value.
+const AF_INET: u16 = 2;
+const AF_INET6: u16 = 23;
+const SOCKADDR_IN_SIZE: usize = 16;
+const SOCKADDR_IN6_SIZE: usize = 28;
+
+/// The field offsets of the records, in the 64 bits layout.
+#[cfg(target_pointer_width = "64")]
+pub(crate) mod offsets {
+    pub const POINTER_SIZE: usize = 8;
+
+    /// `IP_ADAPTER_ADDRESSES_LH`
+    pub mod adapter {
+        pub const IF_INDEX: usize = 4;
+        pub const NEXT: usize = 8;
+        pub const ADAPTER_NAME: usize = 16;
+        pub const FIRST_UNICAST_ADDRESS: usize = 24;
+        pub const FIRST_ANYCAST_ADDRESS: usize = 32;
+        pub const FIRST_MULTICAST_ADDRESS: usize = 40;
+        pub const FIRST_DNS_SERVER_ADDRESS: usize = 48;
+        pub const DNS_SUFFIX: usize = 56;
+        pub const DESCRIPTION: usize = 64;
+        pub const FRIENDLY_NAME: usize = 72;
+        pub const PHYSICAL_ADDRESS: usize = 80;
+        pub const PHYSICAL_ADDRESS_LENGTH: usize = 88;
+        pub const FLAGS: usize = 92;
+        pub const MTU: usize = 96;
+        pub const IF_TYPE: usize = 100;
+        pub const OPER_STATUS: usize = 104;
+        pub const IPV6_IF_INDEX: usize = 108;
+        pub const ZONE_INDICES: usize = 112;
+        pub const FIRST_PREFIX: usize = 176;
+        pub const TRANSMIT_LINK_SPEED: usize = 184;
+        pub const RECEIVE_LINK_SPEED: usize = 192;
+        pub const FIRST_WINS_SERVER_ADDRESS: usize = 200;
+        pub const FIRST_GATEWAY_ADDRESS: usize = 208;
+        pub const IPV4_METRIC: usize = 216;
+        pub const IPV6_METRIC: usize = 220;
+        pub const LUID: usize = 224;
+        pub const DHCPV4_SERVER: usize = 232;
+        pub const NETWORK_GUID: usize = 252;
+        pub const CONNECTION_TYPE: usize = 268;
+        pub const TUNNEL_TYPE: usize = 272;
+        pub const DHCPV6_SERVER: usize = 280;
+        pub const DHCPV6_CLIENT_DUID: usize = 296;
+        pub const DHCPV6_CLIENT_DUID_LENGTH: usize = 428;
+        pub const DHCPV6_IAID: usize = 432;
+        pub const FIRST_DNS_SUFFIX: usize = 440;
+        /// The size of `IP_ADAPTER_ADDRESSES_XP`, the fields after it read
+        /// as zero in older records.
+        pub const MIN_SIZE: usize = 184;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 448;
+    }
+
+    /// The address lists, which all start with the length, the `Next`
+    /// pointer and the `SOCKET_ADDRESS`.
+    pub mod address {
+        pub const NEXT: usize = 8;
+        pub const ADDRESS: usize = 16;
+        pub const SIZE: usize = 32;
+    }
+
+    /// `IP_ADAPTER_UNICAST_ADDRESS_LH`
+    pub mod unicast {
+        pub const PREFIX_ORIGIN: usize = 32;
+        pub const SUFFIX_ORIGIN: usize = 36;
+        pub const DAD_STATE: usize = 40;
+        pub const VALID_LIFETIME: usize = 44;
+        pub const PREFERRED_LIFETIME: usize = 48;
+        pub const LEASE_LIFETIME: usize = 52;
+        pub const ON_LINK_PREFIX_LENGTH: usize = 56;
+        /// The size of `IP_ADAPTER_UNICAST_ADDRESS_XP`.
+        pub const MIN_SIZE: usize = 56;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 64;
+    }
+
+    /// `IP_ADAPTER_PREFIX_XP`
+    pub mod prefix {
+        pub const PREFIX_LENGTH: usize = 32;
+        pub const MIN_SIZE: usize = 36;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 40;
+    }
+
+    /// `IP_ADAPTER_DNS_SUFFIX`, which has no length.
+    pub mod dns_suffix {
+        pub const NEXT: usize = 0;
+        pub const STRING: usize = 8;
+        pub const STRING_LEN: usize = 256;
+        pub const SIZE: usize = 520;
+    }
+}
+
+/// The field offsets of the records, in the 32 bits layout.
+#[cfg(target_pointer_width = "32")]
+pub(crate) mod offsets {
+    pub const POINTER_SIZE: usize = 4;
+
+    /// `IP_ADAPTER_ADDRESSES_LH`
+    pub mod adapter {
+        pub const IF_INDEX: usize = 4;
+        pub const NEXT: usize = 8;
+        pub const ADAPTER_NAME: usize = 12;
+        pub const FIRST_UNICAST_ADDRESS: usize = 16;
+        pub const FIRST_ANYCAST_ADDRESS: usize = 20;
+        pub const FIRST_MULTICAST_ADDRESS: usize = 24;
+        pub const FIRST_DNS_SERVER_ADDRESS: usize = 28;
+        pub const DNS_SUFFIX: usize = 32;
+        pub const DESCRIPTION: usize = 36;
+        pub const FRIENDLY_NAME: usize = 40;
+        pub const PHYSICAL_ADDRESS: usize = 44;
+        pub const PHYSICAL_ADDRESS_LENGTH: usize = 52;
+        pub const FLAGS: usize = 56;
+        pub const MTU: usize = 60;
+        pub const IF_TYPE: usize = 64;
+        pub const OPER_STATUS: usize = 68;
+        pub const IPV6_IF_INDEX: usize = 72;
+        pub const ZONE_INDICES: usize = 76;
+        pub const FIRST_PREFIX: usize = 140;
+        pub const TRANSMIT_LINK_SPEED: usize = 144;
+        pub const RECEIVE_LINK_SPEED: usize = 152;
+        pub const FIRST_WINS_SERVER_ADDRESS: usize = 160;
+        pub const FIRST_GATEWAY_ADDRESS: usize = 164;
+        pub const IPV4_METRIC: usize = 168;
+        pub const IPV6_METRIC: usize = 172;
+        pub const LUID: usize = 176;
+        pub const DHCPV4_SERVER: usize = 184;
+        pub const NETWORK_GUID: usize = 196;
+        pub const CONNECTION_TYPE: usize = 212;
+        pub const TUNNEL_TYPE: usize = 216;
+        pub const DHCPV6_SERVER: usize = 220;
+        pub const DHCPV6_CLIENT_DUID: usize = 228;
+        pub const DHCPV6_CLIENT_DUID_LENGTH: usize = 360;
+        pub const DHCPV6_IAID: usize = 364;
+        pub const FIRST_DNS_SUFFIX: usize = 368;
+        /// The size of `IP_ADAPTER_ADDRESSES_XP`, the fields after it read
+        /// as zero in older records.
+        pub const MIN_SIZE: usize = 144;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 376;
+    }
+
+    /// The address lists, which all start with the length, the `Next`
+    /// pointer and the `SOCKET_ADDRESS`.
+    pub mod address {
+        pub const NEXT: usize = 8;
+        pub const ADDRESS: usize = 12;
+        pub const SIZE: usize = 24;
+    }
+
+    /// `IP_ADAPTER_UNICAST_ADDRESS_LH`
+    pub mod unicast {
+        pub const PREFIX_ORIGIN: usize = 20;
+        pub const SUFFIX_ORIGIN: usize = 24;
+        pub const DAD_STATE: usize = 28;
+        pub const VALID_LIFETIME: usize = 32;
+        pub const PREFERRED_LIFETIME: usize = 36;
+        pub const LEASE_LIFETIME: usize = 40;
+        pub const ON_LINK_PREFIX_LENGTH: usize = 44;
+        /// The size of `IP_ADAPTER_UNICAST_ADDRESS_XP`.
+        pub const MIN_SIZE: usize = 44;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 48;
+    }
+
+    /// `IP_ADAPTER_PREFIX_XP`
+    pub mod prefix {
+        pub const PREFIX_LENGTH: usize = 20;
+        pub const MIN_SIZE: usize = 24;
+        #[cfg_attr(not(windows), allow(dead_code))]
+        pub const SIZE: usize = 24;
+    }
+
+    /// `IP_ADAPTER_DNS_SUFFIX`, which has no length.
+    pub mod dns_suffix {
+        pub const NEXT: usize = 0;
+        pub const STRING: usize = 4;
+        pub const STRING_LEN: usize = 256;
+        pub const SIZE: usize = 516;
+    }
+}
+
+// The offsets must match the Windows headers.
+#[cfg(windows)]
+const _: () = {
+    use std::mem::{offset_of, size_of};
+    use windows_sys::Win32::NetworkManagement::IpHelper::*;
+    use windows_sys::Win32::Networking::WinSock::SOCKET_ADDRESS;
+
+    use offsets::{adapter, address, dns_suffix, prefix, unicast, POINTER_SIZE};
+
+    assert!(size_of::<usize>() == POINTER_SIZE);
+    assert!(offset_of!(SOCKET_ADDRESS, iSockaddrLength) == POINTER_SIZE);
+
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Next) == adapter::NEXT);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, AdapterName) == adapter::ADAPTER_NAME);
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstUnicastAddress) == adapter::FIRST_UNICAST_ADDRESS
+    );
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstAnycastAddress) == adapter::FIRST_ANYCAST_ADDRESS
+    );
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstMulticastAddress)
+            == adapter::FIRST_MULTICAST_ADDRESS
+    );
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstDnsServerAddress)
+            == adapter::FIRST_DNS_SERVER_ADDRESS
+    );
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, DnsSuffix) == adapter::DNS_SUFFIX);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Description) == adapter::DESCRIPTION);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, FriendlyName) == adapter::FRIENDLY_NAME);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, PhysicalAddress) == adapter::PHYSICAL_ADDRESS);
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, PhysicalAddressLength)
+            == adapter::PHYSICAL_ADDRESS_LENGTH
+    );
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Mtu) == adapter::MTU);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, IfType) == adapter::IF_TYPE);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, OperStatus) == adapter::OPER_STATUS);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Ipv6IfIndex) == adapter::IPV6_IF_INDEX);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, ZoneIndices) == adapter::ZONE_INDICES);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstPrefix) == adapter::FIRST_PREFIX);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, TransmitLinkSpeed) == adapter::TRANSMIT_LINK_SPEED);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, ReceiveLinkSpeed) == adapter::RECEIVE_LINK_SPEED);
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstWinsServerAddress)
+            == adapter::FIRST_WINS_SERVER_ADDRESS
+    );
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstGatewayAddress) == adapter::FIRST_GATEWAY_ADDRESS
+    );
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Ipv4Metric) == adapter::IPV4_METRIC);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Ipv6Metric) == adapter::IPV6_METRIC);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Luid) == adapter::LUID);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Dhcpv4Server) == adapter::DHCPV4_SERVER);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, NetworkGuid) == adapter::NETWORK_GUID);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, ConnectionType) == adapter::CONNECTION_TYPE);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, TunnelType) == adapter::TUNNEL_TYPE);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Dhcpv6Server) == adapter::DHCPV6_SERVER);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Dhcpv6ClientDuid) == adapter::DHCPV6_CLIENT_DUID);
+    assert!(
+        offset_of!(IP_ADAPTER_ADDRESSES_LH, Dhcpv6ClientDuidLength)
+            == adapter::DHCPV6_CLIENT_DUID_LENGTH
+    );
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, Dhcpv6Iaid) == adapter::DHCPV6_IAID);
+    assert!(offset_of!(IP_ADAPTER_ADDRESSES_LH, FirstDnsSuffix) == adapter::FIRST_DNS_SUFFIX);
+    assert!(size_of::<IP_ADAPTER_ADDRESSES_LH>() == adapter::SIZE);
+
+    assert!(offset_of!(IP_ADAPTER_GATEWAY_ADDRESS_LH, Next) == address::NEXT);
+    assert!(offset_of!(IP_ADAPTER_GATEWAY_ADDRESS_LH, Address) == address::ADDRESS);
+    assert!(size_of::<IP_ADAPTER_GATEWAY_ADDRESS_LH>() == address::SIZE);
+    assert!(offset_of!(IP_ADAPTER_DNS_SERVER_ADDRESS_XP, Address) == address::ADDRESS);
+    assert!(size_of::<IP_ADAPTER_DNS_SERVER_ADDRESS_XP>() == address::SIZE);
+
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, Address) == address::ADDRESS);
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, PrefixOrigin) == unicast::PREFIX_ORIGIN);
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, SuffixOrigin) == unicast::SUFFIX_ORIGIN);
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, DadState) == unicast::DAD_STATE);
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, ValidLifetime) == unicast::VALID_LIFETIME);
+    assert!(
+        offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, PreferredLifetime) == unicast::PREFERRED_LIFETIME
+    );
+    assert!(offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, LeaseLifetime) == unicast::LEASE_LIFETIME);
+    assert!(
+        offset_of!(IP_ADAPTER_UNICAST_ADDRESS_LH, OnLinkPrefixLength)
+            == unicast::ON_LINK_PREFIX_LENGTH
+    );
+    assert!(size_of::<IP_ADAPTER_UNICAST_ADDRESS_LH>() == unicast::SIZE);
+
+    assert!(offset_of!(IP_ADAPTER_PREFIX_XP, Address) == address::ADDRESS);
+    assert!(offset_of!(IP_ADAPTER_PREFIX_XP, PrefixLength) == prefix::PREFIX_LENGTH);
+    assert!(size_of::<IP_ADAPTER_PREFIX_XP>() == prefix::SIZE);
+
+    assert!(offset_of!(IP_ADAPTER_DNS_SUFFIX, Next) == dns_suffix::NEXT);
+    assert!(offset_of!(IP_ADAPTER_DNS_SUFFIX, String) == dns_suffix::STRING);
+    assert!(size_of::<IP_ADAPTER_DNS_SUFFIX>() == dns_suffix::SIZE);
+};
+
+/// The pointer width of the target, in bits.
+const POINTER_WIDTH: u32 = usize::BITS;
+
+/// The buffer filled by `GetAdaptersAddresses`, with the address it was at.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdapterBuffer {
+    bytes: Vec<u8>,
+    base: u64,
+    /// Missing from the captures written before it was recorded, which are
+    /// assumed to come from the same kind of build.
+    #[cfg_attr(feature = "serde", serde(default = "pointer_width"))]
+    pointer_width: u32,
+}
+
+#[cfg(feature = "serde")]
+fn pointer_width() -> u32 {
+    POINTER_WIDTH
+}
+
+impl Default for AdapterBuffer {
+    fn default() -> Self {
+        AdapterBuffer::new(vec![], 0)
+    }
+}
+
+impl AdapterBuffer {
+    /// The buffer `bytes` as it was at address `base`. The first adapter is
+    /// at `base`, an empty buffer has no adapters.
+    pub fn new(bytes: Vec<u8>, base: u64) -> Self {
+        AdapterBuffer::with_pointer_width(bytes, base, POINTER_WIDTH)
+    }
+
+    /// A buffer laid out by a build whose pointers are `pointer_width` bits,
+    /// see [`AdapterBuffer::new`].
+    pub fn with_pointer_width(bytes: Vec<u8>, base: u64, pointer_width: u32) -> Self {
+        AdapterBuffer {
+            bytes,
+            base,
+            pointer_width,
+        }
+    }
+
+    pub fn bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    pub fn base(&self) -> u64 {
+        self.base
+    }
+
+    pub fn pointer_width(&self) -> u32 {
+        self.pointer_width
+    }
+
+    /// Decode the adapters of the buffer.
+    ///
+    /// The adapters with a malformed field are reported in
+    /// [`AdapterList::errors`], a malformed adapter list fails the whole
+    /// buffer. The administrative and media states are not part of the buffer
+    /// and are left unknown.
+    ///
+    /// A buffer of another pointer width is [`ErrorKind::PointerWidth`].
+    pub fn decode(&self) -> Result<AdapterList> {
+        if self.pointer_width != POINTER_WIDTH {
+            return Err(ErrorKind::PointerWidth {
+                buffer: self.pointer_width,
+                target: POINTER_WIDTH,
+            }
+            .into());
+        }
+        decode_adapters(&self.bytes, self.base, None)
+    }
+
+    /// Lay out `adapters` the way `GetAdaptersAddresses` does, the inverse of
+    /// [`AdapterBuffer::decode`] for the backends without a real buffer.
+    ///
+    /// The strings are truncated and the addresses lose their zone index
+    /// where the buffer has no room for them, as the records do on Windows.
+    pub fn encode(adapters: &[Adapter]) -> Self {
+        let mut writer = Writer::default();
+        let mut link = None;
+        for adapter in adapters {
+            let at = writer.adapter(adapter);
+            if let Some(link) = link {
+                writer.put_pointer(link, at);
+            }
+            link = Some(at + offsets::adapter::NEXT as u64);
+        }
+        AdapterBuffer::new(writer.bytes, ENCODE_BASE)
+    }
+}
+
+/// Decode the adapters of a buffer at address `base`. With a `lookup`, the
+/// adapters it can't match are skipped without being decoded.
+pub(crate) fn decode_adapters(
+    bytes: &[u8],
+    base: u64,
+    lookup: Option<&AdapterLookup>,
+) -> Result<AdapterList> {
+    use offsets::adapter;
+
+    let reader = Reader { bytes, base };
+    let first = if bytes.is_empty() { 0 } else { base };
+    let records = reader.walk(first, "adapter", |address| {
+        let record = reader.record(address, adapter::MIN_SIZE, "adapter")?;
+        Ok((record, record.pointer(adapter::NEXT)))
+    })?;
+
+    let mut list = AdapterList::default();
+    for record in records {
+        if lookup.is_some_and(|lookup| !may_match(&record, lookup)) {
+            continue;
+        }
+        match reader.adapter(&record) {
+            Ok(adapter) => list.adapters.push(adapter),
+            Err(error) => list.errors.push(AdapterError {
+                adapter_name: reader
+                    .ansi_string(record.pointer(adapter::ADAPTER_NAME), "adapter name")
+                    .unwrap_or_default(),
+                luid: record.u64(adapter::LUID),
+                error,
+            }),
+        }
+    }
+    Ok(list)
+}
+
+/// Whether the adapter of `record` may match `lookup`, from the LUID and the
+/// indexes of the record. The other lookups need the decoded adapter.
+fn may_match(record: &Record, lookup: &AdapterLookup) -> bool {
+    use offsets::adapter;
+
+    match lookup {
+        AdapterLookup::Luid(luid) => record.u64(adapter::LUID) == *luid,
+        AdapterLookup::Index(index) => {
+            *index != 0
+                && (record.u32(adapter::IF_INDEX) == *index
+                    || record.u32(adapter::IPV6_IF_INDEX) == *index)
+        }
+        AdapterLookup::Guid(_) | AdapterLookup::Name(_) => true,
+    }
+}
+
+/// A record of the buffer, the fields past its length read as zero.
+#[derive(Debug, Clone, Copy)]
+struct Record<'a> {
+    bytes: &'a [u8],
+    address: u64,
+}
+
+impl Record<'_> {
+    fn array<const N: usize>(&self, offset: usize) -> [u8; N] {
+        let mut array = [0; N];
+        if let Some(bytes) = self.bytes.get(offset..offset + N) {
+            array.copy_from_slice(bytes);
+        }
+        array
+    }
+
+    fn u8(&self, offset: usize) -> u8 {
+        self.array::<1>(offset)[0]
+    }
+
+    fn u32(&self, offset: usize) -> u32 {
+        u32::from_le_bytes(self.array(offset))
+    }
+
+    fn i32(&self, offset: usize) -> i32 {
+        i32::from_le_bytes(self.array(offset))
+    }
+
+    fn u64(&self, offset: usize) -> u64 {
+        u64::from_le_bytes(self.array(offset))
+    }
+
+    /// A pointer, as wide as the pointers of the target.
+    #[cfg(target_pointer_width = "64")]
+    fn pointer(&self, offset: usize) -> u64 {
+        self.u64(offset)
+    }
+
+    /// A pointer, as wide as the pointers of the target.
+    #[cfg(target_pointer_width = "32")]
+    fn pointer(&self, offset: usize) -> u64 {
+        u64::from(self.u32(offset))
+    }
+}
+
+struct Reader<'a> {
+    bytes: &'a [u8],
+    base: u64,
+}
+
+impl<'a> Reader<'a> {
+    /// The `len` bytes at `address`.
+    fn slice(&self, address: u64, len: usize, what: &'static str) -> Result<&'a [u8]> {
+        let offset = self.offset(address, what)?;
+        offset
+            .checked_add(len)
+            .and_then(|end| self.bytes.get(offset..end))
+            .ok_or_else(|| Error::malformed(what, address, "out of bounds"))
+    }
+
+    /// The bytes from `address` to the end of the buffer.
+    fn rest(&self, address: u64, what: &'static str) -> Result<&'a [u8]> {
+        Ok(&self.bytes[self.offset(address, what)?..])
+    }
+
+    fn offset(&self, address: u64, what: &'static str) -> Result<usize> {
+        address
+            .checked_sub(self.base)
+            .and_then(|offset| usize::try_from(offset).ok())
+            .filter(|&offset| offset < self.bytes.len())
+            .ok_or_else(|| Error::malformed(what, address, "out of bounds"))
+    }
+
+    /// A record that starts with its `ULONG` length.
+    fn record(&self, address: u64, min_len: usize, what: &'static str) -> Result<Record<'a>> {
+        let len = u32::from_le_bytes(self.slice(address, 4, what)?.try_into().unwrap());
+        let len = usize::try_from(len).unwrap_or(usize::MAX);
+        if len < min_len {
+            return Err(Error::malformed(what, address, "invalid length"));
+        }
+        Ok(Record {
+            bytes: self.slice(address, len, what)?,
+            address,
+        })
+    }
+
+    /// Walk a linked list from `first`, `next` returns an element and the
+    /// address of the following one, null at the end.
+    fn walk<T>(
+        &self,
+        first: u64,
+        what: &'static str,
+        mut next: impl FnMut(u64) -> Result<(T, u64)>,
+    ) -> Result<Vec<T>> {
+        let mut visited = HashSet::new();
+        let mut items = vec![];
+        let mut address = first;
+        while address != 0 {
+            if !visited.insert(address) {
+                return Err(Error::malformed(what, address, "cycle"));
+            }
+            let (item, following) = next(address)?;
+            items.push(item);
+            address = following;
+        }
+        Ok(items)
+    }
+
+    /// The records of a list of addresses.
+    fn address_list(
+        &self,
+        first: u64,
+        min_len: usize,
+        what: &'static str,
+    ) -> Result<Vec<Record<'a>>> {
+        self.walk(first, what, |address| {
+            let record = self.record(address, min_len, what)?;
+            Ok((record, record.pointer(offsets::address::NEXT)))
+        })
+    }
+
+    /// The addresses of a list of `IP_ADAPTER_*_ADDRESS` records.
+    fn addresses(&self, first: u64, what: &'static str) -> Result<Vec<ScopedIpAddr>> {
+        self.address_list(first, offsets::address::SIZE, what)?
+            .iter()
+            .map(|record| self.socket_address(record, offsets::address::ADDRESS, what))
+            .collect()
+    }
+
+    /// The `SOCKET_ADDRESS` at `offset` of a record.
+    fn socket_address(
+        &self,
+        record: &Record,
+        offset: usize,
+        what: &'static str,
+    ) -> Result<ScopedIpAddr> {
+        self.optional_socket_address(record, offset, what)?
+            .ok_or_else(|| Error::malformed(what, record.address, "null address"))
+    }
+
+    /// The `SOCKET_ADDRESS` at `offset` of a record, `None` if it is null.
+    fn optional_socket_address(
+        &self,
+        record: &Record,
+        offset: usize,
+        what: &'static str,
+    ) -> Result<Option<ScopedIpAddr>> {
+        let address = record.pointer(offset);
+        let len = record.i32(offset + offsets::POINTER_SIZE);
+        if address == 0 || len == 0 {
+            return Ok(None);
+        }
+        let len = usize::try_from(len)
+            .map_err(|_| Error::malformed(what, record.address, "invalid length"))?;
+        let sockaddr = self.slice(address, len, what)?;
+        let family = match sockaddr {
+            [low, high, ..] => u16::from_le_bytes([*low, *high]),
+            _ => return Err(Error::malformed(what, address, "invalid length")),
+        };
+        match family {
+            AF_INET if len >= SOCKADDR_IN_SIZE => {
+                let octets: [u8; 4] = sockaddr[4..8].try_into().unwrap();
+                Ok(Some(IpAddr::from(Ipv4Addr::from(octets)).into()))
+            }
+            AF_INET6 if len >= SOCKADDR_IN6_SIZE => {
+                let octets: [u8; 16] = sockaddr[8..24].try_into().unwrap();
+                let scope_id = u32::from_le_bytes(sockaddr[24..28].try_into().unwrap());
+                Ok(Some(ScopedIpAddr::new(
+                    Ipv6Addr::from(octets).into(),
+                    scope_id,
+                )))
+            }
+            AF_INET | AF_INET6 => Err(Error::malformed(what, address, "invalid length")),
+            _ => Err(Error::malformed(what, address, "unknown address family")),
+        }
+    }
+
+    /// A null terminated UTF-16 string, a null pointer is an empty string.
+    fn wide_string(&self, address: u64, what: &'static str) -> Result<Vec<u16>> {
+        if address == 0 {
+            return Ok(vec![]);
+        }
+        let units = self
+            .rest(address, what)?
+            .chunks_exact(2)
+            .map(|pair| u16::from_le_bytes([pair[0], pair[1]]));
+        let mut string = vec![];
+        for unit in units {
+            if unit == 0 {
+                return Ok(string);
+            }
+            string.push(unit);
+        }
+        Err(Error::malformed(what, address, "missing terminator"))
+    }
+
+    /// A null terminated ANSI string, a null pointer is an empty string.
+    fn ansi_string(&self, address: u64, what: &'static str) -> Result<OsString> {
+        if address == 0 {
+            return Ok(OsString::new());
+        }
+        let string = self.rest(address, what)?;
+        match string.iter().position(|&b| b == 0) {
+            Some(len) => Ok(os_string_from_ansi(&string[..len])),
+            None => Err(Error::malformed(what, address, "missing terminator")),
+        }
+    }
+
+    fn unicast_addresses(&self, first: u64) -> Result<Vec<(ScopedIpAddr, UnicastAddress)>> {
+        use offsets::unicast;
+
+        let what = "unicast address";
+        self.address_list(first, unicast::MIN_SIZE, what)?
+            .iter()
+            .map(|record| {
+                let addr = self.socket_address(record, offsets::address::ADDRESS, what)?;
+                let prefix_length = record.u8(unicast::ON_LINK_PREFIX_LENGTH);
+                // a bad length only loses the on-link prefix, not the adapter
+                let address = IpNetwork::new(addr.ip(), prefix_length).unwrap_or_else(|_| {
+                    tracing::warn!(
+                        "using the host prefix for {} with invalid on-link length {}",
+                        addr.ip(),
+                        prefix_length
+                    );
+                    IpNetwork::from(addr.ip())
+                });
+                let details = UnicastAddress {
+                    address,
+                    prefix_origin: PrefixOrigin::from(record.u32(unicast::PREFIX_ORIGIN)),
+                    suffix_origin: SuffixOrigin::from(record.u32(unicast::SUFFIX_ORIGIN)),
+                    dad_state: DadState::from(record.u32(unicast::DAD_STATE)),
+                    valid_lifetime: lifetime(record.u32(unicast::VALID_LIFETIME)),
+                    preferred_lifetime: lifetime(record.u32(unicast::PREFERRED_LIFETIME)),
+                    lease_lifetime: lifetime(record.u32(unicast::LEASE_LIFETIME)),
+                };
+                Ok((addr, details))
+            })
+            .collect()
+    }
+
+    fn prefixes(&self, first: u64) -> Result<Vec<IpNetwork>> {
+        use offsets::prefix;
+
+        let what = "prefix";
+        let mut prefixes = vec![];
+        for record in self.address_list(first, prefix::MIN_SIZE, what)? {
+            let addr = self.socket_address(&record, offsets::address::ADDRESS, what)?;
+            let prefix_length = record.u32(prefix::PREFIX_LENGTH);
+            // a bad length only loses the prefix, not the adapter
+            match u8::try_from(prefix_length)
+                .ok()
+                .and_then(|len| IpNetwork::new(addr.ip(), len).ok())
+            {
+                Some(network) => prefixes.push(network),
+                None => tracing::warn!(
+                    "skipping prefix {} with invalid length {}",
+                    addr.ip(),
+                    prefix_length
+                ),
+            }
+        }
+        Ok(prefixes)
+    }
+
+    fn dns_suffixes(&self, first: u64) -> Result<Vec<OsString>> {
+        use offsets::dns_suffix;
+
+        let what = "DNS suffix";
+        self.walk(first, what, |address| {
+            let record = Record {
+                bytes: self.slice(address, dns_suffix::SIZE, what)?,
+                address,
+            };
+            let units: Vec<u16> = (0..dns_suffix::STRING_LEN)
+                .map(|i| u16::from_le_bytes(record.array(dns_suffix::STRING + 2 * i)))
+                .take_while(|&unit| unit != 0)
+                .collect();
+            Ok((
+                os_string_from_wide(&units),
+                record.pointer(dns_suffix::NEXT),
+            ))
+        })
+    }
+
+    // ref: https://learn.microsoft.com/en-us/windows/win32/api/iptypes/ns-iptypes-ip_adapter_addresses_lh
+    fn adapter(&self, record: &Record) -> Result<Adapter> {
+        use offsets::adapter;
+
+        let (ip_addresses, unicast_addresses) = self
+            .unicast_addresses(record.pointer(adapter::FIRST_UNICAST_ADDRESS))?
+            .into_iter()
+            .unzip();
+        let ips = |addresses: Vec<ScopedIpAddr>| addresses.into_iter().map(|a| a.ip()).collect();
+        let anycast_addresses = self.addresses(
+            record.pointer(adapter::FIRST_ANYCAST_ADDRESS),
+            "anycast address",
+        )?;
+        let multicast_addresses = self.addresses(
+            record.pointer(adapter::FIRST_MULTICAST_ADDRESS),
+            "multicast address",
+        )?;
+        let wins_servers = self.addresses(
+            record.pointer(adapter::FIRST_WINS_SERVER_ADDRESS),
+            "WINS server",
+        )?;
+
+        let dns_suffix = self.wide_string(record.pointer(adapter::DNS_SUFFIX), "DNS suffix")?;
+        let physical_address_length = record.u32(adapter::PHYSICAL_ADDRESS_LENGTH) as usize;
+        let physical_address = if physical_address_length == 0 {
+            None
+        } else {
+            let address: [u8; 8] = record.array(adapter::PHYSICAL_ADDRESS);
+            Some(address[..physical_address_length.min(address.len())].to_vec())
+        };
+        let duid_length = record.u32(adapter::DHCPV6_CLIENT_DUID_LENGTH) as usize;
+        let dhcpv6_client_duid = if duid_length == 0 {
+            None
+        } else {
+            let duid: [u8; 130] = record.array(adapter::DHCPV6_CLIENT_DUID);
+            Some(duid[..duid_length.min(duid.len())].to_vec())
+        };
+        let mut zone_indices = [0; 16];
+        for (i, zone_index) in zone_indices.iter_mut().enumerate() {
+            *zone_index = record.u32(adapter::ZONE_INDICES + 4 * i);
+        }
+
+        Ok(Adapter {
+            adapter_name: self
+                .ansi_string(record.pointer(adapter::ADAPTER_NAME), "adapter name")?,
+            network_guid: record.array(adapter::NETWORK_GUID),
+            luid: record.u64(adapter::LUID),
+            ipv4_if_index: record.u32(adapter::IF_INDEX),
+            ip_addresses,
+            unicast_addresses,
+            prefixes: self.prefixes(record.pointer(adapter::FIRST_PREFIX))?,
+            anycast_addresses: ips(anycast_addresses),
+            multicast_addresses: ips(multicast_addresses),
+            gateways: self.addresses(record.pointer(adapter::FIRST_GATEWAY_ADDRESS), "gateway")?,
+            dns_servers: self.addresses(
+                record.pointer(adapter::FIRST_DNS_SERVER_ADDRESS),
+                "DNS server",
+            )?,
+            wins_servers: ips(wins_servers),
+            dns_suffix: os_string_from_wide(&dns_suffix),
+            dns_suffixes: self.dns_suffixes(record.pointer(adapter::FIRST_DNS_SUFFIX))?,
+            description: os_string_from_wide(
+                &self.wide_string(record.pointer(adapter::DESCRIPTION), "description")?,
+            ),
+            // null with GAA_FLAG_SKIP_FRIENDLY_NAME on some versions
+            friendly_name: os_string_from_wide(
+                &self.wide_string(record.pointer(adapter::FRIENDLY_NAME), "friendly name")?,
+            ),
+            physical_address,
+            flags: AdapterFlags(record.u32(adapter::FLAGS)),
+            mtu: record.u32(adapter::MTU),
+            dhcpv4_server: self
+                .optional_socket_address(record, adapter::DHCPV4_SERVER, "DHCPv4 server")?
+                .map(|a| a.ip()),
+            dhcpv6_server: self
+                .optional_socket_address(record, adapter::DHCPV6_SERVER, "DHCPv6 server")?
+                .map(|a| a.ip()),
+            dhcpv6_client_duid,
+            dhcpv6_iaid: record.u32(adapter::DHCPV6_IAID),
+            receive_link_speed: record.u64(adapter::RECEIVE_LINK_SPEED),
+            transmit_link_speed: record.u64(adapter::TRANSMIT_LINK_SPEED),
+            oper_status: OperStatus::from(record.u32(adapter::OPER_STATUS)),
+            if_type: IfType::from(record.u32(adapter::IF_TYPE)),
+            ipv6_if_index: record.u32(adapter::IPV6_IF_INDEX),
+            ipv4_metric: record.u32(adapter::IPV4_METRIC),
+            ipv6_metric: record.u32(adapter::IPV6_METRIC),
+            tunnel_type: TunnelType::from(record.u32(adapter::TUNNEL_TYPE)),
+            connection_type: ConnectionType::from(record.u32(adapter::CONNECTION_TYPE)),
+            zone_indices,
+            ..Default::default()
+        })
+    }
+}
+
+/// The address [`AdapterBuffer::encode`] lays the buffers out at.
+const ENCODE_BASE: u64 = 0x1000_0000;
+
+/// The lifetime in seconds of a record, `u32::MAX` is infinite.
+fn lifetime_secs(lifetime: Option<std::time::Duration>) -> u32 {
+    lifetime.map_or(u32::MAX, |lifetime| {
+        u32::try_from(lifetime.as_secs()).map_or(u32::MAX - 1, |secs| secs.min(u32::MAX - 1))
+    })
+}
+
+/// Writes records at [`ENCODE_BASE`], each one 8 bytes aligned.
+#[derive(Default)]
+struct Writer {
+    bytes: Vec<u8>,
+}
+
+impl Writer {
+    fn alloc(&mut self, len: usize) -> u64 {
+        let offset = (self.bytes.len() + 7) & !7;
+        self.bytes.resize(offset + len, 0);
+        ENCODE_BASE + offset as u64
+    }
+
+    fn put(&mut self, at: u64, data: &[u8]) {
+        let offset = (at - ENCODE_BASE) as usize;
+        self.bytes[offset..offset + data.len()].copy_from_slice(data);
+    }
+
+    fn put_u32(&mut self, at: u64, value: u32) {
+        self.put(at, &value.to_le_bytes());
+    }
+
+    fn put_u64(&mut self, at: u64, value: u64) {
+        self.put(at, &value.to_le_bytes());
+    }
+
+    fn put_pointer(&mut self, at: u64, value: u64) {
+        self.put(at, &value.to_le_bytes()[..offsets::POINTER_SIZE]);
+    }
+
+    /// A record that starts with its `ULONG` length.
+    fn record(&mut self, len: usize) -> u64 {
+        let at = self.alloc(len);
+        self.put_u32(at, len as u32);
+        at
+    }
+
+    fn wide_string(&mut self, s: &std::ffi::OsStr) -> u64 {
+        let units = os_string_to_wide(s);
+        let at = self.alloc(2 * (units.len() + 1));
+        let bytes: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+        self.put(at, &bytes);
+        at
+    }
+
+    fn ansi_string(&mut self, s: &std::ffi::OsStr) -> u64 {
+        let bytes = os_string_to_ansi(s);
+        let at = self.alloc(bytes.len() + 1);
+        self.put(at, &bytes);
+        at
+    }
+
+    /// A `SOCKADDR_IN` or `SOCKADDR_IN6`, stored as a `SOCKET_ADDRESS` at
+    /// `at`.
+    fn socket_address(&mut self, at: u64, addr: ScopedIpAddr) {
+        let (sockaddr, len) = match addr.ip() {
+            IpAddr::V4(ip) => {
+                let sockaddr = self.alloc(SOCKADDR_IN_SIZE);
+                self.put(sockaddr, &AF_INET.to_le_bytes());
+                self.put(sockaddr + 4, &ip.octets());
+                (sockaddr, SOCKADDR_IN_SIZE)
+            }
+            IpAddr::V6(ip) => {
+                let sockaddr = self.alloc(SOCKADDR_IN6_SIZE);
+                self.put(sockaddr, &AF_INET6.to_le_bytes());
+                self.put(sockaddr + 8, &ip.octets());
+                self.put_u32(sockaddr + 24, addr.scope_id());
+                (sockaddr, SOCKADDR_IN6_SIZE)
+            }
+        };
+        self.put_pointer(at, sockaddr);
+        self.put_u32(at + offsets::POINTER_SIZE as u64, len as u32);
+    }
+
+    /// A linked list whose head pointer is at `head`, `write` writes a record
+    /// and returns its address.
+    fn list<T>(
+        &mut self,
+        head: u64,
+        next: usize,
+        items: impl IntoIterator<Item = T>,
+        mut write: impl FnMut(&mut Self, T) -> u64,
+    ) {
+        let mut link = head;
+        for item in items {
+            let record = write(self, item);
+            self.put_pointer(link, record);
+            link = record + next as u64;
+        }
+    }
+
+    /// A list of `IP_ADAPTER_*_ADDRESS` records.
+    fn addresses(&mut self, head: u64, addresses: impl IntoIterator<Item = ScopedIpAddr>) {
+        use offsets::address;
+
+        self.list(head, address::NEXT, addresses, |w, addr| {
+            let record = w.record(address::SIZE);
+            w.socket_address(record + address::ADDRESS as u64, addr);
+            record
+        });
+    }
+
+    fn adapter(&mut self, a: &Adapter) -> u64 {
+        use offsets::{adapter, address, dns_suffix, prefix, unicast};
+
+        let at = self.record(adapter::SIZE);
+        let field = |offset: usize| at + offset as u64;
+        self.put_u32(field(adapter::IF_INDEX), a.ipv4_if_index);
+        let name = self.ansi_string(&a.adapter_name);
+        self.put_pointer(field(adapter::ADAPTER_NAME), name);
+
+        // the details of an address without any are those of a host route
+        let unicast_addresses = a.ip_addresses.iter().enumerate().map(|(i, &addr)| {
+            let details = a
+                .unicast_addresses
+                .get(i)
+                .filter(|details| details.ip() == addr.ip())
+                .cloned();
+            let details = details.unwrap_or_else(|| {
+                let len = if addr.ip().is_ipv4() { 32 } else { 128 };
+                IpNetwork::new(addr.ip(), len).unwrap().into()
+            });
+            (addr, details)
+        });
+        self.list(
+            field(adapter::FIRST_UNICAST_ADDRESS),
+            address::NEXT,
+            unicast_addresses,
+            |w, (addr, details)| {
+                let record = w.record(unicast::SIZE);
+                let field = |offset: usize| record + offset as u64;
+                w.socket_address(field(address::ADDRESS), addr);
+                w.put_u32(field(unicast::PREFIX_ORIGIN), details.prefix_origin.into());
+                w.put_u32(field(unicast::SUFFIX_ORIGIN), details.suffix_origin.into());
+                w.put_u32(field(unicast::DAD_STATE), details.dad_state.into());
+                let lifetimes = [
+                    (unicast::VALID_LIFETIME, details.valid_lifetime),
+                    (unicast::PREFERRED_LIFETIME, details.preferred_lifetime),
+                    (unicast::LEASE_LIFETIME, details.lease_lifetime),
+                ];
+                for (offset, lifetime) in lifetimes {
+                    w.put_u32(field(offset), lifetime_secs(lifetime));
+                }
+                w.put(
+                    field(unicast::ON_LINK_PREFIX_LENGTH),
+                    &[details.address.prefix()],
+                );
+                record
+            },
+        );
+        let unscoped = |ips: &[IpAddr]| -> Vec<ScopedIpAddr> {
+            ips.iter().map(|&ip| ScopedIpAddr::from(ip)).collect()
+        };
+        self.addresses(
+            field(adapter::FIRST_ANYCAST_ADDRESS),
+            unscoped(&a.anycast_addresses),
+        );
+        self.addresses(
+            field(adapter::FIRST_MULTICAST_ADDRESS),
+            unscoped(&a.multicast_addresses),
+        );
+        self.addresses(
+            field(adapter::FIRST_DNS_SERVER_ADDRESS),
+            a.dns_servers.iter().copied(),
+        );
+        let dns_suffix = self.wide_string(&a.dns_suffix);
+        self.put_pointer(field(adapter::DNS_SUFFIX), dns_suffix);
+        let description = self.wide_string(&a.description);
+        self.put_pointer(field(adapter::DESCRIPTION), description);
+        let friendly_name = self.wide_string(&a.friendly_name);
+        self.put_pointer(field(adapter::FRIENDLY_NAME), friendly_name);
+        if let Some(physical_address) = &a.physical_address {
+            let physical_address = &physical_address[..physical_address.len().min(8)];
+            self.put(field(adapter::PHYSICAL_ADDRESS), physical_address);
+            self.put_u32(
+                field(adapter::PHYSICAL_ADDRESS_LENGTH),
+                physical_address.len() as u32,
+            );
+        }
+        self.put_u32(field(adapter::FLAGS), a.flags.0);
+        self.put_u32(field(adapter::MTU), a.mtu);
+        self.put_u32(field(adapter::IF_TYPE), a.if_type.into());
+        self.put_u32(field(adapter::OPER_STATUS), a.oper_status.into());
+        self.put_u32(field(adapter::IPV6_IF_INDEX), a.ipv6_if_index);
+        for (i, &zone_index) in a.zone_indices.iter().enumerate() {
+            self.put_u32(field(adapter::ZONE_INDICES + 4 * i), zone_index);
+        }
+        self.list(
+            field(adapter::FIRST_PREFIX),
+            address::NEXT,
+            &a.prefixes,
+            |w, network| {
+                let record = w.record(prefix::SIZE);
+                w.socket_address(record + address::ADDRESS as u64, network.addr().into());
+                w.put_u32(
+                    record + prefix::PREFIX_LENGTH as u64,
+                    network.prefix().into(),
+                );
+                record
+            },
+        );
+        self.put_u64(field(adapter::TRANSMIT_LINK_SPEED), a.transmit_link_speed);
+        self.put_u64(field(adapter::RECEIVE_LINK_SPEED), a.receive_link_speed);
+        self.addresses(
+            field(adapter::FIRST_WINS_SERVER_ADDRESS),
+            unscoped(&a.wins_servers),
+        );
+        self.addresses(
+            field(adapter::FIRST_GATEWAY_ADDRESS),
+            a.gateways.iter().copied(),
+        );
+        self.put_u32(field(adapter::IPV4_METRIC), a.ipv4_metric);
+        self.put_u32(field(adapter::IPV6_METRIC), a.ipv6_metric);
+        self.put_u64(field(adapter::LUID), a.luid);
+        if let Some(server) = a.dhcpv4_server {
+            self.socket_address(field(adapter::DHCPV4_SERVER), server.into());
+        }
+        self.put(field(adapter::NETWORK_GUID), &a.network_guid);
+        self.put_u32(field(adapter::CONNECTION_TYPE), a.connection_type.into());
+        self.put_u32(field(adapter::TUNNEL_TYPE), a.tunnel_type.into());
+        if let Some(server) = a.dhcpv6_server {
+            self.socket_address(field(adapter::DHCPV6_SERVER), server.into());
+        }
+        if let Some(duid) = &a.dhcpv6_client_duid {
+            let duid = &duid[..duid.len().min(130)];
+            self.put(field(adapter::DHCPV6_CLIENT_DUID), duid);
+            self.put_u32(field(adapter::DHCPV6_CLIENT_DUID_LENGTH), duid.len() as u32);
+        }
+        self.put_u32(field(adapter::DHCPV6_IAID), a.dhcpv6_iaid);
+        self.list(
+            field(adapter::FIRST_DNS_SUFFIX),
+            dns_suffix::NEXT,
+            &a.dns_suffixes,
+            |w, suffix| {
+                let record = w.alloc(dns_suffix::SIZE);
+                let mut units = os_string_to_wide(suffix);
+                units.truncate(dns_suffix::STRING_LEN - 1);
+                let bytes: Vec<u8> = units.iter().flat_map(|unit| unit.to_le_bytes()).collect();
+                w.put(record + dns_suffix::STRING as u64, &bytes);
+                record
+            },
+        );
+        at
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::offsets::{adapter, address, dns_suffix, prefix, unicast, POINTER_SIZE};
+    use super::*;
+    use crate::error::ErrorKind;
+
+    const BASE: u64 = 0x7ff0_0000;
+
+    /// Builds a buffer the way `GetAdaptersAddresses` lays it out.
+    #[derive(Default)]
+    struct Builder {
+        bytes: Vec<u8>,
+    }
+
+    impl Builder {
+        fn alloc(&mut self, len: usize) -> u64 {
+            let offset = (self.bytes.len() + 7) & !7;
+            self.bytes.resize(offset + len, 0);
+            BASE + offset as u64
+        }
+
+        fn put(&mut self, at: u64, data: &[u8]) {
+            let offset = (at - BASE) as usize;
+            self.bytes[offset..offset + data.len()].copy_from_slice(data);
+        }
+
+        fn put_u32(&mut self, at: u64, value: u32) {
+            self.put(at, &value.to_le_bytes());
+        }
+
+        fn put_u64(&mut self, at: u64, value: u64) {
+            self.put(at, &value.to_le_bytes());
+        }
+
+        fn put_pointer(&mut self, at: u64, value: u64) {
+            self.put(at, &value.to_le_bytes()[..POINTER_SIZE]);
+        }
+
+        fn read(&self, at: u64) -> u64 {
+            let offset = (at - BASE) as usize;
+            let mut bytes = [0; 8];
+            bytes[..POINTER_SIZE].copy_from_slice(&self.bytes[offset..offset + POINTER_SIZE]);
+            u64::from_le_bytes(bytes)
+        }
+
+        fn record(&mut self, len: usize) -> u64 {
+            let at = self.alloc(len);
+            self.put_u32(at, len as u32);
+            at
+        }
+
+        fn wide(&mut self, s: &str) -> u64 {
+            let units: Vec<u16> = s.encode_utf16().chain([0]).collect();
+            let at = self.alloc(units.len() * 2);
+            for (i, unit) in units.iter().enumerate() {
+                self.put(at + 2 * i as u64, &unit.to_le_bytes());
+            }
+            at
+        }
+
+        fn ansi(&mut self, s: &str) -> u64 {
+            let at = self.alloc(s.len() + 1);
+            self.put(at, s.as_bytes());
+            at
+        }
+
+        /// A `SOCKADDR_IN` or `SOCKADDR_IN6`, stored as a `SOCKET_ADDRESS`
+        /// at `at`.
+        fn socket_address(&mut self, at: u64, addr: ScopedIpAddr) {
+            let sockaddr = match addr.ip() {
+                IpAddr::V4(ip) => {
+                    let sockaddr = self.alloc(SOCKADDR_IN_SIZE);
+                    self.put(sockaddr, &AF_INET.to_le_bytes());
+                    self.put(sockaddr + 4, &ip.octets());
+                    (sockaddr, SOCKADDR_IN_SIZE)
+                }
+                IpAddr::V6(ip) => {
+                    let sockaddr = self.alloc(SOCKADDR_IN6_SIZE);
+                    self.put(sockaddr, &AF_INET6.to_le_bytes());
+                    self.put(sockaddr + 8, &ip.octets());
+                    self.put_u32(sockaddr + 24, addr.scope_id());
+                    (sockaddr, SOCKADDR_IN6_SIZE)
+                }
+            };
+            self.put_pointer(at, sockaddr.0);
+            self.put_u32(at + POINTER_SIZE as u64, sockaddr.1 as u32);
+        }
+
+        /// Append a record to the list whose head pointer is at `head`.
+        fn push(&mut self, head: u64, next_offset: u64, record: u64) {
+            let mut link = head;
+            while self.read(link) != 0 {
+                link = self.read(link) + next_offset;
+            }
+            self.put_pointer(link, record);
+        }
+
+        fn push_address(&mut self, head: u64, len: usize, addr: &str) -> u64 {
+            let record = self.record(len);
+            self.socket_address(record + address::ADDRESS as u64, addr.parse().unwrap());
+            self.push(head, address::NEXT as u64, record);
+            record
+        }
+
+        fn adapter(&mut self, name: &str) -> u64 {
+            let at = self.record(adapter::SIZE);
+            let name = self.ansi(name);
+            self.put_pointer(at + adapter::ADAPTER_NAME as u64, name);
+            if at != BASE {
+                self.push(BASE + adapter::NEXT as u64, adapter::NEXT as u64, at);
+            }
+            at
+        }
+    }
+
+    fn field(at: u64, offset: usize) -> u64 {
+        at + offset as u64
+    }
+
+    /// An Ethernet adapter with every list filled.
+    fn ethernet(b: &mut Builder) -> u64 {
+        let at = b.adapter("{5B1E2E5A-9C53-4A23-8F4B-8E42C2C1B2D1}");
+        b.put_u32(field(at, adapter::IF_INDEX), 7);
+        let description = b.wide("Intel(R) Ethernet Connection");
+        b.put_pointer(field(at, adapter::DESCRIPTION), description);
+        let friendly_name = b.wide("Ethernet");
+        b.put_pointer(field(at, adapter::FRIENDLY_NAME), friendly_name);
+        let suffix = b.wide("corp.example.com");
+        b.put_pointer(field(at, adapter::DNS_SUFFIX), suffix);
+        b.put(
+            field(at, adapter::PHYSICAL_ADDRESS),
+            &[0, 0x15, 0x5d, 1, 2, 3],
+        );
+        b.put_u32(field(at, adapter::PHYSICAL_ADDRESS_LENGTH), 6);
+        b.put_u32(field(at, adapter::FLAGS), AdapterFlags::DHCP_ENABLED);
+        b.put_u32(field(at, adapter::MTU), 1500);
+        b.put_u32(field(at, adapter::IF_TYPE), 6);
+        b.put_u32(field(at, adapter::OPER_STATUS), 1);
+        b.put_u32(field(at, adapter::IPV6_IF_INDEX), 7);
+        b.put_u32(field(at, adapter::ZONE_INDICES + 4), 7);
+        b.put_u64(field(at, adapter::TRANSMIT_LINK_SPEED), 1_000_000_000);
+        b.put_u64(field(at, adapter::RECEIVE_LINK_SPEED), 1_000_000_000);
+        b.put_u32(field(at, adapter::IPV4_METRIC), 25);
+        b.put_u64(field(at, adapter::LUID), 0x0006_0000_0100_0000);
+        b.put(field(at, adapter::NETWORK_GUID), &[0xab; 16]);
+        b.put_u32(field(at, adapter::CONNECTION_TYPE), 1);
+        b.socket_address(
+            field(at, adapter::DHCPV4_SERVER),
+            "192.168.1.1".parse().unwrap(),
+        );
+        b.put(field(at, adapter::DHCPV6_CLIENT_DUID), &[0, 1, 0, 1, 0x2a]);
+        b.put_u32(field(at, adapter::DHCPV6_CLIENT_DUID_LENGTH), 5);
+
+        let ipv4 = b.push_address(
+            field(at, adapter::FIRST_UNICAST_ADDRESS),
+            unicast::SIZE,
+            "192.168.1.20",
+        );
+        b.put_u32(field(ipv4, unicast::PREFIX_ORIGIN), 3);
+        b.put_u32(field(ipv4, unicast::VALID_LIFETIME), 3600);
+        b.put_u32(field(ipv4, unicast::PREFERRED_LIFETIME), u32::MAX);
+        b.put(field(ipv4, unicast::ON_LINK_PREFIX_LENGTH), &[24]);
+        let ipv6 = b.push_address(
+            field(at, adapter::FIRST_UNICAST_ADDRESS),
+            unicast::SIZE,
+            "fe80::1%7",
+        );
+        b.put(field(ipv6, unicast::ON_LINK_PREFIX_LENGTH), &[64]);
+
+        let prefix = b.push_address(
+            field(at, adapter::FIRST_PREFIX),
+            prefix::SIZE,
+            "192.168.1.0",
+        );
+        b.put_u32(field(prefix, prefix::PREFIX_LENGTH), 24);
+        b.push_address(
+            field(at, adapter::FIRST_GATEWAY_ADDRESS),
+            address::SIZE,
+            "192.168.1.1",
+        );
+        b.push_address(
+            field(at, adapter::FIRST_DNS_SERVER_ADDRESS),
+            address::SIZE,
+            "fec0:0:0:ffff::1%1",
+        );
+        b.push_address(
+            field(at, adapter::FIRST_MULTICAST_ADDRESS),
+            address::SIZE,
+            "ff02::1",
+        );
+        for name in ["corp.example.com", "example.com"] {
+            let suffix = b.alloc(dns_suffix::SIZE);
+            let units: Vec<u8> = name.encode_utf16().flat_map(u16::to_le_bytes).collect();
+            b.put(field(suffix, dns_suffix::STRING), &units);
+            b.push(
+                field(at, adapter::FIRST_DNS_SUFFIX),
+                dns_suffix::NEXT as u64,
+                suffix,
+            );
+        }
+        at
+    }
+
+    fn decode(b: &Builder) -> Result<AdapterList> {
+        AdapterBuffer::new(b.bytes.clone(), BASE).decode()
+    }
+
+    fn malformed(err: &Error) -> (&'static str, &'static str) {
+        match err.kind() {
+            ErrorKind::Malformed { what, reason, .. } => (what, reason),
+            kind => panic!("not malformed: {:?}", kind),
+        }
+    }
+
+    #[test]
+    fn test_decode() {
+        let mut b = Builder::default();
+        ethernet(&mut b);
+        let loopback = b.adapter("loopback");
+        b.put_u32(field(loopback, adapter::IF_TYPE), 24);
+        b.push_address(
+            field(loopback, adapter::FIRST_UNICAST_ADDRESS),
+            unicast::MIN_SIZE,
+            "127.0.0.1",
+        );
+
+        let list = decode(&b).unwrap();
+        assert!(list.errors.is_empty());
+        assert_eq!(list.adapters.len(), 2);
+        let a = &list.adapters[0];
+        assert_eq!(a.adapter_name, "{5B1E2E5A-9C53-4A23-8F4B-8E42C2C1B2D1}");
+        assert_eq!(a.description, "Intel(R) Ethernet Connection");
+        assert_eq!(a.friendly_name, "Ethernet");
+        assert_eq!(a.dns_suffix, "corp.example.com");
+        assert_eq!(a.dns_suffixes, ["corp.example.com", "example.com"]);
+        assert_eq!(a.ipv4_if_index, 7);
+        assert_eq!(a.luid, 0x0006_0000_0100_0000);
+        assert_eq!(a.network_guid, [0xab; 16]);
+        assert_eq!(
+            a.physical_address.as_deref(),
+            Some(&[0, 0x15, 0x5d, 1, 2, 3][..])
+        );
+        assert!(a.flags.dhcp_enabled());
+        assert_eq!(a.mtu, 1500);
+        assert_eq!(a.if_type, IfType::EthernetCsmacd);
+        assert_eq!(a.oper_status, OperStatus::IfOperStatusUp);
+        assert_eq!(a.zone_indices[1], 7);
+        assert_eq!(a.receive_link_speed, 1_000_000_000);
+        assert_eq!(a.ipv4_metric, 25);
+        assert_eq!(a.connection_type, ConnectionType::Dedicated);
+        assert_eq!(a.dhcpv4_server, Some("192.168.1.1".parse().unwrap()));
+        assert_eq!(a.dhcpv6_server, None);
+        assert_eq!(
+            a.dhcpv6_client_duid.as_deref(),
+            Some(&[0, 1, 0, 1, 0x2a][..])
+        );
+
+        let ips: Vec<String> = a.ip_addresses.iter().map(|ip| ip.to_string()).collect();
+        assert_eq!(ips, ["192.168.1.20", "fe80::1%7"]);
+        let ipv4 = &a.unicast_addresses[0];
+        assert_eq!(ipv4.address, "192.168.1.20/24".parse().unwrap());
+        assert_eq!(ipv4.prefix_origin, PrefixOrigin::Dhcp);
+        assert_eq!(
+            ipv4.valid_lifetime,
+            Some(std::time::Duration::from_secs(3600))
+        );
+        assert_eq!(ipv4.preferred_lifetime, None);
+        assert_eq!(a.unicast_addresses[1].address.prefix(), 64);
+        assert_eq!(a.prefixes, ["192.168.1.0/24".parse().unwrap()]);
+        assert_eq!(a.gateways, ["192.168.1.1".parse().unwrap()]);
+        assert_eq!(a.dns_servers[0].to_string(), "fec0:0:0:ffff::1%1");
+        assert_eq!(
+            a.multicast_addresses,
+            ["ff02::1".parse::<IpAddr>().unwrap()]
+        );
+        assert!(a.anycast_addresses.is_empty());
+
+        let lo = &list.adapters[1];
+        assert_eq!(lo.adapter_name, "loopback");
+        assert_eq!(lo.if_type, IfType::SoftwareLoopback);
+        // an XP unicast record has no on-link prefix length
+        assert_eq!(
+            lo.unicast_addresses[0].address,
+            "127.0.0.1/0".parse().unwrap()
+        );
+
+        // an invalid prefix length only skips the prefix
+        let mut b = Builder::default();
+        let at = ethernet(&mut b);
+        let bad = b.push_address(field(at, adapter::FIRST_PREFIX), prefix::SIZE, "10.0.0.0");
+        b.put_u32(field(bad, prefix::PREFIX_LENGTH), 33);
+        let list = decode(&b).unwrap();
+        assert!(list.errors.is_empty());
+        assert_eq!(
+            list.adapters[0].prefixes,
+            ["192.168.1.0/24".parse().unwrap()]
+        );
+
+        assert!(AdapterBuffer::default()
+            .decode()
+            .unwrap()
+            .adapters
+            .is_empty());
+    }
+
+    #[test]
+    fn test_encode() {
+        let mut b = Builder::default();
+        ethernet(&mut b);
+        let loopback = b.adapter("loopback");
+        b.put_u32(field(loopback, adapter::IF_TYPE), 24);
+        b.push_address(
+            field(loopback, adapter::FIRST_UNICAST_ADDRESS),
+            unicast::MIN_SIZE,
+            "127.0.0.1",
+        );
+        let adapters = decode(&b).unwrap().adapters;
+
+        let list = AdapterBuffer::encode(&adapters).decode().unwrap();
+        assert!(list.errors.is_empty());
+        assert_eq!(list.adapters, adapters);
+
+        // a name that is not UTF-8 is encoded back to its ANSI bytes
+        let mut b = Builder::default();
+        let at = ethernet(&mut b);
+        let name = b.alloc(5);
+        b.put(name, b"caf\xe9\0");
+        b.put_pointer(field(at, adapter::ADAPTER_NAME), name);
+        let latin1 = decode(&b).unwrap().adapters;
+        assert_eq!(latin1[0].adapter_name, "café");
+        let buffer = AdapterBuffer::encode(&latin1);
+        assert!(buffer.bytes.windows(5).any(|w| w == b"caf\xe9\0"));
+        assert_eq!(buffer.decode().unwrap().adapters, latin1);
+
+        // the fields the records have no room for are truncated
+        let mut a = adapters[0].clone();
+        a.physical_address = Some(vec![1; 10]);
+        a.dns_suffixes = vec![OsString::from("a".repeat(300))];
+        let list = AdapterBuffer::encode(&[a]).decode().unwrap();
+        let a = &list.adapters[0];
+        assert_eq!(a.physical_address, Some(vec![1; 8]));
+        assert_eq!(a.dns_suffixes, [OsString::from("a".repeat(255))]);
+
+        assert!(AdapterBuffer::encode(&[])
+            .decode()
+            .unwrap()
+            .adapters
+            .is_empty());
+    }
+
+    #[test]
+    fn test_decode_malformed_adapter() {
+        type Corrupt = fn(&mut Builder, u64);
+        let cases: [(Corrupt, &str, &str); 8] = [
+            (
+                |b, at| {
+                    let record = b.read(field(at, adapter::FIRST_UNICAST_ADDRESS));
+                    b.put_u32(field(record, address::ADDRESS + POINTER_SIZE), 8);
+                },
+                "unicast address",
+                "invalid length",
+            ),
+            (
+                |b, at| {
+                    let first = field(at, adapter::FIRST_GATEWAY_ADDRESS);
+                    let record = b.read(first);
+                    b.put_u32(field(record, address::ADDRESS + POINTER_SIZE), u32::MAX);
+                },
+                "gateway",
+                "invalid length",
+            ),
+            (
+                |b, at| {
+                    let record = b.read(field(at, adapter::FIRST_GATEWAY_ADDRESS));
+                    let sockaddr = b.read(field(record, address::ADDRESS));
+                    b.put(sockaddr, &17u16.to_le_bytes());
+                },
+                "gateway",
+                "unknown address family",
+            ),
+            (
+                |b, at| {
+                    let record = b.read(field(at, adapter::FIRST_DNS_SERVER_ADDRESS));
+                    b.put_pointer(field(record, address::ADDRESS), 0x10);
+                },
+                "DNS server",
+                "out of bounds",
+            ),
+            (
+                |b, at| {
+                    let first = b.read(field(at, adapter::FIRST_UNICAST_ADDRESS));
+                    let second = b.read(field(first, address::NEXT));
+                    b.put_pointer(field(second, address::NEXT), first);
+                },
+                "unicast address",
+                "cycle",
+            ),
+            (
+                |b, at| {
+                    let record = b.read(field(at, adapter::FIRST_PREFIX));
+                    b.put_u32(record, 16);
+                },
+                "prefix",
+                "invalid length",
+            ),
+            (
+                |b, at| {
+                    let suffix = b.read(field(at, adapter::FIRST_DNS_SUFFIX));
+                    b.put_pointer(field(suffix, dns_suffix::NEXT), BASE + b.bytes.len() as u64);
+                },
+                "DNS suffix",
+                "out of bounds",
+            ),
+            (
+                |b, at| {
+                    // a description running to the end of the buffer
+                    let description = b.alloc(6);
+                    b.put(description, &[b'a', 0, b'b', 0, b'c', 0]);
+                    b.put_pointer(field(at, adapter::DESCRIPTION), description);
+                },
+                "description",
+                "missing terminator",
+            ),
+        ];
+        for (corrupt, what, reason) in cases {
+            let mut b = Builder::default();
+            let at = ethernet(&mut b);
+            b.adapter("second");
+            corrupt(&mut b, at);
+
+            let list = decode(&b).unwrap();
+            assert_eq!(list.adapters.len(), 1, "{} {}", what, reason);
+            assert_eq!(list.adapters[0].adapter_name, "second");
+            assert_eq!(list.errors.len(), 1);
+            let err = &list.errors[0];
+            assert_eq!(err.adapter_name, "{5B1E2E5A-9C53-4A23-8F4B-8E42C2C1B2D1}");
+            assert_eq!(err.luid, 0x0006_0000_0100_0000);
+            assert_eq!(malformed(&err.error), (what, reason));
+        }
+    }
+
+    #[test]
+    fn test_decode_invalid_on_link_prefix_length() {
+        let mut b = Builder::default();
+        let at = ethernet(&mut b);
+        let ipv4 = b.read(field(at, adapter::FIRST_UNICAST_ADDRESS));
+        b.put(field(ipv4, unicast::ON_LINK_PREFIX_LENGTH), &[33]);
+        let ipv6 = b.read(field(ipv4, address::NEXT));
+        b.put(field(ipv6, unicast::ON_LINK_PREFIX_LENGTH), &[129]);
+        b.adapter("second");
+
+        // the addresses keep their host prefix, the adapter is not an error
+        let list = decode(&b).unwrap();
+        assert!(list.errors.is_empty());
+        assert_eq!(list.adapters.len(), 2);
+        let a = &list.adapters[0];
+        assert_eq!(
+            a.unicast_addresses[0].address,
+            "192.168.1.20/32".parse().unwrap()
+        );
+        assert_eq!(a.unicast_addresses[1].address.prefix(), 128);
+        assert_eq!(a.ip_addresses.len(), 2);
+    }
+
+    #[test]
+    fn test_decode_invalid_utf16() {
+        // "corp", an unpaired surrogate and ".com"
+        let units: Vec<u16> = "corp\u{fffd}.com"
+            .encode_utf16()
+            .map(|unit| if unit == 0xfffd { 0xd800 } else { unit })
+            .collect();
+        let bytes: Vec<u8> = units
+            .iter()
+            .chain([&0])
+            .flat_map(|unit| unit.to_le_bytes())
+            .collect();
+
+        let mut b = Builder::default();
+        let at = ethernet(&mut b);
+        b.put(b.read(field(at, adapter::DNS_SUFFIX)), &bytes);
+        let suffix = b.read(field(at, adapter::FIRST_DNS_SUFFIX));
+        b.put(field(suffix, dns_suffix::STRING), &bytes);
+        b.adapter("second");
+
+        // the strings are kept as they are, the adapter is not an error
+        let list = decode(&b).unwrap();
+        assert!(list.errors.is_empty());
+        assert_eq!(list.adapters.len(), 2);
+        let a = &list.adapters[0];
+        assert_eq!(crate::utils::os_string_to_wide(&a.dns_suffix), units);
+        assert_eq!(crate::utils::os_string_to_wide(&a.dns_suffixes[0]), units);
+        assert!(a.dns_suffix_lossy().starts_with("corp\u{fffd}"));
+        assert!(a.dns_suffix_lossy().ends_with("\u{fffd}.com"));
+        assert_eq!(a.dns_suffixes[1], "example.com");
+    }
+
+    #[test]
+    fn test_decode_lookup() {
+        let mut b = Builder::default();
+        let at = ethernet(&mut b);
+        // the malformed adapter is not decoded when the lookup skips it
+        let gateway = b.read(field(at, adapter::FIRST_GATEWAY_ADDRESS));
+        b.put_u32(field(gateway, address::ADDRESS + POINTER_SIZE), u32::MAX);
+        let second = b.adapter("second");
+        b.put_u32(field(second, adapter::IF_INDEX), 9);
+        b.put_u64(field(second, adapter::LUID), 0x0006_0000_0200_0000);
+
+        let cases = [
+            (AdapterLookup::Index(9), 1, 0),
+            (AdapterLookup::Luid(0x0006_0000_0200_0000), 1, 0),
+            (AdapterLookup::Index(7), 0, 1),
+            (AdapterLookup::Index(0), 0, 0),
+            (AdapterLookup::Name("second".into()), 1, 1),
+        ];
+        for (lookup, adapters, errors) in cases {
+            let list = decode_adapters(&b.bytes, BASE, Some(&lookup)).unwrap();
+            assert_eq!(list.adapters.len(), adapters, "{:?}", lookup);
+            assert_eq!(list.errors.len(), errors, "{:?}", lookup);
+        }
+    }
+
+    #[test]
+    fn test_decode_malformed_list() {
+        type Corrupt = fn(&mut Builder, u64, u64);
+        let cases: [(Corrupt, &str); 4] = [
+            (
+                |b, _, second| b.put_pointer(field(second, adapter::NEXT), BASE),
+                "cycle",
+            ),
+            (|b, _, second| b.put_u32(second, 64), "invalid length"),
+            (|b, _, second| b.put_u32(second, 0x10_0000), "out of bounds"),
+            (
+                |b, first, _| b.put_pointer(field(first, adapter::NEXT), 8),
+                "out of bounds",
+            ),
+        ];
+        for (corrupt, reason) in cases {
+            let mut b = Builder::default();
+            let first = ethernet(&mut b);
+            let second = b.adapter("second");
+            corrupt(&mut b, first, second);
+            let err = decode(&b).unwrap_err();
+            assert_eq!(malformed(&err), ("adapter", reason));
+        }
+
+        let truncated = AdapterBuffer::new(vec![0xc0, 1, 0, 0], BASE);
+        assert!(truncated.decode().is_err());
+    }
+
+    #[test]
+    fn test_decode_corrupted_bytes() {
+        let mut b = Builder::default();
+        ethernet(&mut b);
+        b.adapter("second");
+        // whatever the bytes, decoding must not panic
+        for offset in 0..b.bytes.len() {
+            for value in [0x00, 0x01, 0x7f, 0x80, 0xff] {
+                let mut bytes = b.bytes.clone();
+                bytes[offset] = value;
+                let _ = AdapterBuffer::new(bytes, BASE).decode();
+            }
+        }
+        for len in 0..b.bytes.len() {
+            let _ = AdapterBuffer::new(b.bytes[..len].to_vec(), BASE).decode();
+        }
+    }
+}
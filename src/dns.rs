@@ -1,82 +1,115 @@
-use crate::{error::*, utils::luid_to_guid};
+use crate::backend::{Backend, DnsSettings, OsBackend};
+use crate::utils::{bytes_to_guid, check, os_string_from_wide_ptr};
+use crate::{error::*, interface::InterfaceId};
 use widestring::WideCString;
-use windows_sys::Win32::{Foundation::GetLastError, NetworkManagement::IpHelper};
+use windows_sys::Win32::NetworkManagement::IpHelper;
 
-// U can get the dns servers in `Adapter`'s field, here is the detailed version
-pub fn get_dns_setting(luid: u64) -> Result<IpHelper::DNS_INTERFACE_SETTINGS> {
+/// The IPv4 name servers and search list of an interface, the detailed
+/// version of the `dns_servers` of [`Adapter`](crate::Adapter).
+pub fn get_dns_setting(interface: impl Into<InterfaceId>) -> Result<DnsSettings> {
+    get_dns_settings(interface, false)
+}
+
+/// The name servers and search list of an interface for IPv4 or IPv6.
+pub fn get_dns_settings(interface: impl Into<InterfaceId>, is_ipv6: bool) -> Result<DnsSettings> {
+    OsBackend.dns_settings(&interface.into(), is_ipv6)
+}
+
+/// Replace the name servers and search list of an interface for IPv4 or
+/// IPv6.
+pub fn set_dns_settings(
+    interface: impl Into<InterfaceId>,
+    is_ipv6: bool,
+    settings: &DnsSettings,
+) -> Result<()> {
+    OsBackend.set_dns_settings(&interface.into(), is_ipv6, settings)
+}
+
+pub fn set_dns_setting_v4(
+    interface: impl Into<InterfaceId>,
+    is_ipv6: bool,
+    servers: &[&str],
+    search_list: &[&str],
+) -> Result<()> {
+    let settings = DnsSettings::from_lists(&servers.join(","), &search_list.join(","))?;
+    set_dns_settings(interface, is_ipv6, &settings)
+}
+
+pub(crate) fn read_dns_settings(interface: &InterfaceId, is_ipv6: bool) -> Result<DnsSettings> {
+    let guid = bytes_to_guid(interface.guid()?);
     let mut dns_setting: IpHelper::DNS_INTERFACE_SETTINGS;
     unsafe {
         dns_setting = core::mem::zeroed();
     }
     dns_setting.Version = IpHelper::DNS_INTERFACE_SETTINGS_VERSION3;
-    let guid = luid_to_guid(luid);
-    let code = unsafe { IpHelper::GetInterfaceDnsSettings(guid, &mut dns_setting as *mut _) };
-    if code != 0 {
-        return Err(Error {
-            kind: ErrorKind::Os(unsafe { GetLastError() }),
-        });
+    if is_ipv6 {
+        dns_setting.Flags = IpHelper::DNS_SETTING_IPV6 as u64;
     }
-    Ok(dns_setting)
+    let code = unsafe { IpHelper::GetInterfaceDnsSettings(guid, &mut dns_setting as *mut _) };
+    check(code, "GetInterfaceDnsSettings").map_err(|err| err.with_interface(interface))?;
+    let (name_servers, search_list) = unsafe {
+        let lists = (
+            os_string_from_wide_ptr(dns_setting.NameServer),
+            os_string_from_wide_ptr(dns_setting.SearchList),
+        );
+        IpHelper::FreeInterfaceDnsSettings(&mut dns_setting as *mut _);
+        lists
+    };
+    DnsSettings::from_lists(
+        &name_servers.to_string_lossy(),
+        &search_list.to_string_lossy(),
+    )
 }
 
-pub fn set_dns_setting_v4(
-    luid: u64,
+pub(crate) fn write_dns_settings(
+    interface: &InterfaceId,
     is_ipv6: bool,
-    servers: &[&str],
-    search_list: &[&str],
+    settings: &DnsSettings,
 ) -> Result<()> {
+    let guid = bytes_to_guid(interface.guid()?);
     let mut dns_setting: IpHelper::DNS_INTERFACE_SETTINGS;
     unsafe {
         dns_setting = core::mem::zeroed();
     }
     dns_setting.Version = IpHelper::DNS_INTERFACE_SETTINGS_VERSION3;
-    let servers = WideCString::from_str(servers.join(",")).unwrap();
-    let search_list = WideCString::from_str(search_list.join(",")).unwrap();
+    let (servers, search_list) = settings.to_lists();
+    // the name servers are addresses, only the search list can hold a NUL
+    let servers = WideCString::from_str(servers).unwrap();
+    let search_list = WideCString::from_str(&search_list)
+        .map_err(|_| Error::parse("search list", &search_list).with_interface(interface))?;
     dns_setting.NameServer = servers.as_ptr();
     dns_setting.SearchList = search_list.as_ptr();
     dns_setting.Flags = (IpHelper::DNS_SETTING_NAMESERVER | IpHelper::DNS_SETTING_SEARCHLIST) as _;
     if is_ipv6 {
         dns_setting.Flags |= IpHelper::DNS_SETTING_IPV6 as u64;
     }
-    unsafe { set_dns_setting(luid, &dns_setting) }
+    unsafe { set_dns_setting(guid, &dns_setting) }.map_err(|err| err.with_interface(interface))
 }
 
 /// #safety: the dns_setting must be valid
-unsafe fn set_dns_setting(luid: u64, dns_setting: &IpHelper::DNS_INTERFACE_SETTINGS) -> Result<()> {
-    let guid = luid_to_guid(luid);
-    let code = IpHelper::SetInterfaceDnsSettings(guid, dns_setting);
-    if code != 0 {
-        return Err(Error {
-            kind: ErrorKind::Os(GetLastError()),
-        });
-    }
-    Ok(())
+unsafe fn set_dns_setting(
+    guid: windows_sys::core::GUID,
+    dns_setting: &IpHelper::DNS_INTERFACE_SETTINGS,
+) -> Result<()> {
+    check(
+        IpHelper::SetInterfaceDnsSettings(guid, dns_setting),
+        "SetInterfaceDnsSettings",
+    )
 }
 
 #[test]
 fn test_get_dns() {
-    use widestring::WideCString;
     let adapters = crate::get_adapters().unwrap();
-
-    unsafe {
-        for adapter in adapters {
-            let luid = adapter.luid;
-            let setting = get_dns_setting(luid).unwrap();
-            let nameserver = if setting.NameServer.is_null() {
-                None
-            } else {
-                Some(WideCString::from_ptr_str(setting.NameServer).to_string_lossy())
-            };
-            let search_list = if setting.SearchList.is_null() {
-                None
-            } else {
-                Some(WideCString::from_ptr_str(setting.SearchList).to_string_lossy())
-            };
-            println!(
-                "name:{}, LUID: {}, nameserver: {:?}, search_list: {:?}",
-                adapter.friendly_name, luid, nameserver, search_list
-            );
-        }
+    for adapter in adapters {
+        let luid = crate::Luid::from(adapter.luid);
+        let setting = get_dns_setting(luid).unwrap();
+        println!(
+            "name:{}, LUID: {}, nameserver: {:?}, search_list: {:?}",
+            adapter.friendly_name_lossy(),
+            luid,
+            setting.name_servers,
+            setting.search_list
+        );
     }
 }
 
@@ -87,18 +120,26 @@ fn test_set_dns() {
         .into_iter()
         .find(|a| a.friendly_name == "utun64")
         .unwrap();
-    let luid = adapter.luid;
+    let luid = crate::Luid::from(adapter.luid);
     let servers = &["1.0.0.1"];
     let search_list = &[];
     set_dns_setting_v4(luid, false, servers, search_list).unwrap();
     let new_settings = get_dns_setting(luid).unwrap();
-    assert!(new_settings.NameServer != std::ptr::null());
     assert_eq!(
-        unsafe {
-            WideCString::from_ptr_str(new_settings.NameServer)
-                .to_string()
-                .unwrap()
-        },
-        servers.join(",")
+        new_settings.name_servers,
+        ["1.0.0.1".parse::<std::net::IpAddr>().unwrap()]
     );
+
+    let settings = DnsSettings {
+        name_servers: vec![],
+        search_list: vec!["corp\0.example.com".to_owned()],
+    };
+    let err = set_dns_settings(luid, false, &settings).unwrap_err();
+    assert!(matches!(
+        err.kind(),
+        ErrorKind::Parse {
+            what: "search list",
+            ..
+        }
+    ));
 }
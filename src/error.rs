@@ -1,25 +1,221 @@
+pub mod codes;
+
 /// The Error type.
+///
+/// Besides its [`ErrorKind`], an error carries the OS code it was created
+/// from, the operation that failed and the interface it was applied to, when
+/// they are known.
 #[derive(Debug)]
 pub struct Error {
-    pub(crate) kind: ErrorKind,
+    kind: ErrorKind,
+    code: Option<u32>,
+    operation: Option<&'static str>,
+    interface: Option<String>,
 }
 
-/// The kind of an error. For the time being, it will remain private.
+/// The kind of an error.
+///
+/// The OS codes are classified in the semantic kinds, see
+/// [`codes::classify`], the others are [`ErrorKind::Fwp`] or
+/// [`ErrorKind::Os`]. The code is kept in [`Error::code`].
 #[derive(Debug)]
-pub(crate) enum ErrorKind {
+#[non_exhaustive]
+pub enum ErrorKind {
     Utf8(std::str::Utf8Error),
     FromUtf16(widestring::error::Utf16Error),
     Io(::std::io::Error),
-    Os(u32),
+    AccessDenied,
+    NotFound,
+    AlreadyExists,
+    /// The buffer was too small for the result.
+    BufferOverflow,
+    InvalidParameter,
+    /// Any other `FWP_E_*` code.
+    Fwp,
+    /// Any other OS code.
+    Os,
+    Parse {
+        what: &'static str,
+        input: String,
+    },
+    InvalidPrefixLength(u8),
+    InterfaceNotFound(String),
+    OutOfRange {
+        what: &'static str,
+        value: u64,
+    },
+    /// A record of an `IP_ADAPTER_ADDRESSES` buffer that can't be decoded.
+    Malformed {
+        what: &'static str,
+        address: u64,
+        reason: &'static str,
+    },
+    /// An `IP_ADAPTER_ADDRESSES` buffer laid out for pointers of `buffer`
+    /// bits, which this build, of `target` bits, can't decode.
+    PointerWidth {
+        buffer: u32,
+        target: u32,
+    },
+    /// A WFP filter that the engine would reject, `condition` is the index
+    /// of the offending condition.
+    InvalidFilter {
+        condition: Option<usize>,
+        reason: &'static str,
+    },
+}
+
+impl Error {
+    /// An error from a Win32 error, a `HRESULT` or an `FWP_E_*` code.
+    pub(crate) fn os(code: u32) -> Self {
+        Error {
+            code: Some(code),
+            ..codes::classify(code).into()
+        }
+    }
+
+    pub(crate) fn parse(what: &'static str, input: &str) -> Self {
+        ErrorKind::Parse {
+            what,
+            input: input.to_owned(),
+        }
+        .into()
+    }
+
+    pub(crate) fn out_of_range(what: &'static str, value: u64) -> Self {
+        ErrorKind::OutOfRange { what, value }.into()
+    }
+
+    pub(crate) fn malformed(what: &'static str, address: u64, reason: &'static str) -> Self {
+        ErrorKind::Malformed {
+            what,
+            address,
+            reason,
+        }
+        .into()
+    }
+
+    pub(crate) fn invalid_filter(condition: Option<usize>, reason: &'static str) -> Self {
+        ErrorKind::InvalidFilter { condition, reason }.into()
+    }
+
+    pub(crate) fn interface_not_found(id: &crate::interface::InterfaceId) -> Self {
+        ErrorKind::InterfaceNotFound(id.to_string()).into()
+    }
+
+    /// Name the failed operation, usually the Windows function.
+    pub(crate) fn with_operation(mut self, operation: &'static str) -> Self {
+        self.operation = Some(operation);
+        self
+    }
+
+    #[cfg_attr(not(windows), allow(dead_code))]
+    pub(crate) fn with_interface(mut self, interface: &crate::interface::InterfaceId) -> Self {
+        self.interface = Some(interface.to_string());
+        self
+    }
+
+    pub fn kind(&self) -> &ErrorKind {
+        &self.kind
+    }
+
+    /// The Win32 error, `HRESULT` or `FWP_E_*` code of an OS error.
+    pub fn code(&self) -> Option<u32> {
+        self.code
+    }
+
+    /// The symbolic name of the code, e.g. `ERROR_ACCESS_DENIED`.
+    pub fn code_name(&self) -> Option<String> {
+        codes::name(self.code?)
+    }
+
+    /// The operation that failed, e.g. `FwpmSubLayerAdd0`.
+    pub fn operation(&self) -> Option<&str> {
+        self.operation
+    }
+
+    /// The interface the operation was applied to, as displayed by
+    /// `InterfaceId`.
+    pub fn interface(&self) -> Option<&str> {
+        self.interface.as_deref()
+    }
+
+    /// Whether an interface identifier didn't resolve to any interface.
+    pub fn is_interface_not_found(&self) -> bool {
+        matches!(self.kind, ErrorKind::InterfaceNotFound(_))
+    }
+}
+
+impl From<ErrorKind> for Error {
+    fn from(kind: ErrorKind) -> Self {
+        Error {
+            kind,
+            code: None,
+            operation: None,
+            interface: None,
+        }
+    }
 }
 
 impl std::fmt::Display for Error {
     fn fmt(&self, f: &mut std::fmt::Formatter) -> std::fmt::Result {
+        if let Some(operation) = self.operation {
+            write!(f, "{} failed", operation)?;
+            if let Some(interface) = &self.interface {
+                write!(f, " on {}", interface)?;
+            }
+            write!(f, ": ")?;
+        } else if let Some(interface) = &self.interface {
+            write!(f, "{}: ", interface)?;
+        }
+
+        if let Some(code) = self.code {
+            // HRESULTs are usually written in hexadecimal
+            let code_str = if code & 0x8000_0000 != 0 {
+                format!("{:#010x}", code)
+            } else {
+                code.to_string()
+            };
+            return match (codes::name(code), codes::message(code)) {
+                (Some(name), Some(message)) => write!(f, "{} ({}): {}", name, code_str, message),
+                _ => write!(f, "OS error: {}", code_str),
+            };
+        }
+
         match &self.kind {
             ErrorKind::Utf8(err) => write!(f, "Utf8 error: {}", err),
             ErrorKind::FromUtf16(err) => write!(f, "FromUtf16 error: {}", err),
             ErrorKind::Io(err) => write!(f, "IO error: {}", err),
-            ErrorKind::Os(err) => write!(f, "OS error: {}", err),
+            ErrorKind::AccessDenied => write!(f, "access denied"),
+            ErrorKind::NotFound => write!(f, "not found"),
+            ErrorKind::AlreadyExists => write!(f, "already exists"),
+            ErrorKind::BufferOverflow => write!(f, "buffer overflow"),
+            ErrorKind::InvalidParameter => write!(f, "invalid parameter"),
+            ErrorKind::Fwp => write!(f, "WFP error"),
+            ErrorKind::Os => write!(f, "OS error"),
+            ErrorKind::Parse { what, input } => write!(f, "invalid {}: {:?}", what, input),
+            ErrorKind::InvalidPrefixLength(prefix) => {
+                write!(f, "invalid prefix length: {}", prefix)
+            }
+            ErrorKind::InterfaceNotFound(id) => write!(f, "interface not found: {}", id),
+            ErrorKind::OutOfRange { what, value } => write!(f, "{} out of range: {}", what, value),
+            ErrorKind::Malformed {
+                what,
+                address,
+                reason,
+            } => write!(f, "malformed {} at {:#x}: {}", what, address, reason),
+            ErrorKind::PointerWidth { buffer, target } => write!(
+                f,
+                "adapter buffer of a {} bits build can't be decoded by a {} bits build",
+                buffer, target
+            ),
+            ErrorKind::InvalidFilter {
+                condition: Some(index),
+                reason,
+            } => write!(f, "invalid filter condition {}: {}", index, reason),
+            ErrorKind::InvalidFilter {
+                condition: None,
+                reason,
+            } => write!(f, "invalid filter: {}", reason),
         }
     }
 }
@@ -30,7 +226,7 @@ impl std::error::Error for Error {
             ErrorKind::Utf8(err) => Some(err),
             ErrorKind::FromUtf16(err) => Some(err),
             ErrorKind::Io(err) => Some(err),
-            ErrorKind::Os(_) => None,
+            _ => None,
         }
     }
 }
@@ -39,24 +235,123 @@ pub type Result<T> = std::result::Result<T, Error>;
 
 impl From<std::str::Utf8Error> for Error {
     fn from(err: std::str::Utf8Error) -> Self {
-        Error {
-            kind: ErrorKind::Utf8(err),
-        }
+        ErrorKind::Utf8(err).into()
     }
 }
 
 impl From<widestring::error::Utf16Error> for Error {
     fn from(err: widestring::error::Utf16Error) -> Self {
-        Error {
-            kind: ErrorKind::FromUtf16(err),
-        }
+        ErrorKind::FromUtf16(err).into()
     }
 }
 
 impl From<std::io::Error> for Error {
     fn from(err: std::io::Error) -> Self {
-        Error {
-            kind: ErrorKind::Io(err),
-        }
+        ErrorKind::Io(err).into()
+    }
+}
+
+/// The error is kept as the source, its kind is mapped to the closest
+/// `io::ErrorKind`.
+impl From<Error> for std::io::Error {
+    fn from(err: Error) -> Self {
+        use std::io::ErrorKind as Io;
+
+        let kind = match &err.kind {
+            ErrorKind::Io(err) => err.kind(),
+            ErrorKind::AccessDenied => Io::PermissionDenied,
+            ErrorKind::NotFound | ErrorKind::InterfaceNotFound(_) => Io::NotFound,
+            ErrorKind::AlreadyExists => Io::AlreadyExists,
+            ErrorKind::InvalidParameter
+            | ErrorKind::Parse { .. }
+            | ErrorKind::InvalidPrefixLength(_)
+            | ErrorKind::OutOfRange { .. }
+            | ErrorKind::InvalidFilter { .. } => Io::InvalidInput,
+            ErrorKind::Utf8(_)
+            | ErrorKind::FromUtf16(_)
+            | ErrorKind::Malformed { .. }
+            | ErrorKind::PointerWidth { .. } => Io::InvalidData,
+            ErrorKind::BufferOverflow | ErrorKind::Fwp | ErrorKind::Os => Io::Other,
+        };
+        std::io::Error::new(kind, err)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::interface::InterfaceId;
+
+    #[test]
+    fn test_io_error() {
+        use std::io::ErrorKind as Io;
+
+        let io = std::io::Error::from(Error::os(5).with_operation("GetAdaptersAddresses"));
+        assert_eq!(io.kind(), Io::PermissionDenied);
+        assert_eq!(
+            io.to_string(),
+            "GetAdaptersAddresses failed: ERROR_ACCESS_DENIED (5): Access is denied."
+        );
+        let err = Error::interface_not_found(&InterfaceId::Name("eth9".into()));
+        assert_eq!(std::io::Error::from(err).kind(), Io::NotFound);
+        let err = Error::parse("GUID", "not a guid");
+        assert_eq!(std::io::Error::from(err).kind(), Io::InvalidInput);
+        let err = Error::from(std::io::Error::from(Io::TimedOut));
+        assert_eq!(std::io::Error::from(err).kind(), Io::TimedOut);
+        assert_eq!(std::io::Error::from(Error::os(12345)).kind(), Io::Other);
+    }
+
+    #[test]
+    fn test_os_error() {
+        let err = Error::os(0x8032_0009).with_operation("FwpmSubLayerAdd0");
+        assert!(matches!(err.kind(), ErrorKind::AlreadyExists));
+        assert_eq!(err.code(), Some(0x8032_0009));
+        assert_eq!(err.code_name().as_deref(), Some("FWP_E_ALREADY_EXISTS"));
+        assert_eq!(err.operation(), Some("FwpmSubLayerAdd0"));
+        assert_eq!(
+            err.to_string(),
+            "FwpmSubLayerAdd0 failed: FWP_E_ALREADY_EXISTS (0x80320009): \
+             An object with that GUID or LUID already exists."
+        );
+
+        let err = Error::os(5)
+            .with_operation("SetInterfaceDnsSettings")
+            .with_interface(&InterfaceId::Index(7));
+        assert!(matches!(err.kind(), ErrorKind::AccessDenied));
+        assert_eq!(err.interface(), Some("index 7"));
+        assert_eq!(
+            err.to_string(),
+            "SetInterfaceDnsSettings failed on index 7: ERROR_ACCESS_DENIED (5): Access is denied."
+        );
+
+        let err = Error::os(12345);
+        assert!(matches!(err.kind(), ErrorKind::Os));
+        assert_eq!(err.to_string(), "OS error: 12345");
+        assert_eq!(Error::os(0x8032_ff00).to_string(), "OS error: 0x8032ff00");
+    }
+
+    #[test]
+    fn test_other_errors() {
+        let err = Error::parse("LUID", "x");
+        assert!(matches!(err.kind(), ErrorKind::Parse { what: "LUID", .. }));
+        assert_eq!(err.code(), None);
+        assert_eq!(err.to_string(), "invalid LUID: \"x\"");
+
+        let err = Error::from(ErrorKind::InvalidPrefixLength(33));
+        assert_eq!(err.to_string(), "invalid prefix length: 33");
+        assert_eq!(
+            Error::malformed("unicast address", 0x1040, "cycle").to_string(),
+            "malformed unicast address at 0x1040: cycle"
+        );
+        assert_eq!(
+            Error::invalid_filter(Some(1), "empty range").to_string(),
+            "invalid filter condition 1: empty range"
+        );
+        assert_eq!(
+            Error::from(ErrorKind::NotFound)
+                .with_interface(&InterfaceId::Name("eth0".into()))
+                .to_string(),
+            "name \"eth0\": not found"
+        );
     }
 }
@@ -0,0 +1,466 @@
+//! Symbolic names and messages of the Win32, HRESULT and `FWP_E_*` codes
+//! returned by the APIs of this crate, and their classification.
+
+use super::ErrorKind;
+
+/// `FACILITY_WIN32` HRESULTs, `HRESULT_FROM_WIN32(code)`.
+const WIN32_HRESULT: u32 = 0x8007_0000;
+/// `FACILITY_FWP` HRESULTs, the `FWP_E_*` codes.
+const FWP_HRESULT: u32 = 0x8032_0000;
+const FACILITY_MASK: u32 = 0xffff_0000;
+
+/// (code, name, message)
+const CODES: &[(u32, &str, &str)] = &[
+    (0, "ERROR_SUCCESS", "The operation completed successfully."),
+    (1, "ERROR_INVALID_FUNCTION", "Incorrect function."),
+    (
+        2,
+        "ERROR_FILE_NOT_FOUND",
+        "The system cannot find the file specified.",
+    ),
+    (
+        3,
+        "ERROR_PATH_NOT_FOUND",
+        "The system cannot find the path specified.",
+    ),
+    (5, "ERROR_ACCESS_DENIED", "Access is denied."),
+    (6, "ERROR_INVALID_HANDLE", "The handle is invalid."),
+    (
+        8,
+        "ERROR_NOT_ENOUGH_MEMORY",
+        "Not enough memory resources are available to process this command.",
+    ),
+    (13, "ERROR_INVALID_DATA", "The data is invalid."),
+    (
+        14,
+        "ERROR_OUTOFMEMORY",
+        "Not enough memory resources are available to complete this operation.",
+    ),
+    (21, "ERROR_NOT_READY", "The device is not ready."),
+    (
+        31,
+        "ERROR_GEN_FAILURE",
+        "A device attached to the system is not functioning.",
+    ),
+    (50, "ERROR_NOT_SUPPORTED", "The request is not supported."),
+    (80, "ERROR_FILE_EXISTS", "The file exists."),
+    (87, "ERROR_INVALID_PARAMETER", "The parameter is incorrect."),
+    (111, "ERROR_BUFFER_OVERFLOW", "The file name is too long."),
+    (
+        122,
+        "ERROR_INSUFFICIENT_BUFFER",
+        "The data area passed to a system call is too small.",
+    ),
+    (
+        123,
+        "ERROR_INVALID_NAME",
+        "The filename, directory name, or volume label syntax is incorrect.",
+    ),
+    (
+        183,
+        "ERROR_ALREADY_EXISTS",
+        "Cannot create a file when that file already exists.",
+    ),
+    (232, "ERROR_NO_DATA", "The pipe is being closed."),
+    (234, "ERROR_MORE_DATA", "More data is available."),
+    (259, "ERROR_NO_MORE_ITEMS", "No more data is available."),
+    (
+        1062,
+        "ERROR_SERVICE_NOT_ACTIVE",
+        "The service has not been started.",
+    ),
+    (
+        1167,
+        "ERROR_DEVICE_NOT_CONNECTED",
+        "The device is not connected.",
+    ),
+    (1168, "ERROR_NOT_FOUND", "Element not found."),
+    (
+        1314,
+        "ERROR_PRIVILEGE_NOT_HELD",
+        "A required privilege is not held by the client.",
+    ),
+    (
+        1722,
+        "RPC_S_SERVER_UNAVAILABLE",
+        "The RPC server is unavailable.",
+    ),
+    (
+        4312,
+        "ERROR_OBJECT_NOT_FOUND",
+        "The object identifier does not represent a valid object.",
+    ),
+    (
+        5010,
+        "ERROR_OBJECT_ALREADY_EXISTS",
+        "The object already exists.",
+    ),
+    (0x8000_4001, "E_NOTIMPL", "Not implemented."),
+    (0x8000_4003, "E_POINTER", "Invalid pointer."),
+    (0x8000_4005, "E_FAIL", "Unspecified error."),
+    (0x8000_ffff, "E_UNEXPECTED", "Catastrophic failure."),
+    (0x8007_0005, "E_ACCESSDENIED", "Access is denied."),
+    (
+        0x8007_000e,
+        "E_OUTOFMEMORY",
+        "Not enough memory resources are available to complete this operation.",
+    ),
+    (0x8007_0057, "E_INVALIDARG", "The parameter is incorrect."),
+    (
+        0x8032_0001,
+        "FWP_E_CALLOUT_NOT_FOUND",
+        "The callout does not exist.",
+    ),
+    (
+        0x8032_0002,
+        "FWP_E_CONDITION_NOT_FOUND",
+        "The filter condition does not exist.",
+    ),
+    (
+        0x8032_0003,
+        "FWP_E_FILTER_NOT_FOUND",
+        "The filter does not exist.",
+    ),
+    (
+        0x8032_0004,
+        "FWP_E_LAYER_NOT_FOUND",
+        "The layer does not exist.",
+    ),
+    (
+        0x8032_0005,
+        "FWP_E_PROVIDER_NOT_FOUND",
+        "The provider does not exist.",
+    ),
+    (
+        0x8032_0006,
+        "FWP_E_PROVIDER_CONTEXT_NOT_FOUND",
+        "The provider context does not exist.",
+    ),
+    (
+        0x8032_0007,
+        "FWP_E_SUBLAYER_NOT_FOUND",
+        "The sublayer does not exist.",
+    ),
+    (0x8032_0008, "FWP_E_NOT_FOUND", "The object does not exist."),
+    (
+        0x8032_0009,
+        "FWP_E_ALREADY_EXISTS",
+        "An object with that GUID or LUID already exists.",
+    ),
+    (
+        0x8032_000a,
+        "FWP_E_IN_USE",
+        "The object is referenced by other objects so cannot be deleted.",
+    ),
+    (
+        0x8032_000b,
+        "FWP_E_DYNAMIC_SESSION_IN_PROGRESS",
+        "The call is not allowed from within a dynamic session.",
+    ),
+    (
+        0x8032_000c,
+        "FWP_E_WRONG_SESSION",
+        "The call was made from the wrong session so cannot be completed.",
+    ),
+    (
+        0x8032_000d,
+        "FWP_E_NO_TXN_IN_PROGRESS",
+        "The call must be made from within an explicit transaction.",
+    ),
+    (
+        0x8032_000e,
+        "FWP_E_TXN_IN_PROGRESS",
+        "The call is not allowed from within an explicit transaction.",
+    ),
+    (
+        0x8032_000f,
+        "FWP_E_TXN_ABORTED",
+        "The explicit transaction has been forcibly cancelled.",
+    ),
+    (
+        0x8032_0010,
+        "FWP_E_SESSION_ABORTED",
+        "The session has been cancelled.",
+    ),
+    (
+        0x8032_0011,
+        "FWP_E_INCOMPATIBLE_TXN",
+        "The call is not allowed from within a read-only transaction.",
+    ),
+    (
+        0x8032_0012,
+        "FWP_E_TIMEOUT",
+        "The call timed out while waiting to acquire the transaction lock.",
+    ),
+    (
+        0x8032_0013,
+        "FWP_E_NET_EVENTS_DISABLED",
+        "Collection of network diagnostic events is disabled.",
+    ),
+    (
+        0x8032_0014,
+        "FWP_E_INCOMPATIBLE_LAYER",
+        "The operation is not supported by the specified layer.",
+    ),
+    (
+        0x8032_0015,
+        "FWP_E_KM_CLIENTS_ONLY",
+        "The call is allowed for kernel-mode callers only.",
+    ),
+    (
+        0x8032_0016,
+        "FWP_E_LIFETIME_MISMATCH",
+        "The call tried to associate two objects with incompatible lifetimes.",
+    ),
+    (
+        0x8032_0017,
+        "FWP_E_BUILTIN_OBJECT",
+        "The object is built in so cannot be deleted.",
+    ),
+    (
+        0x8032_0018,
+        "FWP_E_TOO_MANY_CALLOUTS",
+        "The maximum number of callouts has been reached.",
+    ),
+    (
+        0x8032_0019,
+        "FWP_E_NOTIFICATION_DROPPED",
+        "A notification could not be delivered because a message queue is at its maximum capacity.",
+    ),
+    (
+        0x8032_001a,
+        "FWP_E_TRAFFIC_MISMATCH",
+        "The traffic parameters do not match those for the security association context.",
+    ),
+    (
+        0x8032_001b,
+        "FWP_E_INCOMPATIBLE_SA_STATE",
+        "The call is not allowed for the current security association state.",
+    ),
+    (
+        0x8032_001c,
+        "FWP_E_NULL_POINTER",
+        "A required pointer is null.",
+    ),
+    (
+        0x8032_001d,
+        "FWP_E_INVALID_ENUMERATOR",
+        "An enumerator is not valid.",
+    ),
+    (
+        0x8032_001e,
+        "FWP_E_INVALID_FLAGS",
+        "The flags field contains an invalid value.",
+    ),
+    (
+        0x8032_001f,
+        "FWP_E_INVALID_NET_MASK",
+        "A network mask is not valid.",
+    ),
+    (
+        0x8032_0020,
+        "FWP_E_INVALID_RANGE",
+        "An FWP_RANGE is not valid.",
+    ),
+    (
+        0x8032_0021,
+        "FWP_E_INVALID_INTERVAL",
+        "The time interval is not valid.",
+    ),
+    (
+        0x8032_0022,
+        "FWP_E_ZERO_LENGTH_ARRAY",
+        "An array that must contain at least one element is zero length.",
+    ),
+    (
+        0x8032_0023,
+        "FWP_E_NULL_DISPLAY_NAME",
+        "The displayData.name field cannot be null.",
+    ),
+    (
+        0x8032_0024,
+        "FWP_E_INVALID_ACTION_TYPE",
+        "The action type is not one of the allowed action types for a filter.",
+    ),
+    (
+        0x8032_0025,
+        "FWP_E_INVALID_WEIGHT",
+        "The filter weight is not valid.",
+    ),
+    (
+        0x8032_0026,
+        "FWP_E_MATCH_TYPE_MISMATCH",
+        "A filter condition contains a match type that is not compatible with the operands.",
+    ),
+    (
+        0x8032_0027,
+        "FWP_E_TYPE_MISMATCH",
+        "An FWP_VALUE or FWPM_CONDITION_VALUE is of the wrong type.",
+    ),
+    (
+        0x8032_0028,
+        "FWP_E_OUT_OF_BOUNDS",
+        "An integer value is outside the allowed range.",
+    ),
+    (
+        0x8032_0029,
+        "FWP_E_RESERVED",
+        "A reserved field is nonzero.",
+    ),
+    (
+        0x8032_002a,
+        "FWP_E_DUPLICATE_CONDITION",
+        "A filter cannot contain multiple conditions operating on a single field.",
+    ),
+    (
+        0x8032_002b,
+        "FWP_E_DUPLICATE_KEYMOD",
+        "A policy cannot contain the same keying module more than once.",
+    ),
+    (
+        0x8032_002c,
+        "FWP_E_ACTION_INCOMPATIBLE_WITH_LAYER",
+        "The action type is not compatible with the layer.",
+    ),
+    (
+        0x8032_002d,
+        "FWP_E_ACTION_INCOMPATIBLE_WITH_SUBLAYER",
+        "The action type is not compatible with the sublayer.",
+    ),
+    (
+        0x8032_002e,
+        "FWP_E_CONTEXT_INCOMPATIBLE_WITH_LAYER",
+        "The raw context or the provider context is not compatible with the layer.",
+    ),
+    (
+        0x8032_002f,
+        "FWP_E_CONTEXT_INCOMPATIBLE_WITH_CALLOUT",
+        "The raw context or the provider context is not compatible with the callout.",
+    ),
+    (
+        0x8032_0035,
+        "FWP_E_INVALID_PARAMETER",
+        "The parameter is incorrect.",
+    ),
+    (
+        0x8032_0036,
+        "FWP_E_TOO_MANY_SUBLAYERS",
+        "The maximum number of sublayers has been reached.",
+    ),
+    (
+        0x8032_0037,
+        "FWP_E_CALLOUT_NOTIFICATION_FAILED",
+        "The notification function for a callout returned an error.",
+    ),
+];
+
+fn lookup(code: u32) -> Option<&'static (u32, &'static str, &'static str)> {
+    CODES
+        .binary_search_by_key(&code, |(code, _, _)| *code)
+        .ok()
+        .map(|i| &CODES[i])
+}
+
+/// The Win32 error of a `HRESULT_FROM_WIN32` code.
+fn win32_of(code: u32) -> Option<u32> {
+    if code & FACILITY_MASK == WIN32_HRESULT {
+        Some(code & 0xffff)
+    } else {
+        None
+    }
+}
+
+/// The symbolic name of a code, e.g. `FWP_E_ALREADY_EXISTS`.
+///
+/// An `HRESULT_FROM_WIN32` code without a name of its own is named after its
+/// Win32 error, e.g. `HRESULT_FROM_WIN32(ERROR_NOT_FOUND)`.
+pub fn name(code: u32) -> Option<String> {
+    if let Some((_, name, _)) = lookup(code) {
+        return Some(name.to_string());
+    }
+    let (_, name, _) = lookup(win32_of(code)?)?;
+    Some(format!("HRESULT_FROM_WIN32({})", name))
+}
+
+/// The message of a code, as `FormatMessage` would print it.
+pub fn message(code: u32) -> Option<&'static str> {
+    lookup(code)
+        .or_else(|| lookup(win32_of(code)?))
+        .map(|(_, _, message)| *message)
+}
+
+/// The kind of error of a Win32 error, a `HRESULT` or an `FWP_E_*` code.
+pub fn classify(code: u32) -> ErrorKind {
+    if let Some(code) = win32_of(code) {
+        return classify(code);
+    }
+    match code {
+        5 | 1314 => ErrorKind::AccessDenied,
+        2 | 3 | 1168 | 4312 | 0x8032_0001..=0x8032_0008 => ErrorKind::NotFound,
+        80 | 183 | 5010 | 0x8032_0009 => ErrorKind::AlreadyExists,
+        111 | 122 | 234 => ErrorKind::BufferOverflow,
+        87 | 0x8032_0035 => ErrorKind::InvalidParameter,
+        code if code & FACILITY_MASK == FWP_HRESULT => ErrorKind::Fwp,
+        _ => ErrorKind::Os,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_table_is_sorted() {
+        assert!(CODES.windows(2).all(|w| w[0].0 < w[1].0));
+    }
+
+    #[test]
+    fn test_names() {
+        let cases = [
+            (5, Some("ERROR_ACCESS_DENIED")),
+            (0x8007_0005, Some("E_ACCESSDENIED")),
+            (0x8007_0490, Some("HRESULT_FROM_WIN32(ERROR_NOT_FOUND)")),
+            (0x8032_0009, Some("FWP_E_ALREADY_EXISTS")),
+            (0x8032_ffff, None),
+            (0x8007_ffff, None),
+            (12345, None),
+        ];
+        for (code, name_) in cases {
+            assert_eq!(name(code).as_deref(), name_, "{:#x}", code);
+        }
+        assert_eq!(message(0x8007_0490), Some("Element not found."));
+        assert_eq!(
+            message(0x8032_0017),
+            Some("The object is built in so cannot be deleted.")
+        );
+    }
+
+    #[test]
+    fn test_classify() {
+        let cases = [
+            (5, ErrorKind::AccessDenied),
+            (0x8007_0005, ErrorKind::AccessDenied),
+            (2, ErrorKind::NotFound),
+            (0x8032_0007, ErrorKind::NotFound),
+            (0x8007_0490, ErrorKind::NotFound),
+            (183, ErrorKind::AlreadyExists),
+            (0x8032_0009, ErrorKind::AlreadyExists),
+            (111, ErrorKind::BufferOverflow),
+            (122, ErrorKind::BufferOverflow),
+            (87, ErrorKind::InvalidParameter),
+            (0x8007_0057, ErrorKind::InvalidParameter),
+            (0x8032_0017, ErrorKind::Fwp),
+            (0x8032_ffff, ErrorKind::Fwp),
+            (1722, ErrorKind::Os),
+            (0x8000_4005, ErrorKind::Os),
+        ];
+        for (code, kind) in cases {
+            assert_eq!(
+                std::mem::discriminant(&classify(code)),
+                std::mem::discriminant(&kind),
+                "{:#x}",
+                code
+            );
+        }
+    }
+}
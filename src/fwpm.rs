@@ -1,17 +1,43 @@
 /// doc: https://learn.microsoft.com/en-us/windows/win32/fwp/viewing-current-state
+#[cfg(windows)]
 use crate::error::*;
 use widestring::WideCString;
+#[cfg(windows)]
 use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::{self as fwpm};
 
-pub use filters::get_filters;
+pub mod arbitration;
+pub mod catalogue;
+pub mod dns_leak;
+pub mod filter;
+pub mod keys;
+
+#[cfg(windows)]
+pub use dns_leak::InstalledPolicy;
+pub use dns_leak::{CompiledPolicy, DnsLeakPolicy};
+pub use filter::{
+    ConditionValue, Filter, FilterAction, FilterBuilder, FilterCondition, FilterWeight, MatchType,
+};
+#[cfg(windows)]
+pub use filters::{add_filter, get_filters, get_layer_filters};
+#[cfg(windows)]
+pub(crate) use sub_layer::add_sub_layer_in;
+pub use sub_layer::SubLayer;
+#[cfg(windows)]
 pub use sub_layer::{add_sub_layer, get_sub_layers};
 
-#[derive(Clone, Debug)]
+#[derive(Clone, Debug, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct DisplayData {
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::wide_string"))]
     pub name: WideCString,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::schema::optional_wide_string")
+    )]
     pub desc: Option<WideCString>,
 }
 
+#[cfg(windows)]
 impl From<fwpm::FWPM_DISPLAY_DATA0> for DisplayData {
     fn from(value: fwpm::FWPM_DISPLAY_DATA0) -> Self {
         let name = unsafe { WideCString::from_ptr_str(value.name) };
@@ -32,6 +58,7 @@ impl DisplayData {
         }
     }
 
+    #[cfg(windows)]
     pub fn as_raw(&self) -> fwpm::FWPM_DISPLAY_DATA0 {
         fwpm::FWPM_DISPLAY_DATA0 {
             name: self.name.as_ptr(),
@@ -44,6 +71,7 @@ impl DisplayData {
     }
 }
 
+#[cfg(windows)]
 #[inline(always)]
 fn empty_provider_data() -> fwpm::FWP_BYTE_BLOB {
     fwpm::FWP_BYTE_BLOB {
@@ -52,6 +80,7 @@ fn empty_provider_data() -> fwpm::FWP_BYTE_BLOB {
     }
 }
 
+#[cfg(windows)]
 fn get_engine_handle() -> Result<isize> {
     let mut session: fwpm::FWPM_SESSION0;
     unsafe {
@@ -69,29 +98,86 @@ fn get_engine_handle() -> Result<isize> {
         )
     };
     if code != 0 {
-        return Err(Error {
-            kind: ErrorKind::Os(code),
-        });
+        return Err(Error::os(code).with_operation("FwpmEngineOpen0"));
     }
     Ok(engine_handle)
 }
 
+/// A dynamic session with the filter engine, closed when dropped.
+///
+/// The objects added in the session are removed by the engine when it is
+/// closed.
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct Engine {
+    handle: isize,
+}
+
+#[cfg(windows)]
+impl Engine {
+    pub fn open() -> Result<Self> {
+        Ok(Engine {
+            handle: get_engine_handle()?,
+        })
+    }
+
+    pub fn handle(&self) -> isize {
+        self.handle
+    }
+
+    /// Run `f` in a transaction, committed if it succeeds and aborted
+    /// otherwise.
+    pub fn transaction<T>(&self, f: impl FnOnce() -> Result<T>) -> Result<T> {
+        let code = unsafe { fwpm::FwpmTransactionBegin0(self.handle, 0) };
+        if code != 0 {
+            return Err(Error::os(code).with_operation("FwpmTransactionBegin0"));
+        }
+        let result = f();
+        if result.is_err() {
+            let code = unsafe { fwpm::FwpmTransactionAbort0(self.handle) };
+            if code != 0 {
+                tracing::warn!("FwpmTransactionAbort0 failed: {}", code);
+            }
+            return result;
+        }
+        let code = unsafe { fwpm::FwpmTransactionCommit0(self.handle) };
+        if code != 0 {
+            return Err(Error::os(code).with_operation("FwpmTransactionCommit0"));
+        }
+        result
+    }
+}
+
+#[cfg(windows)]
+impl Drop for Engine {
+    fn drop(&mut self) {
+        let code = unsafe { fwpm::FwpmEngineClose0(self.handle) };
+        if code != 0 {
+            tracing::warn!("FwpmEngineClose0 failed: {}", code);
+        }
+    }
+}
+
+#[cfg(windows)]
 mod filters {
     use super::*;
 
-    /// will get all the filters in the raw format
-    pub unsafe fn get_filters() -> Result<Vec<fwpm::FWPM_FILTER0>> {
-        let engine_handle = get_engine_handle()?;
+    /// Get the filters of the ALE connect layer for IPv4.
+    pub fn get_filters() -> Result<Vec<Filter>> {
+        get_layer_filters(&keys::FWPM_LAYER_ALE_AUTH_CONNECT_V4)
+    }
 
-        let template = [0u8; std::mem::size_of::<fwpm::FWPM_FILTER_ENUM_TEMPLATE0>()];
-        let mut template: fwpm::FWPM_FILTER_ENUM_TEMPLATE0 =
-            unsafe { std::mem::transmute(template) };
+    /// Get the filters of a layer, e.g. one of the [`keys`] constants.
+    pub fn get_layer_filters(layer_key: &[u8; 16]) -> Result<Vec<Filter>> {
+        let engine = Engine::open()?;
+        let engine_handle = engine.handle();
 
-        template.layerKey = fwpm::FWPM_LAYER_ALE_AUTH_CONNECT_V4;
+        let mut template: fwpm::FWPM_FILTER_ENUM_TEMPLATE0 = unsafe { std::mem::zeroed() };
+        template.layerKey = crate::utils::bytes_to_guid(*layer_key);
         template.actionMask = 0xFFFFFFFF;
 
         let mut enum_handle = 0_isize;
-        let code = {
+        let code = unsafe {
             fwpm::FwpmFilterCreateEnumHandle0(
                 engine_handle,
                 &template as *const _,
@@ -100,14 +186,12 @@ mod filters {
         };
 
         if code != 0 {
-            return Err(Error {
-                kind: ErrorKind::Os(code),
-            });
+            return Err(Error::os(code).with_operation("FwpmFilterCreateEnumHandle0"));
         }
 
         const NUM_REQ: usize = usize::MAX;
         let mut num_filters = 0_u32;
-        let mut filters = std::ptr::null_mut() as *mut *mut fwpm::FWPM_FILTER0;
+        let mut filters: *mut *mut fwpm::FWPM_FILTER0 = std::ptr::null_mut();
         let code = unsafe {
             fwpm::FwpmFilterEnum0(
                 engine_handle,
@@ -117,66 +201,69 @@ mod filters {
                 &mut num_filters as *mut _,
             )
         };
+        unsafe { fwpm::FwpmFilterDestroyEnumHandle0(engine_handle, enum_handle) };
 
         if code != 0 {
-            return Err(Error {
-                kind: ErrorKind::Os(code),
-            });
+            return Err(Error::os(code).with_operation("FwpmFilterEnum0"));
         }
-
-        let mut results = Vec::with_capacity(num_filters as usize);
-
-        for i in 0..num_filters {
-            let filter_ptr = filters.add(i as usize);
-            if filter_ptr.is_null() {
-                break;
-            }
-            let filter = unsafe { *filter_ptr };
-            let mut filter_data = [0u8; std::mem::size_of::<fwpm::FWPM_FILTER0>()];
-            std::ptr::copy_nonoverlapping(
-                filter as *const u8,
-                &mut filter_data as *mut _,
-                std::mem::size_of::<fwpm::FWPM_FILTER0>(),
-            );
-            let filter_data: fwpm::FWPM_FILTER0 = unsafe { std::mem::transmute(filter_data) };
-            results.push(filter_data);
+        if filters.is_null() {
+            return Ok(vec![]);
         }
 
+        // copy the filters out of the memory owned by the engine
+        let results = unsafe { std::slice::from_raw_parts(filters, num_filters as usize) }
+            .iter()
+            .filter(|filter| !filter.is_null())
+            .map(|&filter| unsafe { Filter::from_raw(&*filter) })
+            .collect();
+        unsafe { fwpm::FwpmFreeMemory0(&mut filters as *mut _ as *mut *mut core::ffi::c_void) };
+
         Ok(results)
     }
 
-    // pub fn add_filter(filter: &fwpm::FWPM_FILTER0) -> Result<u64> {
-    //     let engine_handle = get_engine_handle()?;
-    //     let mut filter_id = 0_u64;
-    //     let code = unsafe {
-    //         fwpm::FwpmFilterAdd0(
-    //             engine_handle,
-    //             filter,
-    //             std::ptr::null(),
-    //             &mut filter_id as *mut _,
-    //         )
-    //     };
-    //     if code != 0 {
-    //         return Err(Error {
-    //             kind: ErrorKind::Os(code),
-    //         });
-    //     }
-    //     Ok(filter_id)
-    // }
+    /// Validate and add a filter in the session of `engine`, returning its
+    /// ID.
+    ///
+    /// The session is dynamic, so the filter is removed when `engine` is
+    /// closed.
+    pub fn add_filter(engine: &Engine, filter: &Filter) -> Result<u64> {
+        filter.validate()?;
+        let raw = filter.to_raw();
+        let mut filter_id = 0_u64;
+        let code = unsafe {
+            fwpm::FwpmFilterAdd0(
+                engine.handle(),
+                raw.as_ptr(),
+                std::ptr::null(),
+                &mut filter_id as *mut _,
+            )
+        };
+        if code != 0 {
+            return Err(Error::os(code).with_operation("FwpmFilterAdd0"));
+        }
+        Ok(filter_id)
+    }
 }
 
 mod sub_layer {
+    #[cfg(windows)]
     use crate::utils::{bytes_to_guid, guid_to_bytes};
 
     use super::*;
 
-    #[derive(Clone, Debug)]
+    #[derive(Clone, Debug, PartialEq, Eq)]
+    #[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
     pub struct SubLayer {
+        #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
         pub sub_layer_key: [u8; 16],
         pub display_data: DisplayData,
         pub flags: u32,
+        /// The priority of the sublayer within its layers, higher first.
+        #[cfg_attr(feature = "serde", serde(default))]
+        pub weight: u16,
     }
 
+    #[cfg(windows)]
     impl From<fwpm::FWPM_SUBLAYER0> for SubLayer {
         fn from(value: fwpm::FWPM_SUBLAYER0) -> Self {
             let sub_layer_key = guid_to_bytes(&value.subLayerKey);
@@ -186,10 +273,12 @@ mod sub_layer {
                 sub_layer_key,
                 display_data,
                 flags,
+                weight: value.weight,
             }
         }
     }
 
+    #[cfg(windows)]
     impl SubLayer {
         pub fn as_raw(&self) -> fwpm::FWPM_SUBLAYER0 {
             fwpm::FWPM_SUBLAYER0 {
@@ -198,11 +287,12 @@ mod sub_layer {
                 flags: self.flags,
                 providerKey: std::ptr::null_mut(),
                 providerData: empty_provider_data(),
-                weight: u16::MAX,
+                weight: self.weight,
             }
         }
     }
 
+    #[cfg(windows)]
     fn get_enum_handle(engine_handle: isize) -> Result<isize> {
         let template = [0u8; std::mem::size_of::<fwpm::FWPM_SUBLAYER_ENUM_TEMPLATE0>()];
         let template: fwpm::FWPM_SUBLAYER_ENUM_TEMPLATE0 = unsafe { std::mem::transmute(template) };
@@ -217,15 +307,15 @@ mod sub_layer {
         };
 
         if code != 0 {
-            return Err(Error {
-                kind: ErrorKind::Os(code),
-            });
+            return Err(Error::os(code).with_operation("FwpmSubLayerCreateEnumHandle0"));
         }
         Ok(enum_handle)
     }
 
+    #[cfg(windows)]
     pub fn get_sub_layers() -> Result<Vec<SubLayer>> {
-        let engine_handle = get_engine_handle()?;
+        let engine = Engine::open()?;
+        let engine_handle = engine.handle();
         let enum_handle = get_enum_handle(engine_handle)?;
 
         // list all sublayers
@@ -242,14 +332,10 @@ mod sub_layer {
             )
         };
         if code != 0 {
-            return Err(Error {
-                kind: ErrorKind::Os(code),
-            });
+            return Err(Error::os(code).with_operation("FwpmSubLayerEnum0"));
         }
         if layers.is_null() {
-            return Err(Error {
-                kind: ErrorKind::Os(0),
-            });
+            return Err(Error::os(0).with_operation("FwpmSubLayerEnum0"));
         }
         let mut results = Vec::with_capacity(num_layers as usize);
 
@@ -273,22 +359,32 @@ mod sub_layer {
         Ok(results)
     }
 
+    #[cfg(windows)]
     pub fn add_sub_layer(sub_layer: &SubLayer) -> Result<()> {
-        let engine_handle = get_engine_handle()?;
+        add_sub_layer_to(get_engine_handle()?, sub_layer)
+    }
+
+    /// Add a sublayer in the session of `engine`, it is removed when
+    /// `engine` is closed.
+    #[cfg(windows)]
+    pub(crate) fn add_sub_layer_in(engine: &Engine, sub_layer: &SubLayer) -> Result<()> {
+        add_sub_layer_to(engine.handle(), sub_layer)
+    }
+
+    #[cfg(windows)]
+    fn add_sub_layer_to(engine_handle: isize, sub_layer: &SubLayer) -> Result<()> {
         let sub_layer = sub_layer.as_raw();
         let code = unsafe {
             fwpm::FwpmSubLayerAdd0(engine_handle, &sub_layer as *const _, std::ptr::null())
         };
         if code != 0 {
-            return Err(Error {
-                kind: ErrorKind::Os(code),
-            });
+            return Err(Error::os(code).with_operation("FwpmSubLayerAdd0"));
         }
         Ok(())
     }
 }
 
-#[cfg(test)]
+#[cfg(all(test, windows))]
 mod tests {
 
     use self::sub_layer::{add_sub_layer, SubLayer};
@@ -297,39 +393,28 @@ mod tests {
 
     #[test]
     fn test_get_filters() {
-        let filters = unsafe { filters::get_filters().unwrap() };
-        unsafe {
-            for filter in filters {
-                let display = WideCString::from_ptr_str(filter.displayData.name)
-                    .to_string()
-                    .unwrap();
-                let desc = if !filter.displayData.description.is_null() {
-                    Some(
-                        WideCString::from_ptr_str(filter.displayData.description)
-                            .to_string()
-                            .unwrap(),
-                    )
-                } else {
-                    None
-                };
-                println!("[FILTER] {:?}, {:?}", display, desc);
-                if display == "sing-tun" {
-                    // print each field of the filter
-                    let data = dump_filter(filter);
-                    println!("{:x?}", &data);
-                }
+        let filters = filters::get_filters().unwrap();
+        for filter in filters {
+            println!(
+                "[FILTER] {:?}, {:?}, {}",
+                filter.display_data.name,
+                filter.display_data.desc,
+                catalogue::name_of(&filter.sub_layer_key)
+            );
+            for condition in &filter.conditions {
+                println!(
+                    "  {} {:?} {:?}",
+                    catalogue::name_of(&condition.field_key),
+                    condition.match_type,
+                    condition.value
+                );
+            }
+            if filter.display_data.name.to_string_lossy() == "sing-tun" {
+                println!("{:#x?}", filter);
             }
         }
     }
 
-    const FILTER_DATA_SIZE: usize = std::mem::size_of::<fwpm::FWPM_FILTER0>();
-
-    unsafe fn dump_filter(filter: fwpm::FWPM_FILTER0) -> [u8; FILTER_DATA_SIZE] {
-        let raw_data: [u8; std::mem::size_of::<fwpm::FWPM_FILTER0>()] =
-            unsafe { std::mem::transmute(filter) };
-        raw_data
-    }
-
     #[test]
     fn test_get_sub_layers() {
         let sub_layers = sub_layer::get_sub_layers().unwrap();
@@ -345,6 +430,7 @@ mod tests {
             sub_layer_key: guid_to_bytes(&generate_guid()),
             display_data,
             flags: 0,
+            weight: u16::MAX,
         };
         println!("adding sub layer {:?}", sub_layer);
 
@@ -361,22 +447,28 @@ mod tests {
         println!("find the added sub layer {:?}", find.display_data);
     }
 
-    // #[test]
-    // #[expect(fail)]
-    // fn test_add_filter() {
-    //     let mut filter: fwpm::FWPM_FILTER0;
-    //     unsafe {
-    //         filter = core::mem::zeroed();
-    //     }
-    //     filter.numFilterConditions = 0;
-    //     filter.filterCondition = std::ptr::null_mut();
-    //     let f1_display = DisplayData::new("filter1".into(), Some("filter1".into()));
-    //     filter.displayData = f1_display.as_raw();
-    //     filter.layerKey = fwpm::FWPM_LAYER_ALE_AUTH_CONNECT_V4;
-    //     filter.action.r#type = fwpm::FWP_ACTION_FLAG_TERMINATING;
-    //     filter.flags = fwpm::FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT;
-    //     filter.weight.r#type = fwpm::FWP_EMPTY;
-    //     add_filter(&filter).unwrap();
-    //     drop(f1_display);
-    // }
+    #[test]
+    fn test_add_filter() {
+        let filter = Filter::builder(
+            "filter1",
+            keys::FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            FilterAction::Block,
+        )
+        .description("filter1")
+        .flags(filter::FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT)
+        .condition(FilterCondition::remote_network(
+            "192.0.2.1/32".parse().unwrap(),
+        ))
+        .build()
+        .unwrap();
+        let engine = Engine::open().unwrap();
+        let filter_id = filters::add_filter(&engine, &filter).unwrap();
+        assert_ne!(filter_id, 0);
+        let is_added = |filters: Vec<Filter>| filters.iter().any(|f| f.filter_id == filter_id);
+        assert!(is_added(filters::get_filters().unwrap()));
+
+        // closing the session removes the block filter
+        drop(engine);
+        assert!(!is_added(filters::get_filters().unwrap()));
+    }
 }
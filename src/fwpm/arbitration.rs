@@ -0,0 +1,621 @@
+//! A simulator of WFP filter arbitration.
+//!
+//! [`Arbiter::evaluate`] runs a [`Connection`] through the filters of its
+//! layer the way the filter engine does:
+//!
+//! - Sublayers are evaluated from the highest weight to the lowest. Filters
+//!   whose sublayer isn't known are put in a sublayer of their own, after
+//!   the known ones. Filters added without a sublayer are in
+//!   `FWPM_SUBLAYER_UNIVERSAL`, of weight [`UNIVERSAL_WEIGHT`] unless the
+//!   sublayers list it.
+//! - Within a sublayer, filters are evaluated from the highest weight to
+//!   the lowest and the first matching `Permit` or `Block` is the decision
+//!   of the sublayer. Callouts can't be run, the matching ones are skipped
+//!   and reported.
+//! - Across sublayers, a `Block` overrides a `Permit` and is final. A
+//!   `Permit` whose filter has [`FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT`] is a
+//!   hard permit, which lower sublayers can only override with a veto: a
+//!   `Block` whose filter also has the flag.
+//! - Without any decision the connection is permitted.
+//!
+//! The weights of the filters added with [`FilterWeight::Auto`] or
+//! [`FilterWeight::Range`] are computed by the engine; unless the filter
+//! has an effective weight, they are approximated by the number of
+//! conditions within the range, the engine also favours the more specific
+//! filters.
+
+use std::collections::HashMap;
+use std::net::{IpAddr, SocketAddr};
+
+use super::filter::*;
+use super::keys::*;
+use super::SubLayer;
+
+/// The weight of `FWPM_SUBLAYER_UNIVERSAL`.
+pub const UNIVERSAL_WEIGHT: u16 = 0x8000;
+
+/// A connection to run through the filters, the values of the fields that
+/// the conditions test.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Connection {
+    /// The `FWPM_LAYER_*` GUID.
+    pub layer_key: [u8; 16],
+    pub protocol: u8,
+    pub local: Option<SocketAddr>,
+    pub remote: SocketAddr,
+    /// The app ID blob, see [`ConditionValue::app_id`].
+    pub app_id: Option<Vec<u8>>,
+    /// The LUID of the local interface.
+    pub local_interface: Option<u64>,
+}
+
+impl Connection {
+    pub fn new(layer_key: [u8; 16], protocol: u8, remote: SocketAddr) -> Self {
+        Connection {
+            layer_key,
+            protocol,
+            local: None,
+            remote,
+            app_id: None,
+            local_interface: None,
+        }
+    }
+
+    pub fn local(mut self, local: SocketAddr) -> Self {
+        self.local = Some(local);
+        self
+    }
+
+    /// The application, by NT device path.
+    pub fn app(mut self, device_path: &str) -> Self {
+        if let ConditionValue::ByteBlob(blob) = ConditionValue::app_id(device_path) {
+            self.app_id = Some(blob);
+        }
+        self
+    }
+
+    pub fn local_interface(mut self, luid: u64) -> Self {
+        self.local_interface = Some(luid);
+        self
+    }
+
+    /// The value of a field, `None` if the connection doesn't have it.
+    fn field(&self, field_key: [u8; 16]) -> Option<ConditionValue> {
+        match field_key {
+            FWPM_CONDITION_IP_REMOTE_ADDRESS => Some(self.remote.ip().into()),
+            FWPM_CONDITION_IP_REMOTE_PORT => Some(ConditionValue::Uint16(self.remote.port())),
+            FWPM_CONDITION_IP_LOCAL_ADDRESS => self.local.map(|local| local.ip().into()),
+            FWPM_CONDITION_IP_LOCAL_PORT => {
+                self.local.map(|local| ConditionValue::Uint16(local.port()))
+            }
+            FWPM_CONDITION_IP_PROTOCOL => Some(ConditionValue::Uint8(self.protocol)),
+            FWPM_CONDITION_ALE_APP_ID => self.app_id.clone().map(ConditionValue::ByteBlob),
+            FWPM_CONDITION_IP_LOCAL_INTERFACE => self.local_interface.map(ConditionValue::Uint64),
+            _ => None,
+        }
+    }
+}
+
+/// The outcome of a connection.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Verdict {
+    Permit,
+    Block,
+}
+
+/// What the decision of a sublayer did to the outcome.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Effect {
+    /// No filter of the sublayer matched.
+    NoMatch,
+    /// The decision was taken, it is final unless a lower sublayer
+    /// overrode it.
+    Taken,
+    /// The decision was a block that couldn't override a hard permit.
+    Ignored,
+    /// The decision was a veto that overrode a hard permit.
+    Veto,
+    /// The sublayer wasn't evaluated, a higher one blocked.
+    NotEvaluated,
+}
+
+/// The decision of one sublayer.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SubLayerDecision {
+    pub sub_layer_key: [u8; 16],
+    /// `None` for a sublayer that isn't known.
+    pub weight: Option<u16>,
+    /// The index of the deciding filter.
+    pub filter: Option<usize>,
+    pub verdict: Option<Verdict>,
+    /// Whether the filter has [`FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT`].
+    pub hard: bool,
+    pub effect: Effect,
+    /// The indexes of the matching callout filters, which weren't run.
+    pub skipped_callouts: Vec<usize>,
+}
+
+/// The outcome of a connection and how it was reached.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Evaluation {
+    pub verdict: Verdict,
+    /// The index of the filter that decided, `None` for the default permit.
+    pub filter: Option<usize>,
+    pub vetoed: bool,
+    /// The sublayers with a filter at the layer, in evaluation order.
+    pub sub_layers: Vec<SubLayerDecision>,
+}
+
+/// The sublayers and filters of a machine, see the module documentation.
+#[derive(Debug, Clone, Default)]
+pub struct Arbiter {
+    sub_layers: Vec<SubLayer>,
+    filters: Vec<Filter>,
+}
+
+impl Arbiter {
+    /// Filters are identified by their index in `filters`.
+    pub fn new(sub_layers: Vec<SubLayer>, filters: Vec<Filter>) -> Self {
+        Arbiter {
+            sub_layers,
+            filters,
+        }
+    }
+
+    pub fn filters(&self) -> &[Filter] {
+        &self.filters
+    }
+
+    pub fn evaluate(&self, connection: &Connection) -> Evaluation {
+        // the enabled filters of the layer, by sublayer
+        let mut by_sub_layer: HashMap<[u8; 16], Vec<usize>> = HashMap::new();
+        for (index, filter) in self.filters.iter().enumerate() {
+            if filter.layer_key == connection.layer_key
+                && filter.flags & FWPM_FILTER_FLAG_DISABLED == 0
+            {
+                // the engine puts the filters without a sublayer in the
+                // universal one
+                let key = if filter.sub_layer_key == [0; 16] {
+                    FWPM_SUBLAYER_UNIVERSAL
+                } else {
+                    filter.sub_layer_key
+                };
+                by_sub_layer.entry(key).or_default().push(index);
+            }
+        }
+        let mut sub_layers: Vec<_> = by_sub_layer
+            .into_iter()
+            .map(|(key, mut filters)| {
+                let weight = self
+                    .sub_layers
+                    .iter()
+                    .find(|sub_layer| sub_layer.sub_layer_key == key)
+                    .map(|sub_layer| sub_layer.weight)
+                    .or((key == FWPM_SUBLAYER_UNIVERSAL).then_some(UNIVERSAL_WEIGHT));
+                // stable, the first filter wins a tie
+                filters.sort_by_key(|&index| std::cmp::Reverse(sort_weight(&self.filters[index])));
+                (key, weight, filters)
+            })
+            .collect();
+        sub_layers.sort_by_key(|&(key, weight, _)| (std::cmp::Reverse(weight), key));
+
+        let mut evaluation = Evaluation {
+            verdict: Verdict::Permit,
+            filter: None,
+            vetoed: false,
+            sub_layers: vec![],
+        };
+        // the decision so far and whether it is a hard permit
+        let mut decided: Option<(Verdict, bool)> = None;
+        for (sub_layer_key, weight, filters) in sub_layers {
+            let mut decision = SubLayerDecision {
+                sub_layer_key,
+                weight,
+                filter: None,
+                verdict: None,
+                hard: false,
+                effect: Effect::NoMatch,
+                skipped_callouts: vec![],
+            };
+            if decided.is_some_and(|(verdict, _)| verdict == Verdict::Block) {
+                decision.effect = Effect::NotEvaluated;
+                evaluation.sub_layers.push(decision);
+                continue;
+            }
+            for index in filters {
+                let filter = &self.filters[index];
+                if !matches(filter, connection) {
+                    continue;
+                }
+                let verdict = match filter.action {
+                    FilterAction::Permit => Verdict::Permit,
+                    FilterAction::Block => Verdict::Block,
+                    FilterAction::CalloutTerminating(_)
+                    | FilterAction::CalloutInspection(_)
+                    | FilterAction::CalloutUnknown(_) => {
+                        decision.skipped_callouts.push(index);
+                        continue;
+                    }
+                    FilterAction::Unknown(_) => continue,
+                };
+                decision.filter = Some(index);
+                decision.verdict = Some(verdict);
+                decision.hard = filter.flags & FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT != 0;
+                break;
+            }
+            if let Some(verdict) = decision.verdict {
+                decision.effect = match decided {
+                    Some((Verdict::Permit, true)) if verdict == Verdict::Block && decision.hard => {
+                        evaluation.vetoed = true;
+                        Effect::Veto
+                    }
+                    Some((Verdict::Permit, true)) => Effect::Ignored,
+                    _ => Effect::Taken,
+                };
+                if decision.effect != Effect::Ignored {
+                    decided = Some((verdict, decision.hard));
+                    evaluation.verdict = verdict;
+                    evaluation.filter = decision.filter;
+                }
+            }
+            evaluation.sub_layers.push(decision);
+        }
+        evaluation
+    }
+}
+
+/// The weight of a filter within its sublayer, see the module
+/// documentation.
+fn sort_weight(filter: &Filter) -> u64 {
+    if let Some(weight) = filter.effective_weight {
+        return weight;
+    }
+    let conditions = filter.conditions.len() as u64;
+    match filter.weight {
+        FilterWeight::Exact(weight) => weight,
+        FilterWeight::Range(range) => (u64::from(range) << 60) | conditions,
+        FilterWeight::Auto => conditions,
+    }
+}
+
+/// Conditions on the same field are OR'ed, the fields are AND'ed.
+fn matches(filter: &Filter, connection: &Connection) -> bool {
+    let mut fields: HashMap<[u8; 16], bool> = HashMap::new();
+    for condition in &filter.conditions {
+        let matched = connection
+            .field(condition.field_key)
+            .is_some_and(|value| condition_matches(condition, &value));
+        *fields.entry(condition.field_key).or_default() |= matched;
+    }
+    fields.values().all(|&matched| matched)
+}
+
+fn condition_matches(condition: &FilterCondition, value: &ConditionValue) -> bool {
+    use std::cmp::Ordering;
+    let expected = &condition.value;
+    let ordering = compare(value, expected);
+    match condition.match_type {
+        MatchType::Equal => equals(value, expected),
+        MatchType::NotEqual => !equals(value, expected),
+        MatchType::Greater => ordering == Some(Ordering::Greater),
+        MatchType::Less => ordering == Some(Ordering::Less),
+        MatchType::GreaterOrEqual => matches!(ordering, Some(Ordering::Greater | Ordering::Equal)),
+        MatchType::LessOrEqual => matches!(ordering, Some(Ordering::Less | Ordering::Equal)),
+        MatchType::Range => match expected {
+            ConditionValue::Range(low, high) => {
+                matches!(
+                    compare(value, low),
+                    Some(Ordering::Greater | Ordering::Equal)
+                ) && matches!(compare(value, high), Some(Ordering::Less | Ordering::Equal))
+            }
+            _ => false,
+        },
+        MatchType::FlagsAllSet => flags(value, expected).is_some_and(|(v, f)| v & f == f),
+        MatchType::FlagsAnySet => flags(value, expected).is_some_and(|(v, f)| v & f != 0),
+        MatchType::FlagsNoneSet => flags(value, expected).is_some_and(|(v, f)| v & f == 0),
+        MatchType::EqualCaseInsensitive => match (value, expected) {
+            (ConditionValue::ByteBlob(v), ConditionValue::ByteBlob(e)) => v.eq_ignore_ascii_case(e),
+            _ => false,
+        },
+        MatchType::Prefix | MatchType::NotPrefix => {
+            let is_prefix = match (value, expected) {
+                (ConditionValue::ByteBlob(v), ConditionValue::ByteBlob(e)) => {
+                    // without the terminating NUL of the prefix
+                    let e = e.strip_suffix(&[0, 0]).unwrap_or(e);
+                    v.starts_with(e)
+                }
+                _ => return false,
+            };
+            is_prefix == (condition.match_type == MatchType::Prefix)
+        }
+        MatchType::Unknown(_) => false,
+    }
+}
+
+fn equals(value: &ConditionValue, expected: &ConditionValue) -> bool {
+    match expected {
+        ConditionValue::AddrMask(network) => {
+            address(value).is_some_and(|addr| network.contains(addr))
+        }
+        expected => compare(value, expected) == Some(std::cmp::Ordering::Equal),
+    }
+}
+
+/// Compare values of the same type, integers of any width.
+fn compare(value: &ConditionValue, expected: &ConditionValue) -> Option<std::cmp::Ordering> {
+    match (integer(value), integer(expected)) {
+        (Some(v), Some(e)) => return Some(v.cmp(&e)),
+        (None, None) => {}
+        _ => return None,
+    }
+    match (value, expected) {
+        (ConditionValue::ByteArray16(v), ConditionValue::ByteArray16(e)) => Some(v.cmp(e)),
+        (ConditionValue::ByteBlob(v), ConditionValue::ByteBlob(e)) => Some(v.cmp(e)),
+        _ => None,
+    }
+}
+
+fn integer(value: &ConditionValue) -> Option<u64> {
+    match *value {
+        ConditionValue::Uint8(v) => Some(v.into()),
+        ConditionValue::Uint16(v) => Some(v.into()),
+        ConditionValue::Uint32(v) => Some(v.into()),
+        ConditionValue::Uint64(v) => Some(v),
+        _ => None,
+    }
+}
+
+fn flags(value: &ConditionValue, expected: &ConditionValue) -> Option<(u64, u64)> {
+    Some((integer(value)?, integer(expected)?))
+}
+
+fn address(value: &ConditionValue) -> Option<IpAddr> {
+    match *value {
+        ConditionValue::Uint32(v) => Some(IpAddr::from(v.to_be_bytes())),
+        ConditionValue::ByteArray16(v) => Some(IpAddr::from(v)),
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::fwpm::DisplayData;
+
+    const VPN: [u8; 16] = [1; 16];
+    const OTHER: [u8; 16] = [2; 16];
+
+    fn sub_layer(key: [u8; 16], weight: u16) -> SubLayer {
+        SubLayer {
+            sub_layer_key: key,
+            display_data: DisplayData::new("sublayer".into(), None),
+            flags: 0,
+            weight,
+        }
+    }
+
+    fn filter(sub_layer: [u8; 16], action: FilterAction, weight: u64) -> FilterBuilder {
+        Filter::builder("filter", FWPM_LAYER_ALE_AUTH_CONNECT_V4, action)
+            .sub_layer(sub_layer)
+            .weight(FilterWeight::Exact(weight))
+    }
+
+    fn dns(remote: &str) -> Connection {
+        Connection::new(
+            FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            17,
+            SocketAddr::new(remote.parse().unwrap(), 53),
+        )
+    }
+
+    fn verdicts(evaluation: &Evaluation) -> Vec<(Option<usize>, Effect)> {
+        evaluation
+            .sub_layers
+            .iter()
+            .map(|sub_layer| (sub_layer.filter, sub_layer.effect))
+            .collect()
+    }
+
+    #[test]
+    fn test_within_sub_layer() {
+        let arbiter = Arbiter::new(
+            vec![sub_layer(VPN, 10)],
+            vec![
+                filter(VPN, FilterAction::Block, 1)
+                    .condition(FilterCondition::remote_port(53))
+                    .build()
+                    .unwrap(),
+                filter(VPN, FilterAction::Permit, 2)
+                    .condition(FilterCondition::remote_network(
+                        "10.0.0.0/8".parse().unwrap(),
+                    ))
+                    .build()
+                    .unwrap(),
+                filter(VPN, FilterAction::CalloutInspection([9; 16]), 3)
+                    .build()
+                    .unwrap(),
+            ],
+        );
+        let evaluation = arbiter.evaluate(&dns("10.0.0.53"));
+        assert_eq!(evaluation.verdict, Verdict::Permit);
+        assert_eq!(evaluation.filter, Some(1));
+        assert_eq!(evaluation.sub_layers[0].skipped_callouts, [2]);
+
+        let evaluation = arbiter.evaluate(&dns("8.8.8.8"));
+        assert_eq!(evaluation.verdict, Verdict::Block);
+        assert_eq!(evaluation.filter, Some(0));
+
+        // other layers are ignored and the default is permit
+        let mut connection = dns("8.8.8.8");
+        connection.layer_key = FWPM_LAYER_ALE_AUTH_CONNECT_V6;
+        let evaluation = arbiter.evaluate(&connection);
+        assert_eq!(evaluation.verdict, Verdict::Permit);
+        assert_eq!(evaluation.filter, None);
+        assert!(evaluation.sub_layers.is_empty());
+    }
+
+    #[test]
+    fn test_across_sub_layers() {
+        let permit = |sub_layer, flags| {
+            filter(sub_layer, FilterAction::Permit, 1)
+                .flags(flags)
+                .build()
+                .unwrap()
+        };
+        let block = |sub_layer, flags| {
+            filter(sub_layer, FilterAction::Block, 1)
+                .flags(flags)
+                .condition(FilterCondition::remote_port(53))
+                .build()
+                .unwrap()
+        };
+        let hard = FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT;
+        let sub_layers = vec![sub_layer(VPN, 20), sub_layer(OTHER, 10)];
+        let cases = [
+            // a lower block overrides a soft permit
+            (
+                vec![permit(VPN, 0), block(OTHER, 0)],
+                Verdict::Block,
+                Some(1),
+                false,
+                vec![(Some(0), Effect::Taken), (Some(1), Effect::Taken)],
+            ),
+            // a higher block is final
+            (
+                vec![block(VPN, 0), permit(OTHER, hard)],
+                Verdict::Block,
+                Some(0),
+                false,
+                vec![(Some(0), Effect::Taken), (None, Effect::NotEvaluated)],
+            ),
+            // a hard permit resists a soft block
+            (
+                vec![permit(VPN, hard), block(OTHER, 0)],
+                Verdict::Permit,
+                Some(0),
+                false,
+                vec![(Some(0), Effect::Taken), (Some(1), Effect::Ignored)],
+            ),
+            // but not a veto
+            (
+                vec![permit(VPN, hard), block(OTHER, hard)],
+                Verdict::Block,
+                Some(1),
+                true,
+                vec![(Some(0), Effect::Taken), (Some(1), Effect::Veto)],
+            ),
+            // unknown sublayers are evaluated last
+            (
+                vec![block([3; 16], 0), permit(VPN, hard)],
+                Verdict::Permit,
+                Some(1),
+                false,
+                vec![(Some(1), Effect::Taken), (Some(0), Effect::Ignored)],
+            ),
+            // filters without a sublayer are in the universal one
+            (
+                vec![permit(VPN, hard), block([0; 16], 0)],
+                Verdict::Block,
+                Some(1),
+                false,
+                vec![(Some(1), Effect::Taken), (None, Effect::NotEvaluated)],
+            ),
+        ];
+        for (filters, verdict, filter, vetoed, decisions) in cases {
+            let evaluation = Arbiter::new(sub_layers.clone(), filters).evaluate(&dns("8.8.8.8"));
+            assert_eq!(
+                (evaluation.verdict, evaluation.filter, evaluation.vetoed),
+                (verdict, filter, vetoed)
+            );
+            assert_eq!(verdicts(&evaluation), decisions);
+        }
+
+        let evaluation =
+            Arbiter::new(sub_layers, vec![block([0; 16], 0)]).evaluate(&dns("8.8.8.8"));
+        assert_eq!(
+            evaluation.sub_layers[0].sub_layer_key,
+            FWPM_SUBLAYER_UNIVERSAL
+        );
+        assert_eq!(evaluation.sub_layers[0].weight, Some(UNIVERSAL_WEIGHT));
+        // the weight of the listed sublayer wins
+        let sub_layers = vec![sub_layer(FWPM_SUBLAYER_UNIVERSAL, 1)];
+        let evaluation =
+            Arbiter::new(sub_layers, vec![block([0; 16], 0)]).evaluate(&dns("8.8.8.8"));
+        assert_eq!(evaluation.sub_layers[0].weight, Some(1));
+    }
+
+    #[test]
+    fn test_conditions() {
+        let app = "\\device\\harddiskvolume3\\app.exe";
+        let connection = dns("192.168.1.1")
+            .local("192.168.1.20:50000".parse().unwrap())
+            .app(app)
+            .local_interface(7);
+        let cases = [
+            (FilterCondition::remote_port_range(50, 60), true),
+            (FilterCondition::remote_port_range(54, 60), false),
+            (FilterCondition::local_port(50000), true),
+            (FilterCondition::protocol(6), false),
+            (
+                FilterCondition::app_id("\\Device\\HarddiskVolume3\\App.exe"),
+                true,
+            ),
+            (FilterCondition::local_interface(8), false),
+            (
+                FilterCondition::local_network("192.168.0.0/16".parse().unwrap()),
+                true,
+            ),
+            (
+                FilterCondition::remote_network("192.168.1.2/32".parse().unwrap()),
+                false,
+            ),
+            (
+                FilterCondition::new(
+                    FWPM_CONDITION_ALE_APP_ID,
+                    MatchType::Prefix,
+                    ConditionValue::app_id("\\device\\harddiskvolume3\\"),
+                ),
+                true,
+            ),
+            (
+                FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_PORT,
+                    MatchType::NotEqual,
+                    ConditionValue::Uint16(53),
+                ),
+                false,
+            ),
+            (
+                FilterCondition::new(
+                    FWPM_CONDITION_IP_PROTOCOL,
+                    MatchType::FlagsAnySet,
+                    ConditionValue::Uint8(0x10),
+                ),
+                true,
+            ),
+        ];
+        for (condition, expected) in cases {
+            let filter = filter(VPN, FilterAction::Block, 1)
+                .condition(condition.clone())
+                .build()
+                .unwrap();
+            assert_eq!(matches(&filter, &connection), expected, "{:?}", condition);
+        }
+
+        // the same field is OR'ed, different fields AND'ed
+        let filter = filter(VPN, FilterAction::Block, 1)
+            .condition(FilterCondition::remote_port(853))
+            .condition(FilterCondition::remote_port(53))
+            .condition(FilterCondition::protocol(17))
+            .build()
+            .unwrap();
+        assert!(matches(&filter, &connection));
+        assert!(!matches(
+            &filter,
+            &Connection {
+                protocol: 6,
+                ..connection
+            }
+        ));
+    }
+}
@@ -0,0 +1,1121 @@
+//! A catalogue of the documented WFP layers, built-in sublayers, condition
+//! fields and built-in providers, to name the GUIDs of enumerated objects
+//! and to check which conditions a layer supports.
+//!
+//! The conditions of the layers follow "Filtering conditions available at
+//! each filtering layer" of the WFP documentation. The fast layers only
+//! take callouts and have no conditions.
+
+use std::borrow::Cow;
+
+use super::keys::*;
+use crate::query::AddressFamily;
+use crate::utils::guid_to_string;
+
+/// The type of object a GUID identifies.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Kind {
+    Layer,
+    SubLayer,
+    Condition,
+    Provider,
+}
+
+/// A well-known GUID.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Entry {
+    pub kind: Kind,
+    /// The name of the constant, e.g. `FWPM_LAYER_ALE_AUTH_CONNECT_V4`.
+    pub name: &'static str,
+    pub key: [u8; 16],
+    pub description: &'static str,
+    /// `Unspec` for the objects that aren't specific to a family.
+    pub family: AddressFamily,
+}
+
+impl Entry {
+    /// The condition fields available at a layer, empty for the other
+    /// kinds.
+    pub fn conditions(&self) -> &'static [[u8; 16]] {
+        match self.kind {
+            Kind::Layer => layer_conditions(&self.key),
+            _ => &[],
+        }
+    }
+}
+
+/// The family of a constant from its `_V4` or `_V6` suffix.
+const fn family(name: &str) -> AddressFamily {
+    const fn ends_with(name: &[u8], suffix: &[u8]) -> bool {
+        if name.len() < suffix.len() {
+            return false;
+        }
+        let start = name.len() - suffix.len();
+        let mut i = 0;
+        while i < suffix.len() {
+            if name[start + i] != suffix[i] {
+                return false;
+            }
+            i += 1;
+        }
+        true
+    }
+    let name = name.as_bytes();
+    if ends_with(name, b"_V4") || ends_with(name, b"_V4_DISCARD") {
+        AddressFamily::Ipv4
+    } else if ends_with(name, b"_V6") || ends_with(name, b"_V6_DISCARD") {
+        AddressFamily::Ipv6
+    } else {
+        AddressFamily::Unspec
+    }
+}
+
+macro_rules! entries {
+    ($kind:ident; $($name:ident => $description:literal,)*) => {
+        &[$(Entry {
+            kind: Kind::$kind,
+            name: stringify!($name),
+            key: $name,
+            description: $description,
+            family: family(stringify!($name)),
+        },)*]
+    };
+}
+
+static LAYERS: &[Entry] = entries![Layer;
+    FWPM_LAYER_INBOUND_IPPACKET_V4 => "Received IP packets, before the IP header is processed.",
+    FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD => "Received IP packets that are discarded.",
+    FWPM_LAYER_INBOUND_IPPACKET_V6 => "Received IP packets, before the IP header is processed.",
+    FWPM_LAYER_INBOUND_IPPACKET_V6_DISCARD => "Received IP packets that are discarded.",
+    FWPM_LAYER_OUTBOUND_IPPACKET_V4 => "Sent IP packets, before fragmentation.",
+    FWPM_LAYER_OUTBOUND_IPPACKET_V4_DISCARD => "Sent IP packets that are discarded.",
+    FWPM_LAYER_OUTBOUND_IPPACKET_V6 => "Sent IP packets, before fragmentation.",
+    FWPM_LAYER_OUTBOUND_IPPACKET_V6_DISCARD => "Sent IP packets that are discarded.",
+    FWPM_LAYER_IPFORWARD_V4 => "Forwarded IP packets.",
+    FWPM_LAYER_IPFORWARD_V4_DISCARD => "Forwarded IP packets that are discarded.",
+    FWPM_LAYER_IPFORWARD_V6 => "Forwarded IP packets.",
+    FWPM_LAYER_IPFORWARD_V6_DISCARD => "Forwarded IP packets that are discarded.",
+    FWPM_LAYER_INBOUND_TRANSPORT_V4 => "Received packets at the transport layer.",
+    FWPM_LAYER_INBOUND_TRANSPORT_V4_DISCARD => "Received transport packets that are discarded.",
+    FWPM_LAYER_INBOUND_TRANSPORT_V6 => "Received packets at the transport layer.",
+    FWPM_LAYER_INBOUND_TRANSPORT_V6_DISCARD => "Received transport packets that are discarded.",
+    FWPM_LAYER_OUTBOUND_TRANSPORT_V4 => "Sent packets at the transport layer.",
+    FWPM_LAYER_OUTBOUND_TRANSPORT_V4_DISCARD => "Sent transport packets that are discarded.",
+    FWPM_LAYER_OUTBOUND_TRANSPORT_V6 => "Sent packets at the transport layer.",
+    FWPM_LAYER_OUTBOUND_TRANSPORT_V6_DISCARD => "Sent transport packets that are discarded.",
+    FWPM_LAYER_STREAM_V4 => "TCP stream data.",
+    FWPM_LAYER_STREAM_V4_DISCARD => "TCP stream data that is discarded.",
+    FWPM_LAYER_STREAM_V6 => "TCP stream data.",
+    FWPM_LAYER_STREAM_V6_DISCARD => "TCP stream data that is discarded.",
+    FWPM_LAYER_DATAGRAM_DATA_V4 => "Datagrams, e.g. UDP.",
+    FWPM_LAYER_DATAGRAM_DATA_V4_DISCARD => "Datagrams that are discarded.",
+    FWPM_LAYER_DATAGRAM_DATA_V6 => "Datagrams, e.g. UDP.",
+    FWPM_LAYER_DATAGRAM_DATA_V6_DISCARD => "Datagrams that are discarded.",
+    FWPM_LAYER_INBOUND_ICMP_ERROR_V4 => "Received ICMP error messages.",
+    FWPM_LAYER_INBOUND_ICMP_ERROR_V4_DISCARD => "Received ICMP error messages that are discarded.",
+    FWPM_LAYER_INBOUND_ICMP_ERROR_V6 => "Received ICMP error messages.",
+    FWPM_LAYER_INBOUND_ICMP_ERROR_V6_DISCARD => "Received ICMP error messages that are discarded.",
+    FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4 => "Sent ICMP error messages.",
+    FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4_DISCARD => "Sent ICMP error messages that are discarded.",
+    FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6 => "Sent ICMP error messages.",
+    FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6_DISCARD => "Sent ICMP error messages that are discarded.",
+    FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4 => "Port assignments, when a socket is bound.",
+    FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4_DISCARD => "Port assignments that are refused.",
+    FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6 => "Port assignments, when a socket is bound.",
+    FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6_DISCARD => "Port assignments that are refused.",
+    FWPM_LAYER_ALE_AUTH_LISTEN_V4 => "TCP listen calls.",
+    FWPM_LAYER_ALE_AUTH_LISTEN_V4_DISCARD => "TCP listen calls that are refused.",
+    FWPM_LAYER_ALE_AUTH_LISTEN_V6 => "TCP listen calls.",
+    FWPM_LAYER_ALE_AUTH_LISTEN_V6_DISCARD => "TCP listen calls that are refused.",
+    FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4 => "Incoming connections, and the first packet of other incoming flows.",
+    FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4_DISCARD => "Incoming connections that are refused.",
+    FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6 => "Incoming connections, and the first packet of other incoming flows.",
+    FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6_DISCARD => "Incoming connections that are refused.",
+    FWPM_LAYER_ALE_AUTH_CONNECT_V4 => "Outgoing connections, and the first packet of other outgoing flows.",
+    FWPM_LAYER_ALE_AUTH_CONNECT_V4_DISCARD => "Outgoing connections that are refused.",
+    FWPM_LAYER_ALE_AUTH_CONNECT_V6 => "Outgoing connections, and the first packet of other outgoing flows.",
+    FWPM_LAYER_ALE_AUTH_CONNECT_V6_DISCARD => "Outgoing connections that are refused.",
+    FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4 => "Established flows.",
+    FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4_DISCARD => "Established flows that are refused.",
+    FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6 => "Established flows.",
+    FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6_DISCARD => "Established flows that are refused.",
+    FWPM_LAYER_INBOUND_MAC_FRAME_ETHERNET => "Received Ethernet frames.",
+    FWPM_LAYER_OUTBOUND_MAC_FRAME_ETHERNET => "Sent Ethernet frames.",
+    FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE => "Received native frames, e.g. 802.11.",
+    FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE => "Sent native frames, e.g. 802.11.",
+    FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE_FAST => "Received native frames, for callouts only.",
+    FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE_FAST => "Sent native frames, for callouts only.",
+    FWPM_LAYER_INGRESS_VSWITCH_ETHERNET => "Ethernet frames entering a Hyper-V switch.",
+    FWPM_LAYER_EGRESS_VSWITCH_ETHERNET => "Ethernet frames leaving a Hyper-V switch.",
+    FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V4 => "Transport packets entering a Hyper-V switch.",
+    FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V6 => "Transport packets entering a Hyper-V switch.",
+    FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V4 => "Transport packets leaving a Hyper-V switch.",
+    FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V6 => "Transport packets leaving a Hyper-V switch.",
+    FWPM_LAYER_INBOUND_TRANSPORT_FAST => "Received transport packets, for callouts only.",
+    FWPM_LAYER_OUTBOUND_TRANSPORT_FAST => "Sent transport packets, for callouts only.",
+    FWPM_LAYER_INBOUND_RESERVED2 => "Reserved.",
+    FWPM_LAYER_IPSEC_KM_DEMUX_V4 => "Selection of the IPsec keying module.",
+    FWPM_LAYER_IPSEC_KM_DEMUX_V6 => "Selection of the IPsec keying module.",
+    FWPM_LAYER_IPSEC_V4 => "IPsec quick mode policy.",
+    FWPM_LAYER_IPSEC_V6 => "IPsec quick mode policy.",
+    FWPM_LAYER_IKEEXT_V4 => "IKE and AuthIP main mode policy.",
+    FWPM_LAYER_IKEEXT_V6 => "IKE and AuthIP main mode policy.",
+    FWPM_LAYER_KM_AUTHORIZATION => "Authorization of keying module negotiations.",
+    FWPM_LAYER_RPC_UM => "RPC interface calls.",
+    FWPM_LAYER_RPC_EPMAP => "RPC endpoint mapper requests.",
+    FWPM_LAYER_RPC_EP_ADD => "RPC endpoint registrations.",
+    FWPM_LAYER_RPC_PROXY_CONN => "RPC over HTTP proxy connections.",
+    FWPM_LAYER_RPC_PROXY_IF => "RPC over HTTP proxy interface calls.",
+    FWPM_LAYER_NAME_RESOLUTION_CACHE_V4 => "Name resolution cache entries.",
+    FWPM_LAYER_NAME_RESOLUTION_CACHE_V6 => "Name resolution cache entries.",
+    FWPM_LAYER_ALE_RESOURCE_RELEASE_V4 => "Port releases, when a socket is closed.",
+    FWPM_LAYER_ALE_RESOURCE_RELEASE_V6 => "Port releases, when a socket is closed.",
+    FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V4 => "Endpoint closures.",
+    FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V6 => "Endpoint closures.",
+    FWPM_LAYER_ALE_CONNECT_REDIRECT_V4 => "Redirection of outgoing connections.",
+    FWPM_LAYER_ALE_CONNECT_REDIRECT_V6 => "Redirection of outgoing connections.",
+    FWPM_LAYER_ALE_BIND_REDIRECT_V4 => "Redirection of binds.",
+    FWPM_LAYER_ALE_BIND_REDIRECT_V6 => "Redirection of binds.",
+    FWPM_LAYER_STREAM_PACKET_V4 => "Packets of TCP streams.",
+    FWPM_LAYER_STREAM_PACKET_V6 => "Packets of TCP streams.",
+    FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V4 => "Network policy of outgoing connections.",
+    FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V6 => "Network policy of outgoing connections.",
+];
+
+static SUB_LAYERS: &[Entry] = entries![SubLayer;
+    FWPM_SUBLAYER_RPC_AUDIT => "RPC audit filters.",
+    FWPM_SUBLAYER_IPSEC_TUNNEL => "IPsec tunnel mode filters.",
+    FWPM_SUBLAYER_UNIVERSAL => "The default sublayer, of the filters added without one.",
+    FWPM_SUBLAYER_LIPS => "Legacy IPsec filters.",
+    FWPM_SUBLAYER_SECURE_SOCKET => "Secure socket filters.",
+    FWPM_SUBLAYER_TCP_CHIMNEY_OFFLOAD => "TCP chimney offload filters.",
+    FWPM_SUBLAYER_INSPECTION => "Inspection filters.",
+    FWPM_SUBLAYER_TEREDO => "Teredo edge traversal filters.",
+    FWPM_SUBLAYER_IPSEC_FORWARD_OUTBOUND_TUNNEL => "IPsec filters of forwarded outbound tunnel traffic.",
+    FWPM_SUBLAYER_IPSEC_DOSP => "IPsec DoS protection filters.",
+    FWPM_SUBLAYER_TCP_TEMPLATES => "TCP template filters.",
+    FWPM_SUBLAYER_IPSEC_SECURITY_REALM => "IPsec security realm filters.",
+    FWPM_SUBLAYER_MPSSVC_WSH => "Windows Service Hardening filters.",
+    FWPM_SUBLAYER_MPSSVC_WF => "Windows Firewall rules.",
+    FWPM_SUBLAYER_MPSSVC_QUARANTINE => "Windows Firewall interface quarantine filters.",
+    FWPM_SUBLAYER_MPSSVC_EDP => "Windows Firewall enterprise data protection filters.",
+    FWPM_SUBLAYER_MPSSVC_TENANT_RESTRICTIONS => "Windows Firewall tenant restriction filters.",
+    FWPM_SUBLAYER_MPSSVC_APP_ISOLATION => "Windows Firewall app isolation filters.",
+];
+
+static PROVIDERS: &[Entry] = entries![Provider;
+    FWPM_PROVIDER_IKEEXT => "IKE and AuthIP.",
+    FWPM_PROVIDER_IPSEC_DOSP_CONFIG => "IPsec DoS protection configuration.",
+    FWPM_PROVIDER_TCP_CHIMNEY_OFFLOAD => "TCP chimney offload.",
+    FWPM_PROVIDER_TCP_TEMPLATES => "TCP templates.",
+    FWPM_PROVIDER_MPSSVC_WSH => "Windows Service Hardening.",
+    FWPM_PROVIDER_MPSSVC_WF => "Windows Firewall.",
+    FWPM_PROVIDER_MPSSVC_EDP => "Windows Firewall enterprise data protection.",
+    FWPM_PROVIDER_MPSSVC_TENANT_RESTRICTIONS => "Windows Firewall tenant restrictions.",
+    FWPM_PROVIDER_MPSSVC_APP_ISOLATION => "Windows Firewall app isolation.",
+];
+
+static CONDITIONS: &[Entry] = entries![Condition;
+    FWPM_CONDITION_IP_LOCAL_ADDRESS => "The local IP address.",
+    FWPM_CONDITION_IP_REMOTE_ADDRESS => "The remote IP address.",
+    FWPM_CONDITION_IP_SOURCE_ADDRESS => "The source IP address of a forwarded packet.",
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS => "The destination IP address of a forwarded packet.",
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE => "The type of the local address, a NL_ADDRESS_TYPE.",
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE => "The type of the destination address, a NL_ADDRESS_TYPE.",
+    FWPM_CONDITION_IP_NEXTHOP_ADDRESS => "The IP address of the next hop.",
+    FWPM_CONDITION_IP_LOCAL_INTERFACE => "The LUID of the local interface.",
+    FWPM_CONDITION_IP_ARRIVAL_INTERFACE => "The LUID of the interface the packet arrived on.",
+    FWPM_CONDITION_ARRIVAL_INTERFACE_TYPE => "The IANA type of the arrival interface.",
+    FWPM_CONDITION_ARRIVAL_TUNNEL_TYPE => "The tunnel type of the arrival interface.",
+    FWPM_CONDITION_ARRIVAL_INTERFACE_INDEX => "The index of the arrival interface.",
+    FWPM_CONDITION_NEXTHOP_SUB_INTERFACE_INDEX => "The index of the sub-interface of the next hop.",
+    FWPM_CONDITION_IP_NEXTHOP_INTERFACE => "The LUID of the next hop interface.",
+    FWPM_CONDITION_NEXTHOP_INTERFACE_TYPE => "The IANA type of the next hop interface.",
+    FWPM_CONDITION_NEXTHOP_TUNNEL_TYPE => "The tunnel type of the next hop interface.",
+    FWPM_CONDITION_NEXTHOP_INTERFACE_INDEX => "The index of the next hop interface.",
+    FWPM_CONDITION_ORIGINAL_PROFILE_ID => "The network profile of the connection when it was authorized.",
+    FWPM_CONDITION_CURRENT_PROFILE_ID => "The current network profile.",
+    FWPM_CONDITION_LOCAL_INTERFACE_PROFILE_ID => "The network profile of the local interface.",
+    FWPM_CONDITION_ARRIVAL_INTERFACE_PROFILE_ID => "The network profile of the arrival interface.",
+    FWPM_CONDITION_NEXTHOP_INTERFACE_PROFILE_ID => "The network profile of the next hop interface.",
+    FWPM_CONDITION_REAUTHORIZE_REASON => "Why the connection is authorized again.",
+    FWPM_CONDITION_ORIGINAL_ICMP_TYPE => "The ICMP type of the original packet of an ICMP error.",
+    FWPM_CONDITION_IP_PHYSICAL_ARRIVAL_INTERFACE => "The LUID of the physical arrival interface.",
+    FWPM_CONDITION_IP_PHYSICAL_NEXTHOP_INTERFACE => "The LUID of the physical next hop interface.",
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH => "The quarantine epoch of the interface.",
+    FWPM_CONDITION_INTERFACE_TYPE => "The IANA type of the local interface.",
+    FWPM_CONDITION_TUNNEL_TYPE => "The tunnel type of the local interface.",
+    FWPM_CONDITION_IP_FORWARD_INTERFACE => "The LUID of the interface a packet is forwarded to.",
+    FWPM_CONDITION_IP_PROTOCOL => "The IP protocol, e.g. 6 for TCP.",
+    FWPM_CONDITION_IP_LOCAL_PORT => "The local transport port.",
+    FWPM_CONDITION_IP_REMOTE_PORT => "The remote transport port.",
+    FWPM_CONDITION_EMBEDDED_LOCAL_ADDRESS_TYPE => "The local address type of the packet embedded in an ICMP error.",
+    FWPM_CONDITION_EMBEDDED_REMOTE_ADDRESS => "The remote address of the packet embedded in an ICMP error.",
+    FWPM_CONDITION_EMBEDDED_PROTOCOL => "The protocol of the packet embedded in an ICMP error.",
+    FWPM_CONDITION_EMBEDDED_LOCAL_PORT => "The local port of the packet embedded in an ICMP error.",
+    FWPM_CONDITION_EMBEDDED_REMOTE_PORT => "The remote port of the packet embedded in an ICMP error.",
+    FWPM_CONDITION_FLAGS => "The FWP_CONDITION_FLAG_* of the traffic.",
+    FWPM_CONDITION_DIRECTION => "The direction of the traffic, a FWP_DIRECTION.",
+    FWPM_CONDITION_INTERFACE_INDEX => "The index of the local interface.",
+    FWPM_CONDITION_SUB_INTERFACE_INDEX => "The index of the sub-interface of the local interface.",
+    FWPM_CONDITION_SOURCE_INTERFACE_INDEX => "The index of the interface a forwarded packet came from.",
+    FWPM_CONDITION_SOURCE_SUB_INTERFACE_INDEX => "The sub-interface index of the source interface.",
+    FWPM_CONDITION_DESTINATION_INTERFACE_INDEX => "The index of the interface a forwarded packet goes to.",
+    FWPM_CONDITION_DESTINATION_SUB_INTERFACE_INDEX => "The sub-interface index of the destination interface.",
+    FWPM_CONDITION_ALE_APP_ID => "The application, a NT device path from FwpmGetAppIdFromFileName0.",
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID => "The application that originally made a proxied connection.",
+    FWPM_CONDITION_ALE_USER_ID => "The security descriptor of the local user.",
+    FWPM_CONDITION_ALE_REMOTE_USER_ID => "The security descriptor of the remote user.",
+    FWPM_CONDITION_ALE_REMOTE_MACHINE_ID => "The security descriptor of the remote machine.",
+    FWPM_CONDITION_ALE_PROMISCUOUS_MODE => "Whether the socket is in promiscuous mode.",
+    FWPM_CONDITION_ALE_SIO_FIREWALL_SYSTEM_PORT => "Whether the port is a system port of the firewall.",
+    FWPM_CONDITION_ALE_REAUTH_REASON => "Why the connection is authorized again.",
+    FWPM_CONDITION_ALE_NAP_CONTEXT => "The NAP context.",
+    FWPM_CONDITION_KM_AUTH_NAP_CONTEXT => "The NAP context of a keying module authorization.",
+    FWPM_CONDITION_REMOTE_USER_TOKEN => "The token of the remote user of an RPC call.",
+    FWPM_CONDITION_RPC_IF_UUID => "The UUID of the RPC interface.",
+    FWPM_CONDITION_RPC_IF_VERSION => "The version of the RPC interface.",
+    FWPM_CONDITION_RPC_IF_FLAG => "The flags of the RPC interface.",
+    FWPM_CONDITION_DCOM_APP_ID => "The DCOM application ID.",
+    FWPM_CONDITION_IMAGE_NAME => "The image name of the RPC server.",
+    FWPM_CONDITION_RPC_PROTOCOL => "The RPC protocol sequence.",
+    FWPM_CONDITION_RPC_AUTH_TYPE => "The RPC authentication service.",
+    FWPM_CONDITION_RPC_AUTH_LEVEL => "The RPC authentication level.",
+    FWPM_CONDITION_SEC_ENCRYPT_ALGORITHM => "The encryption algorithm of the RPC security.",
+    FWPM_CONDITION_SEC_KEY_SIZE => "The key size of the RPC security.",
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V4 => "The local IPv4 address of an RPC call.",
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V6 => "The local IPv6 address of an RPC call.",
+    FWPM_CONDITION_PIPE => "The named pipe of an RPC call.",
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V4 => "The remote IPv4 address of an RPC call.",
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V6 => "The remote IPv6 address of an RPC call.",
+    FWPM_CONDITION_PROCESS_WITH_RPC_IF_UUID => "The UUID of the RPC interface registered by the process.",
+    FWPM_CONDITION_RPC_EP_VALUE => "The value of the RPC endpoint.",
+    FWPM_CONDITION_RPC_EP_FLAGS => "The flags of the RPC endpoint.",
+    FWPM_CONDITION_CLIENT_TOKEN => "The token of the RPC over HTTP client.",
+    FWPM_CONDITION_RPC_SERVER_NAME => "The name of the RPC server behind the proxy.",
+    FWPM_CONDITION_RPC_SERVER_PORT => "The port of the RPC server behind the proxy.",
+    FWPM_CONDITION_RPC_PROXY_AUTH_TYPE => "The authentication of the RPC over HTTP proxy.",
+    FWPM_CONDITION_CLIENT_CERT_KEY_LENGTH => "The key length of the client certificate.",
+    FWPM_CONDITION_CLIENT_CERT_OID => "The OID of the client certificate.",
+    FWPM_CONDITION_NET_EVENT_TYPE => "The type of a net event.",
+    FWPM_CONDITION_PEER_NAME => "The name of the peer.",
+    FWPM_CONDITION_REMOTE_ID => "The remote identity of a keying module negotiation.",
+    FWPM_CONDITION_AUTHENTICATION_TYPE => "The authentication type of a keying module negotiation.",
+    FWPM_CONDITION_KM_TYPE => "The keying module, IKE, AuthIP or IKEv2.",
+    FWPM_CONDITION_KM_MODE => "The keying module mode, main, quick or extended.",
+    FWPM_CONDITION_IPSEC_POLICY_KEY => "The key of the IPsec policy.",
+    FWPM_CONDITION_QM_MODE => "The quick mode, transport or tunnel.",
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID => "The IPsec security realm.",
+    FWPM_CONDITION_COMPARTMENT_ID => "The routing compartment.",
+    FWPM_CONDITION_ALE_PACKAGE_ID => "The security identifier of the app container package.",
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE => "The fully qualified binary name of the application.",
+    FWPM_CONDITION_ALE_EFFECTIVE_NAME => "The effective name of the remote peer.",
+    FWPM_CONDITION_MAC_LOCAL_ADDRESS => "The local MAC address.",
+    FWPM_CONDITION_MAC_REMOTE_ADDRESS => "The remote MAC address.",
+    FWPM_CONDITION_MAC_SOURCE_ADDRESS => "The source MAC address.",
+    FWPM_CONDITION_MAC_DESTINATION_ADDRESS => "The destination MAC address.",
+    FWPM_CONDITION_MAC_LOCAL_ADDRESS_TYPE => "The type of the local MAC address, a DL_ADDRESS_TYPE.",
+    FWPM_CONDITION_MAC_REMOTE_ADDRESS_TYPE => "The type of the remote MAC address, a DL_ADDRESS_TYPE.",
+    FWPM_CONDITION_MAC_SOURCE_ADDRESS_TYPE => "The type of the source MAC address, a DL_ADDRESS_TYPE.",
+    FWPM_CONDITION_MAC_DESTINATION_ADDRESS_TYPE => "The type of the destination MAC address, a DL_ADDRESS_TYPE.",
+    FWPM_CONDITION_ETHER_TYPE => "The EtherType of the frame.",
+    FWPM_CONDITION_VLAN_ID => "The VLAN ID of the frame.",
+    FWPM_CONDITION_INTERFACE_MAC_ADDRESS => "The MAC address of the local interface.",
+    FWPM_CONDITION_NDIS_PORT => "The NDIS port of the frame.",
+    FWPM_CONDITION_NDIS_MEDIA_TYPE => "The NDIS media type of the interface.",
+    FWPM_CONDITION_NDIS_PHYSICAL_MEDIA_TYPE => "The NDIS physical media type of the interface.",
+    FWPM_CONDITION_L2_FLAGS => "The FWP_CONDITION_L2_* flags of the frame.",
+    FWPM_CONDITION_IP_SOURCE_PORT => "The source port in a Hyper-V switch.",
+    FWPM_CONDITION_IP_DESTINATION_PORT => "The destination port in a Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_ID => "The ID of the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_NETWORK_TYPE => "The network type of the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_ID => "The source interface in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_ID => "The destination interface in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_SOURCE_VM_ID => "The source virtual machine in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_DESTINATION_VM_ID => "The destination virtual machine in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_TYPE => "The type of the source interface in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_TYPE => "The type of the destination interface in the Hyper-V switch.",
+    FWPM_CONDITION_VSWITCH_TENANT_NETWORK_ID => "The tenant network of the Hyper-V switch.",
+    FWPM_CONDITION_RESERVED0 => "Reserved.",
+    FWPM_CONDITION_RESERVED1 => "Reserved.",
+    FWPM_CONDITION_RESERVED2 => "Reserved.",
+    FWPM_CONDITION_RESERVED3 => "Reserved.",
+    FWPM_CONDITION_RESERVED4 => "Reserved.",
+    FWPM_CONDITION_RESERVED5 => "Reserved.",
+    FWPM_CONDITION_RESERVED6 => "Reserved.",
+    FWPM_CONDITION_RESERVED7 => "Reserved.",
+    FWPM_CONDITION_RESERVED8 => "Reserved.",
+    FWPM_CONDITION_RESERVED9 => "Reserved.",
+    FWPM_CONDITION_RESERVED10 => "Reserved.",
+    FWPM_CONDITION_RESERVED11 => "Reserved.",
+    FWPM_CONDITION_RESERVED12 => "Reserved.",
+    FWPM_CONDITION_RESERVED13 => "Reserved.",
+    FWPM_CONDITION_RESERVED14 => "Reserved.",
+    FWPM_CONDITION_RESERVED15 => "Reserved.",
+];
+
+const IP_PACKET: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+];
+
+const IP_FORWARD: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_SOURCE_ADDRESS,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_IP_FORWARD_INTERFACE,
+    FWPM_CONDITION_SOURCE_INTERFACE_INDEX,
+    FWPM_CONDITION_SOURCE_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_DESTINATION_INTERFACE_INDEX,
+    FWPM_CONDITION_DESTINATION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_IP_PHYSICAL_ARRIVAL_INTERFACE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_IP_PHYSICAL_NEXTHOP_INTERFACE,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const TRANSPORT: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+];
+
+const STREAM: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_DIRECTION,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const STREAM_PACKET: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_DIRECTION,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const DATAGRAM_DATA: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_DIRECTION,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const INBOUND_ICMP_ERROR: &[[u8; 16]] = &[
+    FWPM_CONDITION_EMBEDDED_PROTOCOL,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_EMBEDDED_REMOTE_ADDRESS,
+    FWPM_CONDITION_EMBEDDED_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_EMBEDDED_LOCAL_PORT,
+    FWPM_CONDITION_EMBEDDED_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_ORIGINAL_ICMP_TYPE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_INDEX,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_TYPE,
+    FWPM_CONDITION_ARRIVAL_TUNNEL_TYPE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const OUTBOUND_ICMP_ERROR: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_ORIGINAL_ICMP_TYPE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_RESOURCE_ASSIGNMENT: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_ALE_PROMISCUOUS_MODE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_LOCAL_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_ALE_SIO_FIREWALL_SYSTEM_PORT,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_AUTH_LISTEN: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_LOCAL_INTERFACE_PROFILE_ID,
+    FWPM_CONDITION_ALE_SIO_FIREWALL_SYSTEM_PORT,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_AUTH_RECV_ACCEPT: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_ALE_REMOTE_USER_ID,
+    FWPM_CONDITION_ALE_REMOTE_MACHINE_ID,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_SIO_FIREWALL_SYSTEM_PORT,
+    FWPM_CONDITION_ALE_NAP_CONTEXT,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_TYPE,
+    FWPM_CONDITION_ARRIVAL_TUNNEL_TYPE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_INDEX,
+    FWPM_CONDITION_NEXTHOP_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_IP_NEXTHOP_INTERFACE,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_TYPE,
+    FWPM_CONDITION_NEXTHOP_TUNNEL_TYPE,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_INDEX,
+    FWPM_CONDITION_ORIGINAL_PROFILE_ID,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_REAUTHORIZE_REASON,
+    FWPM_CONDITION_ORIGINAL_ICMP_TYPE,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_AUTH_CONNECT: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_ALE_REMOTE_USER_ID,
+    FWPM_CONDITION_ALE_REMOTE_MACHINE_ID,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_IP_ARRIVAL_INTERFACE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_TYPE,
+    FWPM_CONDITION_ARRIVAL_TUNNEL_TYPE,
+    FWPM_CONDITION_ARRIVAL_INTERFACE_INDEX,
+    FWPM_CONDITION_NEXTHOP_SUB_INTERFACE_INDEX,
+    FWPM_CONDITION_IP_NEXTHOP_INTERFACE,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_TYPE,
+    FWPM_CONDITION_NEXTHOP_TUNNEL_TYPE,
+    FWPM_CONDITION_NEXTHOP_INTERFACE_INDEX,
+    FWPM_CONDITION_ORIGINAL_PROFILE_ID,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_REAUTHORIZE_REASON,
+    FWPM_CONDITION_PEER_NAME,
+    FWPM_CONDITION_ORIGINAL_ICMP_TYPE,
+    FWPM_CONDITION_INTERFACE_QUARANTINE_EPOCH,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_ALE_EFFECTIVE_NAME,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_FLOW_ESTABLISHED: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_ALE_REMOTE_USER_ID,
+    FWPM_CONDITION_ALE_REMOTE_MACHINE_ID,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_DIRECTION,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_TUNNEL_TYPE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ORIGINAL_PROFILE_ID,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_CONNECT_REDIRECT: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_ALE_EFFECTIVE_NAME,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const OUTBOUND_NETWORK_CONNECTION_POLICY: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_ORIGINAL_APP_ID,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_BIND_REDIRECT: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_ALE_SECURITY_ATTRIBUTE_FQBN_VALUE,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_RESOURCE_RELEASE: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const ALE_ENDPOINT_CLOSURE: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_FLAGS,
+    FWPM_CONDITION_ALE_PACKAGE_ID,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const MAC_FRAME_ETHERNET: &[[u8; 16]] = &[
+    FWPM_CONDITION_INTERFACE_MAC_ADDRESS,
+    FWPM_CONDITION_MAC_LOCAL_ADDRESS,
+    FWPM_CONDITION_MAC_REMOTE_ADDRESS,
+    FWPM_CONDITION_MAC_LOCAL_ADDRESS_TYPE,
+    FWPM_CONDITION_MAC_REMOTE_ADDRESS_TYPE,
+    FWPM_CONDITION_ETHER_TYPE,
+    FWPM_CONDITION_VLAN_ID,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_NDIS_PORT,
+    FWPM_CONDITION_L2_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const MAC_FRAME_NATIVE: &[[u8; 16]] = &[
+    FWPM_CONDITION_NDIS_MEDIA_TYPE,
+    FWPM_CONDITION_NDIS_PHYSICAL_MEDIA_TYPE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_INTERFACE_TYPE,
+    FWPM_CONDITION_INTERFACE_INDEX,
+    FWPM_CONDITION_NDIS_PORT,
+    FWPM_CONDITION_L2_FLAGS,
+    FWPM_CONDITION_COMPARTMENT_ID,
+];
+
+const VSWITCH_ETHERNET: &[[u8; 16]] = &[
+    FWPM_CONDITION_MAC_SOURCE_ADDRESS,
+    FWPM_CONDITION_MAC_SOURCE_ADDRESS_TYPE,
+    FWPM_CONDITION_MAC_DESTINATION_ADDRESS,
+    FWPM_CONDITION_MAC_DESTINATION_ADDRESS_TYPE,
+    FWPM_CONDITION_ETHER_TYPE,
+    FWPM_CONDITION_VLAN_ID,
+    FWPM_CONDITION_VSWITCH_TENANT_NETWORK_ID,
+    FWPM_CONDITION_VSWITCH_ID,
+    FWPM_CONDITION_VSWITCH_NETWORK_TYPE,
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_ID,
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_TYPE,
+    FWPM_CONDITION_VSWITCH_SOURCE_VM_ID,
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_ID,
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_TYPE,
+    FWPM_CONDITION_VSWITCH_DESTINATION_VM_ID,
+    FWPM_CONDITION_L2_FLAGS,
+];
+
+const VSWITCH_TRANSPORT: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_SOURCE_ADDRESS,
+    FWPM_CONDITION_IP_DESTINATION_ADDRESS,
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_SOURCE_PORT,
+    FWPM_CONDITION_IP_DESTINATION_PORT,
+    FWPM_CONDITION_VLAN_ID,
+    FWPM_CONDITION_VSWITCH_TENANT_NETWORK_ID,
+    FWPM_CONDITION_VSWITCH_ID,
+    FWPM_CONDITION_VSWITCH_NETWORK_TYPE,
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_ID,
+    FWPM_CONDITION_VSWITCH_SOURCE_INTERFACE_TYPE,
+    FWPM_CONDITION_VSWITCH_SOURCE_VM_ID,
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_ID,
+    FWPM_CONDITION_VSWITCH_DESTINATION_INTERFACE_TYPE,
+    FWPM_CONDITION_VSWITCH_DESTINATION_VM_ID,
+    FWPM_CONDITION_L2_FLAGS,
+];
+
+const IPSEC_KM_DEMUX: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_QM_MODE,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+];
+
+const IPSEC: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_PROTOCOL,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_IP_REMOTE_PORT,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+];
+
+const IKEEXT: &[[u8; 16]] = &[
+    FWPM_CONDITION_IP_LOCAL_ADDRESS,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_IP_LOCAL_INTERFACE,
+    FWPM_CONDITION_CURRENT_PROFILE_ID,
+    FWPM_CONDITION_IPSEC_SECURITY_REALM_ID,
+];
+
+const KM_AUTHORIZATION: &[[u8; 16]] = &[
+    FWPM_CONDITION_REMOTE_ID,
+    FWPM_CONDITION_AUTHENTICATION_TYPE,
+    FWPM_CONDITION_KM_TYPE,
+    FWPM_CONDITION_DIRECTION,
+    FWPM_CONDITION_KM_MODE,
+    FWPM_CONDITION_IPSEC_POLICY_KEY,
+    FWPM_CONDITION_KM_AUTH_NAP_CONTEXT,
+];
+
+const RPC_UM: &[[u8; 16]] = &[
+    FWPM_CONDITION_REMOTE_USER_TOKEN,
+    FWPM_CONDITION_RPC_IF_UUID,
+    FWPM_CONDITION_RPC_IF_VERSION,
+    FWPM_CONDITION_RPC_IF_FLAG,
+    FWPM_CONDITION_DCOM_APP_ID,
+    FWPM_CONDITION_IMAGE_NAME,
+    FWPM_CONDITION_RPC_PROTOCOL,
+    FWPM_CONDITION_RPC_AUTH_TYPE,
+    FWPM_CONDITION_RPC_AUTH_LEVEL,
+    FWPM_CONDITION_SEC_ENCRYPT_ALGORITHM,
+    FWPM_CONDITION_SEC_KEY_SIZE,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V4,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V6,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_PIPE,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V4,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V6,
+];
+
+const RPC_EPMAP: &[[u8; 16]] = &[
+    FWPM_CONDITION_REMOTE_USER_TOKEN,
+    FWPM_CONDITION_RPC_IF_UUID,
+    FWPM_CONDITION_RPC_IF_VERSION,
+    FWPM_CONDITION_RPC_PROTOCOL,
+    FWPM_CONDITION_RPC_AUTH_TYPE,
+    FWPM_CONDITION_RPC_AUTH_LEVEL,
+    FWPM_CONDITION_SEC_ENCRYPT_ALGORITHM,
+    FWPM_CONDITION_SEC_KEY_SIZE,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V4,
+    FWPM_CONDITION_IP_LOCAL_ADDRESS_V6,
+    FWPM_CONDITION_IP_LOCAL_PORT,
+    FWPM_CONDITION_PIPE,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V4,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS_V6,
+];
+
+const RPC_EP_ADD: &[[u8; 16]] = &[
+    FWPM_CONDITION_PROCESS_WITH_RPC_IF_UUID,
+    FWPM_CONDITION_RPC_PROTOCOL,
+    FWPM_CONDITION_RPC_EP_VALUE,
+    FWPM_CONDITION_RPC_EP_FLAGS,
+];
+
+const RPC_PROXY_CONN: &[[u8; 16]] = &[
+    FWPM_CONDITION_CLIENT_TOKEN,
+    FWPM_CONDITION_RPC_SERVER_NAME,
+    FWPM_CONDITION_RPC_SERVER_PORT,
+    FWPM_CONDITION_RPC_PROXY_AUTH_TYPE,
+    FWPM_CONDITION_CLIENT_CERT_KEY_LENGTH,
+    FWPM_CONDITION_CLIENT_CERT_OID,
+];
+
+const RPC_PROXY_IF: &[[u8; 16]] = &[
+    FWPM_CONDITION_CLIENT_TOKEN,
+    FWPM_CONDITION_RPC_IF_UUID,
+    FWPM_CONDITION_RPC_IF_VERSION,
+    FWPM_CONDITION_RPC_SERVER_NAME,
+    FWPM_CONDITION_RPC_SERVER_PORT,
+    FWPM_CONDITION_RPC_PROXY_AUTH_TYPE,
+    FWPM_CONDITION_CLIENT_CERT_KEY_LENGTH,
+    FWPM_CONDITION_CLIENT_CERT_OID,
+];
+
+const NAME_RESOLUTION_CACHE: &[[u8; 16]] = &[
+    FWPM_CONDITION_ALE_USER_ID,
+    FWPM_CONDITION_ALE_APP_ID,
+    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+    FWPM_CONDITION_PEER_NAME,
+];
+
+fn layer_conditions(layer_key: &[u8; 16]) -> &'static [[u8; 16]] {
+    match *layer_key {
+        FWPM_LAYER_INBOUND_IPPACKET_V4
+        | FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD
+        | FWPM_LAYER_INBOUND_IPPACKET_V6
+        | FWPM_LAYER_INBOUND_IPPACKET_V6_DISCARD
+        | FWPM_LAYER_OUTBOUND_IPPACKET_V4
+        | FWPM_LAYER_OUTBOUND_IPPACKET_V4_DISCARD
+        | FWPM_LAYER_OUTBOUND_IPPACKET_V6
+        | FWPM_LAYER_OUTBOUND_IPPACKET_V6_DISCARD => IP_PACKET,
+        FWPM_LAYER_IPFORWARD_V4
+        | FWPM_LAYER_IPFORWARD_V4_DISCARD
+        | FWPM_LAYER_IPFORWARD_V6
+        | FWPM_LAYER_IPFORWARD_V6_DISCARD => IP_FORWARD,
+        FWPM_LAYER_INBOUND_TRANSPORT_V4
+        | FWPM_LAYER_INBOUND_TRANSPORT_V4_DISCARD
+        | FWPM_LAYER_INBOUND_TRANSPORT_V6
+        | FWPM_LAYER_INBOUND_TRANSPORT_V6_DISCARD
+        | FWPM_LAYER_OUTBOUND_TRANSPORT_V4
+        | FWPM_LAYER_OUTBOUND_TRANSPORT_V4_DISCARD
+        | FWPM_LAYER_OUTBOUND_TRANSPORT_V6
+        | FWPM_LAYER_OUTBOUND_TRANSPORT_V6_DISCARD => TRANSPORT,
+        FWPM_LAYER_STREAM_V4
+        | FWPM_LAYER_STREAM_V4_DISCARD
+        | FWPM_LAYER_STREAM_V6
+        | FWPM_LAYER_STREAM_V6_DISCARD => STREAM,
+        FWPM_LAYER_STREAM_PACKET_V4 | FWPM_LAYER_STREAM_PACKET_V6 => STREAM_PACKET,
+        FWPM_LAYER_DATAGRAM_DATA_V4
+        | FWPM_LAYER_DATAGRAM_DATA_V4_DISCARD
+        | FWPM_LAYER_DATAGRAM_DATA_V6
+        | FWPM_LAYER_DATAGRAM_DATA_V6_DISCARD => DATAGRAM_DATA,
+        FWPM_LAYER_INBOUND_ICMP_ERROR_V4
+        | FWPM_LAYER_INBOUND_ICMP_ERROR_V4_DISCARD
+        | FWPM_LAYER_INBOUND_ICMP_ERROR_V6
+        | FWPM_LAYER_INBOUND_ICMP_ERROR_V6_DISCARD => INBOUND_ICMP_ERROR,
+        FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4
+        | FWPM_LAYER_OUTBOUND_ICMP_ERROR_V4_DISCARD
+        | FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6
+        | FWPM_LAYER_OUTBOUND_ICMP_ERROR_V6_DISCARD => OUTBOUND_ICMP_ERROR,
+        FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4
+        | FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V4_DISCARD
+        | FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6
+        | FWPM_LAYER_ALE_RESOURCE_ASSIGNMENT_V6_DISCARD => ALE_RESOURCE_ASSIGNMENT,
+        FWPM_LAYER_ALE_AUTH_LISTEN_V4
+        | FWPM_LAYER_ALE_AUTH_LISTEN_V4_DISCARD
+        | FWPM_LAYER_ALE_AUTH_LISTEN_V6
+        | FWPM_LAYER_ALE_AUTH_LISTEN_V6_DISCARD => ALE_AUTH_LISTEN,
+        FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4
+        | FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V4_DISCARD
+        | FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6
+        | FWPM_LAYER_ALE_AUTH_RECV_ACCEPT_V6_DISCARD => ALE_AUTH_RECV_ACCEPT,
+        FWPM_LAYER_ALE_AUTH_CONNECT_V4
+        | FWPM_LAYER_ALE_AUTH_CONNECT_V4_DISCARD
+        | FWPM_LAYER_ALE_AUTH_CONNECT_V6
+        | FWPM_LAYER_ALE_AUTH_CONNECT_V6_DISCARD => ALE_AUTH_CONNECT,
+        FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4
+        | FWPM_LAYER_ALE_FLOW_ESTABLISHED_V4_DISCARD
+        | FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6
+        | FWPM_LAYER_ALE_FLOW_ESTABLISHED_V6_DISCARD => ALE_FLOW_ESTABLISHED,
+        FWPM_LAYER_ALE_CONNECT_REDIRECT_V4 | FWPM_LAYER_ALE_CONNECT_REDIRECT_V6 => {
+            ALE_CONNECT_REDIRECT
+        }
+        FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V4
+        | FWPM_LAYER_OUTBOUND_NETWORK_CONNECTION_POLICY_V6 => OUTBOUND_NETWORK_CONNECTION_POLICY,
+        FWPM_LAYER_ALE_BIND_REDIRECT_V4 | FWPM_LAYER_ALE_BIND_REDIRECT_V6 => ALE_BIND_REDIRECT,
+        FWPM_LAYER_ALE_RESOURCE_RELEASE_V4 | FWPM_LAYER_ALE_RESOURCE_RELEASE_V6 => {
+            ALE_RESOURCE_RELEASE
+        }
+        FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V4 | FWPM_LAYER_ALE_ENDPOINT_CLOSURE_V6 => {
+            ALE_ENDPOINT_CLOSURE
+        }
+        FWPM_LAYER_INBOUND_MAC_FRAME_ETHERNET | FWPM_LAYER_OUTBOUND_MAC_FRAME_ETHERNET => {
+            MAC_FRAME_ETHERNET
+        }
+        FWPM_LAYER_INBOUND_MAC_FRAME_NATIVE | FWPM_LAYER_OUTBOUND_MAC_FRAME_NATIVE => {
+            MAC_FRAME_NATIVE
+        }
+        FWPM_LAYER_INGRESS_VSWITCH_ETHERNET | FWPM_LAYER_EGRESS_VSWITCH_ETHERNET => {
+            VSWITCH_ETHERNET
+        }
+        FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V4
+        | FWPM_LAYER_INGRESS_VSWITCH_TRANSPORT_V6
+        | FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V4
+        | FWPM_LAYER_EGRESS_VSWITCH_TRANSPORT_V6 => VSWITCH_TRANSPORT,
+        FWPM_LAYER_IPSEC_KM_DEMUX_V4 | FWPM_LAYER_IPSEC_KM_DEMUX_V6 => IPSEC_KM_DEMUX,
+        FWPM_LAYER_IPSEC_V4 | FWPM_LAYER_IPSEC_V6 => IPSEC,
+        FWPM_LAYER_IKEEXT_V4 | FWPM_LAYER_IKEEXT_V6 => IKEEXT,
+        FWPM_LAYER_KM_AUTHORIZATION => KM_AUTHORIZATION,
+        FWPM_LAYER_RPC_UM => RPC_UM,
+        FWPM_LAYER_RPC_EPMAP => RPC_EPMAP,
+        FWPM_LAYER_RPC_EP_ADD => RPC_EP_ADD,
+        FWPM_LAYER_RPC_PROXY_CONN => RPC_PROXY_CONN,
+        FWPM_LAYER_RPC_PROXY_IF => RPC_PROXY_IF,
+        FWPM_LAYER_NAME_RESOLUTION_CACHE_V4 | FWPM_LAYER_NAME_RESOLUTION_CACHE_V6 => {
+            NAME_RESOLUTION_CACHE
+        }
+        // the fast and reserved layers
+        _ => &[],
+    }
+}
+
+/// All the entries: the layers, the sublayers, the conditions and the
+/// providers.
+pub fn entries() -> impl Iterator<Item = &'static Entry> {
+    LAYERS
+        .iter()
+        .chain(SUB_LAYERS)
+        .chain(CONDITIONS)
+        .chain(PROVIDERS)
+}
+
+pub fn by_key(key: &[u8; 16]) -> Option<&'static Entry> {
+    entries().find(|entry| entry.key == *key)
+}
+
+/// Look up the name of a constant, e.g. `FWPM_LAYER_ALE_AUTH_CONNECT_V4`,
+/// with or without the `FWPM_` prefix and in any case.
+pub fn by_name(name: &str) -> Option<&'static Entry> {
+    entries().find(|entry| {
+        entry.name.eq_ignore_ascii_case(name) || entry.name[5..].eq_ignore_ascii_case(name)
+    })
+}
+
+/// The name of a well-known GUID, or the GUID itself, for dumps.
+pub fn name_of(key: &[u8; 16]) -> Cow<'static, str> {
+    match by_key(key) {
+        Some(entry) => entry.name.into(),
+        None => guid_to_string(key).into(),
+    }
+}
+
+/// Whether a condition field is available at a layer, `None` if the layer
+/// isn't known.
+pub fn is_available(layer_key: &[u8; 16], field_key: &[u8; 16]) -> Option<bool> {
+    let layer = by_key(layer_key).filter(|entry| entry.kind == Kind::Layer)?;
+    Some(layer.conditions().contains(field_key))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::collections::HashSet;
+
+    #[test]
+    fn test_lookups() {
+        let layer = by_name("FWPM_LAYER_ALE_AUTH_CONNECT_V6").unwrap();
+        assert_eq!(layer.kind, Kind::Layer);
+        assert_eq!(layer.key, FWPM_LAYER_ALE_AUTH_CONNECT_V6);
+        assert_eq!(layer.family, AddressFamily::Ipv6);
+        assert_eq!(by_key(&FWPM_LAYER_ALE_AUTH_CONNECT_V6), Some(layer));
+        assert_eq!(by_name("layer_ale_auth_connect_v6"), Some(layer));
+
+        let sub_layer = by_key(&FWPM_SUBLAYER_MPSSVC_WF).unwrap();
+        assert_eq!(sub_layer.name, "FWPM_SUBLAYER_MPSSVC_WF");
+        assert_eq!(sub_layer.kind, Kind::SubLayer);
+        assert_eq!(sub_layer.conditions(), &[] as &[[u8; 16]]);
+        assert_eq!(
+            by_key(&FWPM_PROVIDER_MPSSVC_WF).unwrap().kind,
+            Kind::Provider
+        );
+
+        let condition = by_name("FWPM_CONDITION_IP_REMOTE_ADDRESS_V4").unwrap();
+        assert_eq!(condition.kind, Kind::Condition);
+        assert_eq!(condition.family, AddressFamily::Ipv4);
+        assert_eq!(
+            by_key(&FWPM_LAYER_INBOUND_IPPACKET_V4_DISCARD)
+                .unwrap()
+                .family,
+            AddressFamily::Ipv4
+        );
+        assert_eq!(
+            by_key(&FWPM_LAYER_RPC_UM).unwrap().family,
+            AddressFamily::Unspec
+        );
+
+        assert_eq!(
+            name_of(&FWPM_CONDITION_ALE_APP_ID),
+            "FWPM_CONDITION_ALE_APP_ID"
+        );
+        assert_eq!(name_of(&[0x11; 16]), "11111111-1111-1111-1111-111111111111");
+        assert_eq!(by_name("FWPM_LAYER_NOPE"), None);
+    }
+
+    #[test]
+    fn test_conditions() {
+        let connect = FWPM_LAYER_ALE_AUTH_CONNECT_V4;
+        assert_eq!(
+            is_available(&connect, &FWPM_CONDITION_IP_REMOTE_PORT),
+            Some(true)
+        );
+        assert_eq!(
+            is_available(&connect, &FWPM_CONDITION_ETHER_TYPE),
+            Some(false)
+        );
+        assert_eq!(
+            is_available(
+                &FWPM_LAYER_INBOUND_TRANSPORT_FAST,
+                &FWPM_CONDITION_IP_PROTOCOL
+            ),
+            Some(false)
+        );
+        assert_eq!(is_available(&[1; 16], &FWPM_CONDITION_IP_PROTOCOL), None);
+        assert_eq!(
+            is_available(&FWPM_CONDITION_IP_PROTOCOL, &FWPM_CONDITION_IP_PROTOCOL),
+            None
+        );
+    }
+
+    #[test]
+    fn test_catalogue_is_consistent() {
+        let mut keys = HashSet::new();
+        let mut names = HashSet::new();
+        for entry in entries() {
+            assert!(keys.insert(entry.key), "duplicate key {}", entry.name);
+            assert!(names.insert(entry.name), "duplicate name {}", entry.name);
+            let prefix = match entry.kind {
+                Kind::Layer => "FWPM_LAYER_",
+                Kind::SubLayer => "FWPM_SUBLAYER_",
+                Kind::Condition => "FWPM_CONDITION_",
+                Kind::Provider => "FWPM_PROVIDER_",
+            };
+            assert!(entry.name.starts_with(prefix), "{}", entry.name);
+            assert!(entry.description.ends_with('.'), "{}", entry.name);
+            for condition in entry.conditions() {
+                assert_eq!(
+                    by_key(condition).map(|entry| entry.kind),
+                    Some(Kind::Condition)
+                );
+            }
+        }
+        assert_eq!(LAYERS.len(), 97);
+        assert_eq!(SUB_LAYERS.len(), 18);
+        assert_eq!(CONDITIONS.len(), 136);
+        assert_eq!(PROVIDERS.len(), 9);
+    }
+}
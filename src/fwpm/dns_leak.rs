@@ -0,0 +1,527 @@
+//! A policy against DNS leaks, compiled to WFP filters.
+//!
+//! Like sing-tun in TUN mode, plaintext DNS is blocked on every interface
+//! but the allowed ones, usually the TUN, and to every resolver but the
+//! allowed ones. [`DnsLeakPolicy::compile`] turns the policy into a
+//! sublayer and the filters of the ALE connect layers for IPv4 and IPv6,
+//! which can be inspected, run through an [`Arbiter`], and added with
+//! [`CompiledPolicy::install`].
+//!
+//! All the filters clear the action right: the permits resist the soft
+//! blocks of the other sublayers, and the blocks veto their permits.
+
+use std::fmt;
+use std::net::IpAddr;
+
+use super::arbitration::Arbiter;
+use super::catalogue;
+use super::filter::*;
+use super::keys::*;
+use super::{DisplayData, SubLayer};
+use crate::error::*;
+
+const UDP: u8 = 17;
+const TCP: u8 = 6;
+const DNS_PORT: u16 = 53;
+const DOT_PORT: u16 = 853;
+
+/// The weights of the filters within the sublayer, the permits first.
+const PERMIT_WEIGHT: u64 = 2;
+const BLOCK_WEIGHT: u64 = 1;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DnsLeakPolicy {
+    /// The name of the sublayer and the prefix of the names of the filters.
+    pub name: String,
+    pub sub_layer_key: [u8; 16],
+    /// The weight of the sublayer, the highest by default so that the
+    /// policy is evaluated first.
+    pub weight: u16,
+    /// The LUIDs of the interfaces DNS is allowed on.
+    pub interfaces: Vec<u64>,
+    /// The resolvers DNS is allowed to, on any interface.
+    pub resolvers: Vec<IpAddr>,
+    /// Block DNS over TCP too, on by default.
+    pub tcp: bool,
+    /// Block DNS over TLS, TCP port 853.
+    pub dns_over_tls: bool,
+}
+
+impl DnsLeakPolicy {
+    pub fn new(name: impl Into<String>, sub_layer_key: [u8; 16]) -> Self {
+        DnsLeakPolicy {
+            name: name.into(),
+            sub_layer_key,
+            weight: u16::MAX,
+            interfaces: vec![],
+            resolvers: vec![],
+            tcp: true,
+            dns_over_tls: false,
+        }
+    }
+
+    pub fn weight(mut self, weight: u16) -> Self {
+        self.weight = weight;
+        self
+    }
+
+    pub fn allow_interface(mut self, luid: u64) -> Self {
+        self.interfaces.push(luid);
+        self
+    }
+
+    pub fn allow_resolver(mut self, addr: IpAddr) -> Self {
+        self.resolvers.push(addr);
+        self
+    }
+
+    pub fn tcp(mut self, tcp: bool) -> Self {
+        self.tcp = tcp;
+        self
+    }
+
+    pub fn dns_over_tls(mut self, dns_over_tls: bool) -> Self {
+        self.dns_over_tls = dns_over_tls;
+        self
+    }
+
+    /// The blocked protocols and ports.
+    fn blocked(&self) -> Vec<(u8, u16)> {
+        let mut blocked = vec![(UDP, DNS_PORT)];
+        if self.tcp {
+            blocked.push((TCP, DNS_PORT));
+        }
+        if self.dns_over_tls {
+            blocked.push((TCP, DOT_PORT));
+        }
+        blocked
+    }
+
+    /// The permits of the blocked traffic for the given conditions, one per
+    /// protocol and port: conditions on the protocol and the port in one
+    /// filter would also permit e.g. UDP/853.
+    fn permits(
+        &self,
+        layer_key: [u8; 16],
+        what: &str,
+        conditions: &[FilterCondition],
+    ) -> Result<Vec<Filter>> {
+        self.blocked()
+            .into_iter()
+            .map(|(protocol, port)| {
+                let builder = Filter::builder(
+                    format!(
+                        "{}: permit {}/{port} {what}",
+                        self.name,
+                        protocol_name(protocol)
+                    ),
+                    layer_key,
+                    FilterAction::Permit,
+                )
+                .sub_layer(self.sub_layer_key)
+                .weight(FilterWeight::Exact(PERMIT_WEIGHT))
+                .flags(FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT);
+                conditions
+                    .iter()
+                    .cloned()
+                    .fold(builder, FilterBuilder::condition)
+                    .condition(FilterCondition::protocol(protocol))
+                    .condition(FilterCondition::remote_port(port))
+                    .build()
+            })
+            .collect()
+    }
+
+    /// Compile the policy, validating the filters.
+    pub fn compile(&self) -> Result<CompiledPolicy> {
+        let mut filters = vec![];
+        for (layer_key, is_ipv6) in [
+            (FWPM_LAYER_ALE_AUTH_CONNECT_V4, false),
+            (FWPM_LAYER_ALE_AUTH_CONNECT_V6, true),
+        ] {
+            if !self.interfaces.is_empty() {
+                let interfaces: Vec<_> = self
+                    .interfaces
+                    .iter()
+                    .map(|&luid| FilterCondition::local_interface(luid))
+                    .collect();
+                filters.extend(self.permits(
+                    layer_key,
+                    "on the allowed interfaces",
+                    &interfaces,
+                )?);
+            }
+            let resolvers: Vec<_> = self
+                .resolvers
+                .iter()
+                .filter(|addr| addr.is_ipv6() == is_ipv6)
+                .map(|&addr| FilterCondition::remote_network(addr.into()))
+                .collect();
+            if !resolvers.is_empty() {
+                filters.extend(self.permits(layer_key, "to the allowed resolvers", &resolvers)?);
+            }
+            for (protocol, port) in self.blocked() {
+                let filter = Filter::builder(
+                    format!("{}: block {}/{port}", self.name, protocol_name(protocol)),
+                    layer_key,
+                    FilterAction::Block,
+                )
+                .sub_layer(self.sub_layer_key)
+                .weight(FilterWeight::Exact(BLOCK_WEIGHT))
+                .flags(FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT)
+                .condition(FilterCondition::protocol(protocol))
+                .condition(FilterCondition::remote_port(port))
+                .build()?;
+                filters.push(filter);
+            }
+        }
+
+        // the filters have checked the name
+        let sub_layer = SubLayer {
+            sub_layer_key: self.sub_layer_key,
+            display_data: DisplayData::new(self.name.clone(), None),
+            flags: 0,
+            weight: self.weight,
+        };
+        Ok(CompiledPolicy { sub_layer, filters })
+    }
+}
+
+fn protocol_name(protocol: u8) -> &'static str {
+    if protocol == TCP {
+        "TCP"
+    } else {
+        "UDP"
+    }
+}
+
+/// The sublayer and the filters of a [`DnsLeakPolicy`].
+///
+/// The `Display` implementation is a readable dump, with the well-known
+/// GUIDs by their names.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CompiledPolicy {
+    pub sub_layer: SubLayer,
+    pub filters: Vec<Filter>,
+}
+
+impl CompiledPolicy {
+    /// An arbiter of the policy alone, the other sublayers and filters can
+    /// be added to see how the policy interacts with them.
+    pub fn arbiter(&self) -> Arbiter {
+        Arbiter::new(vec![self.sub_layer.clone()], self.filters.clone())
+    }
+
+    /// Add the sublayer and the filters in one transaction of a new
+    /// session, either all of them or none.
+    ///
+    /// The policy is removed when the returned guard is dropped.
+    #[cfg(windows)]
+    pub fn install(&self) -> Result<InstalledPolicy> {
+        let engine = super::Engine::open()?;
+        let filter_ids = engine.transaction(|| {
+            super::add_sub_layer_in(&engine, &self.sub_layer)?;
+            self.filters
+                .iter()
+                .map(|filter| super::add_filter(&engine, filter))
+                .collect::<Result<Vec<_>>>()
+        })?;
+        Ok(InstalledPolicy {
+            engine,
+            sub_layer_key: self.sub_layer.sub_layer_key,
+            filter_ids,
+        })
+    }
+}
+
+/// An installed [`CompiledPolicy`], removed when dropped.
+#[cfg(windows)]
+#[derive(Debug)]
+pub struct InstalledPolicy {
+    engine: super::Engine,
+    sub_layer_key: [u8; 16],
+    filter_ids: Vec<u64>,
+}
+
+#[cfg(windows)]
+impl InstalledPolicy {
+    /// The IDs of the filters, in the order of [`CompiledPolicy::filters`].
+    pub fn filter_ids(&self) -> &[u64] {
+        &self.filter_ids
+    }
+
+    fn remove(&self) -> Result<()> {
+        use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform as fwpm;
+
+        let handle = self.engine.handle();
+        self.engine.transaction(|| {
+            for &filter_id in &self.filter_ids {
+                let code = unsafe { fwpm::FwpmFilterDeleteById0(handle, filter_id) };
+                if code != 0 {
+                    return Err(Error::os(code).with_operation("FwpmFilterDeleteById0"));
+                }
+            }
+            let key = crate::utils::bytes_to_guid(self.sub_layer_key);
+            let code = unsafe { fwpm::FwpmSubLayerDeleteByKey0(handle, &key as *const _) };
+            if code != 0 {
+                return Err(Error::os(code).with_operation("FwpmSubLayerDeleteByKey0"));
+            }
+            Ok(())
+        })
+    }
+}
+
+#[cfg(windows)]
+impl Drop for InstalledPolicy {
+    fn drop(&mut self) {
+        // the session is dynamic, closing it removes what couldn't be deleted
+        if let Err(err) = self.remove() {
+            tracing::warn!(
+                "failed to remove the policy {}: {}",
+                crate::utils::guid_to_string(&self.sub_layer_key),
+                err
+            );
+        }
+    }
+}
+
+impl fmt::Display for CompiledPolicy {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        writeln!(
+            f,
+            "sublayer {} {:?} weight {}",
+            crate::utils::guid_to_string(&self.sub_layer.sub_layer_key),
+            self.sub_layer.display_data.name.to_string_lossy(),
+            self.sub_layer.weight
+        )?;
+        for filter in &self.filters {
+            writeln!(
+                f,
+                "{} {:?} {:?} {:?} flags {:#x}",
+                catalogue::name_of(&filter.layer_key),
+                filter.display_data.name.to_string_lossy(),
+                filter.action,
+                filter.weight,
+                filter.flags
+            )?;
+            for condition in &filter.conditions {
+                writeln!(f, "    {condition}")?;
+            }
+        }
+        Ok(())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use std::net::SocketAddr;
+
+    use super::*;
+    use crate::fwpm::arbitration::{Connection, Verdict};
+
+    const SUB_LAYER: [u8; 16] = [0x11; 16];
+    const TUN: u64 = 0x0006_0000_0100_0000;
+    const ETHERNET: u64 = 0x0006_0000_0200_0000;
+
+    fn policy() -> DnsLeakPolicy {
+        DnsLeakPolicy::new("tun", SUB_LAYER)
+            .allow_interface(TUN)
+            .allow_resolver("172.19.0.2".parse().unwrap())
+            .allow_resolver("fdfe:dcba:9876::2".parse().unwrap())
+    }
+
+    #[test]
+    fn test_compile() {
+        let compiled = policy().dns_over_tls(true).compile().unwrap();
+        let expected = r#"sublayer 11111111-1111-1111-1111-111111111111 "tun" weight 65535
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit UDP/53 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit TCP/53 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit TCP/853 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit UDP/53 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 172.19.0.2
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit TCP/53 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 172.19.0.2
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: permit TCP/853 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 172.19.0.2
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: block UDP/53" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: block TCP/53" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V4 "tun: block TCP/853" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit UDP/53 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit TCP/53 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit TCP/853 on the allowed interfaces" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_LOCAL_INTERFACE Equal 1688849877041152
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit UDP/53 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal fdfe:dcba:9876::2
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit TCP/53 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal fdfe:dcba:9876::2
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: permit TCP/853 to the allowed resolvers" Permit Exact(2) flags 0x8
+    FWPM_CONDITION_IP_REMOTE_ADDRESS Equal fdfe:dcba:9876::2
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: block UDP/53" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 17
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: block TCP/53" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 53
+FWPM_LAYER_ALE_AUTH_CONNECT_V6 "tun: block TCP/853" Block Exact(1) flags 0x8
+    FWPM_CONDITION_IP_PROTOCOL Equal 6
+    FWPM_CONDITION_IP_REMOTE_PORT Equal 853
+"#;
+        assert_eq!(compiled.to_string(), expected);
+    }
+
+    #[test]
+    fn test_options() {
+        let names = |policy: DnsLeakPolicy| -> Vec<String> {
+            let compiled = policy.compile().unwrap();
+            compiled
+                .filters
+                .iter()
+                .filter(|filter| filter.layer_key == FWPM_LAYER_ALE_AUTH_CONNECT_V4)
+                .map(|filter| filter.display_data.name.to_string_lossy())
+                .collect()
+        };
+        assert_eq!(
+            names(DnsLeakPolicy::new("x", SUB_LAYER).tcp(false)),
+            ["x: block UDP/53"]
+        );
+        assert_eq!(
+            names(
+                DnsLeakPolicy::new("x", SUB_LAYER)
+                    .tcp(false)
+                    .dns_over_tls(true)
+                    .allow_resolver("2001:db8::1".parse().unwrap())
+            ),
+            ["x: block UDP/53", "x: block TCP/853"]
+        );
+
+        let error = DnsLeakPolicy::new("x\0", SUB_LAYER).compile().unwrap_err();
+        assert!(matches!(
+            error.kind(),
+            ErrorKind::InvalidFilter {
+                condition: None,
+                reason: "display data contains a NUL"
+            }
+        ));
+    }
+
+    #[test]
+    fn test_verdicts() {
+        let arbiter = policy().compile().unwrap().arbiter();
+        let connection = |layer, protocol, remote: &str, luid| {
+            Connection::new(layer, protocol, remote.parse::<SocketAddr>().unwrap())
+                .local_interface(luid)
+        };
+        let v4 = FWPM_LAYER_ALE_AUTH_CONNECT_V4;
+        let v6 = FWPM_LAYER_ALE_AUTH_CONNECT_V6;
+        let cases = [
+            (connection(v4, UDP, "8.8.8.8:53", TUN), Verdict::Permit),
+            (connection(v4, UDP, "8.8.8.8:53", ETHERNET), Verdict::Block),
+            (connection(v4, TCP, "8.8.8.8:53", ETHERNET), Verdict::Block),
+            (
+                connection(v4, UDP, "172.19.0.2:53", ETHERNET),
+                Verdict::Permit,
+            ),
+            (
+                connection(v4, TCP, "8.8.8.8:443", ETHERNET),
+                Verdict::Permit,
+            ),
+            // DNS over TLS isn't blocked by default
+            (
+                connection(v4, TCP, "8.8.8.8:853", ETHERNET),
+                Verdict::Permit,
+            ),
+            (
+                connection(v6, UDP, "[2001:4860:4860::8888]:53", TUN),
+                Verdict::Permit,
+            ),
+            (
+                connection(v6, UDP, "[2001:4860:4860::8888]:53", ETHERNET),
+                Verdict::Block,
+            ),
+            (
+                connection(v6, UDP, "[fdfe:dcba:9876::2]:53", ETHERNET),
+                Verdict::Permit,
+            ),
+        ];
+        for (connection, verdict) in cases {
+            assert_eq!(
+                arbiter.evaluate(&connection).verdict,
+                verdict,
+                "{connection:?}"
+            );
+        }
+
+        // the permits don't cover UDP/853, which isn't blocked
+        let arbiter = policy().dns_over_tls(true).compile().unwrap().arbiter();
+        let evaluation = arbiter.evaluate(&connection(v4, UDP, "172.19.0.2:853", TUN));
+        assert_eq!(evaluation.filter, None);
+    }
+
+    #[test]
+    fn test_veto() {
+        // a hard permit of a higher sublayer can't override the blocks
+        let compiled = policy().weight(10).compile().unwrap();
+        let other = SubLayer {
+            sub_layer_key: [0x22; 16],
+            display_data: DisplayData::new("other".into(), None),
+            flags: 0,
+            weight: 20,
+        };
+        let permit = Filter::builder(
+            "permit",
+            FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            FilterAction::Permit,
+        )
+        .sub_layer(other.sub_layer_key)
+        .flags(FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT)
+        .build()
+        .unwrap();
+        let mut filters = compiled.filters.clone();
+        filters.push(permit);
+        let arbiter = Arbiter::new(vec![compiled.sub_layer.clone(), other], filters);
+        let connection = Connection::new(
+            FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            UDP,
+            "8.8.8.8:53".parse().unwrap(),
+        )
+        .local_interface(ETHERNET);
+        let evaluation = arbiter.evaluate(&connection);
+        assert_eq!(evaluation.verdict, Verdict::Block);
+        assert!(evaluation.vetoed);
+    }
+}
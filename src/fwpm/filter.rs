@@ -0,0 +1,1298 @@
+//! An owned model of `FWPM_FILTER0`.
+//!
+//! Filters are built with [`FilterBuilder`], which validates them the way
+//! the filter engine would, so a bad filter is reported before anything
+//! touches the OS.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use super::catalogue::{self, Entry, Kind};
+use super::keys::*;
+use super::DisplayData;
+use crate::error::*;
+use crate::network::IpNetwork;
+use crate::query::AddressFamily;
+
+pub const FWPM_FILTER_FLAG_PERSISTENT: u32 = 0x1;
+pub const FWPM_FILTER_FLAG_BOOTTIME: u32 = 0x2;
+pub const FWPM_FILTER_FLAG_HAS_PROVIDER_CONTEXT: u32 = 0x4;
+pub const FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT: u32 = 0x8;
+pub const FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED: u32 = 0x10;
+pub const FWPM_FILTER_FLAG_DISABLED: u32 = 0x20;
+pub const FWPM_FILTER_FLAG_INDEXED: u32 = 0x40;
+
+/// All the documented `FWPM_FILTER_FLAG_*`.
+const KNOWN_FLAGS: u32 = 0x3fff;
+
+/// The `FWP_DATA_TYPE` values of [`ConditionValue`].
+#[cfg_attr(not(windows), allow(dead_code))]
+mod data_type {
+    pub const UINT8: u32 = 1;
+    pub const UINT16: u32 = 2;
+    pub const UINT32: u32 = 3;
+    pub const UINT64: u32 = 4;
+    pub const BYTE_ARRAY16: u32 = 11;
+    pub const BYTE_BLOB: u32 = 12;
+    pub const V4_ADDR_MASK: u32 = 256;
+    pub const V6_ADDR_MASK: u32 = 257;
+    pub const RANGE: u32 = 258;
+}
+
+/// A WFP filter.
+///
+/// Conditions on the same field are OR'ed, conditions on different fields
+/// are AND'ed.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Filter {
+    /// All zeros to let the engine generate the key.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
+    pub filter_key: [u8; 16],
+    pub display_data: DisplayData,
+    /// The `FWPM_FILTER_FLAG_*`.
+    pub flags: u32,
+    #[cfg_attr(
+        feature = "serde",
+        serde(default, with = "crate::schema::optional_guid")
+    )]
+    pub provider_key: Option<[u8; 16]>,
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
+    pub layer_key: [u8; 16],
+    /// All zeros for the default sublayer.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
+    pub sub_layer_key: [u8; 16],
+    pub weight: FilterWeight,
+    pub conditions: Vec<FilterCondition>,
+    pub action: FilterAction,
+    /// Assigned by the engine, 0 for a filter that wasn't added.
+    pub filter_id: u64,
+    /// Computed by the engine, `None` for a filter that wasn't added.
+    pub effective_weight: Option<u64>,
+}
+
+/// The weight of a filter within its sublayer.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum FilterWeight {
+    /// `FWP_EMPTY`, the engine computes the weight from the conditions.
+    #[default]
+    Auto,
+    /// `FWP_UINT8`, a range from 0 to 15, the engine computes the weight
+    /// within the range.
+    Range(u8),
+    /// `FWP_UINT64`, the exact weight.
+    Exact(u64),
+}
+
+/// What a filter does with the traffic it matches, the `FWP_ACTION_*`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum FilterAction {
+    Block,
+    Permit,
+    CalloutTerminating(
+        #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))] [u8; 16],
+    ),
+    CalloutInspection(#[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))] [u8; 16]),
+    CalloutUnknown(#[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))] [u8; 16]),
+    /// An action type that is not defined.
+    Unknown(u32),
+}
+
+impl FilterAction {
+    /// The `FWP_ACTION_*` type.
+    pub fn action_type(&self) -> u32 {
+        match self {
+            FilterAction::Block => 0x1001,
+            FilterAction::Permit => 0x1002,
+            FilterAction::CalloutTerminating(_) => 0x5003,
+            FilterAction::CalloutInspection(_) => 0x6004,
+            FilterAction::CalloutUnknown(_) => 0x4005,
+            FilterAction::Unknown(v) => *v,
+        }
+    }
+
+    /// The action from its type and the callout key, which is ignored by
+    /// the other actions.
+    pub fn from_raw(action_type: u32, callout_key: [u8; 16]) -> Self {
+        match action_type {
+            0x1001 => FilterAction::Block,
+            0x1002 => FilterAction::Permit,
+            0x5003 => FilterAction::CalloutTerminating(callout_key),
+            0x6004 => FilterAction::CalloutInspection(callout_key),
+            0x4005 => FilterAction::CalloutUnknown(callout_key),
+            v => FilterAction::Unknown(v),
+        }
+    }
+
+    /// Whether the action always decides, `FWP_ACTION_FLAG_TERMINATING`.
+    pub fn is_terminating(&self) -> bool {
+        self.action_type() & 0x1000 != 0
+    }
+
+    pub fn callout_key(&self) -> Option<[u8; 16]> {
+        match self {
+            FilterAction::CalloutTerminating(key)
+            | FilterAction::CalloutInspection(key)
+            | FilterAction::CalloutUnknown(key) => Some(*key),
+            _ => None,
+        }
+    }
+}
+
+/// How a condition compares the field with its value, `FWP_MATCH_TYPE`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum MatchType {
+    #[default]
+    Equal,
+    Greater,
+    Less,
+    GreaterOrEqual,
+    LessOrEqual,
+    Range,
+    FlagsAllSet,
+    FlagsAnySet,
+    FlagsNoneSet,
+    EqualCaseInsensitive,
+    NotEqual,
+    Prefix,
+    NotPrefix,
+    /// A raw value that is not defined by `FWP_MATCH_TYPE`.
+    Unknown(u32),
+}
+
+impl From<u32> for MatchType {
+    fn from(value: u32) -> Self {
+        match value {
+            0 => MatchType::Equal,
+            1 => MatchType::Greater,
+            2 => MatchType::Less,
+            3 => MatchType::GreaterOrEqual,
+            4 => MatchType::LessOrEqual,
+            5 => MatchType::Range,
+            6 => MatchType::FlagsAllSet,
+            7 => MatchType::FlagsAnySet,
+            8 => MatchType::FlagsNoneSet,
+            9 => MatchType::EqualCaseInsensitive,
+            10 => MatchType::NotEqual,
+            11 => MatchType::Prefix,
+            12 => MatchType::NotPrefix,
+            v => MatchType::Unknown(v),
+        }
+    }
+}
+
+impl From<MatchType> for u32 {
+    fn from(value: MatchType) -> Self {
+        match value {
+            MatchType::Equal => 0,
+            MatchType::Greater => 1,
+            MatchType::Less => 2,
+            MatchType::GreaterOrEqual => 3,
+            MatchType::LessOrEqual => 4,
+            MatchType::Range => 5,
+            MatchType::FlagsAllSet => 6,
+            MatchType::FlagsAnySet => 7,
+            MatchType::FlagsNoneSet => 8,
+            MatchType::EqualCaseInsensitive => 9,
+            MatchType::NotEqual => 10,
+            MatchType::Prefix => 11,
+            MatchType::NotPrefix => 12,
+            MatchType::Unknown(v) => v,
+        }
+    }
+}
+
+/// The value of a condition, one variant per `FWP_DATA_TYPE`.
+///
+/// IPv4 addresses are `Uint32` in host order and IPv6 addresses are
+/// `ByteArray16`, see the `From<IpAddr>` implementation.
+#[derive(Debug, Clone, PartialEq, Eq, PartialOrd, Ord)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum ConditionValue {
+    Uint8(u8),
+    Uint16(u16),
+    Uint32(u32),
+    Uint64(u64),
+    ByteArray16([u8; 16]),
+    ByteBlob(Vec<u8>),
+    /// `FWP_V4_ADDR_MASK` or `FWP_V6_ADDR_MASK`.
+    AddrMask(IpNetwork),
+    /// `FWP_RANGE_TYPE`, both bounds included.
+    Range(Box<ConditionValue>, Box<ConditionValue>),
+    /// A data type that isn't modelled, e.g. a SID or a security descriptor.
+    Unsupported(u32),
+}
+
+impl From<IpAddr> for ConditionValue {
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(addr) => ConditionValue::Uint32(addr.into()),
+            IpAddr::V6(addr) => ConditionValue::ByteArray16(addr.octets()),
+        }
+    }
+}
+
+impl ConditionValue {
+    /// The `FWP_DATA_TYPE`.
+    pub fn data_type(&self) -> u32 {
+        match self {
+            ConditionValue::Uint8(_) => data_type::UINT8,
+            ConditionValue::Uint16(_) => data_type::UINT16,
+            ConditionValue::Uint32(_) => data_type::UINT32,
+            ConditionValue::Uint64(_) => data_type::UINT64,
+            ConditionValue::ByteArray16(_) => data_type::BYTE_ARRAY16,
+            ConditionValue::ByteBlob(_) => data_type::BYTE_BLOB,
+            ConditionValue::AddrMask(IpNetwork::V4(_)) => data_type::V4_ADDR_MASK,
+            ConditionValue::AddrMask(IpNetwork::V6(_)) => data_type::V6_ADDR_MASK,
+            ConditionValue::Range(..) => data_type::RANGE,
+            ConditionValue::Unsupported(data_type) => *data_type,
+        }
+    }
+
+    /// The app ID blob of an executable, as `FwpmGetAppIdFromFileName0`
+    /// returns it: the NT device path, e.g.
+    /// `\device\harddiskvolume3\program files\app\app.exe`, lowercased and
+    /// encoded in UTF-16LE with the terminating NUL.
+    pub fn app_id(device_path: &str) -> Self {
+        let blob = device_path
+            .to_lowercase()
+            .encode_utf16()
+            .chain([0])
+            .flat_map(u16::to_le_bytes)
+            .collect();
+        ConditionValue::ByteBlob(blob)
+    }
+
+    fn is_integer(&self) -> bool {
+        matches!(
+            self,
+            ConditionValue::Uint8(_)
+                | ConditionValue::Uint16(_)
+                | ConditionValue::Uint32(_)
+                | ConditionValue::Uint64(_)
+        )
+    }
+
+    /// The IP family of an address value, `None` for the other values.
+    fn is_ipv6(&self) -> Option<bool> {
+        match self {
+            ConditionValue::Uint32(_) => Some(false),
+            ConditionValue::ByteArray16(_) => Some(true),
+            ConditionValue::AddrMask(network) => Some(network.is_ipv6()),
+            ConditionValue::Range(low, _) => low.is_ipv6(),
+            _ => None,
+        }
+    }
+}
+
+impl ConditionValue {
+    /// Write the value, the integers and byte arrays of the address fields
+    /// as addresses and the blobs as UTF-16 strings.
+    fn fmt_field(&self, is_address: bool, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            ConditionValue::Uint32(addr) if is_address => write!(f, "{}", Ipv4Addr::from(*addr)),
+            ConditionValue::ByteArray16(addr) if is_address => {
+                write!(f, "{}", Ipv6Addr::from(*addr))
+            }
+            ConditionValue::Uint8(value) => write!(f, "{value}"),
+            ConditionValue::Uint16(value) => write!(f, "{value}"),
+            ConditionValue::Uint32(value) => write!(f, "{value}"),
+            ConditionValue::Uint64(value) => write!(f, "{value}"),
+            ConditionValue::ByteArray16(bytes) => {
+                write!(f, "{}", crate::utils::guid_to_string(bytes))
+            }
+            ConditionValue::ByteBlob(blob) => {
+                let units: Vec<u16> = blob
+                    .chunks_exact(2)
+                    .map(|unit| u16::from_le_bytes([unit[0], unit[1]]))
+                    .take_while(|&unit| unit != 0)
+                    .collect();
+                write!(f, "{:?}", String::from_utf16_lossy(&units))
+            }
+            ConditionValue::AddrMask(network) => write!(f, "{network}"),
+            ConditionValue::Range(low, high) => {
+                low.fmt_field(is_address, f)?;
+                f.write_str("..=")?;
+                high.fmt_field(is_address, f)
+            }
+            ConditionValue::Unsupported(data_type) => write!(f, "<data type {data_type}>"),
+        }
+    }
+}
+
+/// A condition of a filter, `FWPM_FILTER_CONDITION0`.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct FilterCondition {
+    /// The `FWPM_CONDITION_*` GUID of the field.
+    #[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))]
+    pub field_key: [u8; 16],
+    pub match_type: MatchType,
+    pub value: ConditionValue,
+}
+
+/// The field by its catalogue name, e.g.
+/// `FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 192.0.2.0/24`.
+impl fmt::Display for FilterCondition {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        let is_address = matches!(
+            self.field_key,
+            FWPM_CONDITION_IP_LOCAL_ADDRESS
+                | FWPM_CONDITION_IP_REMOTE_ADDRESS
+                | FWPM_CONDITION_IP_SOURCE_ADDRESS
+                | FWPM_CONDITION_IP_DESTINATION_ADDRESS
+                | FWPM_CONDITION_IP_NEXTHOP_ADDRESS
+                | FWPM_CONDITION_EMBEDDED_REMOTE_ADDRESS
+                | FWPM_CONDITION_IP_LOCAL_ADDRESS_V4
+                | FWPM_CONDITION_IP_LOCAL_ADDRESS_V6
+                | FWPM_CONDITION_IP_REMOTE_ADDRESS_V4
+                | FWPM_CONDITION_IP_REMOTE_ADDRESS_V6
+        );
+        write!(
+            f,
+            "{} {:?} ",
+            catalogue::name_of(&self.field_key),
+            self.match_type
+        )?;
+        self.value.fmt_field(is_address, f)
+    }
+}
+
+impl FilterCondition {
+    pub fn new(field_key: [u8; 16], match_type: MatchType, value: ConditionValue) -> Self {
+        FilterCondition {
+            field_key,
+            match_type,
+            value,
+        }
+    }
+
+    /// The remote address is in `network`.
+    pub fn remote_network(network: IpNetwork) -> Self {
+        Self::address(FWPM_CONDITION_IP_REMOTE_ADDRESS, network)
+    }
+
+    /// The local address is in `network`.
+    pub fn local_network(network: IpNetwork) -> Self {
+        Self::address(FWPM_CONDITION_IP_LOCAL_ADDRESS, network)
+    }
+
+    fn address(field_key: [u8; 16], network: IpNetwork) -> Self {
+        let value = if network.prefix() == network.max_prefix() {
+            network.addr().into()
+        } else {
+            ConditionValue::AddrMask(network)
+        };
+        Self::new(field_key, MatchType::Equal, value)
+    }
+
+    pub fn remote_port(port: u16) -> Self {
+        Self::new(
+            FWPM_CONDITION_IP_REMOTE_PORT,
+            MatchType::Equal,
+            ConditionValue::Uint16(port),
+        )
+    }
+
+    pub fn remote_port_range(low: u16, high: u16) -> Self {
+        Self::port_range(FWPM_CONDITION_IP_REMOTE_PORT, low, high)
+    }
+
+    pub fn local_port(port: u16) -> Self {
+        Self::new(
+            FWPM_CONDITION_IP_LOCAL_PORT,
+            MatchType::Equal,
+            ConditionValue::Uint16(port),
+        )
+    }
+
+    pub fn local_port_range(low: u16, high: u16) -> Self {
+        Self::port_range(FWPM_CONDITION_IP_LOCAL_PORT, low, high)
+    }
+
+    fn port_range(field_key: [u8; 16], low: u16, high: u16) -> Self {
+        Self::new(
+            field_key,
+            MatchType::Range,
+            ConditionValue::Range(
+                Box::new(ConditionValue::Uint16(low)),
+                Box::new(ConditionValue::Uint16(high)),
+            ),
+        )
+    }
+
+    /// The IP protocol, e.g. 6 for TCP and 17 for UDP.
+    pub fn protocol(protocol: u8) -> Self {
+        Self::new(
+            FWPM_CONDITION_IP_PROTOCOL,
+            MatchType::Equal,
+            ConditionValue::Uint8(protocol),
+        )
+    }
+
+    /// The application, see [`ConditionValue::app_id`].
+    pub fn app_id(device_path: &str) -> Self {
+        Self::new(
+            FWPM_CONDITION_ALE_APP_ID,
+            MatchType::Equal,
+            ConditionValue::app_id(device_path),
+        )
+    }
+
+    /// The local interface, by LUID.
+    pub fn local_interface(luid: u64) -> Self {
+        Self::new(
+            FWPM_CONDITION_IP_LOCAL_INTERFACE,
+            MatchType::Equal,
+            ConditionValue::Uint64(luid),
+        )
+    }
+
+    /// Check the value against the match type, and against the field and
+    /// the layer when they are in the catalogue.
+    fn validate(&self, layer: Option<&Entry>) -> std::result::Result<(), &'static str> {
+        if self.field_key == [0; 16] {
+            return Err("missing field");
+        }
+        if layer.is_some_and(|layer| !layer.conditions().contains(&self.field_key)) {
+            return Err("field not available at the layer");
+        }
+        let layer_is_ipv6 = match layer.map(|layer| layer.family) {
+            Some(AddressFamily::Ipv4) => Some(false),
+            Some(AddressFamily::Ipv6) => Some(true),
+            _ => None,
+        };
+        match &self.value {
+            ConditionValue::Unsupported(_) => return Err("unsupported value type"),
+            ConditionValue::Range(low, high) => {
+                if std::mem::discriminant(&**low) != std::mem::discriminant(&**high) {
+                    return Err("range bounds of different types");
+                }
+                if !low.is_integer() && !matches!(**low, ConditionValue::ByteArray16(_)) {
+                    return Err("range bounds must be integers or IPv6 addresses");
+                }
+                if low > high {
+                    return Err("empty range");
+                }
+            }
+            _ => {}
+        }
+
+        let value = &self.value;
+        let valid = match self.match_type {
+            MatchType::Unknown(_) => return Err("unknown match type"),
+            MatchType::Range => matches!(value, ConditionValue::Range(..)),
+            _ if matches!(value, ConditionValue::Range(..)) => {
+                return Err("range value without FWP_MATCH_RANGE")
+            }
+            MatchType::Equal | MatchType::NotEqual => true,
+            MatchType::Greater
+            | MatchType::Less
+            | MatchType::GreaterOrEqual
+            | MatchType::LessOrEqual => {
+                value.is_integer() || matches!(value, ConditionValue::ByteArray16(_))
+            }
+            MatchType::FlagsAllSet | MatchType::FlagsAnySet | MatchType::FlagsNoneSet => {
+                value.is_integer()
+            }
+            MatchType::EqualCaseInsensitive | MatchType::Prefix | MatchType::NotPrefix => {
+                matches!(value, ConditionValue::ByteBlob(_))
+            }
+        };
+        if !valid {
+            return Err("value type not valid for the match type");
+        }
+
+        let bound = match value {
+            ConditionValue::Range(low, _) => &**low,
+            value => value,
+        };
+        let valid = match self.field_key {
+            FWPM_CONDITION_IP_REMOTE_ADDRESS | FWPM_CONDITION_IP_LOCAL_ADDRESS => {
+                let is_ipv6 = value
+                    .is_ipv6()
+                    .ok_or("address field with a non-address value")?;
+                if layer_is_ipv6.is_some_and(|layer| layer != is_ipv6) {
+                    return Err("address of the wrong IP family for the layer");
+                }
+                true
+            }
+            FWPM_CONDITION_IP_REMOTE_PORT | FWPM_CONDITION_IP_LOCAL_PORT => {
+                matches!(bound, ConditionValue::Uint16(_))
+            }
+            FWPM_CONDITION_IP_PROTOCOL => matches!(bound, ConditionValue::Uint8(_)),
+            FWPM_CONDITION_ALE_APP_ID => matches!(value, ConditionValue::ByteBlob(_)),
+            FWPM_CONDITION_IP_LOCAL_INTERFACE => matches!(bound, ConditionValue::Uint64(_)),
+            _ => true,
+        };
+        if !valid {
+            return Err("value type not valid for the field");
+        }
+        Ok(())
+    }
+}
+
+impl Filter {
+    pub fn builder(
+        name: impl Into<String>,
+        layer_key: [u8; 16],
+        action: FilterAction,
+    ) -> FilterBuilder {
+        FilterBuilder {
+            name: name.into(),
+            description: None,
+            filter: Filter {
+                filter_key: [0; 16],
+                display_data: DisplayData::new(String::new(), None),
+                flags: 0,
+                provider_key: None,
+                layer_key,
+                sub_layer_key: [0; 16],
+                weight: FilterWeight::Auto,
+                conditions: vec![],
+                action,
+                filter_id: 0,
+                effective_weight: None,
+            },
+        }
+    }
+
+    /// Check the filter the way the filter engine would, failing with
+    /// [`ErrorKind::InvalidFilter`].
+    pub fn validate(&self) -> Result<()> {
+        let invalid = |reason| Err(Error::invalid_filter(None, reason));
+        if self.layer_key == [0; 16] {
+            return invalid("missing layer");
+        }
+        if self.flags & !KNOWN_FLAGS != 0 {
+            return invalid("unknown flags");
+        }
+        if self.flags & FWPM_FILTER_FLAG_PERSISTENT != 0
+            && self.flags & FWPM_FILTER_FLAG_BOOTTIME != 0
+        {
+            return invalid("a filter can't be both persistent and boot-time");
+        }
+        if self.flags & FWPM_FILTER_FLAG_HAS_PROVIDER_CONTEXT != 0 {
+            return invalid("provider contexts are not supported");
+        }
+        if self.flags & FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT != 0 && !self.action.is_terminating() {
+            return invalid("clearing the action right needs a terminating action");
+        }
+        if self.flags & FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED != 0
+            && !matches!(self.action, FilterAction::CalloutTerminating(_))
+        {
+            return invalid("permit if callout unregistered needs a terminating callout");
+        }
+        match self.action {
+            FilterAction::Unknown(_) => return invalid("unknown action type"),
+            action if action.callout_key() == Some([0; 16]) => return invalid("missing callout"),
+            _ => {}
+        }
+        if let FilterWeight::Range(range) = self.weight {
+            if range > 15 {
+                return invalid("weight range above 15");
+            }
+        }
+        let layer = catalogue::by_key(&self.layer_key).filter(|entry| entry.kind == Kind::Layer);
+        for (index, condition) in self.conditions.iter().enumerate() {
+            condition
+                .validate(layer)
+                .map_err(|reason| Error::invalid_filter(Some(index), reason))?;
+        }
+        Ok(())
+    }
+}
+
+/// A builder of [`Filter`]s, validated by [`FilterBuilder::build`].
+#[derive(Debug, Clone)]
+pub struct FilterBuilder {
+    name: String,
+    description: Option<String>,
+    filter: Filter,
+}
+
+impl FilterBuilder {
+    pub fn key(mut self, key: [u8; 16]) -> Self {
+        self.filter.filter_key = key;
+        self
+    }
+
+    pub fn description(mut self, description: impl Into<String>) -> Self {
+        self.description = Some(description.into());
+        self
+    }
+
+    /// Add `FWPM_FILTER_FLAG_*` flags.
+    pub fn flags(mut self, flags: u32) -> Self {
+        self.filter.flags |= flags;
+        self
+    }
+
+    pub fn provider(mut self, provider_key: [u8; 16]) -> Self {
+        self.filter.provider_key = Some(provider_key);
+        self
+    }
+
+    pub fn sub_layer(mut self, sub_layer_key: [u8; 16]) -> Self {
+        self.filter.sub_layer_key = sub_layer_key;
+        self
+    }
+
+    pub fn weight(mut self, weight: FilterWeight) -> Self {
+        self.filter.weight = weight;
+        self
+    }
+
+    pub fn condition(mut self, condition: FilterCondition) -> Self {
+        self.filter.conditions.push(condition);
+        self
+    }
+
+    pub fn build(mut self) -> Result<Filter> {
+        if self.name.contains('\0') || self.description.as_ref().is_some_and(|d| d.contains('\0')) {
+            return Err(Error::invalid_filter(None, "display data contains a NUL"));
+        }
+        self.filter.display_data = DisplayData::new(self.name, self.description);
+        self.filter.validate()?;
+        Ok(self.filter)
+    }
+}
+
+#[cfg(windows)]
+mod raw {
+    use windows_sys::core::GUID;
+    use windows_sys::Win32::NetworkManagement::WindowsFilteringPlatform::*;
+
+    use super::*;
+    use crate::utils::{bytes_to_guid, guid_to_bytes};
+
+    impl Filter {
+        /// Copy a filter returned by the engine.
+        ///
+        /// # Safety
+        ///
+        /// The pointers of `raw` must be valid.
+        pub unsafe fn from_raw(raw: &FWPM_FILTER0) -> Self {
+            let conditions = if raw.filterCondition.is_null() {
+                &[][..]
+            } else {
+                std::slice::from_raw_parts(raw.filterCondition, raw.numFilterConditions as usize)
+            };
+            Filter {
+                filter_key: guid_to_bytes(&raw.filterKey),
+                display_data: raw.displayData.into(),
+                flags: raw.flags,
+                provider_key: raw.providerKey.as_ref().map(guid_to_bytes),
+                layer_key: guid_to_bytes(&raw.layerKey),
+                sub_layer_key: guid_to_bytes(&raw.subLayerKey),
+                weight: match raw.weight.r#type as u32 {
+                    data_type::UINT8 => FilterWeight::Range(raw.weight.Anonymous.uint8),
+                    data_type::UINT64 => FilterWeight::Exact(*raw.weight.Anonymous.uint64),
+                    _ => FilterWeight::Auto,
+                },
+                conditions: conditions
+                    .iter()
+                    .map(|condition| FilterCondition {
+                        field_key: guid_to_bytes(&condition.fieldKey),
+                        match_type: (condition.matchType as u32).into(),
+                        value: condition_value_from_raw(&condition.conditionValue),
+                    })
+                    .collect(),
+                action: FilterAction::from_raw(
+                    raw.action.r#type,
+                    guid_to_bytes(&raw.action.Anonymous.calloutKey),
+                ),
+                filter_id: raw.filterId,
+                effective_weight: match raw.effectiveWeight.r#type as u32 {
+                    data_type::UINT64 => Some(*raw.effectiveWeight.Anonymous.uint64),
+                    _ => None,
+                },
+            }
+        }
+
+        /// The raw filter, pointing into `self`.
+        pub(crate) fn to_raw(&self) -> RawFilter<'_> {
+            let mut storage = Storage::default();
+            let conditions: Vec<_> = self
+                .conditions
+                .iter()
+                .map(|condition| FWPM_FILTER_CONDITION0 {
+                    fieldKey: bytes_to_guid(condition.field_key),
+                    matchType: u32::from(condition.match_type) as _,
+                    conditionValue: storage.condition_value(&condition.value),
+                })
+                .collect();
+            let weight = match self.weight {
+                FilterWeight::Auto => FWP_VALUE0 {
+                    r#type: FWP_EMPTY,
+                    Anonymous: FWP_VALUE0_0 {
+                        uint64: std::ptr::null_mut(),
+                    },
+                },
+                FilterWeight::Range(range) => storage.value(&ConditionValue::Uint8(range)),
+                FilterWeight::Exact(weight) => storage.value(&ConditionValue::Uint64(weight)),
+            };
+            let provider_key = match self.provider_key {
+                Some(key) => storage.guid(key),
+                None => std::ptr::null_mut(),
+            };
+            let mut filter: FWPM_FILTER0 = unsafe { std::mem::zeroed() };
+            filter.filterKey = bytes_to_guid(self.filter_key);
+            filter.displayData = self.display_data.as_raw();
+            filter.flags = self.flags;
+            filter.providerKey = provider_key;
+            filter.providerData = super::super::empty_provider_data();
+            filter.layerKey = bytes_to_guid(self.layer_key);
+            filter.subLayerKey = bytes_to_guid(self.sub_layer_key);
+            filter.weight = weight;
+            filter.numFilterConditions = conditions.len() as u32;
+            filter.filterCondition = conditions.as_ptr() as *mut _;
+            filter.action = FWPM_ACTION0 {
+                r#type: self.action.action_type(),
+                Anonymous: FWPM_ACTION0_0 {
+                    calloutKey: bytes_to_guid(self.action.callout_key().unwrap_or_default()),
+                },
+            };
+            RawFilter {
+                filter,
+                _conditions: conditions,
+                _storage: storage,
+                _marker: std::marker::PhantomData,
+            }
+        }
+    }
+
+    /// An `FWPM_FILTER0` with the memory its pointers point to.
+    pub(crate) struct RawFilter<'a> {
+        filter: FWPM_FILTER0,
+        _conditions: Vec<FWPM_FILTER_CONDITION0>,
+        _storage: Storage,
+        _marker: std::marker::PhantomData<&'a Filter>,
+    }
+
+    impl RawFilter<'_> {
+        pub fn as_ptr(&self) -> *const FWPM_FILTER0 {
+            &self.filter
+        }
+    }
+
+    /// The values that `FWP_VALUE0` and `FWP_CONDITION_VALUE0` point to,
+    /// boxed so that they don't move.
+    #[derive(Default)]
+    #[allow(clippy::vec_box)]
+    struct Storage {
+        uint64s: Vec<Box<u64>>,
+        guids: Vec<Box<GUID>>,
+        byte_arrays: Vec<Box<FWP_BYTE_ARRAY16>>,
+        blobs: Vec<Box<FWP_BYTE_BLOB>>,
+        v4_masks: Vec<Box<FWP_V4_ADDR_AND_MASK>>,
+        v6_masks: Vec<Box<FWP_V6_ADDR_AND_MASK>>,
+        ranges: Vec<Box<FWP_RANGE0>>,
+    }
+
+    /// Keep `value` alive as long as `storage`, returning its address.
+    fn keep<T>(storage: &mut Vec<Box<T>>, value: T) -> *mut T {
+        let mut value = Box::new(value);
+        let ptr = &mut *value as *mut T;
+        storage.push(value);
+        ptr
+    }
+
+    impl Storage {
+        fn guid(&mut self, key: [u8; 16]) -> *mut GUID {
+            keep(&mut self.guids, bytes_to_guid(key))
+        }
+
+        /// A value without the condition-only types.
+        fn value(&mut self, value: &ConditionValue) -> FWP_VALUE0 {
+            let anonymous = match value {
+                ConditionValue::Uint8(v) => FWP_VALUE0_0 { uint8: *v },
+                ConditionValue::Uint16(v) => FWP_VALUE0_0 { uint16: *v },
+                ConditionValue::Uint32(v) => FWP_VALUE0_0 { uint32: *v },
+                ConditionValue::Uint64(v) => FWP_VALUE0_0 {
+                    uint64: keep(&mut self.uint64s, *v),
+                },
+                ConditionValue::ByteArray16(v) => FWP_VALUE0_0 {
+                    byteArray16: keep(&mut self.byte_arrays, FWP_BYTE_ARRAY16 { byteArray16: *v }),
+                },
+                ConditionValue::ByteBlob(v) => FWP_VALUE0_0 {
+                    // the engine doesn't write to the blob
+                    byteBlob: keep(
+                        &mut self.blobs,
+                        FWP_BYTE_BLOB {
+                            size: v.len() as u32,
+                            data: v.as_ptr() as *mut u8,
+                        },
+                    ),
+                },
+                // rejected by the validation
+                ConditionValue::AddrMask(_)
+                | ConditionValue::Range(..)
+                | ConditionValue::Unsupported(_) => FWP_VALUE0_0 {
+                    uint64: std::ptr::null_mut(),
+                },
+            };
+            FWP_VALUE0 {
+                r#type: value.data_type() as _,
+                Anonymous: anonymous,
+            }
+        }
+
+        fn condition_value(&mut self, value: &ConditionValue) -> FWP_CONDITION_VALUE0 {
+            let anonymous = match value {
+                ConditionValue::AddrMask(IpNetwork::V4(network)) => FWP_CONDITION_VALUE0_0 {
+                    v4AddrMask: keep(
+                        &mut self.v4_masks,
+                        FWP_V4_ADDR_AND_MASK {
+                            addr: network.addr().into(),
+                            mask: network.mask().into(),
+                        },
+                    ),
+                },
+                ConditionValue::AddrMask(IpNetwork::V6(network)) => FWP_CONDITION_VALUE0_0 {
+                    v6AddrMask: keep(
+                        &mut self.v6_masks,
+                        FWP_V6_ADDR_AND_MASK {
+                            addr: network.addr().octets(),
+                            prefixLength: network.prefix(),
+                        },
+                    ),
+                },
+                ConditionValue::Range(low, high) => {
+                    let range = FWP_RANGE0 {
+                        valueLow: self.value(low),
+                        valueHigh: self.value(high),
+                    };
+                    FWP_CONDITION_VALUE0_0 {
+                        rangeValue: keep(&mut self.ranges, range),
+                    }
+                }
+                // the unions share the layout of the common types
+                value => unsafe {
+                    std::mem::transmute::<FWP_VALUE0_0, FWP_CONDITION_VALUE0_0>(
+                        self.value(value).Anonymous,
+                    )
+                },
+            };
+            FWP_CONDITION_VALUE0 {
+                r#type: value.data_type() as _,
+                Anonymous: anonymous,
+            }
+        }
+    }
+
+    unsafe fn value_from_raw(value: &FWP_VALUE0) -> ConditionValue {
+        let data_type = value.r#type as u32;
+        let value = &value.Anonymous;
+        match data_type {
+            data_type::UINT8 => ConditionValue::Uint8(value.uint8),
+            data_type::UINT16 => ConditionValue::Uint16(value.uint16),
+            data_type::UINT32 => ConditionValue::Uint32(value.uint32),
+            data_type::UINT64 => ConditionValue::Uint64(*value.uint64),
+            data_type::BYTE_ARRAY16 => {
+                ConditionValue::ByteArray16((*value.byteArray16).byteArray16)
+            }
+            data_type::BYTE_BLOB => {
+                let blob = &*value.byteBlob;
+                let bytes = if blob.data.is_null() {
+                    vec![]
+                } else {
+                    std::slice::from_raw_parts(blob.data, blob.size as usize).to_vec()
+                };
+                ConditionValue::ByteBlob(bytes)
+            }
+            data_type => ConditionValue::Unsupported(data_type),
+        }
+    }
+
+    unsafe fn condition_value_from_raw(value: &FWP_CONDITION_VALUE0) -> ConditionValue {
+        match value.r#type as u32 {
+            data_type::V4_ADDR_MASK => {
+                let mask = &*value.Anonymous.v4AddrMask;
+                IpNetwork::new(
+                    Ipv4Addr::from(mask.addr).into(),
+                    mask.mask.count_ones() as u8,
+                )
+                .map_or(
+                    ConditionValue::Unsupported(data_type::V4_ADDR_MASK),
+                    ConditionValue::AddrMask,
+                )
+            }
+            data_type::V6_ADDR_MASK => {
+                let mask = &*value.Anonymous.v6AddrMask;
+                IpNetwork::new(Ipv6Addr::from(mask.addr).into(), mask.prefixLength).map_or(
+                    ConditionValue::Unsupported(data_type::V6_ADDR_MASK),
+                    ConditionValue::AddrMask,
+                )
+            }
+            data_type::RANGE => {
+                let range = &*value.Anonymous.rangeValue;
+                ConditionValue::Range(
+                    Box::new(value_from_raw(&range.valueLow)),
+                    Box::new(value_from_raw(&range.valueHigh)),
+                )
+            }
+            _ => value_from_raw(&FWP_VALUE0 {
+                r#type: value.r#type,
+                // the unions share the layout of the common types
+                Anonymous: std::mem::transmute::<FWP_CONDITION_VALUE0_0, FWP_VALUE0_0>(
+                    value.Anonymous,
+                ),
+            }),
+        }
+    }
+
+    #[cfg(test)]
+    mod tests {
+        use super::*;
+
+        #[test]
+        fn test_raw_round_trip() {
+            let filter = Filter::builder(
+                "dns",
+                crate::fwpm::keys::FWPM_LAYER_ALE_AUTH_CONNECT_V6,
+                FilterAction::Block,
+            )
+            .provider([7; 16])
+            .weight(FilterWeight::Exact(42))
+            .condition(FilterCondition::remote_network("fd00::/8".parse().unwrap()))
+            .condition(FilterCondition::remote_port_range(53, 53))
+            .condition(FilterCondition::app_id(
+                "\\device\\harddiskvolume3\\app.exe",
+            ))
+            .condition(FilterCondition::local_interface(0x0006_0000_0100_0000))
+            .build()
+            .unwrap();
+            let raw = filter.to_raw();
+            let copy = unsafe { Filter::from_raw(&*raw.as_ptr()) };
+            assert_eq!(copy, filter);
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn connect_v4() -> FilterBuilder {
+        Filter::builder(
+            "block dns",
+            FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+            FilterAction::Block,
+        )
+    }
+
+    #[test]
+    fn test_build() {
+        let filter = connect_v4()
+            .description("plaintext DNS")
+            .sub_layer([1; 16])
+            .weight(FilterWeight::Range(15))
+            .flags(FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT)
+            .condition(FilterCondition::remote_port(53))
+            .condition(FilterCondition::protocol(17))
+            .condition(FilterCondition::remote_network(
+                "10.0.0.0/8".parse().unwrap(),
+            ))
+            .condition(FilterCondition::remote_network(
+                "1.1.1.1/32".parse().unwrap(),
+            ))
+            .build()
+            .unwrap();
+        assert_eq!(
+            filter.display_data,
+            DisplayData::new("block dns".into(), Some("plaintext DNS".into()))
+        );
+        assert_eq!(filter.flags, FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT);
+        assert_eq!(filter.action.action_type(), 0x1001);
+        assert_eq!(
+            filter.conditions[2].value,
+            ConditionValue::AddrMask("10.0.0.0/8".parse().unwrap())
+        );
+        assert_eq!(
+            filter.conditions[3].value,
+            ConditionValue::Uint32(0x0101_0101)
+        );
+        assert_eq!(filter.conditions[3].value.data_type(), data_type::UINT32);
+        assert_eq!(filter.filter_id, 0);
+    }
+
+    #[test]
+    fn test_app_id() {
+        assert_eq!(
+            ConditionValue::app_id("\\Device\\A.exe"),
+            ConditionValue::ByteBlob(b"\\\0d\0e\0v\0i\0c\0e\0\\\0a\0.\0e\0x\0e\0\0\0".to_vec())
+        );
+    }
+
+    #[test]
+    fn test_display() {
+        let cases = [
+            (
+                FilterCondition::remote_network("192.0.2.0/24".parse().unwrap()),
+                "FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 192.0.2.0/24",
+            ),
+            (
+                FilterCondition::remote_network("2001:db8::1/128".parse().unwrap()),
+                "FWPM_CONDITION_IP_REMOTE_ADDRESS Equal 2001:db8::1",
+            ),
+            (
+                FilterCondition::local_network("192.0.2.1/32".parse().unwrap()),
+                "FWPM_CONDITION_IP_LOCAL_ADDRESS Equal 192.0.2.1",
+            ),
+            (
+                FilterCondition::remote_port_range(80, 443),
+                "FWPM_CONDITION_IP_REMOTE_PORT Range 80..=443",
+            ),
+            (
+                FilterCondition::app_id("\\Device\\A.exe"),
+                "FWPM_CONDITION_ALE_APP_ID Equal \"\\\\device\\\\a.exe\"",
+            ),
+            (
+                FilterCondition::new([1; 16], MatchType::FlagsAnySet, ConditionValue::Uint32(3)),
+                "01010101-0101-0101-0101-010101010101 FlagsAnySet 3",
+            ),
+        ];
+        for (condition, expected) in cases {
+            assert_eq!(condition.to_string(), expected);
+        }
+    }
+
+    #[test]
+    fn test_invalid_filters() {
+        let range = |low, high| ConditionValue::Range(Box::new(low), Box::new(high));
+        let cases: Vec<(FilterBuilder, Option<usize>, &str)> = vec![
+            (
+                Filter::builder("x", [0; 16], FilterAction::Block),
+                None,
+                "missing layer",
+            ),
+            (
+                connect_v4().flags(FWPM_FILTER_FLAG_PERSISTENT | FWPM_FILTER_FLAG_BOOTTIME),
+                None,
+                "a filter can't be both persistent and boot-time",
+            ),
+            (connect_v4().flags(0x8000), None, "unknown flags"),
+            (
+                Filter::builder(
+                    "x",
+                    FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+                    FilterAction::CalloutInspection([1; 16]),
+                )
+                .flags(FWPM_FILTER_FLAG_CLEAR_ACTION_RIGHT),
+                None,
+                "clearing the action right needs a terminating action",
+            ),
+            (
+                connect_v4().flags(FWPM_FILTER_FLAG_PERMIT_IF_CALLOUT_UNREGISTERED),
+                None,
+                "permit if callout unregistered needs a terminating callout",
+            ),
+            (
+                Filter::builder(
+                    "x",
+                    FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+                    FilterAction::CalloutTerminating([0; 16]),
+                ),
+                None,
+                "missing callout",
+            ),
+            (
+                Filter::builder(
+                    "x",
+                    FWPM_LAYER_ALE_AUTH_CONNECT_V4,
+                    FilterAction::Unknown(3),
+                ),
+                None,
+                "unknown action type",
+            ),
+            (
+                connect_v4().weight(FilterWeight::Range(16)),
+                None,
+                "weight range above 15",
+            ),
+            (
+                connect_v4()
+                    .condition(FilterCondition::protocol(6))
+                    .condition(FilterCondition::remote_port_range(443, 80)),
+                Some(1),
+                "empty range",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_PORT,
+                    MatchType::Range,
+                    range(ConditionValue::Uint16(1), ConditionValue::Uint32(2)),
+                )),
+                Some(0),
+                "range bounds of different types",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_PORT,
+                    MatchType::Equal,
+                    range(ConditionValue::Uint16(1), ConditionValue::Uint16(2)),
+                )),
+                Some(0),
+                "range value without FWP_MATCH_RANGE",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_PORT,
+                    MatchType::Range,
+                    ConditionValue::Uint16(1),
+                )),
+                Some(0),
+                "value type not valid for the match type",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_ALE_APP_ID,
+                    MatchType::FlagsAllSet,
+                    ConditionValue::ByteBlob(vec![]),
+                )),
+                Some(0),
+                "value type not valid for the match type",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_PORT,
+                    MatchType::Equal,
+                    ConditionValue::Uint32(53),
+                )),
+                Some(0),
+                "value type not valid for the field",
+            ),
+            (
+                connect_v4().condition(FilterCondition::remote_network("::1/128".parse().unwrap())),
+                Some(0),
+                "address of the wrong IP family for the layer",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_REMOTE_ADDRESS,
+                    MatchType::Equal,
+                    ConditionValue::Uint8(1),
+                )),
+                Some(0),
+                "address field with a non-address value",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    [0; 16],
+                    MatchType::Equal,
+                    ConditionValue::Uint8(1),
+                )),
+                Some(0),
+                "missing field",
+            ),
+            (
+                connect_v4()
+                    .condition(FilterCondition::protocol(17))
+                    .condition(FilterCondition::new(
+                        FWPM_CONDITION_ETHER_TYPE,
+                        MatchType::Equal,
+                        ConditionValue::Uint16(0x0800),
+                    )),
+                Some(1),
+                "field not available at the layer",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_PROTOCOL,
+                    MatchType::Unknown(13),
+                    ConditionValue::Uint8(1),
+                )),
+                Some(0),
+                "unknown match type",
+            ),
+            (
+                connect_v4().condition(FilterCondition::new(
+                    FWPM_CONDITION_IP_PROTOCOL,
+                    MatchType::Equal,
+                    ConditionValue::Unsupported(13),
+                )),
+                Some(0),
+                "unsupported value type",
+            ),
+            (
+                connect_v4().description("a\0b"),
+                None,
+                "display data contains a NUL",
+            ),
+        ];
+        for (builder, expected_condition, expected_reason) in cases {
+            let err = builder.build().unwrap_err();
+            match err.kind() {
+                ErrorKind::InvalidFilter { condition, reason } => {
+                    assert_eq!((*condition, *reason), (expected_condition, expected_reason));
+                }
+                kind => panic!("unexpected error {:?}", kind),
+            }
+        }
+    }
+
+    #[cfg(feature = "serde")]
+    #[test]
+    fn test_serde() {
+        let filter = connect_v4()
+            .provider([2; 16])
+            .condition(FilterCondition::remote_port_range(5000, 6000))
+            .build()
+            .unwrap();
+        let json = serde_json::to_value(&filter).unwrap();
+        assert_eq!(json["layer_key"], "c38d57d1-05a7-4c33-904f-7fbceee60e82");
+        assert_eq!(json["provider_key"], "02020202-0202-0202-0202-020202020202");
+        assert_eq!(json["conditions"][0]["match_type"], "range");
+        assert_eq!(
+            json["conditions"][0]["value"],
+            serde_json::json!({ "range": [{ "uint16": 5000 }, { "uint16": 6000 }] })
+        );
+        assert_eq!(json["action"], "block");
+        assert_eq!(json["weight"], "auto");
+        assert_eq!(
+            serde_json::from_value::<Filter>(json.clone()).unwrap(),
+            filter
+        );
+
+        let callout = FilterAction::CalloutInspection([3; 16]);
+        let value = serde_json::to_value(callout).unwrap();
+        assert_eq!(
+            value,
+            serde_json::json!({ "calloutInspection": "03030303-0303-0303-0303-030303030303" })
+        );
+        assert_eq!(
+            serde_json::from_value::<FilterAction>(value).unwrap(),
+            callout
+        );
+        let weight = serde_json::to_value(FilterWeight::Range(3)).unwrap();
+        assert_eq!(weight, serde_json::json!({ "range": 3 }));
+
+        // the names of a newer writer map to the fallbacks
+        let mut json = json;
+        json["action"] = serde_json::json!({ "calloutSomething": [1, 2] });
+        json["weight"] = serde_json::json!("heaviest");
+        json["conditions"][0]["value"] = serde_json::json!({ "sid": "S-1-5-18" });
+        let parsed = serde_json::from_value::<Filter>(json).unwrap();
+        assert_eq!(parsed.action, FilterAction::Unknown(0));
+        assert_eq!(parsed.weight, FilterWeight::Auto);
+        assert_eq!(
+            parsed.conditions[0].value,
+            ConditionValue::Unsupported(u32::MAX)
+        );
+    }
+}
@@ -1,49 +1,42 @@
 use std::{
     cell::RefCell,
     collections::HashMap,
-    ffi::CString,
-    io::{self, ErrorKind},
-    mem,
+    io, mem,
     os::windows::io::AsRawSocket,
     time::{Duration, Instant},
 };
 
-use windows_sys::Win32::{
-    NetworkManagement::IpHelper::if_nametoindex,
-    Networking::WinSock::{
-        htonl, setsockopt, WSAGetLastError, IPPROTO_IP, IPPROTO_IPV6, IPV6_UNICAST_IF,
-        IP_UNICAST_IF, SOCKET, SOCKET_ERROR,
-    },
+use windows_sys::Win32::Networking::WinSock::{
+    htonl, setsockopt, WSAGetLastError, IPPROTO_IP, IPPROTO_IPV6, IPV6_UNICAST_IF, IP_UNICAST_IF,
+    SOCKET, SOCKET_ERROR,
 };
 
+use crate::backend::{Backend, OsBackend};
+use crate::interface::InterfaceId;
+
 type PCSTR = *const u8;
 
 pub fn find_adapter_interface_index(is_ipv6: bool, iface: &str) -> io::Result<Option<u32>> {
-    let adapaters =
-        crate::get_adapters().map_err(|e| io::Error::new(io::ErrorKind::NotFound, e))?;
-    let if_index = adapaters
-        .iter()
-        .filter(|a| {
-            if is_ipv6 {
-                a.ipv6_if_index != 0
-            } else {
-                a.ipv4_if_index != 0
-            }
-        })
-        .find(|a| a.friendly_name == iface || a.adapter_name == iface)
-        .map(|adapter| adapter.ipv4_if_index);
-    Ok(if_index)
+    OsBackend
+        .interface_index(&InterfaceId::Name(iface.to_owned()), is_ipv6)
+        .map_err(io::Error::from)
 }
 
-fn find_interface_index_cached(is_ipv6: bool, iface: &str) -> io::Result<u32> {
+fn find_interface_index_cached(is_ipv6: bool, iface: &InterfaceId) -> io::Result<u32> {
     const INDEX_EXPIRE_DURATION: Duration = Duration::from_secs(5);
 
     thread_local! {
-        static INTERFACE_INDEX_CACHE: RefCell<HashMap<String, (u32, Instant)>> =
+        // the IPv4 and IPv6 indexes of an interface may differ
+        static INTERFACE_INDEX_CACHE: RefCell<HashMap<(InterfaceId, bool), (u32, Instant)>> =
             RefCell::new(HashMap::new());
     }
 
-    let cache_index = INTERFACE_INDEX_CACHE.with(|cache| cache.borrow().get(iface).cloned());
+    if let InterfaceId::Index(idx) = iface {
+        return Ok(*idx);
+    }
+
+    let key = (iface.clone(), is_ipv6);
+    let cache_index = INTERFACE_INDEX_CACHE.with(|cache| cache.borrow().get(&key).cloned());
     if let Some((idx, insert_time)) = cache_index {
         // short-path, cache hit for most cases
         let now = Instant::now();
@@ -53,31 +46,22 @@ fn find_interface_index_cached(is_ipv6: bool, iface: &str) -> io::Result<u32> {
     }
 
     // Get from API GetAdaptersAddresses
-    let idx = match find_adapter_interface_index(is_ipv6, iface)? {
+    let found = match iface {
+        InterfaceId::Name(name) | InterfaceId::Alias(name) => {
+            find_adapter_interface_index(is_ipv6, name)?
+        }
+        _ => None,
+    };
+    let idx = match found {
         Some(idx) => idx,
-        None => unsafe {
-            //  Windows if_nametoindex requires a C-string for interface name
-            let ifname = CString::new(iface).expect("iface");
-
-            //  https:docs.microsoft.com/en-us/previous-versions/windows/hardware/drivers/ff553788(v=vs.85)
-            let if_index = if_nametoindex(ifname.as_ptr() as PCSTR);
-            if if_index == 0 {
-                // If the if_nametoindex function fails and returns zero, it is not possible to determine an error code.
-                tracing::error!("if_nametoindex {} fails", iface);
-                return Err(io::Error::new(
-                    ErrorKind::InvalidInput,
-                    "invalid interface name",
-                ));
-            }
-
-            if_index
-        },
+        None => iface.index().map_err(|e| {
+            tracing::error!("resolve {} fails: {}", iface, e);
+            io::Error::from(e)
+        })?,
     };
 
     INTERFACE_INDEX_CACHE.with(|cache| {
-        cache
-            .borrow_mut()
-            .insert(iface.to_owned(), (idx, Instant::now()));
+        cache.borrow_mut().insert(key, (idx, Instant::now()));
     });
 
     Ok(idx)
@@ -85,10 +69,15 @@ fn find_interface_index_cached(is_ipv6: bool, iface: &str) -> io::Result<u32> {
 
 // the addr doesn't matter, it's just a mark of ip version
 #[allow(unused)]
-pub fn set_ip_unicast_if<S: AsRawSocket>(socket: &S, is_ipv6: bool, iface: &str) -> io::Result<()> {
+pub fn set_ip_unicast_if<S: AsRawSocket>(
+    socket: &S,
+    is_ipv6: bool,
+    iface: impl Into<InterfaceId>,
+) -> io::Result<()> {
     let handle = socket.as_raw_socket() as SOCKET;
+    let iface = iface.into();
 
-    let if_index = find_interface_index_cached(is_ipv6, iface)?;
+    let if_index = find_interface_index_cached(is_ipv6, &iface)?;
 
     unsafe {
         //  https:docs.microsoft.com/en-us/windows/win32/winsock/ipproto-ip-socket-options
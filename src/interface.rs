@@ -0,0 +1,314 @@
+//! Interface identifiers and the conversions between them.
+//!
+//! Windows identifies an interface by its index, LUID, GUID, name (e.g.
+//! `ethernet_32768`) or alias (the friendly name, e.g. `Ethernet`), Linux by
+//! its index, name (e.g. `eth0`) or alias (`ifalias`). [`InterfaceId`] holds
+//! any of them and resolves it to the others.
+
+use std::fmt;
+
+use crate::adapter::Adapter;
+use crate::error::*;
+use crate::utils::guid_to_string;
+
+/// Any of the identifiers of an interface.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum InterfaceId {
+    Index(u32),
+    /// The `NET_LUID` of the interface, Windows only.
+    Luid(u64),
+    /// The interface GUID, Windows only.
+    Guid([u8; 16]),
+    /// The kernel name. On Windows the `{GUID}` adapter name is accepted too,
+    /// and a name that is not found is looked up as an alias.
+    Name(String),
+    Alias(String),
+}
+
+/// All the identifiers of an interface.
+///
+/// The LUID and GUID are zero on Linux, and the alias is empty if the
+/// interface has none.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ResolvedInterface {
+    pub index: u32,
+    pub luid: u64,
+    pub guid: [u8; 16],
+    pub name: String,
+    pub alias: String,
+}
+
+impl From<[u8; 16]> for InterfaceId {
+    fn from(guid: [u8; 16]) -> Self {
+        InterfaceId::Guid(guid)
+    }
+}
+
+impl From<&str> for InterfaceId {
+    fn from(name: &str) -> Self {
+        InterfaceId::Name(name.to_owned())
+    }
+}
+
+impl From<String> for InterfaceId {
+    fn from(name: String) -> Self {
+        InterfaceId::Name(name)
+    }
+}
+
+impl From<&String> for InterfaceId {
+    fn from(name: &String) -> Self {
+        InterfaceId::Name(name.clone())
+    }
+}
+
+/// The LUID of the adapter, or its name if it has none (Linux).
+impl From<&Adapter> for InterfaceId {
+    fn from(adapter: &Adapter) -> Self {
+        if adapter.luid != 0 {
+            InterfaceId::Luid(adapter.luid)
+        } else {
+            InterfaceId::Name(adapter.adapter_name_lossy().into_owned())
+        }
+    }
+}
+
+impl From<&InterfaceId> for InterfaceId {
+    fn from(id: &InterfaceId) -> Self {
+        id.clone()
+    }
+}
+
+impl fmt::Display for InterfaceId {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            InterfaceId::Index(index) => write!(f, "index {}", index),
+            InterfaceId::Luid(luid) => write!(f, "luid {:#x}", luid),
+            InterfaceId::Guid(guid) => write!(f, "guid {{{}}}", guid_to_string(guid)),
+            InterfaceId::Name(name) => write!(f, "name {:?}", name),
+            InterfaceId::Alias(alias) => write!(f, "alias {:?}", alias),
+        }
+    }
+}
+
+impl InterfaceId {
+    /// Resolve all the identifiers of the interface, fails with an interface
+    /// not found error if there is no such interface.
+    pub fn resolve(&self) -> Result<ResolvedInterface> {
+        resolve(self).ok_or_else(|| Error::interface_not_found(self))
+    }
+
+    pub fn index(&self) -> Result<u32> {
+        match self {
+            InterfaceId::Index(index) if *index != 0 => Ok(*index),
+            _ => Ok(self.resolve()?.index),
+        }
+    }
+
+    pub fn luid(&self) -> Result<u64> {
+        Ok(self.resolve()?.luid)
+    }
+
+    pub fn guid(&self) -> Result<[u8; 16]> {
+        Ok(self.resolve()?.guid)
+    }
+}
+
+#[cfg(windows)]
+fn resolve(id: &InterfaceId) -> Option<ResolvedInterface> {
+    use crate::utils::{
+        bytes_to_guid, guid_from_str, guid_to_bytes, guid_to_luid, index_to_luid, luid_to_guid,
+        luid_to_index,
+    };
+    use widestring::{U16CStr, WideCString};
+    use windows_sys::Win32::NetworkManagement::IpHelper::{self, NET_LUID_LH};
+
+    const IF_MAX_STRING_SIZE: usize = IpHelper::IF_MAX_STRING_SIZE as usize + 1;
+
+    unsafe fn name_to_luid(name: &str, luid: &mut NET_LUID_LH) -> bool {
+        let name = match WideCString::from_str(name) {
+            Ok(name) => name,
+            Err(_) => return false,
+        };
+        IpHelper::ConvertInterfaceNameToLuidW(name.as_ptr() as _, luid) == 0
+            || IpHelper::ConvertInterfaceAliasToLuid(name.as_ptr() as _, luid) == 0
+    }
+
+    let mut luid = NET_LUID_LH { Value: 0 };
+    let mut resolved = ResolvedInterface::default();
+    unsafe {
+        let found = match id {
+            InterfaceId::Index(index) => index_to_luid(*index).map(|v| luid.Value = v).is_ok(),
+            InterfaceId::Luid(value) => {
+                luid.Value = *value;
+                true
+            }
+            InterfaceId::Guid(guid) => guid_to_luid(&bytes_to_guid(*guid))
+                .map(|v| luid.Value = v)
+                .is_ok(),
+            InterfaceId::Name(name) => match guid_from_str(name) {
+                Some(guid) => guid_to_luid(&bytes_to_guid(guid))
+                    .map(|v| luid.Value = v)
+                    .is_ok(),
+                None => name_to_luid(name, &mut luid),
+            },
+            InterfaceId::Alias(alias) => match WideCString::from_str(alias) {
+                Ok(alias) => {
+                    IpHelper::ConvertInterfaceAliasToLuid(alias.as_ptr() as _, &mut luid) == 0
+                }
+                Err(_) => false,
+            },
+        };
+        if !found {
+            return None;
+        }
+        resolved.luid = luid.Value;
+
+        // an unknown LUID fails here
+        resolved.index = luid_to_index(luid.Value).ok()?;
+        resolved.guid = guid_to_bytes(&luid_to_guid(luid.Value).ok()?);
+
+        let mut buf = [0u16; IF_MAX_STRING_SIZE];
+        if IpHelper::ConvertInterfaceLuidToNameW(&luid, buf.as_mut_ptr(), buf.len()) == 0 {
+            resolved.name = U16CStr::from_slice_truncate(&buf).ok()?.to_string_lossy();
+        }
+        let mut buf = [0u16; IF_MAX_STRING_SIZE];
+        if IpHelper::ConvertInterfaceLuidToAlias(&luid, buf.as_mut_ptr(), buf.len()) == 0 {
+            resolved.alias = U16CStr::from_slice_truncate(&buf).ok()?.to_string_lossy();
+        }
+    }
+    Some(resolved)
+}
+
+#[cfg(target_os = "linux")]
+fn resolve(id: &InterfaceId) -> Option<ResolvedInterface> {
+    resolve_in_sysfs(std::path::Path::new("/sys/class/net"), id)
+}
+
+#[cfg(not(any(windows, target_os = "linux")))]
+fn resolve(_id: &InterfaceId) -> Option<ResolvedInterface> {
+    None
+}
+
+/// Resolve with the `<root>/<name>/{ifindex,ifalias}` files of sysfs.
+#[cfg(any(target_os = "linux", test))]
+fn resolve_in_sysfs(root: &std::path::Path, id: &InterfaceId) -> Option<ResolvedInterface> {
+    let read = |name: &str| {
+        let dir = root.join(name);
+        let index = std::fs::read_to_string(dir.join("ifindex")).ok()?;
+        let alias = std::fs::read_to_string(dir.join("ifalias")).unwrap_or_default();
+        Some(ResolvedInterface {
+            index: index.trim().parse().ok()?,
+            name: name.to_owned(),
+            alias: alias.trim_end_matches('\n').to_owned(),
+            ..Default::default()
+        })
+    };
+
+    match id {
+        InterfaceId::Name(name) => {
+            if name.is_empty() || name.contains('/') || name.starts_with('.') {
+                return None;
+            }
+            read(name)
+        }
+        InterfaceId::Index(_) | InterfaceId::Alias(_) => std::fs::read_dir(root)
+            .ok()?
+            .filter_map(|entry| read(entry.ok()?.file_name().to_str()?))
+            .find(|resolved| match id {
+                InterfaceId::Index(index) => resolved.index == *index,
+                InterfaceId::Alias(alias) => !alias.is_empty() && resolved.alias == *alias,
+                _ => unreachable!(),
+            }),
+        InterfaceId::Luid(_) | InterfaceId::Guid(_) => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_conversions() {
+        assert_eq!(
+            InterfaceId::from(crate::Luid::from(1689399632855040)),
+            InterfaceId::Luid(1689399632855040)
+        );
+        assert_eq!(InterfaceId::from("eth0"), InterfaceId::Name("eth0".into()));
+
+        let mut adapter = Adapter {
+            adapter_name: "eth0".into(),
+            ..Default::default()
+        };
+        assert_eq!(
+            InterfaceId::from(&adapter),
+            InterfaceId::Name("eth0".into())
+        );
+        adapter.luid = 1;
+        assert_eq!(InterfaceId::from(&adapter), InterfaceId::Luid(1));
+    }
+
+    #[test]
+    fn test_display() {
+        let guid = crate::utils::guid_from_str("4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8").unwrap();
+        assert_eq!(InterfaceId::Index(7).to_string(), "index 7");
+        assert_eq!(
+            InterfaceId::Luid(0x6000001000000).to_string(),
+            "luid 0x6000001000000"
+        );
+        assert_eq!(
+            InterfaceId::Guid(guid).to_string(),
+            "guid {4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8}"
+        );
+        assert_eq!(
+            InterfaceId::Alias("Wi-Fi".into()).to_string(),
+            "alias \"Wi-Fi\""
+        );
+    }
+
+    #[test]
+    fn test_not_found() {
+        let err = InterfaceId::Name("no such interface/".into())
+            .resolve()
+            .unwrap_err();
+        assert!(err.is_interface_not_found());
+        assert_eq!(
+            err.to_string(),
+            "interface not found: name \"no such interface/\""
+        );
+    }
+
+    #[test]
+    fn test_resolve_in_sysfs() {
+        let root = std::env::temp_dir().join(format!("ipconfig2-sysfs-{}", std::process::id()));
+        for (name, index, alias) in [("lo", 1, None), ("eth0", 2, Some("uplink\n"))] {
+            let dir = root.join(name);
+            std::fs::create_dir_all(&dir).unwrap();
+            std::fs::write(dir.join("ifindex"), format!("{}\n", index)).unwrap();
+            if let Some(alias) = alias {
+                std::fs::write(dir.join("ifalias"), alias).unwrap();
+            }
+        }
+
+        let eth0 = ResolvedInterface {
+            index: 2,
+            name: "eth0".into(),
+            alias: "uplink".into(),
+            ..Default::default()
+        };
+        let resolve = |id: InterfaceId| resolve_in_sysfs(&root, &id);
+        assert_eq!(
+            resolve(InterfaceId::Name("eth0".into())),
+            Some(eth0.clone())
+        );
+        assert_eq!(resolve(InterfaceId::Index(2)), Some(eth0.clone()));
+        assert_eq!(resolve(InterfaceId::Alias("uplink".into())), Some(eth0));
+        assert_eq!(resolve(InterfaceId::Index(1)).unwrap().name, "lo");
+        assert_eq!(resolve(InterfaceId::Index(3)), None);
+        assert_eq!(resolve(InterfaceId::Alias("".into())), None);
+        assert_eq!(resolve(InterfaceId::Name("..".into())), None);
+        assert_eq!(resolve(InterfaceId::Luid(1)), None);
+
+        std::fs::remove_dir_all(&root).unwrap();
+    }
+}
@@ -1,13 +1,56 @@
 pub mod adapter;
+pub mod address;
+pub mod backend;
+pub mod bypass;
+#[cfg(feature = "capture")]
+pub mod capture;
+pub mod decode;
 pub mod error;
+pub mod interface;
+pub mod linux;
+pub mod luid;
+pub mod metric;
+pub mod network;
+pub mod prefix_set;
+pub mod query;
+pub mod route;
+pub mod scope;
+pub mod selection;
+pub mod snapshot;
+pub mod subnet;
 
-#[cfg(feature = "computer")]
+#[cfg(all(windows, feature = "computer"))]
 pub mod computer;
+#[cfg(windows)]
 pub mod dns;
 pub mod fwpm;
+#[cfg(feature = "serde")]
+pub mod schema;
 pub mod utils;
 
+#[cfg(windows)]
 pub mod ifindex;
 
-pub use adapter::{get_adapters, Adapter, IfType, OperStatus};
+#[cfg(windows)]
+pub use adapter::{get_adapters, get_adapters_partial};
+pub use adapter::{
+    Adapter, AdapterError, AdapterFlags, AdapterList, AdminStatus, ConnectionType, IfType,
+    MediaConnectState, OperStatus, TunnelType,
+};
+pub use address::UnicastAddress;
+#[cfg(windows)]
+pub use backend::OsBackend;
+pub use backend::{Backend, DnsSettings, FakeBackend};
+#[cfg(feature = "capture")]
+pub use capture::{Capture, ReplayBackend};
+pub use decode::AdapterBuffer;
+#[cfg(windows)]
 pub use ifindex::{find_adapter_interface_index as if_nametoindex, set_ip_unicast_if};
+pub use interface::InterfaceId;
+pub use luid::Luid;
+pub use network::{IpNetwork, Ipv4Network, Ipv6Network};
+pub use prefix_set::{IpRange, PrefixSet};
+pub use query::{AdapterQuery, AddressFamily};
+pub use route::{Route, RouteTable};
+pub use scope::ScopedIpAddr;
+pub use snapshot::{AdapterSnapshot, SnapshotDiff};
@@ -0,0 +1,316 @@
+//! Linux sources of the adapter fields that have no netlink attribute of their
+//! own, and of the routes. The parsers only work on the file contents so they
+//! are available, and tested, on every platform; the readers are Linux only.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::network::IpNetwork;
+use crate::route::Route;
+use crate::scope::ScopedIpAddr;
+
+const RTF_UP: u32 = 0x0001;
+const RTF_GATEWAY: u32 = 0x0002;
+const RTF_REJECT: u32 = 0x0200;
+const RTF_ANYCAST: u32 = 0x0010_0000;
+const RTF_LOCAL: u32 = 0x8000_0000;
+
+/// Parse `/sys/class/net/<iface>/mtu`.
+pub fn parse_mtu(content: &str) -> Option<u32> {
+    content.trim().parse().ok()
+}
+
+/// Parse `/proc/net/igmp` into `(interface index, group)` pairs.
+///
+/// Each interface line is followed by one indented line per group, with the
+/// group printed as the in-memory `__be32` in host byte order.
+pub fn parse_proc_net_igmp(content: &str) -> Vec<(u32, IpAddr)> {
+    let mut groups = vec![];
+    let mut if_index = None;
+
+    for line in content.lines().skip(1) {
+        let mut fields = line.split_whitespace();
+        let first = match fields.next() {
+            Some(first) => first,
+            None => continue,
+        };
+        if !line.starts_with(char::is_whitespace) {
+            if_index = first.parse().ok();
+            continue;
+        }
+        let (if_index, group) = match (if_index, u32::from_str_radix(first, 16)) {
+            (Some(if_index), Ok(group)) => (if_index, group),
+            _ => continue,
+        };
+        groups.push((if_index, Ipv4Addr::from(group.to_ne_bytes()).into()));
+    }
+
+    groups
+}
+
+/// Parse `/proc/net/igmp6` into `(interface index, group)` pairs.
+pub fn parse_proc_net_igmp6(content: &str) -> Vec<(u32, IpAddr)> {
+    let mut groups = vec![];
+
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 3 {
+            continue;
+        }
+        let if_index = match fields[0].parse() {
+            Ok(if_index) => if_index,
+            Err(_) => continue,
+        };
+        let group = match u128::from_str_radix(fields[2], 16) {
+            Ok(group) if fields[2].len() == 32 => group,
+            _ => continue,
+        };
+        groups.push((if_index, Ipv6Addr::from(group).into()));
+    }
+
+    groups
+}
+
+/// Parse `/proc/net/route`, the IPv4 routes of the main table.
+///
+/// The interface names are resolved to their index with `resolve`, the routes
+/// of unknown interfaces and the routes that are down are skipped.
+pub fn parse_proc_net_route<F>(content: &str, resolve: F) -> Vec<Route>
+where
+    F: Fn(&str) -> Option<u32>,
+{
+    let addr = |field: &str| {
+        let addr = u32::from_str_radix(field, 16).ok()?;
+        Some(Ipv4Addr::from(addr.to_ne_bytes()))
+    };
+    let mut routes = vec![];
+
+    for line in content.lines().skip(1) {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 8 {
+            continue;
+        }
+        let (destination, gateway, mask) = match (addr(fields[1]), addr(fields[2]), addr(fields[7]))
+        {
+            (Some(destination), Some(gateway), Some(mask)) => (destination, gateway, mask),
+            _ => continue,
+        };
+        let flags = u32::from_str_radix(fields[3], 16).unwrap_or(0);
+        let metric = fields[6].parse().unwrap_or(0);
+        let if_index = match resolve(fields[0]) {
+            Some(if_index) if flags & RTF_UP != 0 => if_index,
+            _ => continue,
+        };
+        let prefix = u32::from(mask).count_ones() as u8;
+        let destination = match IpNetwork::new(destination.into(), prefix) {
+            Ok(destination) => destination,
+            Err(_) => continue,
+        };
+        let mut route = Route::new(destination, if_index, metric);
+        if flags & RTF_GATEWAY != 0 {
+            route = route.via(IpAddr::from(gateway));
+        }
+        routes.push(route);
+    }
+
+    routes
+}
+
+/// Parse `/proc/net/ipv6_route`, the IPv6 routes of the main table.
+///
+/// The file lists the routes of all the tables, the local table ones (local,
+/// anycast and multicast routes) and the reject routes are skipped.
+pub fn parse_proc_net_ipv6_route<F>(content: &str, resolve: F) -> Vec<Route>
+where
+    F: Fn(&str) -> Option<u32>,
+{
+    let addr = |field: &str| match u128::from_str_radix(field, 16) {
+        Ok(addr) if field.len() == 32 => Some(Ipv6Addr::from(addr)),
+        _ => None,
+    };
+    let mut routes = vec![];
+
+    for line in content.lines() {
+        let fields: Vec<&str> = line.split_whitespace().collect();
+        if fields.len() < 10 {
+            continue;
+        }
+        let (destination, next_hop) = match (addr(fields[0]), addr(fields[4])) {
+            (Some(destination), Some(next_hop)) => (destination, next_hop),
+            _ => continue,
+        };
+        let prefix = u8::from_str_radix(fields[1], 16).unwrap_or(u8::MAX);
+        let metric = u32::from_str_radix(fields[5], 16).unwrap_or(0);
+        let flags = u32::from_str_radix(fields[8], 16).unwrap_or(0);
+        if flags & RTF_UP == 0
+            || flags & (RTF_REJECT | RTF_LOCAL | RTF_ANYCAST) != 0
+            || destination.is_multicast()
+        {
+            continue;
+        }
+        let if_index = match resolve(fields[9]) {
+            Some(if_index) => if_index,
+            None => continue,
+        };
+        let destination = match IpNetwork::new(destination.into(), prefix) {
+            Ok(destination) => destination,
+            Err(_) => continue,
+        };
+        let mut route = Route::new(destination, if_index, metric);
+        if flags & RTF_GATEWAY != 0 {
+            // link-local gateways are scoped to the outgoing interface
+            route = if next_hop.segments()[0] & 0xffc0 == 0xfe80 {
+                route.via(ScopedIpAddr::new(next_hop.into(), if_index))
+            } else {
+                route.via(IpAddr::from(next_hop))
+            };
+        }
+        routes.push(route);
+    }
+
+    routes
+}
+
+/// Read the MTU of an interface from sysfs.
+#[cfg(target_os = "linux")]
+pub fn read_mtu(iface: &str) -> std::io::Result<u32> {
+    let content = std::fs::read_to_string(format!("/sys/class/net/{}/mtu", iface))?;
+    parse_mtu(&content)
+        .ok_or_else(|| std::io::Error::new(std::io::ErrorKind::InvalidData, "invalid mtu"))
+}
+
+/// Read the IPv4 and IPv6 multicast groups joined by an interface.
+#[cfg(target_os = "linux")]
+pub fn read_multicast_addresses(if_index: u32) -> std::io::Result<Vec<IpAddr>> {
+    let mut groups = parse_proc_net_igmp(&std::fs::read_to_string("/proc/net/igmp")?);
+    // igmp6 is missing when IPv6 is disabled
+    if let Ok(content) = std::fs::read_to_string("/proc/net/igmp6") {
+        groups.extend(parse_proc_net_igmp6(&content));
+    }
+    Ok(groups
+        .into_iter()
+        .filter(|(index, _)| *index == if_index)
+        .map(|(_, group)| group)
+        .collect())
+}
+
+/// Read the IPv4 and IPv6 routes from procfs.
+#[cfg(target_os = "linux")]
+pub fn read_routes() -> std::io::Result<Vec<Route>> {
+    use crate::interface::InterfaceId;
+
+    let resolve = |name: &str| InterfaceId::Name(name.to_owned()).index().ok();
+    let mut routes = parse_proc_net_route(&std::fs::read_to_string("/proc/net/route")?, resolve);
+    // ipv6_route is missing when IPv6 is disabled
+    if let Ok(content) = std::fs::read_to_string("/proc/net/ipv6_route") {
+        routes.extend(parse_proc_net_ipv6_route(&content, resolve));
+    }
+    Ok(routes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_parse_mtu() {
+        assert_eq!(parse_mtu("1500\n"), Some(1500));
+        assert_eq!(parse_mtu(""), None);
+    }
+
+    #[test]
+    fn test_parse_proc_net_igmp() {
+        // the kernel prints the `__be32` as a host integer: a little-endian
+        // host prints 224.0.0.1 as 010000E0, a big-endian one as E0000001
+        #[cfg(target_endian = "little")]
+        let content = "Idx\tDevice    : Count Querier\tGroup    Users Timer\tReporter\n\
+                       1\tlo        :     1      V3\n\
+                       \t\t\t\t010000E0     1 0:00000000\t\t0\n\
+                       2\teth0      :     2      V3\n\
+                       \t\t\t\tFB0000E0     1 0:00000000\t\t0\n\
+                       \t\t\t\t010000E0     1 0:00000000\t\t0\n";
+        #[cfg(target_endian = "big")]
+        let content = "Idx\tDevice    : Count Querier\tGroup    Users Timer\tReporter\n\
+                       1\tlo        :     1      V3\n\
+                       \t\t\t\tE0000001     1 0:00000000\t\t0\n\
+                       2\teth0      :     2      V3\n\
+                       \t\t\t\tE00000FB     1 0:00000000\t\t0\n\
+                       \t\t\t\tE0000001     1 0:00000000\t\t0\n";
+        let groups = parse_proc_net_igmp(content);
+        let all_hosts: IpAddr = Ipv4Addr::new(224, 0, 0, 1).into();
+        let mdns: IpAddr = Ipv4Addr::new(224, 0, 0, 251).into();
+        assert_eq!(groups, [(1, all_hosts), (2, mdns), (2, all_hosts)]);
+    }
+
+    #[test]
+    fn test_parse_proc_net_igmp6() {
+        let content = "1    lo              ff020000000000000000000000000001     1 0000000C 0\n\
+                       2    eth0            ff0200000000000000000001ff8a2b3c     1 00000004 0\n\
+                       2    eth0            garbage\n";
+        let groups = parse_proc_net_igmp6(content);
+        assert_eq!(
+            groups,
+            [
+                (1, "ff02::1".parse().unwrap()),
+                (2, "ff02::1:ff8a:2b3c".parse().unwrap())
+            ]
+        );
+    }
+
+    fn resolve(name: &str) -> Option<u32> {
+        match name {
+            "lo" => Some(1),
+            "eth0" => Some(2),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_proc_net_route() {
+        let addr = |addr: Ipv4Addr| format!("{:08X}", u32::from_ne_bytes(addr.octets()));
+        let content = format!(
+            "Iface\tDestination\tGateway \tFlags\tRefCnt\tUse\tMetric\tMask\t\tMTU\tWindow\tIRTT\n\
+             eth0\t00000000\t{}\t0003\t0\t0\t100\t00000000\t0\t0\t0\n\
+             eth0\t{}\t00000000\t0001\t0\t0\t100\t{}\t0\t0\t0\n\
+             eth0\t{}\t00000000\t0000\t0\t0\t0\t{}\t0\t0\t0\n\
+             wg0\t00000000\t00000000\t0001\t0\t0\t0\t00000000\t0\t0\t0\n",
+            addr(Ipv4Addr::new(192, 168, 1, 1)),
+            addr(Ipv4Addr::new(192, 168, 1, 0)),
+            addr(Ipv4Addr::new(255, 255, 255, 0)),
+            addr(Ipv4Addr::new(10, 0, 0, 0)),
+            addr(Ipv4Addr::new(255, 0, 0, 0)),
+        );
+        let routes = parse_proc_net_route(&content, resolve);
+        assert_eq!(
+            routes,
+            [
+                Route::new("0.0.0.0/0".parse().unwrap(), 2, 100)
+                    .via(IpAddr::from(Ipv4Addr::new(192, 168, 1, 1))),
+                Route::new("192.168.1.0/24".parse().unwrap(), 2, 100),
+            ]
+        );
+    }
+
+    #[test]
+    fn test_parse_proc_net_ipv6_route() {
+        let content = "\
+            20010db8000000000000000000000000 20 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000001 00000000 00000001     eth0\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 fe800000000000000000000000000001 00000400 00000002 00000000 00000003     eth0\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 ffffffff 00000001 00000000 00200200       lo\n\
+            00000000000000000000000000000000 00 00000000000000000000000000000000 00 00000000000000000000000000000000 00000400 00000001 00000000 00000001      wg0\n\
+            20010db8000000010000000000000000 40 00000000000000000000000000000000 00 20010db8000000000000000000000001 00000400 00000003 00000000 00000003     eth0\n\
+            20010db8000000000000000000000005 80 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000002 00000000 80200001     eth0\n\
+            20010db8000000000000000000000000 40 00000000000000000000000000000000 00 00000000000000000000000000000000 00000000 00000002 00000000 00100001     eth0\n\
+            ff000000000000000000000000000000 08 00000000000000000000000000000000 00 00000000000000000000000000000000 00000100 00000002 00000000 00000001     eth0\n";
+        let routes = parse_proc_net_ipv6_route(content, resolve);
+        assert_eq!(
+            routes,
+            [
+                Route::new("2001:db8::/32".parse().unwrap(), 2, 256),
+                Route::new("::/0".parse().unwrap(), 2, 1024)
+                    .via("fe80::1%2".parse::<ScopedIpAddr>().unwrap()),
+                Route::new("2001:db8:0:1::/64".parse().unwrap(), 2, 1024)
+                    .via(IpAddr::from("2001:db8::1".parse::<Ipv6Addr>().unwrap())),
+            ]
+        );
+    }
+}
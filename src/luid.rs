@@ -0,0 +1,165 @@
+//! The `NET_LUID` of an interface.
+//!
+//! The LUID is a 64 bits value made of three bitfields: 24 reserved bits,
+//! the 24 bits `NetLuidIndex`, and the 16 bits IANA `IfType` of the
+//! interface. The index is only unique among the interfaces of one type.
+
+use std::fmt;
+use std::str::FromStr;
+
+use crate::adapter::{Adapter, IfType};
+use crate::error::*;
+use crate::interface::InterfaceId;
+
+const RESERVED_MASK: u64 = 0xff_ffff;
+const INDEX_SHIFT: u32 = 24;
+const INDEX_MASK: u64 = 0xff_ffff;
+const IF_TYPE_SHIFT: u32 = 48;
+
+/// A decoded `NET_LUID`, displayed as `type:index`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+pub struct Luid(u64);
+
+impl Luid {
+    /// Encode a LUID, fails if the type is 0 or doesn't fit in 16 bits, or
+    /// if the index doesn't fit in 24 bits.
+    pub fn new(if_type: impl Into<u32>, net_luid_index: u32) -> Result<Self> {
+        let if_type = if_type.into();
+        if if_type == 0 || if_type > u16::MAX as u32 {
+            return Err(Error::out_of_range("IfType", if_type as u64));
+        }
+        if net_luid_index as u64 > INDEX_MASK {
+            return Err(Error::out_of_range("NetLuidIndex", net_luid_index as u64));
+        }
+        Ok(Luid(
+            (if_type as u64) << IF_TYPE_SHIFT | (net_luid_index as u64) << INDEX_SHIFT,
+        ))
+    }
+
+    pub fn value(&self) -> u64 {
+        self.0
+    }
+
+    pub fn net_luid_index(&self) -> u32 {
+        ((self.0 >> INDEX_SHIFT) & INDEX_MASK) as u32
+    }
+
+    /// The raw IANA interface type, which is kept even when
+    /// [`Luid::if_type`] is `IfType::Unsupported`.
+    pub fn if_type_value(&self) -> u16 {
+        (self.0 >> IF_TYPE_SHIFT) as u16
+    }
+
+    pub fn if_type(&self) -> IfType {
+        IfType::from(self.if_type_value() as u32)
+    }
+
+    /// The reserved bits, always 0 for the LUIDs assigned by Windows.
+    pub fn reserved(&self) -> u32 {
+        (self.0 & RESERVED_MASK) as u32
+    }
+
+    /// The interface GUID, see `ConvertInterfaceLuidToGuid`.
+    #[cfg(windows)]
+    pub fn to_guid(&self) -> Result<[u8; 16]> {
+        let guid = crate::utils::luid_to_guid(self.0)?;
+        Ok(crate::utils::guid_to_bytes(&guid))
+    }
+
+    /// The interface index, see `ConvertInterfaceLuidToIndex`.
+    #[cfg(windows)]
+    pub fn to_index(&self) -> Result<u32> {
+        crate::utils::luid_to_index(self.0)
+    }
+}
+
+impl From<u64> for Luid {
+    fn from(value: u64) -> Self {
+        Luid(value)
+    }
+}
+
+impl From<Luid> for u64 {
+    fn from(luid: Luid) -> Self {
+        luid.0
+    }
+}
+
+impl From<Luid> for InterfaceId {
+    fn from(luid: Luid) -> Self {
+        InterfaceId::Luid(luid.0)
+    }
+}
+
+impl fmt::Display for Luid {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}:{}", self.if_type_value(), self.net_luid_index())
+    }
+}
+
+/// Parses the `type:index` format of `Display`.
+impl FromStr for Luid {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::parse("LUID", s);
+        let (if_type, index) = s.split_once(':').ok_or_else(invalid)?;
+        let if_type: u16 = if_type.parse().map_err(|_| invalid())?;
+        let index: u32 = index.parse().map_err(|_| invalid())?;
+        Luid::new(if_type, index).map_err(|_| invalid())
+    }
+}
+
+impl Adapter {
+    /// The decoded LUID of the adapter.
+    pub fn net_luid(&self) -> Luid {
+        Luid(self.luid)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_decode() {
+        // (value, if type, index, display)
+        let cases = [
+            (1689399632855040, IfType::EthernetCsmacd, 32769, "6:32769"),
+            (0x0018_0000_0000_0000, IfType::SoftwareLoopback, 0, "24:0"),
+            (
+                0x0047_8000_0100_0000,
+                IfType::Ieee80211,
+                0x800001,
+                "71:8388609",
+            ),
+            (0x0035_0000_0800_0000, IfType::Unsupported, 8, "53:8"),
+        ];
+        for (value, if_type, index, display) in cases {
+            let luid = Luid::from(value);
+            assert_eq!(luid.if_type(), if_type, "{}", value);
+            assert_eq!(luid.net_luid_index(), index, "{}", value);
+            assert_eq!(luid.reserved(), 0);
+            assert_eq!(luid.to_string(), display);
+            assert_eq!(display.parse::<Luid>().unwrap(), luid);
+            assert_eq!(u64::from(luid), value);
+        }
+        assert_eq!(Luid::from(0x0006_0000_0000_0001).reserved(), 1);
+    }
+
+    #[test]
+    fn test_encode() {
+        let luid = Luid::new(IfType::EthernetCsmacd, 32769).unwrap();
+        assert_eq!(luid.value(), 1689399632855040);
+        assert_eq!(Luid::new(53u32, 8).unwrap().if_type_value(), 53);
+
+        assert!(Luid::new(IfType::Unsupported, 1).is_err());
+        assert!(Luid::new(0x1_0000u32, 1).is_err());
+        assert!(Luid::new(IfType::Tunnel, 0x100_0000).is_err());
+        for input in ["6", "6:", ":1", "0:1", "6:16777216", "x:1"] {
+            assert!(input.parse::<Luid>().is_err(), "{}", input);
+        }
+    }
+}
@@ -0,0 +1,140 @@
+//! The Windows automatic interface metric.
+//!
+//! When the automatic metric is enabled, which is the default, Windows sets
+//! the metric of an interface from its link speed: a faster link gets a lower
+//! metric and wins over the slower ones for routes of the same prefix. This
+//! is why a wired adapter is usually preferred over Wi-Fi.
+
+use std::cmp::Reverse;
+
+use crate::adapter::{Adapter, OperStatus};
+
+/// The metric of each link speed, as documented for Windows 10 and later:
+/// (minimum speed in bits per second, metric).
+const AUTOMATIC_METRICS: [(u64, u32); 7] = [
+    (2_000_000_000, 15),
+    (200_000_000, 25),
+    (80_000_000, 35),
+    (20_000_000, 45),
+    (4_000_000, 55),
+    (500_000, 65),
+    (0, 75),
+];
+
+/// The automatic interface metric for a link speed in bits per second.
+pub fn automatic_metric(link_speed: u64) -> u32 {
+    AUTOMATIC_METRICS
+        .iter()
+        .find(|(speed, _)| link_speed >= *speed)
+        .map(|(_, metric)| *metric)
+        .unwrap()
+}
+
+impl Adapter {
+    /// The faster of the receive and transmit link speeds, `None` if the
+    /// adapter doesn't report one.
+    pub fn link_speed(&self) -> Option<u64> {
+        let speed = self.receive_link_speed.max(self.transmit_link_speed);
+        if speed == 0 || speed == u64::MAX {
+            None
+        } else {
+            Some(speed)
+        }
+    }
+
+    /// The IPv4 interface metric, `ipv4_metric` if set and the automatic
+    /// metric of the link speed otherwise.
+    pub fn effective_metric(&self) -> u32 {
+        if self.ipv4_metric != 0 {
+            return self.ipv4_metric;
+        }
+        automatic_metric(self.link_speed().unwrap_or(0))
+    }
+}
+
+/// Order the adapters that are up as Windows would prefer them for IPv4
+/// routes of the same prefix and route metric: the lowest effective metric
+/// first, then the adapters with a gateway, then the fastest link.
+pub fn rank_adapters(adapters: &[Adapter]) -> Vec<&Adapter> {
+    let mut ranked: Vec<_> = adapters
+        .iter()
+        .filter(|adapter| adapter.oper_status == OperStatus::IfOperStatusUp)
+        .collect();
+    ranked.sort_by_key(|adapter| {
+        (
+            adapter.effective_metric(),
+            adapter.gateways.is_empty(),
+            Reverse(adapter.link_speed().unwrap_or(0)),
+        )
+    });
+    ranked
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn test_automatic_metric() {
+        let cases = [
+            (100_000_000_000, 15),
+            (10_000_000_000, 15),
+            (2_000_000_000, 15),
+            (1_999_999_999, 25),
+            (1_000_000_000, 25),
+            (866_700_000, 25),
+            (200_000_000, 25),
+            (100_000_000, 35),
+            (54_000_000, 45),
+            (10_000_000, 55),
+            (4_000_000, 55),
+            (1_000_000, 65),
+            (499_999, 75),
+            (0, 75),
+        ];
+        for (speed, metric) in cases {
+            assert_eq!(automatic_metric(speed), metric, "{}", speed);
+        }
+    }
+
+    #[test]
+    fn test_rank_adapters() {
+        let adapter = |name: &str, speed: u64, metric: u32, gateway: bool| Adapter {
+            adapter_name: name.into(),
+            oper_status: OperStatus::IfOperStatusUp,
+            receive_link_speed: speed,
+            transmit_link_speed: speed / 2,
+            ipv4_metric: metric,
+            gateways: if gateway {
+                vec!["192.168.1.1".parse::<std::net::IpAddr>().unwrap().into()]
+            } else {
+                vec![]
+            },
+            ..Default::default()
+        };
+        let mut down = adapter("down", 10_000_000_000, 0, true);
+        down.oper_status = OperStatus::IfOperStatusDown;
+        let adapters = [
+            adapter("wifi", 300_000_000, 0, true),
+            adapter("tun", 100_000_000, 5, false),
+            adapter("ethernet", 1_000_000_000, 0, true),
+            adapter("hyperv", 10_000_000_000, 0, false),
+            adapter("unknown", u64::MAX, 0, true),
+            adapter("lan", 1_000_000_000, 0, false),
+            down,
+        ];
+        assert_eq!(adapters[0].effective_metric(), 25);
+        assert_eq!(adapters[1].effective_metric(), 5);
+        assert_eq!(adapters[4].link_speed(), None);
+        assert_eq!(adapters[4].effective_metric(), 75);
+
+        let names: Vec<_> = rank_adapters(&adapters)
+            .iter()
+            .map(|adapter| adapter.adapter_name.to_str().unwrap())
+            .collect();
+        assert_eq!(
+            names,
+            ["tun", "hyperv", "ethernet", "wifi", "lan", "unknown"]
+        );
+    }
+}
@@ -0,0 +1,544 @@
+//! CIDR networks, used to describe the prefixes of an adapter and the on-link
+//! prefix of each of its unicast addresses.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::error::*;
+
+macro_rules! impl_network {
+    ($network:ident, $addr:ty, $bits:ty, $max:expr, $name:expr) => {
+        impl $network {
+            /// The maximum prefix length of this address family.
+            pub const MAX_PREFIX: u8 = $max;
+
+            /// Create a network, the host bits of `addr` are kept as is.
+            pub fn new(addr: $addr, prefix: u8) -> Result<Self> {
+                if prefix > Self::MAX_PREFIX {
+                    return Err(ErrorKind::InvalidPrefixLength(prefix).into());
+                }
+                Ok(Self { addr, prefix })
+            }
+
+            /// `new` for constants, panics if the prefix length is invalid.
+            pub const fn new_const(addr: $addr, prefix: u8) -> Self {
+                assert!(prefix <= Self::MAX_PREFIX, "invalid prefix length");
+                Self { addr, prefix }
+            }
+
+            /// The address this network was created with.
+            pub fn addr(&self) -> $addr {
+                self.addr
+            }
+
+            pub fn prefix(&self) -> u8 {
+                self.prefix
+            }
+
+            fn mask_bits(&self) -> $bits {
+                match self.prefix {
+                    0 => 0,
+                    p => <$bits>::MAX << ($max - p),
+                }
+            }
+
+            pub fn mask(&self) -> $addr {
+                self.mask_bits().into()
+            }
+
+            pub fn hostmask(&self) -> $addr {
+                (!self.mask_bits()).into()
+            }
+
+            /// The first address of the network.
+            pub fn network(&self) -> $addr {
+                (<$bits>::from(self.addr) & self.mask_bits()).into()
+            }
+
+            /// The last address of the network.
+            pub fn last(&self) -> $addr {
+                (<$bits>::from(self.addr) | !self.mask_bits()).into()
+            }
+
+            /// The same network with the host bits cleared.
+            pub fn trunc(&self) -> Self {
+                Self {
+                    addr: self.network(),
+                    prefix: self.prefix,
+                }
+            }
+
+            pub fn contains(&self, addr: $addr) -> bool {
+                <$bits>::from(addr) & self.mask_bits() == <$bits>::from(self.network())
+            }
+
+            /// Whether `self` is inside `other`, a network is a subnet of itself.
+            pub fn is_subnet_of(&self, other: &Self) -> bool {
+                self.prefix >= other.prefix && other.contains(self.addr)
+            }
+
+            pub fn is_supernet_of(&self, other: &Self) -> bool {
+                other.is_subnet_of(self)
+            }
+
+            pub fn overlaps(&self, other: &Self) -> bool {
+                self.is_subnet_of(other) || other.is_subnet_of(self)
+            }
+
+            /// The network one bit shorter, `None` for a `/0`.
+            pub fn supernet(&self) -> Option<Self> {
+                let prefix = self.prefix.checked_sub(1)?;
+                Some(Self::new(self.addr, prefix).ok()?.trunc())
+            }
+
+            /// All the supernets, from the closest one up to the `/0`.
+            pub fn supernets(&self) -> impl Iterator<Item = Self> {
+                std::iter::successors(self.supernet(), Self::supernet)
+            }
+
+            /// Split the network into subnets of `new_prefix` length, in order.
+            pub fn subnets(&self, new_prefix: u8) -> Result<impl Iterator<Item = Self>> {
+                if new_prefix < self.prefix {
+                    return Err(ErrorKind::InvalidPrefixLength(new_prefix).into());
+                }
+                let first = Self::new(self.network(), new_prefix)?;
+                let last = <$bits>::from(self.last());
+                Ok(std::iter::successors(Some(first), move |subnet| {
+                    let next = <$bits>::from(subnet.last()).checked_add(1)?;
+                    if next > last {
+                        return None;
+                    }
+                    Some(Self {
+                        addr: next.into(),
+                        prefix: new_prefix,
+                    })
+                }))
+            }
+        }
+
+        impl From<$addr> for $network {
+            fn from(addr: $addr) -> Self {
+                Self {
+                    addr,
+                    prefix: Self::MAX_PREFIX,
+                }
+            }
+        }
+
+        impl fmt::Display for $network {
+            fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                write!(f, "{}/{}", self.addr, self.prefix)
+            }
+        }
+
+        /// Accepts `addr/prefix`, or a bare address as a host network.
+        impl FromStr for $network {
+            type Err = Error;
+
+            fn from_str(s: &str) -> Result<Self> {
+                let invalid = || Error::parse($name, s);
+                match s.split_once('/') {
+                    Some((addr, prefix)) => {
+                        let addr = addr.parse().map_err(|_| invalid())?;
+                        // `u8::from_str` accepts a leading '+'
+                        if !prefix.bytes().all(|b| b.is_ascii_digit()) {
+                            return Err(invalid());
+                        }
+                        let prefix = prefix.parse().map_err(|_| invalid())?;
+                        Self::new(addr, prefix).map_err(|_| invalid())
+                    }
+                    None => Ok(s.parse::<$addr>().map_err(|_| invalid())?.into()),
+                }
+            }
+        }
+    };
+}
+
+/// An IPv4 CIDR network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ipv4Network {
+    addr: Ipv4Addr,
+    prefix: u8,
+}
+
+impl_network!(Ipv4Network, Ipv4Addr, u32, 32, "Ipv4Network");
+
+impl Ipv4Network {
+    /// The broadcast address, the same as `last`.
+    pub fn broadcast(&self) -> Ipv4Addr {
+        self.last()
+    }
+
+    /// The first and last usable host addresses, excluding the network and
+    /// broadcast addresses except for `/31` (RFC 3021) and `/32`.
+    pub fn host_range(&self) -> (Ipv4Addr, Ipv4Addr) {
+        if self.prefix >= 31 {
+            return (self.network(), self.last());
+        }
+        let first = u32::from(self.network()) + 1;
+        let last = u32::from(self.last()) - 1;
+        (first.into(), last.into())
+    }
+}
+
+/// An IPv6 CIDR network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Ipv6Network {
+    addr: Ipv6Addr,
+    prefix: u8,
+}
+
+impl_network!(Ipv6Network, Ipv6Addr, u128, 128, "Ipv6Network");
+
+impl Ipv6Network {
+    /// The first and last usable host addresses, excluding the Subnet-Router
+    /// anycast address except for `/127` (RFC 6164) and `/128`.
+    pub fn host_range(&self) -> (Ipv6Addr, Ipv6Addr) {
+        if self.prefix >= 127 {
+            return (self.network(), self.last());
+        }
+        let first = u128::from(self.network()) + 1;
+        (first.into(), self.last())
+    }
+}
+
+/// An IPv4 or IPv6 CIDR network.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub enum IpNetwork {
+    V4(Ipv4Network),
+    V6(Ipv6Network),
+}
+
+impl IpNetwork {
+    pub fn new(addr: IpAddr, prefix: u8) -> Result<Self> {
+        match addr {
+            IpAddr::V4(addr) => Ok(IpNetwork::V4(Ipv4Network::new(addr, prefix)?)),
+            IpAddr::V6(addr) => Ok(IpNetwork::V6(Ipv6Network::new(addr, prefix)?)),
+        }
+    }
+
+    pub fn addr(&self) -> IpAddr {
+        match self {
+            IpNetwork::V4(n) => n.addr().into(),
+            IpNetwork::V6(n) => n.addr().into(),
+        }
+    }
+
+    pub fn prefix(&self) -> u8 {
+        match self {
+            IpNetwork::V4(n) => n.prefix(),
+            IpNetwork::V6(n) => n.prefix(),
+        }
+    }
+
+    pub fn max_prefix(&self) -> u8 {
+        match self {
+            IpNetwork::V4(_) => Ipv4Network::MAX_PREFIX,
+            IpNetwork::V6(_) => Ipv6Network::MAX_PREFIX,
+        }
+    }
+
+    pub fn is_ipv4(&self) -> bool {
+        matches!(self, IpNetwork::V4(_))
+    }
+
+    pub fn is_ipv6(&self) -> bool {
+        matches!(self, IpNetwork::V6(_))
+    }
+
+    pub fn mask(&self) -> IpAddr {
+        match self {
+            IpNetwork::V4(n) => n.mask().into(),
+            IpNetwork::V6(n) => n.mask().into(),
+        }
+    }
+
+    pub fn network(&self) -> IpAddr {
+        match self {
+            IpNetwork::V4(n) => n.network().into(),
+            IpNetwork::V6(n) => n.network().into(),
+        }
+    }
+
+    pub fn last(&self) -> IpAddr {
+        match self {
+            IpNetwork::V4(n) => n.last().into(),
+            IpNetwork::V6(n) => n.last().into(),
+        }
+    }
+
+    /// The IPv4 broadcast address, IPv6 has none.
+    pub fn broadcast(&self) -> Option<Ipv4Addr> {
+        match self {
+            IpNetwork::V4(n) => Some(n.broadcast()),
+            IpNetwork::V6(_) => None,
+        }
+    }
+
+    pub fn host_range(&self) -> (IpAddr, IpAddr) {
+        match self {
+            IpNetwork::V4(n) => {
+                let (first, last) = n.host_range();
+                (first.into(), last.into())
+            }
+            IpNetwork::V6(n) => {
+                let (first, last) = n.host_range();
+                (first.into(), last.into())
+            }
+        }
+    }
+
+    pub fn trunc(&self) -> Self {
+        match self {
+            IpNetwork::V4(n) => IpNetwork::V4(n.trunc()),
+            IpNetwork::V6(n) => IpNetwork::V6(n.trunc()),
+        }
+    }
+
+    /// Whether `addr` is in the network, always `false` across families.
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        match (self, addr) {
+            (IpNetwork::V4(n), IpAddr::V4(addr)) => n.contains(addr),
+            (IpNetwork::V6(n), IpAddr::V6(addr)) => n.contains(addr),
+            _ => false,
+        }
+    }
+
+    pub fn is_subnet_of(&self, other: &IpNetwork) -> bool {
+        match (self, other) {
+            (IpNetwork::V4(a), IpNetwork::V4(b)) => a.is_subnet_of(b),
+            (IpNetwork::V6(a), IpNetwork::V6(b)) => a.is_subnet_of(b),
+            _ => false,
+        }
+    }
+
+    pub fn is_supernet_of(&self, other: &IpNetwork) -> bool {
+        other.is_subnet_of(self)
+    }
+
+    pub fn overlaps(&self, other: &IpNetwork) -> bool {
+        match (self, other) {
+            (IpNetwork::V4(a), IpNetwork::V4(b)) => a.overlaps(b),
+            (IpNetwork::V6(a), IpNetwork::V6(b)) => a.overlaps(b),
+            _ => false,
+        }
+    }
+
+    pub fn supernet(&self) -> Option<Self> {
+        match self {
+            IpNetwork::V4(n) => n.supernet().map(IpNetwork::V4),
+            IpNetwork::V6(n) => n.supernet().map(IpNetwork::V6),
+        }
+    }
+
+    pub fn supernets(&self) -> impl Iterator<Item = Self> {
+        std::iter::successors(self.supernet(), Self::supernet)
+    }
+
+    pub fn subnets(&self, new_prefix: u8) -> Result<Box<dyn Iterator<Item = Self>>> {
+        match self {
+            IpNetwork::V4(n) => Ok(Box::new(n.subnets(new_prefix)?.map(IpNetwork::V4))),
+            IpNetwork::V6(n) => Ok(Box::new(n.subnets(new_prefix)?.map(IpNetwork::V6))),
+        }
+    }
+}
+
+impl From<Ipv4Network> for IpNetwork {
+    fn from(network: Ipv4Network) -> Self {
+        IpNetwork::V4(network)
+    }
+}
+
+impl From<Ipv6Network> for IpNetwork {
+    fn from(network: Ipv6Network) -> Self {
+        IpNetwork::V6(network)
+    }
+}
+
+impl From<IpAddr> for IpNetwork {
+    fn from(addr: IpAddr) -> Self {
+        match addr {
+            IpAddr::V4(addr) => IpNetwork::V4(addr.into()),
+            IpAddr::V6(addr) => IpNetwork::V6(addr.into()),
+        }
+    }
+}
+
+impl fmt::Display for IpNetwork {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match self {
+            IpNetwork::V4(n) => n.fmt(f),
+            IpNetwork::V6(n) => n.fmt(f),
+        }
+    }
+}
+
+impl FromStr for IpNetwork {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        if let Ok(n) = s.parse::<Ipv4Network>() {
+            return Ok(IpNetwork::V4(n));
+        }
+        s.parse::<Ipv6Network>()
+            .map(IpNetwork::V6)
+            .map_err(|_| Error::parse("IpNetwork", s))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(s: &str) -> IpNetwork {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    #[test]
+    fn test_parse() {
+        let cases = [
+            ("10.1.2.3/8", Some("10.1.2.3/8")),
+            ("10.1.2.3", Some("10.1.2.3/32")),
+            ("0.0.0.0/0", Some("0.0.0.0/0")),
+            ("fe80::1/64", Some("fe80::1/64")),
+            ("::1", Some("::1/128")),
+            ("10.0.0.0/33", None),
+            ("::/129", None),
+            ("10.0.0.0/", None),
+            ("10.0.0.0/+8", None),
+            ("10.0.0/8", None),
+            ("", None),
+        ];
+        for (input, expected) in cases {
+            let parsed = input.parse::<IpNetwork>().ok().map(|n| n.to_string());
+            assert_eq!(parsed.as_deref(), expected, "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_ranges() {
+        // (network, mask, network address, last, host range)
+        let cases = [
+            (
+                "192.168.1.77/24",
+                "255.255.255.0",
+                "192.168.1.0",
+                "192.168.1.255",
+                ("192.168.1.1", "192.168.1.254"),
+            ),
+            (
+                "10.0.0.1/31",
+                "255.255.255.254",
+                "10.0.0.0",
+                "10.0.0.1",
+                ("10.0.0.0", "10.0.0.1"),
+            ),
+            (
+                "10.0.0.1/32",
+                "255.255.255.255",
+                "10.0.0.1",
+                "10.0.0.1",
+                ("10.0.0.1", "10.0.0.1"),
+            ),
+            (
+                "1.2.3.4/0",
+                "0.0.0.0",
+                "0.0.0.0",
+                "255.255.255.255",
+                ("0.0.0.1", "255.255.255.254"),
+            ),
+            (
+                "2001:db8::5/64",
+                "ffff:ffff:ffff:ffff::",
+                "2001:db8::",
+                "2001:db8::ffff:ffff:ffff:ffff",
+                ("2001:db8::1", "2001:db8::ffff:ffff:ffff:ffff"),
+            ),
+            (
+                "2001:db8::1/127",
+                "ffff:ffff:ffff:ffff:ffff:ffff:ffff:fffe",
+                "2001:db8::",
+                "2001:db8::1",
+                ("2001:db8::", "2001:db8::1"),
+            ),
+        ];
+        for (n, mask, network, last, (first_host, last_host)) in cases {
+            let n = net(n);
+            assert_eq!(n.mask(), ip(mask));
+            assert_eq!(n.network(), ip(network));
+            assert_eq!(n.last(), ip(last));
+            assert_eq!(n.host_range(), (ip(first_host), ip(last_host)));
+        }
+        assert_eq!(
+            net("192.168.1.77/24").broadcast(),
+            Some("192.168.1.255".parse().unwrap())
+        );
+        assert_eq!(net("fe80::/64").broadcast(), None);
+    }
+
+    #[test]
+    fn test_contains_and_overlaps() {
+        let n = net("172.16.0.0/12");
+        assert!(n.contains(ip("172.31.255.255")));
+        assert!(!n.contains(ip("172.32.0.0")));
+        assert!(!n.contains(ip("::ffff:172.16.0.1")));
+
+        // (a, b, a is a subnet of b, overlaps)
+        let cases = [
+            ("10.1.0.0/16", "10.0.0.0/8", true, true),
+            ("10.0.0.0/8", "10.1.0.0/16", false, true),
+            ("10.0.0.0/8", "10.0.0.0/8", true, true),
+            ("10.0.0.0/8", "11.0.0.0/8", false, false),
+            ("0.0.0.0/1", "128.0.0.0/1", false, false),
+            ("0.0.0.0/0", "::/0", false, false),
+            ("fd00::/8", "fd12:3456::/32", false, true),
+        ];
+        for (a, b, subnet, overlaps) in cases {
+            assert_eq!(net(a).is_subnet_of(&net(b)), subnet, "{} {}", a, b);
+            assert_eq!(net(b).is_supernet_of(&net(a)), subnet, "{} {}", a, b);
+            assert_eq!(net(a).overlaps(&net(b)), overlaps, "{} {}", a, b);
+            assert_eq!(net(b).overlaps(&net(a)), overlaps, "{} {}", a, b);
+        }
+    }
+
+    #[test]
+    fn test_supernets_and_subnets() {
+        assert_eq!(net("10.1.2.3/9").supernet(), Some(net("10.0.0.0/8")));
+        assert_eq!(net("0.0.0.0/0").supernet(), None);
+        let supernets: Vec<_> = net("192.168.0.0/3")
+            .supernets()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(supernets, ["192.0.0.0/2", "128.0.0.0/1", "0.0.0.0/0"]);
+        assert_eq!(net("::/0").supernets().count(), 0);
+
+        let subnets: Vec<_> = net("10.0.0.77/24")
+            .subnets(26)
+            .unwrap()
+            .map(|n| n.to_string())
+            .collect();
+        assert_eq!(
+            subnets,
+            [
+                "10.0.0.0/26",
+                "10.0.0.64/26",
+                "10.0.0.128/26",
+                "10.0.0.192/26"
+            ]
+        );
+        let subnets: Vec<_> = net("255.255.255.254/31").subnets(32).unwrap().collect();
+        assert_eq!(
+            subnets,
+            [net("255.255.255.254/32"), net("255.255.255.255/32")]
+        );
+        assert_eq!(net("0.0.0.0/0").subnets(0).unwrap().count(), 1);
+        assert_eq!(net("fd00::/48").subnets(64).unwrap().count(), 65536);
+        assert!(net("10.0.0.0/24").subnets(16).is_err());
+        assert!(net("10.0.0.0/24").subnets(33).is_err());
+    }
+}
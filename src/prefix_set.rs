@@ -0,0 +1,397 @@
+//! Sets of addresses built from included and excluded prefixes or ranges,
+//! and turned back into the minimal list of prefixes that covers them.
+//!
+//! Routing "everything except the LAN and the proxy server" through a TUN is
+//! [`PrefixSet::from_include_exclude`] with `0.0.0.0/0` and `::/0` included
+//! and the LAN prefixes and server address excluded, followed by
+//! [`PrefixSet::to_routes`].
+
+use std::fmt;
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+use std::str::FromStr;
+
+use crate::error::*;
+use crate::network::IpNetwork;
+use crate::route::Route;
+
+/// An inclusive range of addresses of one family.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct IpRange {
+    start: IpAddr,
+    end: IpAddr,
+}
+
+impl IpRange {
+    /// Fails if the addresses are of different families or `start > end`.
+    pub fn new(start: IpAddr, end: IpAddr) -> Result<Self> {
+        if start.is_ipv4() != end.is_ipv4() || start > end {
+            return Err(Error::parse("IpRange", &format!("{}-{}", start, end)));
+        }
+        Ok(IpRange { start, end })
+    }
+
+    pub fn start(&self) -> IpAddr {
+        self.start
+    }
+
+    pub fn end(&self) -> IpAddr {
+        self.end
+    }
+
+    /// The minimal list of prefixes covering exactly this range.
+    pub fn to_prefixes(&self) -> Vec<IpNetwork> {
+        let (start, end) = (to_u128(self.start), to_u128(self.end));
+        let mut prefixes = vec![];
+        push_prefixes(start, end, self.start.is_ipv4(), &mut prefixes);
+        prefixes
+    }
+}
+
+impl From<IpNetwork> for IpRange {
+    fn from(network: IpNetwork) -> Self {
+        IpRange {
+            start: network.network(),
+            end: network.last(),
+        }
+    }
+}
+
+impl From<IpAddr> for IpRange {
+    fn from(addr: IpAddr) -> Self {
+        IpRange {
+            start: addr,
+            end: addr,
+        }
+    }
+}
+
+impl fmt::Display for IpRange {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}-{}", self.start, self.end)
+    }
+}
+
+/// Accepts `start-end`, a prefix or a single address.
+impl FromStr for IpRange {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        let invalid = || Error::parse("IpRange", s);
+        match s.split_once('-') {
+            Some((start, end)) => IpRange::new(
+                start.trim().parse().map_err(|_| invalid())?,
+                end.trim().parse().map_err(|_| invalid())?,
+            )
+            .map_err(|_| invalid()),
+            None => Ok(s.parse::<IpNetwork>().map_err(|_| invalid())?.into()),
+        }
+    }
+}
+
+fn to_u128(addr: IpAddr) -> u128 {
+    match addr {
+        IpAddr::V4(addr) => u32::from(addr) as u128,
+        IpAddr::V6(addr) => u128::from(addr),
+    }
+}
+
+fn from_u128(value: u128, ipv4: bool) -> IpAddr {
+    if ipv4 {
+        Ipv4Addr::from(value as u32).into()
+    } else {
+        Ipv6Addr::from(value).into()
+    }
+}
+
+/// Split `start..=end` in the largest aligned blocks.
+fn push_prefixes(mut start: u128, end: u128, ipv4: bool, prefixes: &mut Vec<IpNetwork>) {
+    let bits = if ipv4 { 32 } else { 128 };
+    loop {
+        let mut size = start.trailing_zeros().min(bits);
+        while size > 0 && block_end(start, size) > end {
+            size -= 1;
+        }
+        let network = IpNetwork::new(from_u128(start, ipv4), (bits - size) as u8).unwrap();
+        prefixes.push(network);
+        let last = block_end(start, size);
+        if last >= end {
+            break;
+        }
+        start = last + 1;
+    }
+}
+
+/// The last address of the block of `2^size` addresses at `start`.
+fn block_end(start: u128, size: u32) -> u128 {
+    if size >= 128 {
+        u128::MAX
+    } else {
+        start | ((1u128 << size) - 1)
+    }
+}
+
+/// A set of IPv4 and IPv6 addresses.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct PrefixSet {
+    /// Sorted, disjoint and non adjacent inclusive ranges.
+    v4: Vec<(u128, u128)>,
+    v6: Vec<(u128, u128)>,
+}
+
+impl PrefixSet {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// The addresses of `include` that are not in `exclude`.
+    pub fn from_include_exclude<I, E>(include: I, exclude: E) -> Self
+    where
+        I: IntoIterator,
+        I::Item: Into<IpRange>,
+        E: IntoIterator,
+        E::Item: Into<IpRange>,
+    {
+        let mut set = PrefixSet::new();
+        for range in include {
+            set.insert(range);
+        }
+        for range in exclude {
+            set.remove(range);
+        }
+        set
+    }
+
+    fn ranges_mut(&mut self, ipv4: bool) -> &mut Vec<(u128, u128)> {
+        if ipv4 {
+            &mut self.v4
+        } else {
+            &mut self.v6
+        }
+    }
+
+    pub fn insert(&mut self, range: impl Into<IpRange>) {
+        let range = range.into();
+        let (mut start, mut end) = (to_u128(range.start), to_u128(range.end));
+        let ranges = self.ranges_mut(range.start.is_ipv4());
+
+        // merge with the overlapping and adjacent ranges
+        let mut merged = Vec::with_capacity(ranges.len() + 1);
+        for &(s, e) in ranges.iter() {
+            if e.saturating_add(1) < start || end.saturating_add(1) < s {
+                merged.push((s, e));
+            } else {
+                start = start.min(s);
+                end = end.max(e);
+            }
+        }
+        merged.push((start, end));
+        merged.sort_unstable();
+        *ranges = merged;
+    }
+
+    pub fn remove(&mut self, range: impl Into<IpRange>) {
+        let range = range.into();
+        let (start, end) = (to_u128(range.start), to_u128(range.end));
+        let ranges = self.ranges_mut(range.start.is_ipv4());
+
+        let mut kept = Vec::with_capacity(ranges.len() + 1);
+        for &(s, e) in ranges.iter() {
+            if e < start || end < s {
+                kept.push((s, e));
+                continue;
+            }
+            if s < start {
+                kept.push((s, start - 1));
+            }
+            if end < e {
+                kept.push((end + 1, e));
+            }
+        }
+        *ranges = kept;
+    }
+
+    pub fn union(&self, other: &PrefixSet) -> PrefixSet {
+        let mut set = self.clone();
+        for range in other.ranges() {
+            set.insert(range);
+        }
+        set
+    }
+
+    pub fn difference(&self, other: &PrefixSet) -> PrefixSet {
+        let mut set = self.clone();
+        for range in other.ranges() {
+            set.remove(range);
+        }
+        set
+    }
+
+    pub fn contains(&self, addr: IpAddr) -> bool {
+        let ranges = if addr.is_ipv4() { &self.v4 } else { &self.v6 };
+        let value = to_u128(addr);
+        let i = ranges.partition_point(|&(_, e)| e < value);
+        ranges.get(i).is_some_and(|&(s, _)| s <= value)
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.v4.is_empty() && self.v6.is_empty()
+    }
+
+    /// The disjoint ranges of the set, IPv4 first.
+    pub fn ranges(&self) -> impl Iterator<Item = IpRange> + '_ {
+        let v4 = self.v4.iter().map(|&(s, e)| (s, e, true));
+        let v6 = self.v6.iter().map(|&(s, e)| (s, e, false));
+        v4.chain(v6).map(|(s, e, ipv4)| IpRange {
+            start: from_u128(s, ipv4),
+            end: from_u128(e, ipv4),
+        })
+    }
+
+    /// The minimal list of prefixes covering exactly the set, sorted and
+    /// IPv4 first. Adjacent prefixes are aggregated.
+    pub fn prefixes(&self) -> Vec<IpNetwork> {
+        let mut prefixes = vec![];
+        for &(s, e) in &self.v4 {
+            push_prefixes(s, e, true, &mut prefixes);
+        }
+        for &(s, e) in &self.v6 {
+            push_prefixes(s, e, false, &mut prefixes);
+        }
+        prefixes
+    }
+
+    /// On-link routes through `if_index` for the prefixes of the set.
+    pub fn to_routes(&self, if_index: u32, metric: u32) -> Vec<Route> {
+        self.prefixes()
+            .into_iter()
+            .map(|prefix| Route::new(prefix, if_index, metric))
+            .collect()
+    }
+}
+
+impl<R: Into<IpRange>> Extend<R> for PrefixSet {
+    fn extend<T: IntoIterator<Item = R>>(&mut self, ranges: T) {
+        for range in ranges {
+            self.insert(range);
+        }
+    }
+}
+
+impl<R: Into<IpRange>> FromIterator<R> for PrefixSet {
+    fn from_iter<T: IntoIterator<Item = R>>(ranges: T) -> Self {
+        let mut set = PrefixSet::new();
+        set.extend(ranges);
+        set
+    }
+}
+
+/// The minimal list of prefixes covering `prefixes`, with the overlapping
+/// ones removed and the adjacent ones aggregated.
+pub fn aggregate(prefixes: impl IntoIterator<Item = IpNetwork>) -> Vec<IpNetwork> {
+    prefixes.into_iter().collect::<PrefixSet>().prefixes()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn nets(list: &[&str]) -> Vec<IpNetwork> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    fn ranges(list: &[&str]) -> Vec<IpRange> {
+        list.iter().map(|s| s.parse().unwrap()).collect()
+    }
+
+    #[test]
+    fn test_range() {
+        let range: IpRange = "10.0.0.1-10.0.0.6".parse().unwrap();
+        assert_eq!(
+            range.to_prefixes(),
+            nets(&["10.0.0.1/32", "10.0.0.2/31", "10.0.0.4/31", "10.0.0.6/32"])
+        );
+        let range: IpRange = "10.1.0.0/16".parse().unwrap();
+        assert_eq!(range.to_string(), "10.1.0.0-10.1.255.255");
+        assert_eq!(
+            "::-ffff:ffff:ffff:ffff:ffff:ffff:ffff:ffff"
+                .parse::<IpRange>()
+                .unwrap()
+                .to_prefixes(),
+            nets(&["::/0"])
+        );
+        for input in ["10.0.0.6-10.0.0.1", "10.0.0.1-::1", "10.0.0.1-", "x"] {
+            assert!(input.parse::<IpRange>().is_err(), "{}", input);
+        }
+    }
+
+    #[test]
+    fn test_exclude_lan_and_server() {
+        let set = PrefixSet::from_include_exclude(
+            ranges(&["0.0.0.0/0"]),
+            ranges(&[
+                "10.0.0.0/8",
+                "172.16.0.0/12",
+                "192.168.0.0/16",
+                "203.0.113.7",
+            ]),
+        );
+        let prefixes = set.prefixes();
+        assert_eq!(prefixes.len(), 57);
+        for addr in ["10.1.2.3", "172.20.0.1", "192.168.1.1", "203.0.113.7"] {
+            let addr: IpAddr = addr.parse().unwrap();
+            assert!(!set.contains(addr), "{}", addr);
+            assert!(!prefixes.iter().any(|p| p.contains(addr)), "{}", addr);
+        }
+        for addr in [
+            "1.1.1.1",
+            "11.0.0.0",
+            "203.0.113.6",
+            "203.0.113.8",
+            "255.255.255.255",
+        ] {
+            let addr: IpAddr = addr.parse().unwrap();
+            assert!(set.contains(addr), "{}", addr);
+            assert_eq!(prefixes.iter().filter(|p| p.contains(addr)).count(), 1);
+        }
+        assert_eq!(prefixes[..2], nets(&["0.0.0.0/5", "8.0.0.0/7"]));
+    }
+
+    #[test]
+    fn test_ipv6() {
+        let set = PrefixSet::from_include_exclude(ranges(&["::/0"]), ranges(&["fc00::/7", "::/1"]));
+        assert_eq!(
+            set.prefixes(),
+            nets(&["8000::/2", "c000::/3", "e000::/4", "f000::/5", "f800::/6", "fe00::/7"])
+        );
+    }
+
+    #[test]
+    fn test_aggregate() {
+        assert_eq!(
+            aggregate(nets(&[
+                "10.0.0.128/25",
+                "10.0.0.0/25",
+                "10.0.1.0/24",
+                "10.0.1.7/32",
+                "192.168.0.0/24",
+                "2001:db8::/33",
+                "2001:db8:8000::/33",
+            ])),
+            nets(&["10.0.0.0/23", "192.168.0.0/24", "2001:db8::/32"])
+        );
+    }
+
+    #[test]
+    fn test_set_operations() {
+        let a: PrefixSet = nets(&["10.0.0.0/24"]).into_iter().collect();
+        let b: PrefixSet = nets(&["10.0.0.128/25", "10.0.1.0/24"])
+            .into_iter()
+            .collect();
+        assert_eq!(a.union(&b).prefixes(), nets(&["10.0.0.0/23"]));
+        assert_eq!(a.difference(&b).prefixes(), nets(&["10.0.0.0/25"]));
+        assert!(a.difference(&a).is_empty());
+
+        let routes = a.to_routes(7, 1);
+        assert_eq!(routes, [Route::new("10.0.0.0/24".parse().unwrap(), 7, 1)]);
+    }
+}
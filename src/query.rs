@@ -0,0 +1,638 @@
+//! Targeted adapter queries.
+//!
+//! `GetAdaptersAddresses` is slow on machines with many interfaces, mostly
+//! because of the sections most callers don't need. An [`AdapterQuery`] only
+//! asks for the address family and sections it is configured with, and can
+//! look up a single adapter.
+//!
+//! Queries only run on Windows. The crate has no netlink client:
+//! [`AdapterQuery::netlink_requests`] describes the filtered dumps a query
+//! maps to, for a Linux caller to send with its own netlink socket and to
+//! filter the replies with [`AdapterQuery::matches`] and
+//! [`AdapterQuery::apply`].
+
+use std::net::IpAddr;
+
+use crate::adapter::{Adapter, AdapterList};
+use crate::backend::Backend;
+#[cfg(windows)]
+use crate::backend::OsBackend;
+#[cfg(windows)]
+use crate::decode::AdapterBuffer;
+use crate::error::*;
+use crate::interface::InterfaceId;
+use crate::utils::guid_from_str;
+
+/// The `GAA_FLAG_*` flags of `GetAdaptersAddresses`.
+pub mod gaa_flags {
+    pub const SKIP_UNICAST: u32 = 0x0001;
+    pub const SKIP_ANYCAST: u32 = 0x0002;
+    pub const SKIP_MULTICAST: u32 = 0x0004;
+    pub const SKIP_DNS_SERVER: u32 = 0x0008;
+    pub const INCLUDE_PREFIX: u32 = 0x0010;
+    pub const SKIP_FRIENDLY_NAME: u32 = 0x0020;
+    pub const INCLUDE_WINS_INFO: u32 = 0x0040;
+    pub const INCLUDE_GATEWAYS: u32 = 0x0080;
+    pub const INCLUDE_ALL_INTERFACES: u32 = 0x0100;
+    pub const INCLUDE_ALL_COMPARTMENTS: u32 = 0x0200;
+    pub const INCLUDE_TUNNEL_BINDINGORDER: u32 = 0x0400;
+    pub const SKIP_DNS_INFO: u32 = 0x0800;
+}
+
+/// The netlink message types and flags used by [`NetlinkRequest`].
+pub mod netlink {
+    pub const RTM_GETLINK: u16 = 18;
+    pub const RTM_GETADDR: u16 = 22;
+    pub const RTM_GETROUTE: u16 = 26;
+    pub const RTM_GETMULTICAST: u16 = 58;
+    pub const RTM_GETANYCAST: u16 = 62;
+
+    pub const NLM_F_REQUEST: u16 = 0x0001;
+    pub const NLM_F_DUMP: u16 = 0x0300;
+
+    pub const AF_UNSPEC: u8 = 0;
+    pub const AF_INET: u8 = 2;
+    pub const AF_INET6: u8 = 10;
+}
+
+/// The address family of the addresses to retrieve.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash, Default)]
+pub enum AddressFamily {
+    #[default]
+    Unspec,
+    Ipv4,
+    Ipv6,
+}
+
+impl AddressFamily {
+    /// The `AF_*` value of Windows.
+    pub fn to_windows(self) -> u32 {
+        match self {
+            AddressFamily::Unspec => 0,
+            AddressFamily::Ipv4 => 2,
+            AddressFamily::Ipv6 => 23,
+        }
+    }
+
+    /// The `AF_*` value of Linux.
+    pub fn to_linux(self) -> u8 {
+        match self {
+            AddressFamily::Unspec => netlink::AF_UNSPEC,
+            AddressFamily::Ipv4 => netlink::AF_INET,
+            AddressFamily::Ipv6 => netlink::AF_INET6,
+        }
+    }
+
+    fn includes_ipv6(self) -> bool {
+        self != AddressFamily::Ipv4
+    }
+
+    fn includes(self, ip: IpAddr) -> bool {
+        match self {
+            AddressFamily::Unspec => true,
+            AddressFamily::Ipv4 => ip.is_ipv4(),
+            AddressFamily::Ipv6 => ip.is_ipv6(),
+        }
+    }
+}
+
+/// How to find a single adapter.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+pub enum AdapterLookup {
+    Luid(u64),
+    /// The IPv4 or IPv6 interface index.
+    Index(u32),
+    /// The interface GUID, the adapter name on Windows.
+    Guid([u8; 16]),
+    /// The adapter name or the friendly name.
+    Name(String),
+}
+
+impl AdapterLookup {
+    pub fn matches(&self, adapter: &Adapter) -> bool {
+        match self {
+            AdapterLookup::Luid(luid) => adapter.luid == *luid,
+            AdapterLookup::Index(index) => {
+                *index != 0 && (adapter.ipv4_if_index == *index || adapter.ipv6_if_index == *index)
+            }
+            AdapterLookup::Guid(guid) => {
+                adapter.adapter_name.to_str().and_then(guid_from_str) == Some(*guid)
+            }
+            AdapterLookup::Name(name) => {
+                adapter.adapter_name == name.as_str() || adapter.friendly_name == name.as_str()
+            }
+        }
+    }
+}
+
+/// Alias are looked up as names, which match the friendly name.
+impl From<&InterfaceId> for AdapterLookup {
+    fn from(id: &InterfaceId) -> Self {
+        match id {
+            InterfaceId::Index(index) => AdapterLookup::Index(*index),
+            InterfaceId::Luid(luid) => AdapterLookup::Luid(*luid),
+            InterfaceId::Guid(guid) => AdapterLookup::Guid(*guid),
+            InterfaceId::Name(name) | InterfaceId::Alias(name) => AdapterLookup::Name(name.clone()),
+        }
+    }
+}
+
+/// A netlink request of a query, see [`AdapterQuery::netlink_requests`].
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct NetlinkRequest {
+    /// One of the `RTM_GET*` message types.
+    pub message_type: u16,
+    /// `NLM_F_REQUEST`, with `NLM_F_DUMP` unless a single link is requested.
+    pub flags: u16,
+    /// The `AF_*` family of the request header.
+    pub family: u8,
+    /// The interface index to filter on, `ifi_index`, `ifa_index` or
+    /// `RTA_OIF`; dumps are only filtered by the kernel with
+    /// `NETLINK_GET_STRICT_CHK`.
+    pub if_index: Option<u32>,
+    /// The `IFLA_IFNAME` attribute of a link request.
+    pub if_name: Option<String>,
+}
+
+impl NetlinkRequest {
+    fn dump(message_type: u16, family: u8, if_index: Option<u32>) -> Self {
+        NetlinkRequest {
+            message_type,
+            flags: netlink::NLM_F_REQUEST | netlink::NLM_F_DUMP,
+            family,
+            if_index,
+            if_name: None,
+        }
+    }
+
+    pub fn is_dump(&self) -> bool {
+        self.flags & netlink::NLM_F_DUMP == netlink::NLM_F_DUMP
+    }
+}
+
+/// A builder of adapter queries.
+///
+/// The default query returns the same as [`get_adapters`](crate::get_adapters):
+/// all the families, with gateways and prefixes but without WINS servers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct AdapterQuery {
+    family: AddressFamily,
+    flags: u32,
+    lookup: Option<AdapterLookup>,
+}
+
+impl Default for AdapterQuery {
+    fn default() -> Self {
+        AdapterQuery {
+            family: AddressFamily::Unspec,
+            flags: gaa_flags::INCLUDE_GATEWAYS | gaa_flags::INCLUDE_PREFIX,
+            lookup: None,
+        }
+    }
+}
+
+impl AdapterQuery {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    fn set(mut self, flag: u32, set: bool) -> Self {
+        if set {
+            self.flags |= flag;
+        } else {
+            self.flags &= !flag;
+        }
+        self
+    }
+
+    pub fn family(mut self, family: AddressFamily) -> Self {
+        self.family = family;
+        self
+    }
+
+    pub fn unicast(self, include: bool) -> Self {
+        self.set(gaa_flags::SKIP_UNICAST, !include)
+    }
+
+    pub fn dns_servers(self, include: bool) -> Self {
+        self.set(gaa_flags::SKIP_DNS_SERVER, !include)
+    }
+
+    pub fn multicast(self, include: bool) -> Self {
+        self.set(gaa_flags::SKIP_MULTICAST, !include)
+    }
+
+    pub fn anycast(self, include: bool) -> Self {
+        self.set(gaa_flags::SKIP_ANYCAST, !include)
+    }
+
+    pub fn friendly_name(self, include: bool) -> Self {
+        self.set(gaa_flags::SKIP_FRIENDLY_NAME, !include)
+    }
+
+    pub fn gateways(self, include: bool) -> Self {
+        self.set(gaa_flags::INCLUDE_GATEWAYS, include)
+    }
+
+    pub fn prefixes(self, include: bool) -> Self {
+        self.set(gaa_flags::INCLUDE_PREFIX, include)
+    }
+
+    pub fn wins_servers(self, include: bool) -> Self {
+        self.set(gaa_flags::INCLUDE_WINS_INFO, include)
+    }
+
+    /// Also return the hidden interfaces and the ones without any address
+    /// of the family, e.g. the WFP and kernel debug adapters.
+    pub fn all_interfaces(self, include: bool) -> Self {
+        self.set(gaa_flags::INCLUDE_ALL_INTERFACES, include)
+    }
+
+    pub fn luid(mut self, luid: u64) -> Self {
+        self.lookup = Some(AdapterLookup::Luid(luid));
+        self
+    }
+
+    pub fn index(mut self, index: u32) -> Self {
+        self.lookup = Some(AdapterLookup::Index(index));
+        self
+    }
+
+    pub fn guid(mut self, guid: [u8; 16]) -> Self {
+        self.lookup = Some(AdapterLookup::Guid(guid));
+        self
+    }
+
+    pub fn name(mut self, name: impl Into<String>) -> Self {
+        self.lookup = Some(AdapterLookup::Name(name.into()));
+        self
+    }
+
+    pub fn interface(mut self, id: impl Into<InterfaceId>) -> Self {
+        self.lookup = Some(AdapterLookup::from(&id.into()));
+        self
+    }
+
+    /// The `GAA_FLAG_*` flags passed to `GetAdaptersAddresses`.
+    pub fn flags(&self) -> u32 {
+        self.flags
+    }
+
+    pub fn address_family(&self) -> AddressFamily {
+        self.family
+    }
+
+    pub fn lookup(&self) -> Option<&AdapterLookup> {
+        self.lookup.as_ref()
+    }
+
+    fn includes(&self, flag: u32) -> bool {
+        self.flags & flag != 0
+    }
+
+    /// Whether `adapter` is returned by this query.
+    pub fn matches(&self, adapter: &Adapter) -> bool {
+        self.lookup.as_ref().map_or(true, |l| l.matches(adapter))
+    }
+
+    /// What `GetAdaptersAddresses` returns of `adapter` for this query, for
+    /// the backends that hold whole adapters.
+    ///
+    /// `None` if the adapter is not enabled for the family, unless all the
+    /// interfaces are asked for. Otherwise the adapter without the addresses
+    /// of the other family and without the sections the flags leave out. The
+    /// lookup is not applied, see [`AdapterQuery::matches`].
+    pub fn apply(&self, adapter: &Adapter) -> Option<Adapter> {
+        let enabled = match self.family {
+            AddressFamily::Unspec => true,
+            AddressFamily::Ipv4 => adapter.flags.ipv4_enabled(),
+            AddressFamily::Ipv6 => adapter.flags.ipv6_enabled(),
+        };
+        if !enabled && !self.includes(gaa_flags::INCLUDE_ALL_INTERFACES) {
+            return None;
+        }
+
+        let family = self.family;
+        let mut adapter = adapter.clone();
+        adapter.ip_addresses.retain(|a| family.includes(a.ip()));
+        adapter
+            .unicast_addresses
+            .retain(|a| family.includes(a.ip()));
+        adapter.prefixes.retain(|p| family.includes(p.addr()));
+        adapter.anycast_addresses.retain(|&a| family.includes(a));
+        adapter.multicast_addresses.retain(|&a| family.includes(a));
+        adapter.gateways.retain(|a| family.includes(a.ip()));
+        adapter.dns_servers.retain(|a| family.includes(a.ip()));
+        adapter.wins_servers.retain(|&a| family.includes(a));
+
+        if self.includes(gaa_flags::SKIP_UNICAST) {
+            adapter.ip_addresses.clear();
+            adapter.unicast_addresses.clear();
+        }
+        if self.includes(gaa_flags::SKIP_ANYCAST) {
+            adapter.anycast_addresses.clear();
+        }
+        if self.includes(gaa_flags::SKIP_MULTICAST) {
+            adapter.multicast_addresses.clear();
+        }
+        if self.includes(gaa_flags::SKIP_DNS_SERVER) {
+            adapter.dns_servers.clear();
+        }
+        if self.includes(gaa_flags::SKIP_DNS_INFO) {
+            adapter.dns_suffixes.clear();
+        }
+        if self.includes(gaa_flags::SKIP_FRIENDLY_NAME) {
+            adapter.friendly_name.clear();
+        }
+        if !self.includes(gaa_flags::INCLUDE_PREFIX) {
+            adapter.prefixes.clear();
+        }
+        if !self.includes(gaa_flags::INCLUDE_GATEWAYS) {
+            adapter.gateways.clear();
+        }
+        if !self.includes(gaa_flags::INCLUDE_WINS_INFO) {
+            adapter.wins_servers.clear();
+        }
+        Some(adapter)
+    }
+
+    /// The netlink requests that retrieve what this query asks for, in the
+    /// order they should be sent; they are not sent by this crate. Sections
+    /// without netlink equivalent, the DNS and WINS servers and the friendly
+    /// name, don't add any request.
+    ///
+    /// Only index and name lookups can be filtered by the kernel, the replies
+    /// of other lookups have to be filtered with [`AdapterQuery::matches`].
+    pub fn netlink_requests(&self) -> Vec<NetlinkRequest> {
+        let family = self.family.to_linux();
+        let if_index = match self.lookup {
+            Some(AdapterLookup::Index(index)) => Some(index),
+            _ => None,
+        };
+        let mut requests = vec![];
+
+        requests.push(match &self.lookup {
+            Some(AdapterLookup::Index(index)) => NetlinkRequest {
+                message_type: netlink::RTM_GETLINK,
+                flags: netlink::NLM_F_REQUEST,
+                family: netlink::AF_UNSPEC,
+                if_index: Some(*index),
+                if_name: None,
+            },
+            Some(AdapterLookup::Name(name)) => NetlinkRequest {
+                message_type: netlink::RTM_GETLINK,
+                flags: netlink::NLM_F_REQUEST,
+                family: netlink::AF_UNSPEC,
+                if_index: None,
+                if_name: Some(name.clone()),
+            },
+            _ => NetlinkRequest::dump(netlink::RTM_GETLINK, netlink::AF_UNSPEC, None),
+        });
+        if !self.includes(gaa_flags::SKIP_UNICAST) || self.includes(gaa_flags::INCLUDE_PREFIX) {
+            requests.push(NetlinkRequest::dump(netlink::RTM_GETADDR, family, if_index));
+        }
+        if self.includes(gaa_flags::INCLUDE_GATEWAYS) {
+            requests.push(NetlinkRequest::dump(
+                netlink::RTM_GETROUTE,
+                family,
+                if_index,
+            ));
+        }
+        // the kernel only dumps IPv6 anycast and multicast addresses
+        if self.family.includes_ipv6() {
+            if !self.includes(gaa_flags::SKIP_ANYCAST) {
+                requests.push(NetlinkRequest::dump(
+                    netlink::RTM_GETANYCAST,
+                    netlink::AF_INET6,
+                    if_index,
+                ));
+            }
+            if !self.includes(gaa_flags::SKIP_MULTICAST) {
+                requests.push(NetlinkRequest::dump(
+                    netlink::RTM_GETMULTICAST,
+                    netlink::AF_INET6,
+                    if_index,
+                ));
+            }
+        }
+
+        requests
+    }
+
+    /// Get the adapters matching this query.
+    ///
+    /// The adapters that can't be decoded are skipped, see
+    /// [`AdapterQuery::get_partial`].
+    #[cfg(windows)]
+    pub fn get(&self) -> Result<Vec<Adapter>> {
+        self.get_from(&OsBackend)
+    }
+
+    /// Get the adapters matching this query, and the errors of the adapters
+    /// that couldn't be decoded.
+    #[cfg(windows)]
+    pub fn get_partial(&self) -> Result<AdapterList> {
+        self.get_partial_from(&OsBackend)
+    }
+
+    /// Get the adapters matching this query from a backend.
+    pub fn get_from(&self, backend: &(impl Backend + ?Sized)) -> Result<Vec<Adapter>> {
+        let list = self.get_partial_from(backend)?;
+        for err in &list.errors {
+            tracing::warn!(
+                "skipping adapter {:?}: {}",
+                err.adapter_name.to_string_lossy(),
+                err.error
+            );
+        }
+        Ok(list.adapters)
+    }
+
+    /// Get the adapters matching this query from a backend, and the errors of
+    /// the adapters that couldn't be decoded.
+    pub fn get_partial_from(&self, backend: &(impl Backend + ?Sized)) -> Result<AdapterList> {
+        let mut list = backend.adapters(self)?;
+        list.adapters.retain(|a| self.matches(a));
+        Ok(list)
+    }
+
+    /// Get the raw `GetAdaptersAddresses` buffer of this query, to capture it
+    /// and decode it later or elsewhere, see [`AdapterBuffer`].
+    ///
+    /// The adapters of the buffer are not filtered.
+    #[cfg(windows)]
+    pub fn get_buffer(&self) -> Result<AdapterBuffer> {
+        let bytes = crate::adapter::get_adapters_buffer(self.family.to_windows(), self.flags)?;
+        let base = bytes.as_ptr() as u64;
+        Ok(AdapterBuffer::new(bytes, base))
+    }
+
+    /// Get the first adapter matching this query.
+    #[cfg(windows)]
+    pub fn find(&self) -> Result<Option<Adapter>> {
+        self.find_from(&OsBackend)
+    }
+
+    /// Get the first adapter matching this query from a backend.
+    pub fn find_from(&self, backend: &(impl Backend + ?Sized)) -> Result<Option<Adapter>> {
+        Ok(self.get_from(backend)?.into_iter().next())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::AdapterFlags;
+    use crate::network::IpNetwork;
+
+    #[test]
+    fn test_flags() {
+        assert_eq!(
+            AdapterQuery::new().flags(),
+            gaa_flags::INCLUDE_GATEWAYS | gaa_flags::INCLUDE_PREFIX
+        );
+        let query = AdapterQuery::new()
+            .dns_servers(false)
+            .multicast(false)
+            .anycast(false)
+            .friendly_name(false)
+            .gateways(false)
+            .wins_servers(true)
+            .all_interfaces(true);
+        assert_eq!(
+            query.flags(),
+            gaa_flags::INCLUDE_PREFIX
+                | gaa_flags::SKIP_DNS_SERVER
+                | gaa_flags::SKIP_MULTICAST
+                | gaa_flags::SKIP_ANYCAST
+                | gaa_flags::SKIP_FRIENDLY_NAME
+                | gaa_flags::INCLUDE_WINS_INFO
+                | gaa_flags::INCLUDE_ALL_INTERFACES
+        );
+        assert_eq!(query.multicast(true).flags() & gaa_flags::SKIP_MULTICAST, 0);
+    }
+
+    #[test]
+    fn test_lookup() {
+        let adapter = Adapter {
+            adapter_name: "{4A8A1B2C-3D4E-5F60-7182-93A4B5C6D7E8}".into(),
+            friendly_name: "Ethernet".into(),
+            luid: 1689399632855040,
+            ipv4_if_index: 0,
+            ipv6_if_index: 7,
+            ..Default::default()
+        };
+        let guid = guid_from_str("4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8").unwrap();
+        let cases = [
+            (AdapterQuery::new(), true),
+            (AdapterQuery::new().luid(1689399632855040), true),
+            (AdapterQuery::new().luid(1), false),
+            (AdapterQuery::new().index(7), true),
+            (AdapterQuery::new().index(0), false),
+            (AdapterQuery::new().guid(guid), true),
+            (AdapterQuery::new().guid([0; 16]), false),
+            (AdapterQuery::new().name("Ethernet"), true),
+            (AdapterQuery::new().name(adapter.adapter_name_lossy()), true),
+            (AdapterQuery::new().name("Wi-Fi"), false),
+        ];
+        for (query, matches) in cases {
+            assert_eq!(query.matches(&adapter), matches, "{:?}", query.lookup());
+        }
+    }
+
+    #[test]
+    fn test_apply() {
+        let ip = |s: &str| -> IpAddr { s.parse().unwrap() };
+        let adapter = Adapter {
+            flags: AdapterFlags(AdapterFlags::IPV4_ENABLED),
+            friendly_name: "Ethernet".into(),
+            ip_addresses: vec![ip("192.168.1.20").into(), ip("fe80::1").into()],
+            unicast_addresses: vec![
+                "192.168.1.20/24".parse::<IpNetwork>().unwrap().into(),
+                "fe80::1/64".parse::<IpNetwork>().unwrap().into(),
+            ],
+            prefixes: vec!["192.168.1.0/24".parse().unwrap()],
+            multicast_addresses: vec![ip("224.0.0.1"), ip("ff02::1")],
+            gateways: vec![ip("192.168.1.1").into()],
+            dns_servers: vec![ip("192.168.1.1").into(), ip("fe80::53").into()],
+            wins_servers: vec![ip("192.168.1.2")],
+            dns_suffixes: vec!["corp.example.com".into()],
+            ..Default::default()
+        };
+
+        let all = AdapterQuery::new().wins_servers(true).apply(&adapter);
+        assert_eq!(all.as_ref(), Some(&adapter));
+
+        let v4 = AdapterQuery::new()
+            .family(AddressFamily::Ipv4)
+            .gateways(false)
+            .dns_servers(false)
+            .friendly_name(false)
+            .apply(&adapter)
+            .unwrap();
+        assert_eq!(v4.ip_addresses, [ip("192.168.1.20").into()]);
+        assert_eq!(v4.unicast_addresses.len(), 1);
+        assert_eq!(v4.multicast_addresses, [ip("224.0.0.1")]);
+        assert_eq!(v4.prefixes, adapter.prefixes);
+        assert!(v4.gateways.is_empty() && v4.dns_servers.is_empty());
+        assert!(v4.wins_servers.is_empty());
+        assert!(v4.friendly_name.is_empty());
+        assert_eq!(v4.dns_suffixes, adapter.dns_suffixes);
+
+        // IPv6 is not enabled on the adapter
+        let v6 = AdapterQuery::new().family(AddressFamily::Ipv6);
+        assert_eq!(v6.apply(&adapter), None);
+        let v6 = v6.all_interfaces(true).apply(&adapter).unwrap();
+        assert_eq!(v6.ip_addresses, [ip("fe80::1").into()]);
+        assert_eq!(v6.dns_servers, [ip("fe80::53").into()]);
+        assert!(v6.prefixes.is_empty() && v6.gateways.is_empty());
+    }
+
+    #[test]
+    fn test_netlink_requests() {
+        use netlink::*;
+
+        let dump = |message_type, family, if_index| NetlinkRequest {
+            message_type,
+            flags: NLM_F_REQUEST | NLM_F_DUMP,
+            family,
+            if_index,
+            if_name: None,
+        };
+
+        assert_eq!(
+            AdapterQuery::new().netlink_requests(),
+            [
+                dump(RTM_GETLINK, AF_UNSPEC, None),
+                dump(RTM_GETADDR, AF_UNSPEC, None),
+                dump(RTM_GETROUTE, AF_UNSPEC, None),
+                dump(RTM_GETANYCAST, AF_INET6, None),
+                dump(RTM_GETMULTICAST, AF_INET6, None),
+            ]
+        );
+
+        let requests = AdapterQuery::new()
+            .family(AddressFamily::Ipv4)
+            .gateways(false)
+            .index(3)
+            .netlink_requests();
+        assert!(!requests[0].is_dump());
+        assert_eq!(requests[0].if_index, Some(3));
+        assert_eq!(requests[1..], [dump(RTM_GETADDR, AF_INET, Some(3))]);
+
+        let requests = AdapterQuery::new()
+            .family(AddressFamily::Ipv6)
+            .multicast(false)
+            .anycast(false)
+            .name("eth0")
+            .netlink_requests();
+        assert_eq!(requests[0].if_name.as_deref(), Some("eth0"));
+        assert_eq!(
+            requests[1..],
+            [
+                dump(RTM_GETADDR, AF_INET6, None),
+                dump(RTM_GETROUTE, AF_INET6, None)
+            ]
+        );
+    }
+}
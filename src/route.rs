@@ -0,0 +1,459 @@
+//! An in-memory routing table to decide, without asking the OS, which route
+//! and interface a destination would use.
+//!
+//! Routes are kept in one binary prefix trie per table and address family.
+//! A lookup walks the trie once and returns the best route of the longest
+//! matching prefix, the one with the lowest effective metric. On Windows the
+//! effective metric is the route metric plus the metric of its interface, see
+//! [`RouteTable::set_interface_metrics`].
+
+use std::collections::HashMap;
+use std::net::IpAddr;
+
+use crate::adapter::Adapter;
+#[cfg(any(windows, target_os = "linux"))]
+use crate::error::*;
+use crate::network::IpNetwork;
+use crate::scope::ScopedIpAddr;
+
+/// The main routing table of Linux, the only one of Windows.
+pub const MAIN_TABLE: u32 = 254;
+
+/// A route of a routing table.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Route {
+    pub destination: IpNetwork,
+    /// The gateway, `None` for on-link routes.
+    pub next_hop: Option<ScopedIpAddr>,
+    /// The index of the outgoing interface.
+    pub if_index: u32,
+    pub metric: u32,
+    pub table: u32,
+}
+
+impl Route {
+    /// An on-link route of the main table.
+    pub fn new(destination: IpNetwork, if_index: u32, metric: u32) -> Self {
+        Route {
+            destination,
+            next_hop: None,
+            if_index,
+            metric,
+            table: MAIN_TABLE,
+        }
+    }
+
+    pub fn via(mut self, next_hop: impl Into<ScopedIpAddr>) -> Self {
+        self.next_hop = Some(next_hop.into());
+        self
+    }
+
+    pub fn in_table(mut self, table: u32) -> Self {
+        self.table = table;
+        self
+    }
+
+    /// Whether this is a default route, `0.0.0.0/0` or `::/0`.
+    pub fn is_default(&self) -> bool {
+        self.destination.prefix() == 0
+    }
+}
+
+const NONE: u32 = 0;
+
+#[derive(Debug, Clone, Default)]
+struct Node {
+    /// The children for a 0 and 1 bit, `NONE` (the root) if there is none.
+    children: [u32; 2],
+    /// The routes with exactly this prefix.
+    routes: Vec<u32>,
+    best: Option<u32>,
+}
+
+#[derive(Debug, Clone)]
+struct Trie {
+    nodes: Vec<Node>,
+}
+
+impl Default for Trie {
+    fn default() -> Self {
+        Trie {
+            nodes: vec![Node::default()],
+        }
+    }
+}
+
+impl Trie {
+    fn insert(&mut self, key: u128, prefix: u8, route: u32) -> usize {
+        let mut node = 0;
+        for i in 0..prefix {
+            let bit = ((key >> (127 - i)) & 1) as usize;
+            let child = self.nodes[node].children[bit];
+            node = if child == NONE {
+                self.nodes.push(Node::default());
+                let child = (self.nodes.len() - 1) as u32;
+                self.nodes[node].children[bit] = child;
+                child as usize
+            } else {
+                child as usize
+            };
+        }
+        self.nodes[node].routes.push(route);
+        node
+    }
+
+    fn lookup(&self, key: u128, bits: u8) -> Option<u32> {
+        let mut node = &self.nodes[0];
+        let mut best = node.best;
+        for i in 0..bits {
+            let bit = ((key >> (127 - i)) & 1) as usize;
+            match node.children[bit] {
+                NONE => break,
+                child => node = &self.nodes[child as usize],
+            }
+            if node.best.is_some() {
+                best = node.best;
+            }
+        }
+        best
+    }
+}
+
+/// The key of an address in the trie, its bits from the most significant.
+fn key(addr: IpAddr) -> (u128, u8) {
+    match addr {
+        IpAddr::V4(addr) => ((u32::from(addr) as u128) << 96, 32),
+        IpAddr::V6(addr) => (u128::from(addr), 128),
+    }
+}
+
+#[derive(Debug, Clone, Default)]
+struct Tries {
+    v4: Trie,
+    v6: Trie,
+}
+
+impl Tries {
+    fn trie(&self, addr: &IpAddr) -> &Trie {
+        if addr.is_ipv4() {
+            &self.v4
+        } else {
+            &self.v6
+        }
+    }
+
+    fn trie_mut(&mut self, addr: &IpAddr) -> &mut Trie {
+        if addr.is_ipv4() {
+            &mut self.v4
+        } else {
+            &mut self.v6
+        }
+    }
+}
+
+/// A routing table with longest-prefix match lookups.
+#[derive(Debug, Clone, Default)]
+pub struct RouteTable {
+    routes: Vec<Route>,
+    tables: HashMap<u32, Tries>,
+    /// The IPv4 and IPv6 metrics of the interfaces, by interface index.
+    ipv4_metrics: HashMap<u32, u32>,
+    ipv6_metrics: HashMap<u32, u32>,
+}
+
+impl RouteTable {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// A table of `routes`, with the interface metrics of `adapters`.
+    pub fn with_adapters(routes: impl IntoIterator<Item = Route>, adapters: &[Adapter]) -> Self {
+        let mut table = RouteTable::new();
+        for adapter in adapters {
+            table.set_interface_metrics(adapter);
+        }
+        table.extend(routes);
+        table
+    }
+
+    /// Enumerate the routes and adapters of this machine.
+    #[cfg(windows)]
+    pub fn capture() -> Result<Self> {
+        Ok(Self::with_adapters(get_routes()?, &crate::get_adapters()?))
+    }
+
+    /// Read the routes of this machine, Linux has no interface metrics.
+    #[cfg(target_os = "linux")]
+    pub fn capture() -> Result<Self> {
+        Ok(crate::linux::read_routes()?.into_iter().collect())
+    }
+
+    pub fn insert(&mut self, route: Route) {
+        let id = self.routes.len() as u32;
+        let addr = route.destination.network();
+        let (key, _) = key(addr);
+        let prefix = route.destination.prefix();
+        let table = route.table;
+        self.routes.push(route);
+
+        let mut tries = self.tables.remove(&table).unwrap_or_default();
+        let trie = tries.trie_mut(&addr);
+        let node = trie.insert(key, prefix, id);
+        trie.nodes[node].best = self.best_of(&trie.nodes[node].routes);
+        self.tables.insert(table, tries);
+    }
+
+    /// Use the `ipv4_metric` and `ipv6_metric` of the adapter for the routes
+    /// of its interfaces, as Windows does.
+    pub fn set_interface_metrics(&mut self, adapter: &Adapter) {
+        if adapter.ipv4_if_index != 0 {
+            self.ipv4_metrics
+                .insert(adapter.ipv4_if_index, adapter.ipv4_metric);
+        }
+        if adapter.ipv6_if_index != 0 {
+            self.ipv6_metrics
+                .insert(adapter.ipv6_if_index, adapter.ipv6_metric);
+        }
+        self.update_best();
+    }
+
+    /// The metric used to choose between routes of the same prefix, the route
+    /// metric plus the metric of its interface if known.
+    pub fn effective_metric(&self, route: &Route) -> u32 {
+        let metrics = if route.destination.is_ipv4() {
+            &self.ipv4_metrics
+        } else {
+            &self.ipv6_metrics
+        };
+        let interface_metric = metrics.get(&route.if_index).copied().unwrap_or(0);
+        route.metric.saturating_add(interface_metric)
+    }
+
+    /// The route of the main table used to reach `destination`.
+    pub fn lookup(&self, destination: IpAddr) -> Option<&Route> {
+        self.lookup_in(MAIN_TABLE, destination)
+    }
+
+    pub fn lookup_in(&self, table: u32, destination: IpAddr) -> Option<&Route> {
+        let (key, bits) = key(destination);
+        let id = self
+            .tables
+            .get(&table)?
+            .trie(&destination)
+            .lookup(key, bits)?;
+        Some(&self.routes[id as usize])
+    }
+
+    pub fn routes(&self) -> &[Route] {
+        &self.routes
+    }
+
+    pub fn len(&self) -> usize {
+        self.routes.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.routes.is_empty()
+    }
+
+    /// The lowest effective metric, the first inserted on ties.
+    fn best_of(&self, routes: &[u32]) -> Option<u32> {
+        routes
+            .iter()
+            .copied()
+            .min_by_key(|id| (self.effective_metric(&self.routes[*id as usize]), *id))
+    }
+
+    fn update_best(&mut self) {
+        let mut tables = std::mem::take(&mut self.tables);
+        for tries in tables.values_mut() {
+            for node in tries.v4.nodes.iter_mut().chain(tries.v6.nodes.iter_mut()) {
+                node.best = self.best_of(&node.routes);
+            }
+        }
+        self.tables = tables;
+    }
+}
+
+impl Extend<Route> for RouteTable {
+    fn extend<T: IntoIterator<Item = Route>>(&mut self, routes: T) {
+        for route in routes {
+            self.insert(route);
+        }
+    }
+}
+
+impl FromIterator<Route> for RouteTable {
+    fn from_iter<T: IntoIterator<Item = Route>>(routes: T) -> Self {
+        let mut table = RouteTable::new();
+        table.extend(routes);
+        table
+    }
+}
+
+/// Get the IPv4 and IPv6 routes of this machine.
+#[cfg(windows)]
+pub fn get_routes() -> Result<Vec<Route>> {
+    use windows_sys::Win32::NetworkManagement::IpHelper;
+
+    let mut table: *mut IpHelper::MIB_IPFORWARD_TABLE2 = std::ptr::null_mut();
+    let code = unsafe { IpHelper::GetIpForwardTable2(IpHelper::AF_UNSPEC as u16, &mut table) };
+    if code != 0 {
+        return Err(Error::os(code as u32).with_operation("GetIpForwardTable2"));
+    }
+
+    let mut routes = vec![];
+    unsafe {
+        let len = (*table).NumEntries as usize;
+        let rows = std::slice::from_raw_parts((*table).Table.as_ptr(), len);
+        for row in rows {
+            let destination = match sockaddr_inet_to_scoped_ipaddr(&row.DestinationPrefix.Prefix) {
+                Some(addr) => addr.ip(),
+                None => continue,
+            };
+            let destination = match IpNetwork::new(destination, row.DestinationPrefix.PrefixLength)
+            {
+                Ok(destination) => destination,
+                Err(_) => continue,
+            };
+            let next_hop = sockaddr_inet_to_scoped_ipaddr(&row.NextHop)
+                .filter(|addr| !addr.ip().is_unspecified());
+            routes.push(Route {
+                destination,
+                next_hop,
+                if_index: row.InterfaceIndex,
+                metric: row.Metric,
+                table: MAIN_TABLE,
+            });
+        }
+        IpHelper::FreeMibTable(table as *const _);
+    }
+    Ok(routes)
+}
+
+#[cfg(windows)]
+unsafe fn sockaddr_inet_to_scoped_ipaddr(
+    addr: &windows_sys::Win32::Networking::WinSock::SOCKADDR_INET,
+) -> Option<ScopedIpAddr> {
+    use windows_sys::Win32::NetworkManagement::IpHelper::{AF_INET, AF_INET6};
+
+    match addr.si_family as u32 {
+        AF_INET => {
+            let octets = addr.Ipv4.sin_addr.S_un.S_addr.to_ne_bytes();
+            Some(IpAddr::from(octets).into())
+        }
+        AF_INET6 => {
+            let ip = IpAddr::from(addr.Ipv6.sin6_addr.u.Byte);
+            Some(ScopedIpAddr::new(ip, addr.Ipv6.Anonymous.sin6_scope_id))
+        }
+        _ => None,
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn net(s: &str) -> IpNetwork {
+        s.parse().unwrap()
+    }
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    fn fixture() -> RouteTable {
+        vec![
+            Route::new(net("0.0.0.0/0"), 2, 0).via(ip("192.168.1.1")),
+            Route::new(net("0.0.0.0/0"), 3, 0).via(ip("10.8.0.1")),
+            Route::new(net("192.168.1.0/24"), 2, 256),
+            Route::new(net("10.8.0.0/16"), 3, 256),
+            Route::new(net("10.8.1.0/24"), 3, 10),
+            Route::new(net("10.8.1.0/24"), 4, 5),
+            Route::new(net("::/0"), 2, 0).via("fe80::1%2".parse::<ScopedIpAddr>().unwrap()),
+            Route::new(net("2001:db8::/32"), 3, 256),
+            Route::new(net("0.0.0.0/1"), 5, 0).in_table(100),
+        ]
+        .into_iter()
+        .collect()
+    }
+
+    #[test]
+    fn test_longest_prefix_match() {
+        let table = fixture();
+        // (destination, interface of the route)
+        let cases = [
+            ("192.168.1.20", Some(2)),
+            ("10.8.200.1", Some(3)),
+            ("10.8.1.1", Some(4)),
+            ("10.9.0.1", Some(2)),
+            ("2001:db8::1", Some(3)),
+            ("2001:db9::1", Some(2)),
+        ];
+        for (destination, if_index) in cases {
+            let route = table.lookup(ip(destination));
+            assert_eq!(route.map(|r| r.if_index), if_index, "{}", destination);
+        }
+        assert_eq!(table.lookup_in(100, ip("1.1.1.1")).unwrap().if_index, 5);
+        assert_eq!(table.lookup_in(100, ip("200.1.1.1")), None);
+        assert_eq!(table.lookup_in(7, ip("1.1.1.1")), None);
+        assert_eq!(RouteTable::new().lookup(ip("1.1.1.1")), None);
+    }
+
+    #[test]
+    fn test_interface_metric() {
+        let mut table = fixture();
+        assert_eq!(table.lookup(ip("1.1.1.1")).unwrap().if_index, 2);
+
+        // Windows adds the interface metric, the VPN has the lowest
+        let adapter = |index, metric| Adapter {
+            ipv4_if_index: index,
+            ipv4_metric: metric,
+            ..Default::default()
+        };
+        table.set_interface_metrics(&adapter(2, 35));
+        table.set_interface_metrics(&adapter(3, 5));
+        table.set_interface_metrics(&adapter(4, 50));
+        let route = table.lookup(ip("1.1.1.1")).unwrap();
+        assert_eq!(route.if_index, 3);
+        assert_eq!(table.effective_metric(route), 5);
+        assert_eq!(table.lookup(ip("10.8.1.1")).unwrap().if_index, 3);
+        // the IPv6 routes are not affected
+        assert_eq!(table.lookup(ip("2001:db9::1")).unwrap().if_index, 2);
+
+        let routes = table.routes().to_vec();
+        let rebuilt = RouteTable::with_adapters(routes, &[adapter(2, 35), adapter(3, 5)]);
+        assert_eq!(rebuilt.lookup(ip("1.1.1.1")).unwrap().if_index, 3);
+    }
+
+    #[test]
+    fn test_against_linear_scan() {
+        // a small LCG so that the test is deterministic
+        let mut state = 0x2545f4914f6cdd1d_u64;
+        let mut next = move || {
+            state = state.wrapping_mul(6364136223846793005).wrapping_add(1);
+            (state >> 32) as u32
+        };
+
+        let mut routes = vec![];
+        for _ in 0..2000 {
+            let addr = IpAddr::from((next() & 0x0f0f_ffff).to_be_bytes());
+            let prefix = (next() % 33) as u8;
+            let destination = IpNetwork::new(addr, prefix).unwrap().trunc();
+            routes.push(Route::new(destination, next() % 8 + 1, next() % 4));
+        }
+        let table: RouteTable = routes.iter().cloned().collect();
+
+        for _ in 0..2000 {
+            let destination = IpAddr::from((next() & 0x0f0f_ffff).to_be_bytes());
+            let expected = routes
+                .iter()
+                .enumerate()
+                .filter(|(_, r)| r.destination.contains(destination))
+                .min_by_key(|(i, r)| (u8::MAX - r.destination.prefix(), r.metric, *i))
+                .map(|(_, r)| r);
+            assert_eq!(table.lookup(destination), expected, "{}", destination);
+        }
+    }
+}
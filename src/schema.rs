@@ -0,0 +1,722 @@
+//! Serde support, enabled with the `serde` feature.
+//!
+//! The schema is meant to be shipped to other machines and stored, so it is
+//! kept stable and independent of the Rust representation:
+//!
+//! - GUIDs are strings in the registry format without braces,
+//!   e.g. `"c38d57d1-05a7-4c33-904f-7fbceee60e82"`.
+//! - MAC addresses and DHCPv6 DUIDs are colon separated lowercase hex,
+//!   e.g. `"00:15:5d:01:02:03"`.
+//! - Networks are `"addr/prefix"` strings, scoped addresses are `"addr%index"`
+//!   strings, lifetimes are seconds with `null` for infinite.
+//! - Enums are names, e.g. `"up"` or `"routerAdvertisement"`. Raw values
+//!   without a name (the `Unknown(u32)` variants) are numbers.
+//! - Enums with content (`FilterWeight`, `FilterAction` and
+//!   `ConditionValue`) are externally tagged with the same names: a unit
+//!   variant is its name and another variant an object from its name to its
+//!   content, e.g. `{"range": [{"uint16": 5000}, {"uint16": 6000}]}`. So
+//!   are `AdapterKey` and `AdapterChange`, e.g. `{"guid": "…"}` or
+//!   `{"operStatus": {"old": "up", "new": "down"}}`.
+//! - Flags are the raw `u32`.
+//! - Names are strings, invalid UTF-16 is replaced by U+FFFD.
+//!
+//! # Versioning
+//!
+//! Payloads are wrapped in [`Versioned`], whose `schema_version` is only
+//! bumped for incompatible changes. Adding fields or enum names is not one:
+//! readers ignore fields they don't know, fill in missing ones with defaults,
+//! and map enum names they don't know to a fallback (`IfType::Unsupported`,
+//! `OperStatus::IfOperStatusUnknown`, `AdminStatus::Unknown(0)`,
+//! `MediaConnectState::Unknown(0)`, `PrefixOrigin::Other`,
+//! `SuffixOrigin::Other`, `DadState::Unknown(u32::MAX)`,
+//! `TunnelType::Other`, `ConnectionType::Unknown(u32::MAX)`,
+//! `MatchType::Unknown(u32::MAX)`, `FilterWeight::Auto`,
+//! `FilterAction::Unknown(0)` and `ConditionValue::Unsupported(u32::MAX)`),
+//! so an older collector can still read the payloads of a newer agent.
+//! `AdapterKey` and `AdapterChange` have no fallback, adding a variant to
+//! them is an incompatible change.
+
+use std::fmt;
+use std::marker::PhantomData;
+
+use serde::de::{self, Deserializer, Visitor};
+use serde::{Deserialize, Serialize, Serializer};
+
+use crate::adapter::{
+    AdminStatus, ConnectionType, IfType, MediaConnectState, OperStatus, TunnelType,
+};
+use crate::address::{DadState, PrefixOrigin, SuffixOrigin};
+use crate::fwpm::{ConditionValue, FilterAction, FilterWeight, MatchType};
+use crate::network::IpNetwork;
+use crate::scope::ScopedIpAddr;
+
+/// The current version of the schema.
+pub const SCHEMA_VERSION: u32 = 1;
+
+/// A payload tagged with the version of the schema it was written with.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Versioned<T> {
+    pub schema_version: u32,
+    pub data: T,
+}
+
+impl<T> Versioned<T> {
+    /// Tag `data` with the current schema version.
+    pub fn new(data: T) -> Self {
+        Versioned {
+            schema_version: SCHEMA_VERSION,
+            data,
+        }
+    }
+
+    /// Whether this version of the crate can read the payload.
+    pub fn is_supported(&self) -> bool {
+        self.schema_version <= SCHEMA_VERSION
+    }
+}
+
+macro_rules! named_enum {
+    ($ty:ident, $fallback:expr, { $($name:literal => $value:expr),* $(,)? }) => {
+        impl Serialize for $ty {
+            fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+                $(
+                    if *self == $value {
+                        return serializer.serialize_str($name);
+                    }
+                )*
+                serializer.serialize_u32(u32::from(*self))
+            }
+        }
+
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                struct NameVisitor;
+
+                impl<'de> Visitor<'de> for NameVisitor {
+                    type Value = $ty;
+
+                    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+                        f.write_str(concat!("a ", stringify!($ty), " name or raw value"))
+                    }
+
+                    fn visit_str<E: de::Error>(self, v: &str) -> Result<$ty, E> {
+                        match v {
+                            $($name => Ok($value),)*
+                            _ => Ok($fallback),
+                        }
+                    }
+
+                    fn visit_u64<E: de::Error>(self, v: u64) -> Result<$ty, E> {
+                        let v = u32::try_from(v).map_err(|_| E::custom("raw value overflows u32"))?;
+                        Ok($ty::from(v))
+                    }
+                }
+
+                deserializer.deserialize_any(NameVisitor)
+            }
+        }
+    };
+}
+
+named_enum!(OperStatus, OperStatus::IfOperStatusUnknown, {
+    "up" => OperStatus::IfOperStatusUp,
+    "down" => OperStatus::IfOperStatusDown,
+    "testing" => OperStatus::IfOperStatusTesting,
+    "unknown" => OperStatus::IfOperStatusUnknown,
+    "dormant" => OperStatus::IfOperStatusDormant,
+    "notPresent" => OperStatus::IfOperStatusNotPresent,
+    "lowerLayerDown" => OperStatus::IfOperStatusLowerLayerDown,
+});
+
+named_enum!(AdminStatus, AdminStatus::Unknown(0), {
+    "up" => AdminStatus::Up,
+    "down" => AdminStatus::Down,
+    "testing" => AdminStatus::Testing,
+});
+
+named_enum!(MediaConnectState, MediaConnectState::Unknown(0), {
+    "connected" => MediaConnectState::Connected,
+    "disconnected" => MediaConnectState::Disconnected,
+    "unknown" => MediaConnectState::Unknown(0),
+});
+
+named_enum!(IfType, IfType::Unsupported, {
+    "other" => IfType::Other,
+    "ethernetCsmacd" => IfType::EthernetCsmacd,
+    "iso88025TokenRing" => IfType::Iso88025Tokenring,
+    "ppp" => IfType::Ppp,
+    "softwareLoopback" => IfType::SoftwareLoopback,
+    "atm" => IfType::Atm,
+    "ieee80211" => IfType::Ieee80211,
+    "tunnel" => IfType::Tunnel,
+    "ieee1394" => IfType::Ieee1394,
+    "unsupported" => IfType::Unsupported,
+});
+
+named_enum!(TunnelType, TunnelType::Other, {
+    "none" => TunnelType::None,
+    "other" => TunnelType::Other,
+    "direct" => TunnelType::Direct,
+    "6to4" => TunnelType::SixToFour,
+    "isatap" => TunnelType::Isatap,
+    "teredo" => TunnelType::Teredo,
+    "iphttps" => TunnelType::IpHttps,
+});
+
+named_enum!(ConnectionType, ConnectionType::Unknown(u32::MAX), {
+    "dedicated" => ConnectionType::Dedicated,
+    "passive" => ConnectionType::Passive,
+    "demand" => ConnectionType::Demand,
+});
+
+named_enum!(PrefixOrigin, PrefixOrigin::Other, {
+    "other" => PrefixOrigin::Other,
+    "manual" => PrefixOrigin::Manual,
+    "wellKnown" => PrefixOrigin::WellKnown,
+    "dhcp" => PrefixOrigin::Dhcp,
+    "routerAdvertisement" => PrefixOrigin::RouterAdvertisement,
+    "unchanged" => PrefixOrigin::Unchanged,
+});
+
+named_enum!(SuffixOrigin, SuffixOrigin::Other, {
+    "other" => SuffixOrigin::Other,
+    "manual" => SuffixOrigin::Manual,
+    "wellKnown" => SuffixOrigin::WellKnown,
+    "dhcp" => SuffixOrigin::Dhcp,
+    "linkLayerAddress" => SuffixOrigin::LinkLayerAddress,
+    "random" => SuffixOrigin::Random,
+    "unchanged" => SuffixOrigin::Unchanged,
+});
+
+named_enum!(DadState, DadState::Unknown(u32::MAX), {
+    "invalid" => DadState::Invalid,
+    "tentative" => DadState::Tentative,
+    "duplicate" => DadState::Duplicate,
+    "deprecated" => DadState::Deprecated,
+    "preferred" => DadState::Preferred,
+});
+
+named_enum!(MatchType, MatchType::Unknown(u32::MAX), {
+    "equal" => MatchType::Equal,
+    "greater" => MatchType::Greater,
+    "less" => MatchType::Less,
+    "greaterOrEqual" => MatchType::GreaterOrEqual,
+    "lessOrEqual" => MatchType::LessOrEqual,
+    "range" => MatchType::Range,
+    "flagsAllSet" => MatchType::FlagsAllSet,
+    "flagsAnySet" => MatchType::FlagsAnySet,
+    "flagsNoneSet" => MatchType::FlagsNoneSet,
+    "equalCaseInsensitive" => MatchType::EqualCaseInsensitive,
+    "notEqual" => MatchType::NotEqual,
+    "prefix" => MatchType::Prefix,
+    "notPrefix" => MatchType::NotPrefix,
+});
+
+/// An externally tagged enum: a unit variant is its name and another variant
+/// an object from its name to its content. The serialization is derived.
+trait TaggedEnum: Sized {
+    const EXPECTING: &'static str;
+
+    /// The variant of the names the reader doesn't know.
+    fn fallback() -> Self;
+
+    fn unit(name: &str) -> Option<Self>;
+
+    /// The variant `name`, whose content is the next value of `map`.
+    fn variant<'de, A: de::MapAccess<'de>>(
+        name: &str,
+        map: &mut A,
+    ) -> Result<Option<Self>, A::Error>;
+}
+
+struct TaggedVisitor<T>(PhantomData<T>);
+
+impl<'de, T: TaggedEnum> Visitor<'de> for TaggedVisitor<T> {
+    type Value = T;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str(T::EXPECTING)
+    }
+
+    fn visit_str<E: de::Error>(self, v: &str) -> Result<T, E> {
+        Ok(T::unit(v).unwrap_or_else(T::fallback))
+    }
+
+    fn visit_map<A: de::MapAccess<'de>>(self, mut map: A) -> Result<T, A::Error> {
+        let name: String = map
+            .next_key()?
+            .ok_or_else(|| de::Error::custom("missing variant name"))?;
+        match T::variant(&name, &mut map)? {
+            Some(value) => Ok(value),
+            None => {
+                map.next_value::<de::IgnoredAny>()?;
+                Ok(T::fallback())
+            }
+        }
+    }
+}
+
+macro_rules! tagged_enum {
+    ($ty:ident) => {
+        impl<'de> Deserialize<'de> for $ty {
+            fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+                deserializer.deserialize_any(TaggedVisitor(PhantomData))
+            }
+        }
+    };
+}
+
+/// The content of the variants holding a GUID.
+#[derive(Deserialize)]
+#[serde(transparent)]
+struct Guid(#[serde(with = "guid")] [u8; 16]);
+
+impl TaggedEnum for FilterWeight {
+    const EXPECTING: &'static str = "a FilterWeight name or object";
+
+    fn fallback() -> Self {
+        FilterWeight::Auto
+    }
+
+    fn unit(name: &str) -> Option<Self> {
+        (name == "auto").then_some(FilterWeight::Auto)
+    }
+
+    fn variant<'de, A: de::MapAccess<'de>>(
+        name: &str,
+        map: &mut A,
+    ) -> Result<Option<Self>, A::Error> {
+        Ok(Some(match name {
+            "range" => FilterWeight::Range(map.next_value()?),
+            "exact" => FilterWeight::Exact(map.next_value()?),
+            _ => return Ok(None),
+        }))
+    }
+}
+
+impl TaggedEnum for FilterAction {
+    const EXPECTING: &'static str = "a FilterAction name or object";
+
+    fn fallback() -> Self {
+        FilterAction::Unknown(0)
+    }
+
+    fn unit(name: &str) -> Option<Self> {
+        match name {
+            "block" => Some(FilterAction::Block),
+            "permit" => Some(FilterAction::Permit),
+            _ => None,
+        }
+    }
+
+    fn variant<'de, A: de::MapAccess<'de>>(
+        name: &str,
+        map: &mut A,
+    ) -> Result<Option<Self>, A::Error> {
+        Ok(Some(match name {
+            "calloutTerminating" => FilterAction::CalloutTerminating(map.next_value::<Guid>()?.0),
+            "calloutInspection" => FilterAction::CalloutInspection(map.next_value::<Guid>()?.0),
+            "calloutUnknown" => FilterAction::CalloutUnknown(map.next_value::<Guid>()?.0),
+            "unknown" => FilterAction::Unknown(map.next_value()?),
+            _ => return Ok(None),
+        }))
+    }
+}
+
+impl TaggedEnum for ConditionValue {
+    const EXPECTING: &'static str = "a ConditionValue object";
+
+    fn fallback() -> Self {
+        ConditionValue::Unsupported(u32::MAX)
+    }
+
+    fn unit(_: &str) -> Option<Self> {
+        None
+    }
+
+    fn variant<'de, A: de::MapAccess<'de>>(
+        name: &str,
+        map: &mut A,
+    ) -> Result<Option<Self>, A::Error> {
+        Ok(Some(match name {
+            "uint8" => ConditionValue::Uint8(map.next_value()?),
+            "uint16" => ConditionValue::Uint16(map.next_value()?),
+            "uint32" => ConditionValue::Uint32(map.next_value()?),
+            "uint64" => ConditionValue::Uint64(map.next_value()?),
+            "byteArray16" => ConditionValue::ByteArray16(map.next_value()?),
+            "byteBlob" => ConditionValue::ByteBlob(map.next_value()?),
+            "addrMask" => ConditionValue::AddrMask(map.next_value()?),
+            "range" => {
+                let (low, high) = map.next_value()?;
+                ConditionValue::Range(low, high)
+            }
+            "unsupported" => ConditionValue::Unsupported(map.next_value()?),
+            _ => return Ok(None),
+        }))
+    }
+}
+
+tagged_enum!(FilterWeight);
+tagged_enum!(FilterAction);
+tagged_enum!(ConditionValue);
+
+impl Serialize for IpNetwork {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+impl<'de> Deserialize<'de> for IpNetwork {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        s.parse().map_err(de::Error::custom)
+    }
+}
+
+impl Serialize for ScopedIpAddr {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_str(self)
+    }
+}
+
+/// Zone names are not resolved, the payload may come from another machine.
+impl<'de> Deserialize<'de> for ScopedIpAddr {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        ScopedIpAddr::parse_with(&s, |_| None).map_err(de::Error::custom)
+    }
+}
+
+pub(crate) mod guid {
+    use super::*;
+    use crate::utils::{guid_from_str, guid_to_string};
+
+    pub fn serialize<S: Serializer>(guid: &[u8; 16], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&guid_to_string(guid))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<[u8; 16], D::Error> {
+        let s = String::deserialize(deserializer)?;
+        guid_from_str(&s).ok_or_else(|| de::Error::custom(format!("invalid GUID: {:?}", s)))
+    }
+}
+
+pub(crate) mod optional_guid {
+    use super::*;
+    use crate::utils::{guid_from_str, guid_to_string};
+
+    pub fn serialize<S: Serializer>(
+        guid: &Option<[u8; 16]>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        guid.as_ref().map(guid_to_string).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<[u8; 16]>, D::Error> {
+        match Option::<String>::deserialize(deserializer)? {
+            Some(s) => guid_from_str(&s)
+                .map(Some)
+                .ok_or_else(|| de::Error::custom(format!("invalid GUID: {:?}", s))),
+            None => Ok(None),
+        }
+    }
+}
+
+/// Also used for DHCPv6 DUIDs, which are longer than a MAC.
+pub(crate) mod optional_mac {
+    use super::*;
+
+    pub fn serialize<S: Serializer>(
+        bytes: &Option<Vec<u8>>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        match bytes {
+            Some(bytes) => {
+                let hex: Vec<String> = bytes.iter().map(|b| format!("{:02x}", b)).collect();
+                serializer.serialize_some(&hex.join(":"))
+            }
+            None => serializer.serialize_none(),
+        }
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Vec<u8>>, D::Error> {
+        let s = match Option::<String>::deserialize(deserializer)? {
+            Some(s) => s,
+            None => return Ok(None),
+        };
+        s.split(':')
+            .map(|b| match b.len() {
+                2 => u8::from_str_radix(b, 16).ok(),
+                _ => None,
+            })
+            .collect::<Option<Vec<u8>>>()
+            .map(Some)
+            .ok_or_else(|| de::Error::custom(format!("invalid MAC address: {:?}", s)))
+    }
+}
+
+pub(crate) mod optional_lifetime {
+    use super::*;
+    use std::time::Duration;
+
+    pub fn serialize<S: Serializer>(
+        lifetime: &Option<Duration>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        lifetime.map(|l| l.as_secs()).serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<Duration>, D::Error> {
+        Ok(Option::<u64>::deserialize(deserializer)?.map(Duration::from_secs))
+    }
+}
+
+/// Names are written lossily, the schema has no representation for invalid
+/// UTF-16.
+pub(crate) mod os_string {
+    use super::*;
+    use std::ffi::OsString;
+
+    pub fn serialize<S: Serializer>(s: &OsString, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&s.to_string_lossy())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(deserializer: D) -> Result<OsString, D::Error> {
+        Ok(String::deserialize(deserializer)?.into())
+    }
+}
+
+pub(crate) mod os_string_list {
+    use super::*;
+    use std::ffi::OsString;
+
+    pub fn serialize<S: Serializer>(list: &[OsString], serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.collect_seq(list.iter().map(|s| s.to_string_lossy()))
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Vec<OsString>, D::Error> {
+        let list = Vec::<String>::deserialize(deserializer)?;
+        Ok(list.into_iter().map(OsString::from).collect())
+    }
+}
+
+pub(crate) mod wide_string {
+    use super::*;
+    use widestring::WideCString;
+
+    pub fn serialize<S: Serializer>(s: &WideCString, serializer: S) -> Result<S::Ok, S::Error> {
+        serializer.serialize_str(&s.to_string_lossy())
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<WideCString, D::Error> {
+        let s = String::deserialize(deserializer)?;
+        WideCString::from_str(s).map_err(de::Error::custom)
+    }
+}
+
+pub(crate) mod optional_wide_string {
+    use super::*;
+    use widestring::WideCString;
+
+    pub fn serialize<S: Serializer>(
+        s: &Option<WideCString>,
+        serializer: S,
+    ) -> Result<S::Ok, S::Error> {
+        s.as_ref()
+            .map(|s| s.to_string_lossy())
+            .serialize(serializer)
+    }
+
+    pub fn deserialize<'de, D: Deserializer<'de>>(
+        deserializer: D,
+    ) -> Result<Option<WideCString>, D::Error> {
+        Option::<String>::deserialize(deserializer)?
+            .map(|s| WideCString::from_str(s).map_err(de::Error::custom))
+            .transpose()
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::adapter::{Adapter, AdapterFlags};
+    use crate::address::UnicastAddress;
+    use crate::fwpm::{DisplayData, SubLayer};
+    use serde_json::json;
+    use std::net::IpAddr;
+    use std::time::Duration;
+
+    fn adapter() -> Adapter {
+        let mut tentative = UnicastAddress::from("fe80::1/64".parse::<IpNetwork>().unwrap());
+        tentative.prefix_origin = PrefixOrigin::WellKnown;
+        tentative.suffix_origin = SuffixOrigin::LinkLayerAddress;
+        tentative.dad_state = DadState::Tentative;
+        let mut dhcp = UnicastAddress::from("192.168.1.10/24".parse::<IpNetwork>().unwrap());
+        dhcp.prefix_origin = PrefixOrigin::Dhcp;
+        dhcp.valid_lifetime = Some(Duration::from_secs(86400));
+        dhcp.lease_lifetime = Some(Duration::from_secs(86400));
+        Adapter {
+            adapter_name: "{4A8A1B2C-3D4E-5F60-7182-93A4B5C6D7E8}".into(),
+            network_guid: crate::utils::guid_from_str("4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8")
+                .unwrap(),
+            luid: 0x0006_0000_0100_0000,
+            ipv4_if_index: 12,
+            ipv6_if_index: 12,
+            ip_addresses: vec![
+                ScopedIpAddr::new("fe80::1".parse().unwrap(), 12),
+                ScopedIpAddr::from("192.168.1.10".parse::<IpAddr>().unwrap()),
+            ],
+            unicast_addresses: vec![tentative, dhcp],
+            prefixes: vec!["192.168.1.0/24".parse().unwrap()],
+            gateways: vec![ScopedIpAddr::new("fe80::abcd".parse().unwrap(), 12)],
+            dns_servers: vec!["192.168.1.1".parse::<IpAddr>().unwrap().into()],
+            description: "Intel(R) Ethernet Connection".into(),
+            friendly_name: "Ethernet".into(),
+            physical_address: Some(vec![0x00, 0x15, 0x5d, 0x01, 0x02, 0x03]),
+            flags: AdapterFlags(AdapterFlags::DHCP_ENABLED | AdapterFlags::IPV4_ENABLED),
+            mtu: 1500,
+            receive_link_speed: 1_000_000_000,
+            transmit_link_speed: 1_000_000_000,
+            oper_status: OperStatus::IfOperStatusUp,
+            admin_status: AdminStatus::Up,
+            media_connect_state: MediaConnectState::Connected,
+            if_type: IfType::EthernetCsmacd,
+            tunnel_type: TunnelType::None,
+            ipv4_metric: 25,
+            ipv6_metric: 25,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_adapter_round_trip() {
+        let adapter = adapter();
+        let value = serde_json::to_value(Versioned::new(&adapter)).unwrap();
+        assert_eq!(value["schema_version"], SCHEMA_VERSION);
+        let data = &value["data"];
+        assert_eq!(data["network_guid"], "4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8");
+        assert_eq!(data["physical_address"], "00:15:5d:01:02:03");
+        assert_eq!(data["oper_status"], "up");
+        assert_eq!(data["if_type"], "ethernetCsmacd");
+        assert_eq!(data["ip_addresses"], json!(["fe80::1%12", "192.168.1.10"]));
+        assert_eq!(data["unicast_addresses"][0]["dad_state"], "tentative");
+        assert_eq!(data["unicast_addresses"][0]["valid_lifetime"], json!(null));
+        assert_eq!(data["unicast_addresses"][1]["lease_lifetime"], 86400);
+        assert_eq!(data["prefixes"], json!(["192.168.1.0/24"]));
+
+        let parsed: Versioned<Adapter> = serde_json::from_value(value).unwrap();
+        assert!(parsed.is_supported());
+        assert_eq!(parsed.data, adapter);
+    }
+
+    #[test]
+    fn test_raw_values_round_trip() {
+        let adapter = Adapter {
+            oper_status: OperStatus::Unknown(9),
+            tunnel_type: TunnelType::Unknown(12),
+            ..adapter()
+        };
+        let value = serde_json::to_value(&adapter).unwrap();
+        assert_eq!(value["oper_status"], 9);
+        assert_eq!(value["tunnel_type"], 12);
+        assert_eq!(serde_json::from_value::<Adapter>(value).unwrap(), adapter);
+    }
+
+    #[test]
+    fn test_newer_payload() {
+        let mut value = serde_json::to_value(Versioned::new(adapter())).unwrap();
+        let data = value["data"].as_object_mut().unwrap();
+        data.insert("field_from_the_future".into(), json!({"a": 1}));
+        data.insert("oper_status".into(), json!("hibernating"));
+        data.insert("if_type".into(), json!("ieee802154"));
+        data.remove("dns_suffixes");
+        let parsed: Versioned<Adapter> = serde_json::from_value(value).unwrap();
+        assert_eq!(parsed.data.oper_status, OperStatus::IfOperStatusUnknown);
+        assert_eq!(parsed.data.if_type, IfType::Unsupported);
+        assert!(parsed.data.dns_suffixes.is_empty());
+        assert_eq!(parsed.data.friendly_name, "Ethernet");
+    }
+
+    #[test]
+    fn test_invalid_payload() {
+        let mut value = serde_json::to_value(adapter()).unwrap();
+        value["network_guid"] = json!("not a guid");
+        assert!(serde_json::from_value::<Adapter>(value).is_err());
+        let mut value = serde_json::to_value(adapter()).unwrap();
+        value["physical_address"] = json!("00:15:5d:1:02:03");
+        assert!(serde_json::from_value::<Adapter>(value).is_err());
+    }
+
+    #[test]
+    fn test_sub_layer_round_trip() {
+        let sub_layer = SubLayer {
+            sub_layer_key: crate::utils::guid_from_str("b3cdd441-af90-41ba-a745-7c6008ff2300")
+                .unwrap(),
+            display_data: DisplayData::new("clashrs".into(), Some("clash".into())),
+            flags: 0,
+            weight: u16::MAX,
+        };
+        let value = serde_json::to_value(&sub_layer).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "sub_layer_key": "b3cdd441-af90-41ba-a745-7c6008ff2300",
+                "display_data": {"name": "clashrs", "desc": "clash"},
+                "flags": 0,
+                "weight": 65535,
+            })
+        );
+        assert_eq!(
+            serde_json::from_value::<SubLayer>(value).unwrap(),
+            sub_layer
+        );
+
+        // written before the weight was captured
+        let value = json!({
+            "sub_layer_key": "b3cdd441-af90-41ba-a745-7c6008ff2300",
+            "display_data": {"name": "clashrs"},
+            "flags": 0,
+        });
+        assert_eq!(serde_json::from_value::<SubLayer>(value).unwrap().weight, 0);
+    }
+
+    #[test]
+    fn test_adapter_diff_round_trip() {
+        use crate::snapshot::{AdapterChange, AdapterDiff, AdapterKey};
+
+        let diff = AdapterDiff {
+            key: AdapterKey::Luid(0x0006_0000_0100_0000),
+            friendly_name: "Ethernet".into(),
+            changes: vec![
+                AdapterChange::AddressAdded("192.168.1.20".parse().unwrap()),
+                AdapterChange::OperStatus {
+                    old: OperStatus::IfOperStatusUp,
+                    new: OperStatus::IfOperStatusDown,
+                },
+            ],
+        };
+        let value = serde_json::to_value(&diff).unwrap();
+        assert_eq!(
+            value,
+            json!({
+                "key": {"luid": 0x0006_0000_0100_0000u64},
+                "friendly_name": "Ethernet",
+                "changes": [
+                    {"addressAdded": "192.168.1.20"},
+                    {"operStatus": {"old": "up", "new": "down"}},
+                ],
+            })
+        );
+        assert_eq!(serde_json::from_value::<AdapterDiff>(value).unwrap(), diff);
+    }
+}
@@ -0,0 +1,187 @@
+//! IP addresses with their IPv6 zone, formatted and parsed as `addr%zone`.
+//!
+//! Link-local gateways and DNS servers (`fe80::/10`) can't be reached without
+//! the interface they belong to, so the scope id of the sockaddr is kept.
+
+use std::fmt;
+use std::net::{IpAddr, Ipv6Addr, SocketAddr, SocketAddrV6};
+use std::str::FromStr;
+
+use crate::error::*;
+use crate::interface::InterfaceId;
+
+/// An IP address with the scope id (zone index) of its `sockaddr_in6`.
+/// The scope id of IPv4 addresses is always 0.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct ScopedIpAddr {
+    addr: IpAddr,
+    scope_id: u32,
+}
+
+impl ScopedIpAddr {
+    /// The scope id is ignored for IPv4 addresses.
+    pub fn new(addr: IpAddr, scope_id: u32) -> Self {
+        let scope_id = if addr.is_ipv6() { scope_id } else { 0 };
+        ScopedIpAddr { addr, scope_id }
+    }
+
+    pub fn ip(&self) -> IpAddr {
+        self.addr
+    }
+
+    /// The zone index, 0 if the address is not scoped.
+    pub fn scope_id(&self) -> u32 {
+        self.scope_id
+    }
+
+    pub fn is_scoped(&self) -> bool {
+        self.scope_id != 0
+    }
+
+    /// The socket address to connect to this address on `port`.
+    pub fn to_socket_addr(&self, port: u16) -> SocketAddr {
+        match self.addr {
+            IpAddr::V4(addr) => SocketAddr::new(addr.into(), port),
+            IpAddr::V6(addr) => SocketAddrV6::new(addr, port, 0, self.scope_id).into(),
+        }
+    }
+
+    /// Parse `addr%zone`, resolving non numeric zones with `resolve`.
+    pub fn parse_with<F>(s: &str, resolve: F) -> Result<Self>
+    where
+        F: FnOnce(&str) -> Option<u32>,
+    {
+        let invalid = || Error::parse("ScopedIpAddr", s);
+        let (addr, zone) = match s.split_once('%') {
+            Some((addr, zone)) => (addr, Some(zone)),
+            None => (s, None),
+        };
+        let addr: IpAddr = addr.parse().map_err(|_| invalid())?;
+        let zone = match zone {
+            None => return Ok(addr.into()),
+            Some(zone) => zone,
+        };
+        // a zone is only meaningful for IPv6
+        let addr: Ipv6Addr = match addr {
+            IpAddr::V6(addr) => addr,
+            IpAddr::V4(_) => return Err(invalid()),
+        };
+        if zone.is_empty() {
+            return Err(invalid());
+        }
+        let scope_id = if zone.bytes().all(|b| b.is_ascii_digit()) {
+            zone.parse().map_err(|_| invalid())?
+        } else {
+            resolve(zone).ok_or_else(invalid)?
+        };
+        Ok(ScopedIpAddr::new(addr.into(), scope_id))
+    }
+}
+
+impl From<IpAddr> for ScopedIpAddr {
+    fn from(addr: IpAddr) -> Self {
+        ScopedIpAddr { addr, scope_id: 0 }
+    }
+}
+
+impl From<SocketAddrV6> for ScopedIpAddr {
+    fn from(addr: SocketAddrV6) -> Self {
+        ScopedIpAddr::new((*addr.ip()).into(), addr.scope_id())
+    }
+}
+
+impl From<SocketAddr> for ScopedIpAddr {
+    fn from(addr: SocketAddr) -> Self {
+        match addr {
+            SocketAddr::V4(addr) => IpAddr::from(*addr.ip()).into(),
+            SocketAddr::V6(addr) => addr.into(),
+        }
+    }
+}
+
+impl From<ScopedIpAddr> for IpAddr {
+    fn from(addr: ScopedIpAddr) -> Self {
+        addr.addr
+    }
+}
+
+/// Formats as `addr%scope_id` when the address is scoped.
+impl fmt::Display for ScopedIpAddr {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if self.is_scoped() {
+            write!(f, "{}%{}", self.addr, self.scope_id)
+        } else {
+            write!(f, "{}", self.addr)
+        }
+    }
+}
+
+/// Accepts `addr`, `addr%index` and `addr%name`, names are resolved with the
+/// interfaces of this machine.
+impl FromStr for ScopedIpAddr {
+    type Err = Error;
+
+    fn from_str(s: &str) -> Result<Self> {
+        ScopedIpAddr::parse_with(s, resolve_zone)
+    }
+}
+
+/// Resolve an interface name, or alias on Windows, to its index.
+pub(crate) fn resolve_zone(name: &str) -> Option<u32> {
+    InterfaceId::Name(name.to_owned()).index().ok()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn resolve(name: &str) -> Option<u32> {
+        match name {
+            "eth0" => Some(2),
+            "Wi-Fi" => Some(17),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn test_parse_and_display() {
+        // (input, parsed scope id, display)
+        let cases = [
+            ("fe80::1%12", Some(12), "fe80::1%12"),
+            ("fe80::1%eth0", Some(2), "fe80::1%2"),
+            ("fe80::1%Wi-Fi", Some(17), "fe80::1%17"),
+            ("fe80::1%0", Some(0), "fe80::1"),
+            ("fe80::1", Some(0), "fe80::1"),
+            ("192.168.1.1", Some(0), "192.168.1.1"),
+            ("fe80::1%", None, ""),
+            ("fe80::1%wlan9", None, ""),
+            ("192.168.1.1%2", None, ""),
+            ("fe80::1%99999999999", None, ""),
+        ];
+        for (input, scope_id, display) in cases {
+            let parsed = ScopedIpAddr::parse_with(input, resolve);
+            assert_eq!(
+                parsed.as_ref().ok().map(|a| a.scope_id()),
+                scope_id,
+                "{}",
+                input
+            );
+            if let Ok(parsed) = parsed {
+                assert_eq!(parsed.to_string(), display);
+                assert_eq!(display.parse::<ScopedIpAddr>().unwrap(), parsed);
+            }
+        }
+    }
+
+    #[test]
+    fn test_socket_addr() {
+        let addr = ScopedIpAddr::parse_with("fe80::1%3", resolve).unwrap();
+        let socket_addr = addr.to_socket_addr(53);
+        assert_eq!(socket_addr.to_string(), "[fe80::1%3]:53");
+        assert_eq!(ScopedIpAddr::from(socket_addr), addr);
+
+        let addr = ScopedIpAddr::new("10.0.0.1".parse().unwrap(), 3);
+        assert_eq!(addr.scope_id(), 0);
+        assert_eq!(addr.to_socket_addr(53).to_string(), "10.0.0.1:53");
+    }
+}
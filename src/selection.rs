@@ -0,0 +1,643 @@
+//! RFC 6724 default address selection: the source address the OS would pick
+//! for a destination, and the order in which to try candidate destinations.
+//!
+//! Rules 4 (home addresses), 5.5 (next-hop prefixes) and 7 of destination
+//! sorting (native transport) need information the adapters don't have and
+//! are not implemented.
+
+use std::cmp::Ordering;
+use std::net::{IpAddr, Ipv6Addr};
+
+use crate::adapter::Adapter;
+use crate::address::{DadState, UnicastAddress};
+use crate::network::Ipv6Network;
+use crate::route::RouteTable;
+
+/// A row of the policy table.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyEntry {
+    /// IPv4 addresses are matched as IPv4-mapped addresses, `::ffff:0:0/96`.
+    pub prefix: Ipv6Network,
+    pub precedence: u8,
+    pub label: u8,
+}
+
+/// The policy table, matched by longest prefix.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct PolicyTable {
+    entries: Vec<PolicyEntry>,
+}
+
+/// The default policy table of RFC 6724 section 2.1.
+impl Default for PolicyTable {
+    fn default() -> Self {
+        let entries = [
+            ("::1/128", 50, 0),
+            ("::/0", 40, 1),
+            ("::ffff:0:0/96", 35, 4),
+            ("2002::/16", 30, 2),
+            ("2001::/32", 5, 5),
+            ("fc00::/7", 3, 13),
+            ("::/96", 1, 3),
+            ("fec0::/10", 1, 11),
+            ("3ffe::/16", 1, 12),
+        ];
+        PolicyTable::new(
+            entries
+                .iter()
+                .map(|(prefix, precedence, label)| PolicyEntry {
+                    prefix: prefix.parse().unwrap(),
+                    precedence: *precedence,
+                    label: *label,
+                }),
+        )
+    }
+}
+
+impl PolicyTable {
+    pub fn new(entries: impl IntoIterator<Item = PolicyEntry>) -> Self {
+        let mut entries: Vec<PolicyEntry> = entries.into_iter().collect();
+        // longest prefix first, so that the first match is the best one
+        entries.sort_by_key(|e| std::cmp::Reverse(e.prefix.prefix()));
+        PolicyTable { entries }
+    }
+
+    pub fn entries(&self) -> &[PolicyEntry] {
+        &self.entries
+    }
+
+    /// The entry of `addr`, `None` if no prefix matches.
+    pub fn lookup(&self, addr: IpAddr) -> Option<&PolicyEntry> {
+        let addr = to_ipv6(addr);
+        self.entries.iter().find(|e| e.prefix.contains(addr))
+    }
+
+    /// Addresses matching no entry have precedence 0 and a label that no
+    /// entry uses.
+    fn precedence(&self, addr: IpAddr) -> u8 {
+        self.lookup(addr).map_or(0, |e| e.precedence)
+    }
+
+    fn label(&self, addr: IpAddr) -> u16 {
+        self.lookup(addr).map_or(u16::MAX, |e| e.label as u16)
+    }
+}
+
+fn to_ipv6(addr: IpAddr) -> Ipv6Addr {
+    match addr {
+        IpAddr::V4(addr) => addr.to_ipv6_mapped(),
+        IpAddr::V6(addr) => addr,
+    }
+}
+
+/// The scope of an address, the multicast scope values of RFC 4291.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord, Hash)]
+pub struct Scope(pub u8);
+
+impl Scope {
+    pub const INTERFACE_LOCAL: Scope = Scope(0x1);
+    pub const LINK_LOCAL: Scope = Scope(0x2);
+    pub const ADMIN_LOCAL: Scope = Scope(0x4);
+    pub const SITE_LOCAL: Scope = Scope(0x5);
+    pub const ORGANIZATION_LOCAL: Scope = Scope(0x8);
+    pub const GLOBAL: Scope = Scope(0xe);
+
+    /// The scope of an address, IPv4 loopback and link-local addresses have
+    /// link-local scope and all the other IPv4 addresses, private ones too,
+    /// global scope (RFC 6724 section 3.2).
+    pub fn of(addr: IpAddr) -> Scope {
+        match addr {
+            IpAddr::V4(addr) if addr.is_loopback() || addr.is_link_local() => Scope::LINK_LOCAL,
+            IpAddr::V4(_) => Scope::GLOBAL,
+            IpAddr::V6(addr) => {
+                let segments = addr.segments();
+                if addr.is_multicast() {
+                    Scope(addr.octets()[1] & 0x0f)
+                } else if addr.is_loopback() || segments[0] & 0xffc0 == 0xfe80 {
+                    Scope::LINK_LOCAL
+                } else if segments[0] & 0xffc0 == 0xfec0 {
+                    Scope::SITE_LOCAL
+                } else {
+                    Scope::GLOBAL
+                }
+            }
+        }
+    }
+}
+
+/// The number of leading bits `a` and `b` have in common, up to the prefix
+/// length of `a` (RFC 6724 section 2.2).
+fn common_prefix_len(a: &UnicastAddress, b: IpAddr) -> u32 {
+    let common = match (a.ip(), b) {
+        (IpAddr::V4(a), IpAddr::V4(b)) => (u32::from(a) ^ u32::from(b)).leading_zeros(),
+        (IpAddr::V6(a), IpAddr::V6(b)) => (u128::from(a) ^ u128::from(b)).leading_zeros(),
+        _ => 0,
+    };
+    common.min(a.prefix_len() as u32)
+}
+
+/// A candidate source address and the interface it is assigned to.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Candidate {
+    pub address: UnicastAddress,
+    pub if_index: u32,
+}
+
+/// Source address selection and destination sorting over the addresses of a
+/// set of adapters.
+#[derive(Debug, Clone, Default)]
+pub struct AddressSelector {
+    candidates: Vec<Candidate>,
+    policy: PolicyTable,
+    routes: Option<RouteTable>,
+    avoid_temporary: bool,
+}
+
+impl AddressSelector {
+    /// The candidates are the preferred and deprecated addresses of the
+    /// adapters, tentative, duplicate and invalid ones are never used. A
+    /// deprecated address is only selected when rules 1 and 2 prefer it to
+    /// every preferred address.
+    pub fn new(adapters: &[Adapter]) -> Self {
+        let mut candidates = vec![];
+        for adapter in adapters {
+            for address in &adapter.unicast_addresses {
+                if !matches!(
+                    address.dad_state,
+                    DadState::Preferred | DadState::Deprecated
+                ) {
+                    continue;
+                }
+                let if_index = if address.ip().is_ipv4() {
+                    adapter.ipv4_if_index
+                } else {
+                    adapter.ipv6_if_index
+                };
+                candidates.push(Candidate {
+                    address: address.clone(),
+                    if_index,
+                });
+            }
+        }
+        Self::with_candidates(candidates)
+    }
+
+    pub fn with_candidates(candidates: Vec<Candidate>) -> Self {
+        AddressSelector {
+            candidates,
+            ..Default::default()
+        }
+    }
+
+    pub fn policy(mut self, policy: PolicyTable) -> Self {
+        self.policy = policy;
+        self
+    }
+
+    /// The routes used to find the outgoing interface of a destination, for
+    /// rule 5 of source selection.
+    pub fn routes(mut self, routes: RouteTable) -> Self {
+        self.routes = Some(routes);
+        self
+    }
+
+    /// Reverse rule 7, prefer public addresses over temporary ones.
+    pub fn avoid_temporary(mut self, avoid: bool) -> Self {
+        self.avoid_temporary = avoid;
+        self
+    }
+
+    pub fn candidates(&self) -> &[Candidate] {
+        &self.candidates
+    }
+
+    /// The source address for `destination`, on the interface of its route
+    /// if there is a route table.
+    pub fn select_source(&self, destination: IpAddr) -> Option<&Candidate> {
+        let outgoing = self
+            .routes
+            .as_ref()
+            .and_then(|routes| routes.lookup(destination))
+            .map(|route| route.if_index);
+        self.select_source_on(destination, outgoing)
+    }
+
+    /// The source address for `destination` through the interface
+    /// `outgoing`, if known.
+    pub fn select_source_on(
+        &self,
+        destination: IpAddr,
+        outgoing: Option<u32>,
+    ) -> Option<&Candidate> {
+        self.candidates
+            .iter()
+            .filter(|c| c.address.ip().is_ipv4() == destination.is_ipv4())
+            .min_by(|a, b| self.compare_sources(a, b, destination, outgoing))
+    }
+
+    /// `Less` if `a` is the better source.
+    fn compare_sources(
+        &self,
+        a: &Candidate,
+        b: &Candidate,
+        destination: IpAddr,
+        outgoing: Option<u32>,
+    ) -> Ordering {
+        let (sa, sb) = (a.address.ip(), b.address.ip());
+        let prefer = |a: bool, b: bool| b.cmp(&a);
+
+        // rule 1: prefer same address
+        prefer(sa == destination, sb == destination)
+            // rule 2: prefer appropriate scope
+            .then_with(|| {
+                let (scope_a, scope_b, scope_d) =
+                    (Scope::of(sa), Scope::of(sb), Scope::of(destination));
+                match scope_a.cmp(&scope_b) {
+                    Ordering::Less if scope_a < scope_d => Ordering::Greater,
+                    Ordering::Less => Ordering::Less,
+                    Ordering::Greater if scope_b < scope_d => Ordering::Less,
+                    Ordering::Greater => Ordering::Greater,
+                    Ordering::Equal => Ordering::Equal,
+                }
+            })
+            // rule 3: avoid deprecated addresses
+            .then_with(|| prefer(!a.address.is_deprecated(), !b.address.is_deprecated()))
+            // rule 5: prefer outgoing interface
+            .then_with(|| match outgoing {
+                Some(outgoing) => prefer(a.if_index == outgoing, b.if_index == outgoing),
+                None => Ordering::Equal,
+            })
+            // rule 6: prefer matching label
+            .then_with(|| {
+                let label = self.policy.label(destination);
+                prefer(
+                    self.policy.label(sa) == label,
+                    self.policy.label(sb) == label,
+                )
+            })
+            // rule 7: prefer temporary addresses
+            .then_with(|| {
+                let order = prefer(a.address.is_temporary(), b.address.is_temporary());
+                if self.avoid_temporary {
+                    order.reverse()
+                } else {
+                    order
+                }
+            })
+            // rule 8: use longest matching prefix
+            .then_with(|| {
+                common_prefix_len(&b.address, destination)
+                    .cmp(&common_prefix_len(&a.address, destination))
+            })
+    }
+
+    /// Sort `destinations` in the order they should be tried, the order of
+    /// equivalent destinations is kept.
+    pub fn sort_destinations(&self, destinations: &mut [IpAddr]) {
+        let mut keyed: Vec<(IpAddr, Option<&Candidate>)> = destinations
+            .iter()
+            .map(|d| (*d, self.select_source(*d)))
+            .collect();
+        keyed.sort_by(|a, b| self.compare_destinations(a, b));
+        for (destination, (sorted, _)) in destinations.iter_mut().zip(keyed) {
+            *destination = sorted;
+        }
+    }
+
+    /// `Less` if `a` should be tried first.
+    fn compare_destinations(
+        &self,
+        (da, sa): &(IpAddr, Option<&Candidate>),
+        (db, sb): &(IpAddr, Option<&Candidate>),
+    ) -> Ordering {
+        let prefer = |a: bool, b: bool| b.cmp(&a);
+
+        // rule 1: avoid unusable destinations
+        let (sa, sb) = match (sa, sb) {
+            (Some(sa), Some(sb)) => (sa, sb),
+            (sa, sb) => return prefer(sa.is_some(), sb.is_some()),
+        };
+        let (ia, ib) = (sa.address.ip(), sb.address.ip());
+
+        // rule 2: prefer matching scope
+        prefer(
+            Scope::of(*da) == Scope::of(ia),
+            Scope::of(*db) == Scope::of(ib),
+        )
+        // rule 3: avoid deprecated addresses
+        .then_with(|| prefer(!sa.address.is_deprecated(), !sb.address.is_deprecated()))
+        // rule 5: prefer matching label
+        .then_with(|| {
+            prefer(
+                self.policy.label(ia) == self.policy.label(*da),
+                self.policy.label(ib) == self.policy.label(*db),
+            )
+        })
+        // rule 6: prefer higher precedence
+        .then_with(|| {
+            self.policy
+                .precedence(*db)
+                .cmp(&self.policy.precedence(*da))
+        })
+        // rule 8: prefer smaller scope
+        .then_with(|| Scope::of(*da).cmp(&Scope::of(*db)))
+        // rule 9: use longest matching prefix
+        .then_with(|| {
+            if da.is_ipv4() != db.is_ipv4() {
+                return Ordering::Equal;
+            }
+            common_prefix_len(&sb.address, *db).cmp(&common_prefix_len(&sa.address, *da))
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::{PrefixOrigin, SuffixOrigin};
+    use crate::network::IpNetwork;
+
+    fn ip(s: &str) -> IpAddr {
+        s.parse().unwrap()
+    }
+
+    /// `addr/prefix`, with `(deprecated)` or `(temporary)` after it.
+    fn candidate(s: &str) -> Candidate {
+        let (s, state) = match s.split_once(' ') {
+            Some((s, state)) => (s, state),
+            None => (s, ""),
+        };
+        let network: IpNetwork = if s.contains('/') {
+            s.parse().unwrap()
+        } else {
+            let ip = ip(s);
+            let prefix = if ip.is_ipv4() { 24 } else { 64 };
+            IpNetwork::new(ip, prefix).unwrap()
+        };
+        let mut address = UnicastAddress::from(network);
+        match state {
+            "(deprecated)" => address.dad_state = DadState::Deprecated,
+            "(temporary)" => {
+                address.prefix_origin = PrefixOrigin::RouterAdvertisement;
+                address.suffix_origin = SuffixOrigin::Random;
+            }
+            _ => {}
+        }
+        Candidate {
+            address,
+            if_index: 1,
+        }
+    }
+
+    fn selector(candidates: &[&str]) -> AddressSelector {
+        AddressSelector::with_candidates(candidates.iter().map(|c| candidate(c)).collect())
+    }
+
+    #[test]
+    fn test_policy_table() {
+        let policy = PolicyTable::default();
+        // (address, precedence, label)
+        let cases = [
+            ("::1", 50, 0),
+            ("2001:db8::1", 40, 1),
+            ("2001::1", 5, 5),
+            ("10.1.2.3", 35, 4),
+            ("2002:c633:6401::1", 30, 2),
+            ("fd00::1", 3, 13),
+            ("fec0::1", 1, 11),
+        ];
+        for (addr, precedence, label) in cases {
+            let entry = policy.lookup(ip(addr)).unwrap();
+            assert_eq!(
+                (entry.precedence, entry.label),
+                (precedence, label),
+                "{}",
+                addr
+            );
+        }
+    }
+
+    #[test]
+    fn test_scope() {
+        let cases = [
+            ("fe80::1", Scope::LINK_LOCAL),
+            ("::1", Scope::LINK_LOCAL),
+            ("fec0::1", Scope::SITE_LOCAL),
+            ("ff05::1", Scope::SITE_LOCAL),
+            ("ff0e::1", Scope::GLOBAL),
+            ("2001:db8::1", Scope::GLOBAL),
+            ("169.254.1.1", Scope::LINK_LOCAL),
+            ("127.0.0.1", Scope::LINK_LOCAL),
+            ("10.1.2.3", Scope::GLOBAL),
+        ];
+        for (addr, scope) in cases {
+            assert_eq!(Scope::of(ip(addr)), scope, "{}", addr);
+        }
+    }
+
+    /// The examples of RFC 6724 section 10.1, without the home address one.
+    #[test]
+    fn test_source_selection() {
+        // (destination, candidates, expected source)
+        let cases: &[(&str, &[&str], &str)] = &[
+            (
+                "2001:db8:1::1",
+                &["2001:db8:3::1", "fe80::1"],
+                "2001:db8:3::1",
+            ),
+            ("ff05::1", &["2001:db8:3::1", "fe80::1"], "2001:db8:3::1"),
+            (
+                "2001:db8:1::1",
+                &["2001:db8:1::1 (deprecated)", "2001:db8:2::1"],
+                "2001:db8:1::1",
+            ),
+            (
+                "fe80::1",
+                &["fe80::2 (deprecated)", "2001:db8:1::1"],
+                "fe80::2",
+            ),
+            (
+                "2001:db8:1::1",
+                &["2001:db8:1::2", "2001:db8:3::2"],
+                "2001:db8:1::2",
+            ),
+            (
+                "2002:c633:6401::1",
+                &[
+                    "2002:c633:6401::d5e3:7953:13eb:22e8 (temporary)",
+                    "2002:c633:6401::2",
+                ],
+                "2002:c633:6401::d5e3:7953:13eb:22e8",
+            ),
+            (
+                "2001:db8:1::d5e3:0:0:1",
+                &["2001:db8:1::2", "10.1.2.3"],
+                "2001:db8:1::2",
+            ),
+            (
+                "2002:836b:4179::2",
+                &["2002:836b:4179::5", "2001:db8:1::2"],
+                "2002:836b:4179::5",
+            ),
+            (
+                "2001:db8:1::2",
+                &["2002:836b:4179::5", "2001:db8:1::3"],
+                "2001:db8:1::3",
+            ),
+            ("10.1.2.3", &["2001:db8:1::2", "10.1.2.4"], "10.1.2.4"),
+        ];
+        for (destination, candidates, expected) in cases {
+            let selector = selector(candidates);
+            let source = selector.select_source(ip(destination)).unwrap();
+            assert_eq!(source.address.ip(), ip(expected), "{}", destination);
+        }
+        assert!(selector(&["fe80::1"])
+            .select_source(ip("10.0.0.1"))
+            .is_none());
+    }
+
+    #[test]
+    fn test_candidates_of_adapters() {
+        let adapter = |if_index, addresses: &[&str]| Adapter {
+            ipv4_if_index: if_index,
+            ipv6_if_index: if_index,
+            unicast_addresses: addresses.iter().map(|a| candidate(a).address).collect(),
+            ..Default::default()
+        };
+        let mut tentative = candidate("2001:db8:3::1").address;
+        tentative.dad_state = DadState::Tentative;
+        let mut with_tentative = adapter(2, &["10.0.0.2 (deprecated)"]);
+        with_tentative.unicast_addresses.push(tentative);
+        let adapters = [
+            adapter(1, &["2001:db8:1::1", "2001:db8:2::1 (deprecated)"]),
+            with_tentative,
+        ];
+
+        let selector = AddressSelector::new(&adapters);
+        let addresses: Vec<(IpAddr, u32)> = selector
+            .candidates()
+            .iter()
+            .map(|c| (c.address.ip(), c.if_index))
+            .collect();
+        assert_eq!(
+            addresses,
+            [
+                (ip("2001:db8:1::1"), 1),
+                (ip("2001:db8:2::1"), 1),
+                (ip("10.0.0.2"), 2)
+            ]
+        );
+        // rule 3 skips the deprecated address of the same scope
+        let source = selector.select_source(ip("2001:db8:2::5")).unwrap();
+        assert_eq!(source.address.ip(), ip("2001:db8:1::1"));
+        let source = selector.select_source(ip("10.0.0.5")).unwrap();
+        assert_eq!(source.address.ip(), ip("10.0.0.2"));
+
+        // rule 2 picks a deprecated global address over a link-local one
+        let selector =
+            AddressSelector::new(&[adapter(1, &["fe80::1", "2001:db8::1 (deprecated)"])]);
+        let source = selector.select_source(ip("2001:db8:5::1")).unwrap();
+        assert_eq!(source.address.ip(), ip("2001:db8::1"));
+        let source = selector.select_source(ip("fe80::5")).unwrap();
+        assert_eq!(source.address.ip(), ip("fe80::1"));
+    }
+
+    #[test]
+    fn test_source_selection_options() {
+        let mut temporary = candidate("2001:db8:1::d5e3 (temporary)");
+        temporary.if_index = 2;
+        let mut candidates = vec![candidate("2001:db8:1::2"), temporary];
+
+        let selector = AddressSelector::with_candidates(candidates.clone());
+        let source = selector.select_source(ip("2001:db8:5::1")).unwrap();
+        assert_eq!(source.address.ip(), ip("2001:db8:1::d5e3"));
+        let avoiding = selector.clone().avoid_temporary(true);
+        let source = avoiding.select_source(ip("2001:db8:5::1")).unwrap();
+        assert_eq!(source.address.ip(), ip("2001:db8:1::2"));
+
+        // the outgoing interface wins over temporary addresses
+        let routes: RouteTable = vec![crate::route::Route::new("::/0".parse().unwrap(), 1, 0)]
+            .into_iter()
+            .collect();
+        let routed = selector.routes(routes);
+        let source = routed.select_source(ip("2001:db8:5::1")).unwrap();
+        assert_eq!(source.if_index, 1);
+
+        // a custom policy can move 2001:db8::/32 to its own label
+        candidates[1].address.suffix_origin = SuffixOrigin::Manual;
+        candidates.push(candidate("2002:836b:4179::5"));
+        let mut entries = PolicyTable::default().entries().to_vec();
+        entries.push(PolicyEntry {
+            prefix: "2001:db8:5::/48".parse().unwrap(),
+            precedence: 45,
+            label: 2,
+        });
+        let selector =
+            AddressSelector::with_candidates(candidates).policy(PolicyTable::new(entries));
+        let source = selector.select_source(ip("2001:db8:5::1")).unwrap();
+        assert_eq!(source.address.ip(), ip("2002:836b:4179::5"));
+    }
+
+    /// The examples of RFC 6724 section 10.2.
+    #[test]
+    fn test_destination_sorting() {
+        // (candidate sources, destinations, expected order)
+        let cases: &[(&[&str], &[&str], &[&str])] = &[
+            (
+                &["2001:db8:1::2", "fe80::1", "169.254.13.78"],
+                &["198.51.100.121", "2001:db8:1::1"],
+                &["2001:db8:1::1", "198.51.100.121"],
+            ),
+            (
+                &["fe80::1", "198.51.100.117"],
+                &["2001:db8:1::1", "198.51.100.121"],
+                &["198.51.100.121", "2001:db8:1::1"],
+            ),
+            (
+                &["2001:db8:1::2", "fe80::1", "10.1.2.4"],
+                &["10.1.2.3", "2001:db8:1::1"],
+                &["2001:db8:1::1", "10.1.2.3"],
+            ),
+            (
+                &["2001:db8:1::2", "fe80::2"],
+                &["2001:db8:1::1", "fe80::1"],
+                &["fe80::1", "2001:db8:1::1"],
+            ),
+            (
+                &["2001:db8:1::2", "3ffe:1::2", "fe80::2"],
+                &["2001:db8:1::1", "3ffe:1::1"],
+                &["2001:db8:1::1", "3ffe:1::1"],
+            ),
+            (
+                &["2002:c633:6401::2", "fe80::2"],
+                &["2002:c633:6401::1", "::1"],
+                &["::1", "2002:c633:6401::1"],
+            ),
+            (
+                &["2001:db8:1::2", "2002:c633:6401::2", "fe80::2"],
+                &["2002:c633:6401::1", "2001:db8:1::1"],
+                &["2001:db8:1::1", "2002:c633:6401::1"],
+            ),
+            (
+                &["2002:c633:6401::2", "fe80::2"],
+                &["2001:db8:1::1", "2002:c633:6401::1"],
+                &["2002:c633:6401::1", "2001:db8:1::1"],
+            ),
+            (
+                &["2001:db8:1::2", "fe80::2"],
+                &["10.1.2.3", "2001:db8:1::1"],
+                &["2001:db8:1::1", "10.1.2.3"],
+            ),
+        ];
+        for (candidates, destinations, expected) in cases {
+            let mut destinations: Vec<IpAddr> = destinations.iter().map(|d| ip(d)).collect();
+            let mut candidates = candidates.to_vec();
+            if destinations.contains(&ip("::1")) {
+                candidates.push("::1/128");
+            }
+            selector(&candidates).sort_destinations(&mut destinations);
+            let expected: Vec<IpAddr> = expected.iter().map(|d| ip(d)).collect();
+            assert_eq!(destinations, expected, "{:?}", candidates);
+        }
+    }
+}
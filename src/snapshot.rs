@@ -0,0 +1,354 @@
+//! Snapshots of the adapters and what changed between two of them.
+
+use crate::adapter::{Adapter, AdminStatus, MediaConnectState, OperStatus};
+#[cfg(windows)]
+use crate::error::*;
+use crate::scope::ScopedIpAddr;
+use crate::utils::guid_from_str;
+
+/// The adapters enumerated at one point in time.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdapterSnapshot {
+    pub adapters: Vec<Adapter>,
+}
+
+/// Identifies an adapter across snapshots.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum AdapterKey {
+    /// The interface GUID, the adapter name of the Windows adapters.
+    Guid(#[cfg_attr(feature = "serde", serde(with = "crate::schema::guid"))] [u8; 16]),
+    Luid(u64),
+    /// The adapter name, the interface GUID on Windows and the kernel name
+    /// on Linux.
+    Name(String),
+}
+
+/// A change of a single adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(
+    feature = "serde",
+    derive(serde::Serialize, serde::Deserialize),
+    serde(rename_all = "camelCase")
+)]
+pub enum AdapterChange {
+    AddressAdded(ScopedIpAddr),
+    AddressRemoved(ScopedIpAddr),
+    GatewayAdded(ScopedIpAddr),
+    GatewayRemoved(ScopedIpAddr),
+    DnsServerAdded(ScopedIpAddr),
+    DnsServerRemoved(ScopedIpAddr),
+    OperStatus {
+        old: OperStatus,
+        new: OperStatus,
+    },
+    AdminStatus {
+        old: AdminStatus,
+        new: AdminStatus,
+    },
+    MediaConnectState {
+        old: MediaConnectState,
+        new: MediaConnectState,
+    },
+    Ipv4Metric {
+        old: u32,
+        new: u32,
+    },
+    Ipv6Metric {
+        old: u32,
+        new: u32,
+    },
+    ReceiveLinkSpeed {
+        old: u64,
+        new: u64,
+    },
+    TransmitLinkSpeed {
+        old: u64,
+        new: u64,
+    },
+}
+
+/// The changes of an adapter that is in both snapshots.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct AdapterDiff {
+    pub key: AdapterKey,
+    /// The friendly name in the newer snapshot.
+    pub friendly_name: String,
+    pub changes: Vec<AdapterChange>,
+}
+
+/// What changed between two snapshots.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SnapshotDiff {
+    pub added: Vec<Adapter>,
+    pub removed: Vec<Adapter>,
+    pub changed: Vec<AdapterDiff>,
+}
+
+impl SnapshotDiff {
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty() && self.removed.is_empty() && self.changed.is_empty()
+    }
+}
+
+impl Adapter {
+    /// The key used to match this adapter across snapshots, the interface
+    /// GUID if the adapter name is one, then the LUID if there is one and the
+    /// adapter name otherwise.
+    pub fn key(&self) -> AdapterKey {
+        let guid = self.adapter_name.to_str().and_then(guid_from_str);
+        if let Some(guid) = guid {
+            AdapterKey::Guid(guid)
+        } else if self.luid != 0 {
+            AdapterKey::Luid(self.luid)
+        } else {
+            AdapterKey::Name(self.adapter_name_lossy().into_owned())
+        }
+    }
+}
+
+impl From<Vec<Adapter>> for AdapterSnapshot {
+    fn from(adapters: Vec<Adapter>) -> Self {
+        AdapterSnapshot { adapters }
+    }
+}
+
+impl AdapterSnapshot {
+    /// Enumerate the adapters of this machine.
+    #[cfg(windows)]
+    pub fn capture() -> Result<Self> {
+        Ok(crate::get_adapters()?.into())
+    }
+
+    pub fn get(&self, key: &AdapterKey) -> Option<&Adapter> {
+        self.adapters.iter().find(|a| &a.key() == key)
+    }
+
+    /// The changes from `self` to the `newer` snapshot.
+    pub fn diff(&self, newer: &AdapterSnapshot) -> SnapshotDiff {
+        let mut diff = SnapshotDiff::default();
+
+        for old in &self.adapters {
+            if newer.get(&old.key()).is_none() {
+                diff.removed.push(old.clone());
+            }
+        }
+        for new in &newer.adapters {
+            let key = new.key();
+            let old = match self.get(&key) {
+                Some(old) => old,
+                None => {
+                    diff.added.push(new.clone());
+                    continue;
+                }
+            };
+            let changes = adapter_changes(old, new);
+            if !changes.is_empty() {
+                diff.changed.push(AdapterDiff {
+                    key,
+                    friendly_name: new.friendly_name_lossy().into_owned(),
+                    changes,
+                });
+            }
+        }
+
+        diff
+    }
+}
+
+fn adapter_changes(old: &Adapter, new: &Adapter) -> Vec<AdapterChange> {
+    let mut changes = vec![];
+
+    list_changes(
+        &old.ip_addresses,
+        &new.ip_addresses,
+        AdapterChange::AddressAdded,
+        AdapterChange::AddressRemoved,
+        &mut changes,
+    );
+    list_changes(
+        &old.gateways,
+        &new.gateways,
+        AdapterChange::GatewayAdded,
+        AdapterChange::GatewayRemoved,
+        &mut changes,
+    );
+    list_changes(
+        &old.dns_servers,
+        &new.dns_servers,
+        AdapterChange::DnsServerAdded,
+        AdapterChange::DnsServerRemoved,
+        &mut changes,
+    );
+
+    macro_rules! field_change {
+        ($field:ident, $variant:ident) => {
+            if old.$field != new.$field {
+                changes.push(AdapterChange::$variant {
+                    old: old.$field,
+                    new: new.$field,
+                });
+            }
+        };
+    }
+    field_change!(oper_status, OperStatus);
+    field_change!(admin_status, AdminStatus);
+    field_change!(media_connect_state, MediaConnectState);
+    field_change!(ipv4_metric, Ipv4Metric);
+    field_change!(ipv6_metric, Ipv6Metric);
+    field_change!(receive_link_speed, ReceiveLinkSpeed);
+    field_change!(transmit_link_speed, TransmitLinkSpeed);
+
+    changes
+}
+
+/// Removals first, so that a moved address reads as removed then added.
+fn list_changes<T: PartialEq + Clone>(
+    old: &[T],
+    new: &[T],
+    added: fn(T) -> AdapterChange,
+    removed: fn(T) -> AdapterChange,
+    changes: &mut Vec<AdapterChange>,
+) {
+    for item in old.iter().filter(|item| !new.contains(item)) {
+        changes.push(removed(item.clone()));
+    }
+    for item in new.iter().filter(|item| !old.contains(item)) {
+        changes.push(added(item.clone()));
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn addr(s: &str) -> ScopedIpAddr {
+        ScopedIpAddr::parse_with(s, |_| None).unwrap()
+    }
+
+    fn adapter(luid: u64, name: &str) -> Adapter {
+        Adapter {
+            luid,
+            adapter_name: name.into(),
+            friendly_name: name.into(),
+            ip_addresses: vec![addr("192.168.1.10"), addr("fe80::1%12")],
+            gateways: vec![addr("192.168.1.1")],
+            dns_servers: vec![addr("192.168.1.1")],
+            oper_status: OperStatus::IfOperStatusUp,
+            ipv4_metric: 25,
+            receive_link_speed: 1_000_000_000,
+            transmit_link_speed: 1_000_000_000,
+            ..Default::default()
+        }
+    }
+
+    #[test]
+    fn test_no_changes() {
+        let snapshot = AdapterSnapshot::from(vec![adapter(1, "eth0"), adapter(2, "wlan0")]);
+        assert!(snapshot.diff(&snapshot.clone()).is_empty());
+    }
+
+    #[test]
+    fn test_added_and_removed() {
+        let old = AdapterSnapshot::from(vec![adapter(1, "eth0"), adapter(2, "wlan0")]);
+        let new = AdapterSnapshot::from(vec![adapter(2, "wlan0"), adapter(3, "utun64")]);
+        let diff = old.diff(&new);
+        assert_eq!(diff.added, [adapter(3, "utun64")]);
+        assert_eq!(diff.removed, [adapter(1, "eth0")]);
+        assert!(diff.changed.is_empty());
+    }
+
+    #[test]
+    fn test_matched_by_guid() {
+        // the LUID of an adapter changes when its driver is reinstalled, its
+        // interface GUID does not
+        let guid = "{C38D57D1-05A7-4C33-904F-7FBCEEE60E82}";
+        let old = AdapterSnapshot::from(vec![adapter(1, guid)]);
+        let mut new = adapter(2, guid);
+        new.ipv4_metric = 5;
+        let diff = old.diff(&AdapterSnapshot::from(vec![new]));
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(
+            diff.changed[0].key,
+            AdapterKey::Guid(guid_from_str(guid).unwrap())
+        );
+    }
+
+    #[test]
+    fn test_matched_by_luid_then_name() {
+        // a renamed adapter keeps its LUID
+        let old = AdapterSnapshot::from(vec![adapter(1, "eth0")]);
+        let mut renamed = adapter(1, "lan");
+        renamed.ipv4_metric = 5;
+        let diff = old.diff(&AdapterSnapshot::from(vec![renamed]));
+        assert!(diff.added.is_empty() && diff.removed.is_empty());
+        assert_eq!(diff.changed[0].key, AdapterKey::Luid(1));
+        assert_eq!(diff.changed[0].friendly_name, "lan");
+
+        // without LUID (Linux) adapters are matched by kernel name
+        let old = AdapterSnapshot::from(vec![adapter(0, "eth0")]);
+        let mut new = adapter(0, "eth0");
+        new.oper_status = OperStatus::IfOperStatusDown;
+        let diff = old.diff(&AdapterSnapshot::from(vec![new]));
+        assert_eq!(diff.changed[0].key, AdapterKey::Name("eth0".into()));
+    }
+
+    #[test]
+    fn test_changes() {
+        let old = adapter(1, "eth0");
+        let mut new = adapter(1, "eth0");
+        new.ip_addresses = vec![addr("fe80::1%12"), addr("192.168.1.20")];
+        new.gateways.push(addr("fe80::abcd%12"));
+        new.dns_servers = vec![];
+        new.oper_status = OperStatus::IfOperStatusLowerLayerDown;
+        new.media_connect_state = MediaConnectState::Disconnected;
+        new.ipv4_metric = 35;
+        new.receive_link_speed = 100_000_000;
+
+        let diff = AdapterSnapshot::from(vec![old]).diff(&vec![new].into());
+        assert_eq!(
+            diff.changed[0].changes,
+            [
+                AdapterChange::AddressRemoved(addr("192.168.1.10")),
+                AdapterChange::AddressAdded(addr("192.168.1.20")),
+                AdapterChange::GatewayAdded(addr("fe80::abcd%12")),
+                AdapterChange::DnsServerRemoved(addr("192.168.1.1")),
+                AdapterChange::OperStatus {
+                    old: OperStatus::IfOperStatusUp,
+                    new: OperStatus::IfOperStatusLowerLayerDown,
+                },
+                AdapterChange::MediaConnectState {
+                    old: MediaConnectState::Unknown(0),
+                    new: MediaConnectState::Disconnected,
+                },
+                AdapterChange::Ipv4Metric { old: 25, new: 35 },
+                AdapterChange::ReceiveLinkSpeed {
+                    old: 1_000_000_000,
+                    new: 100_000_000,
+                },
+            ]
+        );
+    }
+
+    #[test]
+    fn test_scope_change_is_a_change() {
+        let old = adapter(1, "eth0");
+        let mut new = adapter(1, "eth0");
+        new.ip_addresses[1] = addr("fe80::1%13");
+        let diff = AdapterSnapshot::from(vec![old]).diff(&vec![new].into());
+        assert_eq!(
+            diff.changed[0].changes,
+            [
+                AdapterChange::AddressRemoved(addr("fe80::1%12")),
+                AdapterChange::AddressAdded(addr("fe80::1%13")),
+            ]
+        );
+    }
+}
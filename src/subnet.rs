@@ -0,0 +1,365 @@
+//! Address conflicts between adapters, and the selection of a free private
+//! subnet for a new interface.
+//!
+//! [`find_conflicts`] reports the subnets that overlap and the addresses
+//! assigned twice across adapters. [`SubnetAllocator`] picks an IPv4 block
+//! from private pools that no adapter uses, and an RFC 4193 ULA /64 derived
+//! from a stable seed such as the MAC address of the adapter.
+
+use std::net::{IpAddr, Ipv4Addr, Ipv6Addr};
+
+use crate::adapter::Adapter;
+use crate::network::{IpNetwork, Ipv4Network, Ipv6Network};
+use crate::prefix_set::PrefixSet;
+use crate::snapshot::AdapterKey;
+
+/// The RFC 1918 private blocks.
+pub const RFC1918: [Ipv4Network; 3] = [
+    Ipv4Network::new_const(Ipv4Addr::new(10, 0, 0, 0), 8),
+    Ipv4Network::new_const(Ipv4Addr::new(172, 16, 0, 0), 12),
+    Ipv4Network::new_const(Ipv4Addr::new(192, 168, 0, 0), 16),
+];
+
+/// The RFC 6598 shared address space, used by carrier-grade NAT.
+pub const CGNAT: Ipv4Network = Ipv4Network::new_const(Ipv4Addr::new(100, 64, 0, 0), 10);
+
+/// The RFC 2544 benchmarking block.
+pub const BENCHMARKING: Ipv4Network = Ipv4Network::new_const(Ipv4Addr::new(198, 18, 0, 0), 15);
+
+/// Two adapters with overlapping on-link subnets.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct SubnetOverlap {
+    pub first: AdapterKey,
+    pub first_subnet: IpNetwork,
+    pub second: AdapterKey,
+    pub second_subnet: IpNetwork,
+}
+
+/// An address assigned to more than one adapter.
+#[derive(Debug, Clone, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct DuplicateAddress {
+    pub address: IpAddr,
+    pub adapters: Vec<AdapterKey>,
+}
+
+/// The conflicts found by [`find_conflicts`].
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
+pub struct Conflicts {
+    pub overlaps: Vec<SubnetOverlap>,
+    pub duplicates: Vec<DuplicateAddress>,
+}
+
+impl Conflicts {
+    pub fn is_empty(&self) -> bool {
+        self.overlaps.is_empty() && self.duplicates.is_empty()
+    }
+}
+
+/// Loopback and link-local addresses are per link, they never conflict.
+fn is_per_link(addr: IpAddr) -> bool {
+    match addr {
+        IpAddr::V4(addr) => addr.is_loopback() || addr.is_link_local(),
+        IpAddr::V6(addr) => addr.is_loopback() || (addr.segments()[0] & 0xffc0) == 0xfe80,
+    }
+}
+
+/// The on-link subnets of the unicast addresses of an adapter.
+fn subnets(adapter: &Adapter) -> Vec<IpNetwork> {
+    let mut subnets: Vec<_> = adapter
+        .unicast_addresses
+        .iter()
+        .map(|unicast| unicast.address.trunc())
+        .filter(|subnet| !is_per_link(subnet.addr()))
+        .collect();
+    subnets.sort();
+    subnets.dedup();
+    subnets
+}
+
+/// Find the subnets that overlap and the addresses that are assigned to
+/// several adapters, loopback and link-local addresses excluded.
+pub fn find_conflicts(adapters: &[Adapter]) -> Conflicts {
+    let mut conflicts = Conflicts::default();
+
+    let subnets: Vec<_> = adapters.iter().map(subnets).collect();
+    for (i, first) in adapters.iter().enumerate() {
+        for (j, second) in adapters.iter().enumerate().skip(i + 1) {
+            for a in &subnets[i] {
+                for b in subnets[j].iter().filter(|b| a.overlaps(b)) {
+                    conflicts.overlaps.push(SubnetOverlap {
+                        first: first.key(),
+                        first_subnet: *a,
+                        second: second.key(),
+                        second_subnet: *b,
+                    });
+                }
+            }
+        }
+    }
+
+    let mut addresses: Vec<(IpAddr, usize)> = vec![];
+    for (i, adapter) in adapters.iter().enumerate() {
+        for unicast in &adapter.unicast_addresses {
+            let addr = unicast.address.addr();
+            if !is_per_link(addr) && !addresses.contains(&(addr, i)) {
+                addresses.push((addr, i));
+            }
+        }
+    }
+    addresses.sort();
+    let mut start = 0;
+    while start < addresses.len() {
+        let address = addresses[start].0;
+        let len = addresses[start..]
+            .iter()
+            .take_while(|&&(a, _)| a == address)
+            .count();
+        let group = &addresses[start..start + len];
+        start += len;
+        if group.len() > 1 {
+            conflicts.duplicates.push(DuplicateAddress {
+                address: group[0].0,
+                adapters: group.iter().map(|&(_, i)| adapters[i].key()).collect(),
+            });
+        }
+    }
+
+    conflicts
+}
+
+/// The RFC 4193 /48 for `seed`.
+///
+/// The RFC derives the 40 bits global ID from a SHA-1 of the time and an
+/// EUI-64; any stable pseudo-random value is as good, this uses the 64 bits
+/// FNV-1a hash of the seed so that the same MAC always gives the same prefix.
+pub fn ula_prefix(seed: &[u8]) -> Ipv6Network {
+    let mut hash: u64 = 0xcbf2_9ce4_8422_2325;
+    for byte in seed {
+        hash ^= *byte as u64;
+        hash = hash.wrapping_mul(0x0100_0000_01b3);
+    }
+    let global_id = (hash & 0xff_ffff_ffff) as u128;
+    let addr = Ipv6Addr::from(0xfd << 120 | global_id << 80);
+    Ipv6Network::new(addr, 48).unwrap()
+}
+
+/// The /64 `subnet_id` of the RFC 4193 /48 for `seed`.
+pub fn ula_subnet(seed: &[u8], subnet_id: u16) -> Ipv6Network {
+    let prefix = u128::from(ula_prefix(seed).network());
+    Ipv6Network::new(Ipv6Addr::from(prefix | (subnet_id as u128) << 64), 64).unwrap()
+}
+
+/// Picks subnets that don't overlap the ones already in use.
+///
+/// The IPv4 pools default to [`RFC1918`]. Blocks are allocated first-fit,
+/// in the order of the pools, and are reserved once returned.
+#[derive(Debug, Clone)]
+pub struct SubnetAllocator {
+    pools: Vec<Ipv4Network>,
+    used: PrefixSet,
+}
+
+impl Default for SubnetAllocator {
+    fn default() -> Self {
+        SubnetAllocator {
+            pools: RFC1918.to_vec(),
+            used: PrefixSet::new(),
+        }
+    }
+}
+
+impl SubnetAllocator {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// An allocator avoiding the prefixes and the subnets of the addresses
+    /// of `adapters`. Default routes are ignored.
+    pub fn with_adapters(adapters: &[Adapter]) -> Self {
+        let mut allocator = Self::default();
+        for adapter in adapters {
+            allocator.reserve_adapter(adapter);
+        }
+        allocator
+    }
+
+    pub fn pools(mut self, pools: impl IntoIterator<Item = Ipv4Network>) -> Self {
+        self.pools = pools.into_iter().collect();
+        self
+    }
+
+    pub fn reserve(&mut self, network: impl Into<IpNetwork>) {
+        let network = network.into();
+        if network.prefix() != 0 {
+            self.used.insert(network.trunc());
+        }
+    }
+
+    pub fn reserve_adapter(&mut self, adapter: &Adapter) {
+        for prefix in &adapter.prefixes {
+            self.reserve(*prefix);
+        }
+        for unicast in &adapter.unicast_addresses {
+            self.reserve(unicast.address);
+        }
+    }
+
+    /// Whether `network` overlaps a reserved subnet.
+    pub fn is_used(&self, network: impl Into<IpNetwork>) -> bool {
+        let network: PrefixSet = std::iter::once(network.into()).collect();
+        network.difference(&self.used) != network
+    }
+
+    /// Allocate a free IPv4 block of length `prefix` from the pools.
+    pub fn allocate_v4(&mut self, prefix: u8) -> Option<Ipv4Network> {
+        if prefix > Ipv4Network::MAX_PREFIX {
+            return None;
+        }
+        let free = self.pools.iter().flat_map(|pool| {
+            let pool: PrefixSet = std::iter::once(IpNetwork::V4(*pool)).collect();
+            pool.difference(&self.used).prefixes()
+        });
+        // the free prefixes are aligned, the first address of one that is
+        // large enough starts a block
+        let network = free
+            .filter_map(|free| match free {
+                IpNetwork::V4(free) if free.prefix() <= prefix => Some(free),
+                _ => None,
+            })
+            .map(|free| Ipv4Network::new(free.network(), prefix).unwrap())
+            .next()?;
+        self.reserve(network);
+        Some(network)
+    }
+
+    /// Allocate the first free /64 of the ULA /48 derived from `seed`.
+    pub fn allocate_ula(&mut self, seed: &[u8]) -> Option<Ipv6Network> {
+        let network = (0..=u16::MAX)
+            .map(|subnet_id| ula_subnet(seed, subnet_id))
+            .find(|network| !self.is_used(*network))?;
+        self.reserve(network);
+        Some(network)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::address::UnicastAddress;
+
+    fn adapter(name: &str, addresses: &[&str]) -> Adapter {
+        Adapter {
+            adapter_name: name.into(),
+            unicast_addresses: addresses
+                .iter()
+                .map(|addr| UnicastAddress::from(addr.parse::<IpNetwork>().unwrap()))
+                .collect(),
+            ..Default::default()
+        }
+    }
+
+    fn key(name: &str) -> AdapterKey {
+        AdapterKey::Name(name.into())
+    }
+
+    #[test]
+    fn test_conflicts() {
+        let adapters = [
+            adapter("eth0", &["192.168.1.10/24", "fe80::1/64", "127.0.0.1/8"]),
+            adapter("docker0", &["172.17.0.1/16", "fe80::1/64"]),
+            adapter("tun0", &["192.168.0.0/16", "172.17.0.1/32", "fd00::1/64"]),
+            adapter("tun1", &["fd00::2/64", "127.0.0.1/8"]),
+        ];
+        let conflicts = find_conflicts(&adapters);
+        assert_eq!(
+            conflicts.overlaps,
+            [
+                SubnetOverlap {
+                    first: key("eth0"),
+                    first_subnet: "192.168.1.0/24".parse().unwrap(),
+                    second: key("tun0"),
+                    second_subnet: "192.168.0.0/16".parse().unwrap(),
+                },
+                SubnetOverlap {
+                    first: key("docker0"),
+                    first_subnet: "172.17.0.0/16".parse().unwrap(),
+                    second: key("tun0"),
+                    second_subnet: "172.17.0.1/32".parse().unwrap(),
+                },
+                SubnetOverlap {
+                    first: key("tun0"),
+                    first_subnet: "fd00::/64".parse().unwrap(),
+                    second: key("tun1"),
+                    second_subnet: "fd00::/64".parse().unwrap(),
+                },
+            ]
+        );
+        assert_eq!(
+            conflicts.duplicates,
+            [DuplicateAddress {
+                address: "172.17.0.1".parse().unwrap(),
+                adapters: vec![key("docker0"), key("tun0")],
+            }]
+        );
+        assert!(find_conflicts(&adapters[..2]).is_empty());
+    }
+
+    #[test]
+    fn test_allocate_v4() {
+        let mut eth0 = adapter("eth0", &["10.0.0.5/24"]);
+        eth0.prefixes = vec!["0.0.0.0/0".parse().unwrap(), "10.0.1.0/24".parse().unwrap()];
+        let docker0 = adapter("docker0", &["10.0.2.1/23"]);
+        let mut allocator = SubnetAllocator::with_adapters(&[eth0, docker0]);
+
+        let allocate = |allocator: &mut SubnetAllocator, prefix| {
+            allocator.allocate_v4(prefix).map(|n| n.to_string())
+        };
+        assert_eq!(allocate(&mut allocator, 24).as_deref(), Some("10.0.4.0/24"));
+        assert_eq!(allocate(&mut allocator, 24).as_deref(), Some("10.0.5.0/24"));
+        assert_eq!(allocate(&mut allocator, 30).as_deref(), Some("10.0.6.0/30"));
+        assert_eq!(allocate(&mut allocator, 8).as_deref(), None);
+        assert_eq!(
+            allocate(&mut allocator, 12).as_deref(),
+            Some("10.16.0.0/12")
+        );
+        assert_eq!(allocate(&mut allocator, 33), None);
+        assert!(allocator.is_used("10.0.0.0/16".parse::<IpNetwork>().unwrap()));
+        assert!(!allocator.is_used("10.1.0.0/16".parse::<IpNetwork>().unwrap()));
+
+        let mut allocator = SubnetAllocator::new().pools([CGNAT, BENCHMARKING]);
+        allocator.reserve("100.64.0.0/11".parse::<IpNetwork>().unwrap());
+        assert_eq!(allocate(&mut allocator, 10).as_deref(), None);
+        assert_eq!(
+            allocate(&mut allocator, 11).as_deref(),
+            Some("100.96.0.0/11")
+        );
+        assert_eq!(allocate(&mut allocator, 11).as_deref(), None);
+        assert_eq!(
+            allocate(&mut allocator, 15).as_deref(),
+            Some("198.18.0.0/15")
+        );
+    }
+
+    #[test]
+    fn test_ula() {
+        let mac = [0x00, 0x15, 0x5d, 0x01, 0x02, 0x03];
+        let prefix = ula_prefix(&mac);
+        assert_eq!(prefix, ula_prefix(&mac));
+        assert_ne!(prefix, ula_prefix(&[0x00, 0x15, 0x5d, 0x01, 0x02, 0x04]));
+        assert_eq!(prefix.prefix(), 48);
+        assert_eq!(prefix.network().segments()[0] >> 8, 0xfd);
+
+        let subnet = ula_subnet(&mac, 0x1234);
+        assert!(subnet.is_subnet_of(&prefix));
+        assert_eq!(subnet.prefix(), 64);
+        assert_eq!(subnet.network().segments()[3], 0x1234);
+
+        let mut allocator = SubnetAllocator::new();
+        allocator.reserve(ula_subnet(&mac, 0));
+        assert_eq!(allocator.allocate_ula(&mac), Some(ula_subnet(&mac, 1)));
+        assert_eq!(allocator.allocate_ula(&mac), Some(ula_subnet(&mac, 2)));
+    }
+}
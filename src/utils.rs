@@ -1,5 +1,14 @@
-use windows_sys::Win32::NetworkManagement::IpHelper::ConvertInterfaceLuidToGuid;
+use std::ffi::{OsStr, OsString};
 
+#[cfg(windows)]
+use crate::error::*;
+#[cfg(windows)]
+use windows_sys::Win32::NetworkManagement::IpHelper::{
+    ConvertInterfaceGuidToLuid, ConvertInterfaceIndexToLuid, ConvertInterfaceLuidToGuid,
+    ConvertInterfaceLuidToIndex, NET_LUID_LH,
+};
+
+#[cfg(windows)]
 pub fn guid_to_bytes(guid: &windows_sys::core::GUID) -> [u8; 16] {
     let data1_bytes = guid.data1.to_ne_bytes();
     let data2_bytes = guid.data2.to_ne_bytes();
@@ -24,6 +33,7 @@ pub fn guid_to_bytes(guid: &windows_sys::core::GUID) -> [u8; 16] {
     ]
 }
 
+#[cfg(windows)]
 pub fn bytes_to_guid(bytes: [u8; 16]) -> windows_sys::core::GUID {
     windows_sys::core::GUID {
         data1: u32::from_ne_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]),
@@ -35,20 +45,62 @@ pub fn bytes_to_guid(bytes: [u8; 16]) -> windows_sys::core::GUID {
     }
 }
 
-pub fn luid_to_guid(luid: u64) -> windows_sys::core::GUID {
-    let mut guid = windows_sys::core::GUID {
-        data1: 0,
-        data2: 0,
-        data3: 0,
-        data4: [0; 8],
-    };
-    unsafe {
-        let luid = windows_sys::Win32::NetworkManagement::IpHelper::NET_LUID_LH { Value: luid };
-        ConvertInterfaceLuidToGuid(&luid as *const _, &mut guid as *mut _);
+/// Turn the Win32 error code a function returns into a result.
+#[cfg(windows)]
+pub(crate) fn check(code: i32, operation: &'static str) -> Result<()> {
+    if code != 0 {
+        return Err(Error::os(code as u32).with_operation(operation));
     }
-    guid
+    Ok(())
+}
+
+/// See `ConvertInterfaceLuidToGuid`.
+#[cfg(windows)]
+pub fn luid_to_guid(luid: u64) -> Result<windows_sys::core::GUID> {
+    let mut guid = bytes_to_guid([0; 16]);
+    let luid = NET_LUID_LH { Value: luid };
+    check(
+        unsafe { ConvertInterfaceLuidToGuid(&luid, &mut guid) },
+        "ConvertInterfaceLuidToGuid",
+    )?;
+    Ok(guid)
 }
 
+/// See `ConvertInterfaceGuidToLuid`.
+#[cfg(windows)]
+pub fn guid_to_luid(guid: &windows_sys::core::GUID) -> Result<u64> {
+    let mut luid = NET_LUID_LH { Value: 0 };
+    check(
+        unsafe { ConvertInterfaceGuidToLuid(guid, &mut luid) },
+        "ConvertInterfaceGuidToLuid",
+    )?;
+    Ok(unsafe { luid.Value })
+}
+
+/// See `ConvertInterfaceLuidToIndex`.
+#[cfg(windows)]
+pub fn luid_to_index(luid: u64) -> Result<u32> {
+    let mut index = 0;
+    let luid = NET_LUID_LH { Value: luid };
+    check(
+        unsafe { ConvertInterfaceLuidToIndex(&luid, &mut index) },
+        "ConvertInterfaceLuidToIndex",
+    )?;
+    Ok(index)
+}
+
+/// See `ConvertInterfaceIndexToLuid`.
+#[cfg(windows)]
+pub fn index_to_luid(index: u32) -> Result<u64> {
+    let mut luid = NET_LUID_LH { Value: 0 };
+    check(
+        unsafe { ConvertInterfaceIndexToLuid(index, &mut luid) },
+        "ConvertInterfaceIndexToLuid",
+    )?;
+    Ok(unsafe { luid.Value })
+}
+
+#[cfg(windows)]
 pub fn generate_guid() -> windows_sys::core::GUID {
     let mut guid = windows_sys::core::GUID {
         data1: 0,
@@ -61,3 +113,241 @@ pub fn generate_guid() -> windows_sys::core::GUID {
     }
     guid
 }
+
+/// Format GUID bytes, as produced by `guid_to_bytes`, in the registry format
+/// without braces, e.g. `4a8a1b2c-3d4e-5f60-7182-93a4b5c6d7e8`.
+pub fn guid_to_string(bytes: &[u8; 16]) -> String {
+    let data1 = u32::from_le_bytes([bytes[0], bytes[1], bytes[2], bytes[3]]);
+    let data2 = u16::from_le_bytes([bytes[4], bytes[5]]);
+    let data3 = u16::from_le_bytes([bytes[6], bytes[7]]);
+    let mut s = format!("{:08x}-{:04x}-{:04x}-", data1, data2, data3);
+    for (i, b) in bytes[8..].iter().enumerate() {
+        if i == 2 {
+            s.push('-');
+        }
+        s.push_str(&format!("{:02x}", b));
+    }
+    s
+}
+
+/// Parse a GUID string, with or without braces, into GUID bytes.
+pub fn guid_from_str(s: &str) -> Option<[u8; 16]> {
+    let s = s
+        .strip_prefix('{')
+        .and_then(|s| s.strip_suffix('}'))
+        .unwrap_or(s);
+    let groups: Vec<&str> = s.split('-').collect();
+    let lens: Vec<usize> = groups.iter().map(|g| g.len()).collect();
+    if lens != [8, 4, 4, 4, 12] || !s.bytes().all(|b| b == b'-' || b.is_ascii_hexdigit()) {
+        return None;
+    }
+    let data1 = u32::from_str_radix(groups[0], 16).ok()?;
+    let data2 = u16::from_str_radix(groups[1], 16).ok()?;
+    let data3 = u16::from_str_radix(groups[2], 16).ok()?;
+    let data4 = format!("{}{}", groups[3], groups[4]);
+
+    let mut bytes = [0u8; 16];
+    bytes[0..4].copy_from_slice(&data1.to_le_bytes());
+    bytes[4..6].copy_from_slice(&data2.to_le_bytes());
+    bytes[6..8].copy_from_slice(&data3.to_le_bytes());
+    for (i, byte) in bytes[8..].iter_mut().enumerate() {
+        *byte = u8::from_str_radix(&data4[i * 2..i * 2 + 2], 16).ok()?;
+    }
+    Some(bytes)
+}
+
+/// Convert UTF-16 to an `OsString` without losing unpaired surrogates.
+///
+/// On Windows this is `OsStringExt::from_wide`. Elsewhere the string is
+/// encoded in WTF-8, the UTF-8 generalization that Windows uses for its
+/// `OsString`, which [`os_string_to_wide`] decodes back.
+pub fn os_string_from_wide(wide: &[u16]) -> OsString {
+    #[cfg(windows)]
+    {
+        std::os::windows::ffi::OsStringExt::from_wide(wide)
+    }
+    #[cfg(not(windows))]
+    {
+        let mut bytes = vec![];
+        for c in char::decode_utf16(wide.iter().copied()) {
+            let c = c.map_or_else(|err| err.unpaired_surrogate() as u32, u32::from);
+            // the UTF-8 encoding, extended to the surrogates
+            match c {
+                0..=0x7f => bytes.push(c as u8),
+                0x80..=0x7ff => bytes.extend([0xc0 | (c >> 6) as u8, 0x80 | (c & 0x3f) as u8]),
+                0x800..=0xffff => bytes.extend([
+                    0xe0 | (c >> 12) as u8,
+                    0x80 | ((c >> 6) & 0x3f) as u8,
+                    0x80 | (c & 0x3f) as u8,
+                ]),
+                _ => bytes.extend([
+                    0xf0 | (c >> 18) as u8,
+                    0x80 | ((c >> 12) & 0x3f) as u8,
+                    0x80 | ((c >> 6) & 0x3f) as u8,
+                    0x80 | (c & 0x3f) as u8,
+                ]),
+            }
+        }
+        std::os::unix::ffi::OsStringExt::from_vec(bytes)
+    }
+}
+
+/// The UTF-16 of an `OsStr`, the inverse of [`os_string_from_wide`].
+///
+/// Outside of Windows, the bytes that are not WTF-8 are replaced by U+FFFD.
+pub fn os_string_to_wide(s: &OsStr) -> Vec<u16> {
+    #[cfg(windows)]
+    {
+        std::os::windows::ffi::OsStrExt::encode_wide(s).collect()
+    }
+    #[cfg(not(windows))]
+    {
+        let bytes = std::os::unix::ffi::OsStrExt::as_bytes(s);
+        let mut wide = vec![];
+        let mut i = 0;
+        while i < bytes.len() {
+            let len = match bytes[i] {
+                0x00..=0x7f => 1,
+                0xc0..=0xdf => 2,
+                0xe0..=0xef => 3,
+                0xf0..=0xf7 => 4,
+                _ => 0,
+            };
+            let seq = bytes
+                .get(i..i + len)
+                .filter(|seq| len > 0 && seq[1..].iter().all(|b| b & 0xc0 == 0x80));
+            let c = match seq {
+                Some(seq) => {
+                    let first = (seq[0] & [0x7f, 0x1f, 0x0f, 0x07][len - 1]) as u32;
+                    seq[1..]
+                        .iter()
+                        .fold(first, |c, b| c << 6 | (b & 0x3f) as u32)
+                }
+                None => 0xfffd,
+            };
+            i += seq.map_or(1, |seq| seq.len());
+            match char::from_u32(c) {
+                Some(c) => wide.extend(c.encode_utf16(&mut [0; 2]).iter()),
+                // a surrogate
+                None if c <= 0xffff => wide.push(c as u16),
+                None => wide.push(0xfffd),
+            }
+        }
+        wide
+    }
+}
+
+/// Read a null terminated UTF-16 string, a null pointer is an empty string.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a null terminated UTF-16 string.
+pub unsafe fn os_string_from_wide_ptr(ptr: *const u16) -> OsString {
+    if ptr.is_null() {
+        return OsString::new();
+    }
+    let mut len = 0;
+    while *ptr.add(len) != 0 {
+        len += 1;
+    }
+    os_string_from_wide(std::slice::from_raw_parts(ptr, len))
+}
+
+/// Decode an ANSI string as UTF-8, or as Latin-1 if it isn't valid UTF-8 so
+/// that every byte is kept.
+///
+/// The buffer may come from another machine, so the ANSI code page is not
+/// known: the Latin-1 fallback is only right for code page 1252, the bytes of
+/// other code pages are kept but decode to the wrong characters. The adapter
+/// names, the only ANSI strings, are ASCII GUIDs in practice.
+pub fn os_string_from_ansi(bytes: &[u8]) -> OsString {
+    match std::str::from_utf8(bytes) {
+        Ok(s) => s.into(),
+        Err(_) => bytes.iter().map(|&b| b as char).collect::<String>().into(),
+    }
+}
+
+/// The ANSI bytes of an `OsStr`, the inverse of [`os_string_from_ansi`].
+///
+/// A string decoded from Latin-1 is encoded back to the same bytes, any other
+/// string is encoded as UTF-8.
+pub fn os_string_to_ansi(s: &OsStr) -> Vec<u8> {
+    let s = s.to_string_lossy();
+    let latin1: Option<Vec<u8>> = s.chars().map(|c| u8::try_from(c).ok()).collect();
+    match latin1 {
+        // valid UTF-8 would not decode back to Latin-1
+        Some(bytes) if std::str::from_utf8(&bytes).is_err() => bytes,
+        _ => s.as_bytes().to_vec(),
+    }
+}
+
+/// Read a null terminated ANSI string with [`os_string_from_ansi`], a null
+/// pointer is an empty string.
+///
+/// # Safety
+///
+/// `ptr` must be null or point to a null terminated string.
+pub unsafe fn os_string_from_ansi_ptr(ptr: *const u8) -> OsString {
+    if ptr.is_null() {
+        return OsString::new();
+    }
+    os_string_from_ansi(std::ffi::CStr::from_ptr(ptr as _).to_bytes())
+}
+
+#[test]
+fn test_guid_string() {
+    // FWPM_LAYER_ALE_AUTH_CONNECT_V4
+    let s = "c38d57d1-05a7-4c33-904f-7fbceee60e82";
+    let bytes = guid_from_str(s).unwrap();
+    assert_eq!(
+        &bytes[..8],
+        &[0xd1, 0x57, 0x8d, 0xc3, 0xa7, 0x05, 0x33, 0x4c]
+    );
+    assert_eq!(
+        &bytes[8..],
+        &[0x90, 0x4f, 0x7f, 0xbc, 0xee, 0xe6, 0x0e, 0x82]
+    );
+    assert_eq!(guid_to_string(&bytes), s);
+    let braced = format!("{{{}}}", s.to_uppercase());
+    assert_eq!(guid_from_str(&braced), Some(bytes));
+    assert_eq!(guid_from_str("c38d57d1-05a7-4c33-904f-7fbceee60e8"), None);
+    assert_eq!(guid_from_str("c38d57d1-05a7-4c33-904f-7fbceee60e8g"), None);
+    assert_eq!(guid_from_str("+38d57d1-05a7-4c33-904f-7fbceee60e82"), None);
+    assert_eq!(guid_from_str("{c38d57d1-05a7-4c33-904f-7fbceee60e82"), None);
+}
+
+#[test]
+fn test_os_string_from_wide() {
+    // "Eth", an unpaired surrogate, "0" and a pair (U+1F600)
+    let wide = [0x45, 0x74, 0x68, 0xd800, 0x30, 0xd83d, 0xde00, 0];
+    let s = unsafe { os_string_from_wide_ptr(wide.as_ptr()) };
+    assert_eq!(os_string_to_wide(&s), &wide[..7]);
+    // the surrogate is one unit on Windows and three WTF-8 bytes elsewhere
+    let replaced = if cfg!(windows) {
+        "\u{fffd}"
+    } else {
+        "\u{fffd}\u{fffd}\u{fffd}"
+    };
+    assert_eq!(s.to_string_lossy(), format!("Eth{}0\u{1f600}", replaced));
+
+    let valid: Vec<u16> = "Wi-Fi 2 (été)".encode_utf16().collect();
+    assert_eq!(os_string_from_wide(&valid), "Wi-Fi 2 (été)");
+    assert_eq!(os_string_to_wide(OsStr::new("Wi-Fi 2 (été)")), valid);
+    assert_eq!(unsafe { os_string_from_wide_ptr(std::ptr::null()) }, "");
+}
+
+#[test]
+fn test_os_string_from_ansi() {
+    let name = b"{4A8A1B2C}\0";
+    assert_eq!(
+        unsafe { os_string_from_ansi_ptr(name.as_ptr()) },
+        "{4A8A1B2C}"
+    );
+    let latin1 = b"caf\xe9\0";
+    assert_eq!(unsafe { os_string_from_ansi_ptr(latin1.as_ptr()) }, "café");
+    assert_eq!(os_string_to_ansi(OsStr::new("café")), b"caf\xe9");
+    // "Ã©" as Latin-1 would be the UTF-8 of "é"
+    assert_eq!(os_string_to_ansi(OsStr::new("Ã©")), "Ã©".as_bytes());
+    assert_eq!(os_string_to_ansi(OsStr::new("{4A8A1B2C}")), b"{4A8A1B2C}");
+    assert_eq!(unsafe { os_string_from_ansi_ptr(std::ptr::null()) }, "");
+}